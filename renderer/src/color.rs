@@ -7,7 +7,9 @@
 
 //use core::{fmt::Display, time::Duration};
 
-use crate::{MinistdRenderer, Mutex, MutexGuard};
+use core::ops::DerefMut;
+
+use crate::{MinistdRenderer, Mutex};
 
 
 
@@ -55,6 +57,7 @@ impl Color {
 
     /// Tries to lock the renderer and set `self` as the color of the `RENDERER`
     /// - returns `false` if fails
+    /// - for the interrupt-safe ministd `RENDERER` use `set_locked` with its guard
     #[inline]
     pub fn set<R>(&self, renderer: &Mutex<R>) -> bool
     where R: crate::MinistdRenderer {
@@ -68,9 +71,10 @@ impl Color {
     }
 
     /// Sets `self` as the color of the renderer behind the `guard`
+    /// - accepts any guard (`MutexGuard`, the interrupt-safe guard of the ministd `RENDERER`, ...)
     #[inline(always)]
-    pub fn set_locked<R>(&self, guard: &mut MutexGuard<R>)
-    where R: MinistdRenderer {
+    pub fn set_locked<R, G>(&self, guard: &mut G)
+    where R: MinistdRenderer, G: DerefMut<Target = R> {
         guard.set_color(self.as_int());
     }
 
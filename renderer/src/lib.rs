@@ -9,7 +9,7 @@
 
 
 
-use spin::{Mutex, MutexGuard};
+use spin::Mutex;
 
 
 //  renderer configuration
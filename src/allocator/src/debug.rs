@@ -0,0 +1,493 @@
+//! Debug wrapper around the active heap backend (the `debug-heap` feature)
+//! - every allocation is surrounded by red zones (guard bytes)
+//! - fresh memory is filled with `ALLOC_FILL`, freed memory with `FREE_FILL`
+//! - live allocations are kept in a fixed table
+//!
+//! Deallocation detects double frees, frees with mismatched `Layout` and overwritten red zones
+//! - the offending block is leaked and the violation is stored
+//! - `ministd` takes the violation with `take_violation()` (after it unlocks the heap) and reports it
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::MaybeUninit;
+use core::ptr::{NonNull, drop_in_place, null_mut, write_bytes};
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::{AllocError, MinistdAllocator, RemoveError};
+use crate::stats::HeapStats;
+use crate::alloc::Heap as Backend;
+
+
+/// Number of guard bytes placed before and after every allocation
+pub const REDZONE: usize = 16;
+
+/// Number of live allocations that can be tracked
+/// - allocations over this limit are only checked with their header
+pub const LIVE_TABLE_SIZE: usize = 1024;
+
+/// Fill of freshly allocated memory
+pub const ALLOC_FILL: u8 = 0xAA;
+/// Fill of freed memory
+pub const FREE_FILL: u8 = 0xDD;
+/// Fill of the red zones
+pub const GUARD_FILL: u8 = 0xFD;
+
+/// Marks header of a live allocation
+const MAGIC: usize = 0x6d69_6e69_7374_6421u64 as usize;
+
+/// Slot of allocation that does not fit the live table
+const UNTRACKED: usize = usize::MAX;
+
+
+/// Header placed right before the front red zone
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Header {
+    magic: usize,
+    size: usize,
+    align: usize,
+    /// Index into the live table
+    slot: usize,
+}
+
+/// Entry of the live table
+#[derive(Clone, Copy)]
+struct Entry {
+    /// Address of the user data (`0` if the entry is free)
+    ptr: usize,
+    size: usize,
+    align: usize,
+}
+
+impl Entry {
+    const EMPTY: Self = Self { ptr: 0, size: 0, align: 0 };
+
+    #[inline]
+    const fn layout(&self) -> Layout {
+        unsafe { Layout::from_size_align_unchecked(self.size, self.align) }
+    }
+}
+
+
+/// Type of the detected heap corruption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The pointer is not allocated (freed twice or never allocated)
+    DoubleFree,
+    /// The `Layout` passed to deallocation differs from the one used for allocation
+    LayoutMismatch,
+    /// Bytes before the allocation were overwritten
+    FrontRedZone,
+    /// Bytes after the allocation were overwritten
+    BackRedZone,
+}
+
+/// Heap corruption detected by the debug heap
+#[derive(Debug, Clone, Copy)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// Address passed to deallocation
+    pub address: usize,
+    /// Layout passed to deallocation
+    pub layout: Layout,
+    /// Layout used for allocation (if known)
+    pub allocated: Option<Layout>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            ViolationKind::DoubleFree => "double free (or free of invalid pointer)",
+            ViolationKind::LayoutMismatch => "free with mismatched layout",
+            ViolationKind::FrontRedZone => "red zone before allocation overwritten",
+            ViolationKind::BackRedZone => "red zone after allocation overwritten",
+        };
+        write!(f, "{} at {:#x} (size {}, align {})", what, self.address, self.layout.size(), self.layout.align())?;
+        if let Some(allocated) = self.allocated {
+            write!(f, ", allocated with size {}, align {}", allocated.size(), allocated.align())?;
+        }
+        Ok(())
+    }
+}
+
+
+struct State {
+    table: [Entry; LIVE_TABLE_SIZE],
+    /// Index where the search for a free entry starts
+    hint: usize,
+    /// Number of live allocations that do not fit the table
+    untracked: usize,
+    /// The first violation that was not reported yet
+    violation: Option<Violation>,
+}
+
+impl State {
+
+    /// Inserts allocation into the live table
+    /// - returns its slot (or `UNTRACKED` if the table is full)
+    fn track(&mut self, ptr: usize, layout: Layout) -> usize {
+        for i in 0..LIVE_TABLE_SIZE {
+            let slot = (self.hint + i) % LIVE_TABLE_SIZE;
+            if self.table[slot].ptr == 0 {
+                self.table[slot] = Entry { ptr, size: layout.size(), align: layout.align() };
+                self.hint = slot + 1;
+                return slot;
+            }
+        }
+        self.untracked += 1;
+        UNTRACKED
+    }
+
+    /// Searches the live table for allocation
+    fn find(&self, ptr: usize) -> Option<usize> {
+        self.table.iter().position(|e| e.ptr == ptr)
+    }
+
+    fn report(&mut self, violation: Violation) {
+        if self.violation.is_none() {
+            self.violation = Some(violation);
+        }
+    }
+
+}
+
+
+pub struct Heap {
+    inner: Backend,
+    state: UnsafeCell<State>,
+}
+
+
+impl Heap {
+    /// Gets mutable reference to the debug state
+    const fn state(&self) -> &mut State {
+        unsafe { &mut *self.state.get() }
+    }
+
+    pub const fn new() -> Self {
+        Self {
+            inner: Backend::new(),
+            state: UnsafeCell::new(State {
+                table: [Entry::EMPTY; LIVE_TABLE_SIZE],
+                hint: 0,
+                untracked: 0,
+                violation: None,
+            }),
+        }
+    }
+
+    /// Returns the first detected violation that was not reported yet
+    pub fn take_violation(&self) -> Option<Violation> {
+        self.state().violation.take()
+    }
+
+    /// Returns number of live allocations
+    pub fn live_allocations(&self) -> usize {
+        let state = self.state();
+        state.table.iter().filter(|e| e.ptr != 0).count() + state.untracked
+    }
+
+    /// Returns offset of the user data from the start of the block
+    #[inline(always)]
+    const fn front(align: usize) -> usize {
+        (size_of::<Header>() + REDZONE).next_multiple_of(align)
+    }
+
+    /// Returns layout of the whole block (header, red zones and the user data)
+    fn outer(layout: Layout) -> Option<Layout> {
+        let size = Self::front(layout.align()).checked_add(layout.size())?.checked_add(REDZONE)?;
+        Layout::from_size_align(size, core::cmp::max(layout.align(), align_of::<Header>())).ok()
+    }
+
+    /// Returns pointer to header of the allocation
+    #[inline(always)]
+    const fn header(user: usize) -> *mut Header {
+        (user - REDZONE - size_of::<Header>()) as *mut Header
+    }
+
+    /// Allocates guarded block and returns pointer to the user data
+    fn alloc_guarded(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let outer = Self::outer(layout).ok_or(AllocError::capacity_overflow())?;
+        let base = NonNull::new(unsafe { self.inner.alloc(outer) }).ok_or(AllocError::out_of_memory(layout))?;
+        let front = Self::front(layout.align());
+
+        unsafe {
+            let user = base.as_ptr().add(front);
+
+            write_bytes(base.as_ptr(), GUARD_FILL, front);
+            write_bytes(user, ALLOC_FILL, layout.size());
+            write_bytes(user.add(layout.size()), GUARD_FILL, REDZONE);
+
+            let slot = self.state().track(user as usize, layout);
+
+            Self::header(user as usize).write_unaligned(Header {
+                magic: MAGIC,
+                size: layout.size(),
+                align: layout.align(),
+                slot,
+            });
+
+            Ok(NonNull::new_unchecked(user))
+        }
+    }
+
+    /// Checks the allocation and frees it
+    /// - the block is leaked if any violation is detected
+    fn dealloc_guarded(&self, ptr: NonNull<u8>, layout: Layout) {
+        let user = ptr.as_ptr() as usize;
+
+        let (allocated, slot) = match self.check(user, layout) {
+            Ok(found) => found,
+            Err(violation) => {
+                self.state().report(violation);
+                return;
+            }
+        };
+
+        let state = self.state();
+        if slot == UNTRACKED {
+            state.untracked -= 1;
+        } else {
+            state.table[slot] = Entry::EMPTY;
+        }
+
+        let outer = Self::outer(allocated).expect("layout of live allocation is invalid");
+        let base = user - Self::front(allocated.align());
+
+        unsafe {
+            write_bytes(base as *mut u8, FREE_FILL, outer.size());
+            self.inner.dealloc(base as *mut u8, outer);
+        }
+    }
+
+    /// Checks whether `user` is a live allocation with `layout` and intact red zones
+    /// - returns the allocation layout and its slot in the live table
+    fn check(&self, user: usize, layout: Layout) -> Result<(Layout, usize), Violation> {
+        let state = self.state();
+        let header = unsafe { Self::header(user).read_unaligned() };
+
+        let violation = |kind, allocated| Violation { kind, address: user, layout, allocated };
+
+        let (allocated, slot) = if header.magic == MAGIC && header.slot != UNTRACKED {
+            match state.table.get(header.slot) {
+                Some(entry) if entry.ptr == user => (entry.layout(), header.slot),
+                _ => return Err(violation(ViolationKind::DoubleFree, None)),
+            }
+        } else if header.magic == MAGIC && Layout::from_size_align(header.size, header.align).is_ok() {
+            //  untracked allocation - only the header is available
+            (unsafe { Layout::from_size_align_unchecked(header.size, header.align) }, UNTRACKED)
+        } else {
+            //  the header is damaged, the table tells whether the allocation is live
+            return Err(match state.find(user) {
+                Some(slot) => violation(ViolationKind::FrontRedZone, Some(state.table[slot].layout())),
+                None => violation(ViolationKind::DoubleFree, None),
+            });
+        };
+
+        if allocated != layout {
+            return Err(violation(ViolationKind::LayoutMismatch, Some(allocated)));
+        }
+
+        let front = unsafe { core::slice::from_raw_parts((user - REDZONE) as *const u8, REDZONE) };
+        if front.iter().any(|b| *b != GUARD_FILL) || header.size != allocated.size() || header.align != allocated.align() {
+            return Err(violation(ViolationKind::FrontRedZone, Some(allocated)));
+        }
+
+        let back = unsafe { core::slice::from_raw_parts((user + allocated.size()) as *const u8, REDZONE) };
+        if back.iter().any(|b| *b != GUARD_FILL) {
+            return Err(violation(ViolationKind::BackRedZone, Some(allocated)));
+        }
+
+        Ok((allocated, slot))
+    }
+}
+
+
+unsafe impl GlobalAlloc for Heap {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.alloc_guarded(layout) {
+            Ok(p) => p.as_ptr(),
+            Err(_) => null_mut(),
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        match self.alloc_guarded(layout) {
+            Ok(p) => {
+                unsafe { write_bytes(p.as_ptr(), 0, layout.size()) };
+                p.as_ptr()
+            },
+            Err(_) => null_mut()
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr.is_null() {
+            return
+        }
+        self.dealloc_guarded(unsafe { NonNull::new_unchecked(ptr) }, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        let new = match self.alloc_guarded(new_layout) {
+            Ok(ptr) => ptr.as_ptr(),
+            Err(_) => return null_mut()
+        };
+
+        let count = core::cmp::min(new_size, layout.size());
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(ptr, new, count);
+            self.dealloc(ptr, layout);
+        }
+
+        new
+
+    }
+
+}
+
+impl MinistdAllocator for Heap {
+    type AddError = <Backend as MinistdAllocator>::AddError;
+
+    unsafe fn allocate<T: Sized>(&mut self, val: T) -> Result<NonNull<T>, T> {
+        let data: NonNull<T> = match self.alloc_guarded(Layout::new::<T>()) {
+            Ok(ptr) => ptr.cast(),
+            Err(_) => return Err(val)
+        };
+
+        unsafe { data.write(val); }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_uninit<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        Ok(self.alloc_guarded(Layout::new::<T>())?.cast())
+    }
+
+    unsafe fn allocate_zeroed<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        let ptr = self.alloc_guarded(Layout::new::<T>())?.cast();
+        unsafe { ptr.write_bytes(0, 1) }
+        Ok(ptr)
+    }
+
+    unsafe fn allocate_array<T: Sized + Clone>(&mut self, size: usize, val: T) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let data = NonNull::slice_from_raw_parts(self.alloc_guarded(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(val.clone());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&mut self, size: usize, f: &mut F) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let data = NonNull::slice_from_raw_parts(self.alloc_guarded(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(f());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_uninit<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        Ok(NonNull::slice_from_raw_parts(self.alloc_guarded(AllocError::array_layout::<T>(size)?)?.cast(), size))
+    }
+
+    unsafe fn allocate_array_zeroed<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let ptr = NonNull::slice_from_raw_parts(self.alloc_guarded(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            ptr.cast::<MaybeUninit<T>>().write_bytes(0, size);
+        }
+
+        Ok(ptr)
+    }
+
+    unsafe fn delete<T: Sized>(&mut self, ptr: NonNull<T>) {
+        //  check before dropping, the value may be already freed
+        if let Err(violation) = self.check(ptr.as_ptr() as usize, Layout::new::<T>()) {
+            self.state().report(violation);
+            return;
+        }
+        unsafe { drop_in_place(ptr.as_ptr()); }
+        self.dealloc_guarded(ptr.cast(), Layout::new::<T>());
+    }
+
+    unsafe fn add_to_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), Self::AddError> {
+        unsafe { self.inner.add_to_heap(start, size) }
+    }
+
+    unsafe fn remove_from_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), RemoveError> {
+        unsafe { self.inner.remove_from_heap(start, size) }
+    }
+
+    /// Includes the red zones and headers
+    fn total_bytes(&self) -> usize { self.inner.total_bytes() }
+
+    /// Includes the red zones and headers
+    fn allocated_bytes(&self) -> usize { self.inner.allocated_bytes() }
+
+    /// Statistics of the wrapped backend (red zones and headers are counted as allocated)
+    fn stats(&self) -> HeapStats { self.inner.stats() }
+
+
+    unsafe fn reallocate<T: Sized + Default>(&mut self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+        if size == ptr.len() { return Ok(ptr) }
+
+        let data: NonNull<T> = self.alloc_guarded(AllocError::array_layout::<T>(size)?)?.cast();
+
+        unsafe {
+            core::ptr::copy(ptr.as_ptr() as *mut T, data.as_ptr(), core::cmp::min(size, ptr.len()));
+        }
+        self.dealloc_guarded(ptr.cast(), AllocError::array_layout::<T>(ptr.len())?);
+
+        Ok(NonNull::slice_from_raw_parts(data, size))
+
+    }
+
+    unsafe fn delete_array<T: Sized>(&mut self, mut ptr: NonNull<[T]>) {
+        let Ok(layout) = AllocError::array_layout::<T>(ptr.len()) else {
+            return;
+        };
+        //  check before dropping, the elements may be already freed
+        if let Err(violation) = self.check(ptr.cast::<u8>().as_ptr() as usize, layout) {
+            self.state().report(violation);
+            return;
+        }
+        unsafe {
+            drop_in_place(ptr.as_mut());
+        }
+        self.dealloc_guarded(ptr.cast(), layout);
+    }
+
+}
+
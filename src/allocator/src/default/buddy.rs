@@ -0,0 +1,313 @@
+//! Binary buddy allocator used by the default backend
+//! - blocks have power-of-two sizes, the smallest one holds one `usize`
+//! - free blocks are kept in intrusive lists (the first word of each free block points to the next one)
+//! - allocated blocks can grow by merging with free buddies and shrink by splitting off their upper halves
+//! - free ranges can be removed again, the blocks that cross the range boundaries are split
+
+
+use core::alloc::Layout;
+use core::ptr::{NonNull, null_mut};
+
+
+/// Number of block orders
+/// - block of order `n` has `2^n` bytes
+pub(crate) const ORDERS: usize = 32;
+
+
+/// Header written into every free block
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+
+pub(crate) struct Buddy {
+    free: [*mut FreeBlock; ORDERS],
+    total: usize,
+    allocated: usize,
+}
+
+impl Buddy {
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            free: [null_mut(); ORDERS],
+            total: 0,
+            allocated: 0,
+        }
+    }
+
+    /// Returns the order of the block that is used for `layout`
+    /// - returns `None` if no block is large enough
+    pub(crate) const fn order_for(layout: Layout) -> Option<usize> {
+        let Some(size) = layout.size().checked_next_power_of_two() else {
+            return None;
+        };
+        let size = if size > layout.align() { size } else { layout.align() };
+        let size = if size > size_of::<usize>() { size } else { size_of::<usize>() };
+
+        let order = size.trailing_zeros() as usize;
+
+        if order < ORDERS { Some(order) } else { None }
+    }
+
+    /// Adds `[start, end)` to the free lists
+    /// - the range is shrunk to `usize` boundaries
+    pub(crate) unsafe fn add(&mut self, start: usize, end: usize) {
+
+        let word = size_of::<usize>();
+        let mut current = (start + word - 1) & !(word - 1);
+        let end = end & !(word - 1);
+
+        while current + word <= end {
+            //  the largest block that is naturally aligned at `current` and still fits
+            let align_bits = current.trailing_zeros();
+            let fit_bits = usize::BITS - 1 - (end - current).leading_zeros();
+            let order = core::cmp::min(core::cmp::min(align_bits, fit_bits) as usize, ORDERS - 1);
+
+            unsafe { self.push(order, current); }
+
+            current += 1 << order;
+            self.total += 1 << order;
+        }
+    }
+
+    /// Allocates one block of some order
+    /// - larger blocks are split if there is no free block of this order
+    pub(crate) fn alloc(&mut self, order: usize) -> Option<NonNull<u8>> {
+
+        let mut current = order;
+        while current < ORDERS && self.free[current].is_null() {
+            current += 1;
+        }
+
+        if current >= ORDERS {
+            return None;
+        }
+
+        let block = self.pop(current)?;
+
+        //  split the block and give back the upper halves
+        while current > order {
+            current -= 1;
+            unsafe { self.push(current, block + (1 << current)); }
+        }
+
+        self.allocated += 1 << order;
+
+        NonNull::new(block as *mut u8)
+    }
+
+    /// Returns block of some order back to the free lists
+    /// - merges the block with its buddy as long as the buddy is free
+    pub(crate) unsafe fn dealloc(&mut self, ptr: NonNull<u8>, order: usize) {
+
+        self.allocated -= 1 << order;
+
+        let mut addr = ptr.as_ptr() as usize;
+        let mut order = order;
+
+        while order < ORDERS - 1 {
+            let buddy = addr ^ (1 << order);
+            if !self.remove(order, buddy) {
+                break;
+            }
+            addr = core::cmp::min(addr, buddy);
+            order += 1;
+        }
+
+        unsafe { self.push(order, addr); }
+    }
+
+    /// Grows allocated block from `order` to `new_order` without moving it
+    /// - the block is merged with its upper buddies, so all of them have to be free
+    /// - returns `Err` (and changes nothing) if the block cannot grow
+    pub(crate) fn grow(&mut self, ptr: NonNull<u8>, order: usize, new_order: usize) -> Result<(), ()> {
+
+        let addr = ptr.as_ptr() as usize;
+
+        //  the block must be the lower half on every level and every upper half must be free
+        for current in order..new_order {
+            if addr & (1 << current) != 0 || !self.contains(current, addr + (1 << current)) {
+                return Err(());
+            }
+        }
+
+        for current in order..new_order {
+            self.remove(current, addr + (1 << current));
+        }
+
+        self.allocated += (1 << new_order) - (1 << order);
+
+        Ok(())
+    }
+
+    /// Shrinks allocated block from `order` to `new_order` without moving it
+    /// - the upper halves are given back to the free lists
+    pub(crate) unsafe fn shrink(&mut self, ptr: NonNull<u8>, order: usize, new_order: usize) {
+
+        let addr = ptr.as_ptr() as usize;
+
+        //  the buddy of every upper half is the (still allocated) lower half, so nothing can be merged
+        for current in new_order..order {
+            unsafe { self.push(current, addr + (1 << current)); }
+        }
+
+        self.allocated -= (1 << order) - (1 << new_order);
+    }
+
+    /// Removes `[start, end)` from the free lists
+    /// - the range is shrunk to `usize` boundaries
+    /// - returns `false` (and changes nothing) if any byte in the range is allocated
+    pub(crate) fn remove_range(&mut self, start: usize, end: usize) -> bool {
+
+        let word = size_of::<usize>();
+        let start = (start + word - 1) & !(word - 1);
+        let end = end & !(word - 1);
+
+        if start >= end {
+            return true;
+        }
+
+        //  every byte of the range has to be free
+        let mut free = 0;
+        self.for_each_free(|addr, size| {
+            let from = core::cmp::max(addr, start);
+            let to = core::cmp::min(addr + size, end);
+            if from < to {
+                free += to - from;
+            }
+        });
+
+        if free != end - start {
+            return false;
+        }
+
+        //  split blocks that cross the range boundaries
+        //  - the boundaries are word-aligned, so the smallest blocks never cross them
+        'split: loop {
+            for order in 1..ORDERS {
+                let mut block = self.free[order];
+                while !block.is_null() {
+                    let addr = block as usize;
+                    let size = 1 << order;
+                    let overlaps = addr < end && addr + size > start;
+                    let inside = addr >= start && addr + size <= end;
+                    if overlaps && !inside {
+                        self.remove(order, addr);
+                        unsafe {
+                            self.push(order - 1, addr);
+                            self.push(order - 1, addr + size / 2);
+                        }
+                        continue 'split;
+                    }
+                    block = unsafe { (*block).next };
+                }
+            }
+            break;
+        }
+
+        //  drop blocks inside the range
+        for order in 0..ORDERS {
+            let mut link: *mut *mut FreeBlock = &mut self.free[order];
+            unsafe {
+                while !(*link).is_null() {
+                    let addr = *link as usize;
+                    if addr >= start && addr < end {
+                        *link = (**link).next;
+                    } else {
+                        link = &mut (**link).next;
+                    }
+                }
+            }
+        }
+
+        self.total -= end - start;
+        true
+    }
+
+    /// Calls `f` with address and size of every free block
+    fn for_each_free<F: FnMut(usize, usize)>(&self, mut f: F) {
+        for order in 0..ORDERS {
+            let mut block = self.free[order];
+            while !block.is_null() {
+                f(block as usize, 1 << order);
+                block = unsafe { (*block).next };
+            }
+        }
+    }
+
+    /// Returns number of bytes managed by the allocator
+    #[inline(always)]
+    pub(crate) const fn total(&self) -> usize { self.total }
+
+    /// Returns number of bytes that are given out
+    #[inline(always)]
+    pub(crate) const fn allocated(&self) -> usize { self.allocated }
+
+    /// Adds number of free blocks of each order to `histogram`
+    /// - `histogram` is indexed by log2 of the block size
+    pub(crate) fn free_blocks(&self, histogram: &mut [usize]) {
+        for order in 0..ORDERS {
+            let mut block = self.free[order];
+            while !block.is_null() {
+                histogram[order] += 1;
+                block = unsafe { (*block).next };
+            }
+        }
+    }
+
+    /// Returns size of the largest free block
+    pub(crate) fn largest_free(&self) -> Option<usize> {
+        (0..ORDERS).rev()
+            .find(|order| !self.free[*order].is_null())
+            .map(|order| 1 << order)
+    }
+
+    /// Pushes free block to the list of some order
+    unsafe fn push(&mut self, order: usize, addr: usize) {
+        let block = addr as *mut FreeBlock;
+        unsafe { block.write(FreeBlock { next: self.free[order] }); }
+        self.free[order] = block;
+    }
+
+    /// Pops free block from the list of some order
+    fn pop(&mut self, order: usize) -> Option<usize> {
+        let block = self.free[order];
+        if block.is_null() {
+            return None;
+        }
+        self.free[order] = unsafe { (*block).next };
+        Some(block as usize)
+    }
+
+    /// Returns whether specific block is in the list of some order
+    fn contains(&self, order: usize, addr: usize) -> bool {
+        let mut block = self.free[order];
+        while !block.is_null() {
+            if block as usize == addr {
+                return true;
+            }
+            block = unsafe { (*block).next };
+        }
+        false
+    }
+
+    /// Removes specific block from the list of some order
+    /// - returns `false` if the block is not free
+    fn remove(&mut self, order: usize, addr: usize) -> bool {
+        let mut link: *mut *mut FreeBlock = &mut self.free[order];
+
+        unsafe {
+            while !(*link).is_null() {
+                if *link as usize == addr {
+                    *link = (**link).next;
+                    return true;
+                }
+                link = &mut (**link).next;
+            }
+        }
+
+        false
+    }
+
+}
@@ -0,0 +1,306 @@
+
+//! The default allocator functionality (a binary buddy allocator, see [`buddy`])
+//! - blocks are resized in place by merging with free buddies or splitting off their upper halves
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::MaybeUninit;
+use core::ptr::{NonNull, copy_nonoverlapping, drop_in_place, null_mut};
+use core::cell::UnsafeCell;
+
+use crate::{AllocError, MinistdAllocator, RemoveError};
+use crate::stats::{HeapStats, Counters, HISTOGRAM_SIZE};
+
+mod buddy;
+
+use buddy::Buddy;
+
+
+pub struct Heap {
+    heap: UnsafeCell<Buddy>,
+    counters: Counters,
+}
+
+//  the heap only holds pointers into memory that it owns
+unsafe impl Send for Heap {}
+
+
+impl Heap {
+    /// Gets mutable reference to the inner value
+    const fn mutable(&self) -> &mut Buddy {
+        unsafe { &mut *self.heap.get() }
+    }
+
+    pub const fn new() -> Self {
+        Self {
+            heap: UnsafeCell::new(Buddy::new()),
+            counters: Counters::new(),
+        }
+    }
+
+    /// Allocates memory and updates the counters
+    fn alloc_counted(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let order = Buddy::order_for(layout).ok_or(AllocError::out_of_memory(layout))?;
+        let ptr = self.mutable().alloc(order).ok_or(AllocError::out_of_memory(layout))?;
+        self.counters.alloc(self.mutable().allocated());
+        Ok(ptr)
+    }
+
+    /// Deallocates memory and updates the counters
+    fn dealloc_counted(&self, ptr: NonNull<u8>, layout: Layout) {
+        let Some(order) = Buddy::order_for(layout) else {
+            return;
+        };
+        unsafe { self.mutable().dealloc(ptr, order); }
+        self.counters.free();
+    }
+
+    /// Resizes the block without moving it and updates the counters
+    /// - returns `Err` if the block cannot be resized in place
+    fn resize_counted(&self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> Result<(), ()> {
+        let new_layout = Layout::from_size_align(new_size, layout.align()).map_err(|_| ())?;
+
+        let order = Buddy::order_for(layout).ok_or(())?;
+        let new_order = Buddy::order_for(new_layout).ok_or(())?;
+
+        if new_order > order {
+            self.mutable().grow(ptr, order, new_order)?;
+        } else if new_order < order {
+            unsafe { self.mutable().shrink(ptr, order, new_order); }
+        }
+
+        self.counters.resize(self.mutable().allocated());
+        Ok(())
+    }
+}
+
+
+unsafe impl GlobalAlloc for Heap {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.alloc_counted(layout) {
+            Ok(p) => p.as_ptr(),
+            Err(_) => null_mut(),
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        match self.alloc_counted(layout) {
+            Ok(p) => {
+                unsafe { core::ptr::write_bytes(p.as_ptr(), 0, layout.size()) };
+                p.as_ptr()
+            },
+            Err(_) => null_mut()
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr.is_null() {
+            return
+        }
+        self.dealloc_counted(unsafe { NonNull::new_unchecked(ptr) }, layout);
+    }
+
+    /// Resizes the block in place if possible, otherwise moves it to new block
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+
+        if let Some(block) = NonNull::new(ptr) && self.resize_counted(block, layout, new_size).is_ok() {
+            self.counters.realloc(true);
+            return ptr;
+        }
+
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        let new = match self.alloc_counted(new_layout) {
+            Ok(ptr) => ptr.as_ptr(),
+            Err(_) => return null_mut()
+        };
+
+        let count = core::cmp::min(new_size, layout.size());
+
+        unsafe {
+            copy_nonoverlapping(ptr, new, count);
+            self.dealloc(ptr, layout);
+        }
+
+        self.counters.realloc(false);
+        new
+
+    }
+
+}
+
+impl MinistdAllocator for Heap {
+    type AddError = ();
+
+    unsafe fn allocate<T: Sized>(&mut self, val: T) -> Result<core::ptr::NonNull<T>, T> {
+        let data: NonNull<T> = match self.alloc_counted(Layout::new::<T>()) {
+            Ok(ptr) => ptr.cast(),
+            Err(_) => return Err(val)
+        };
+
+        unsafe { data.write(val); }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_uninit<T: Sized>(&mut self) -> Result<NonNull<core::mem::MaybeUninit<T>>, AllocError> {
+        Ok(self.alloc_counted(Layout::new::<T>())?.cast())
+    }
+
+    unsafe fn allocate_zeroed<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        let ptr = self.alloc_counted(Layout::new::<T>())?.cast();
+        unsafe { ptr.write_bytes(0, 1) }
+        Ok(ptr)
+    }
+
+    unsafe fn allocate_array<T: Sized + Clone>(&mut self, size: usize, val: T) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+
+        let data = NonNull::slice_from_raw_parts(self.alloc_counted(layout)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(val.clone());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&mut self, size: usize, f: &mut F) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+
+        let data = NonNull::slice_from_raw_parts(self.alloc_counted(layout)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(f());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_uninit<T: Sized>(&mut self, size: usize) -> Result<NonNull<[core::mem::MaybeUninit<T>]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+
+        Ok(NonNull::slice_from_raw_parts(self.alloc_counted(layout)?.cast::<MaybeUninit<T>>(), size))
+    }
+
+    unsafe fn allocate_array_zeroed<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+        
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let ptr = NonNull::slice_from_raw_parts(self.alloc_counted(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            ptr.cast::<MaybeUninit<T>>().write_bytes(0, size);
+        }
+
+        Ok(ptr)
+    }
+
+    unsafe fn delete<T: Sized>(&mut self, ptr: NonNull<T>) {
+        unsafe { drop_in_place(ptr.as_ptr()); }
+        self.dealloc_counted(ptr.cast(), Layout::new::<T>());
+    }
+
+    unsafe fn add_to_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), Self::AddError> {
+
+        if size == 0 { return Err(()) }
+
+        let start = start.as_ptr() as usize;
+        let end = start.checked_add(size).ok_or(())?;
+        unsafe { self.mutable().add(start, end) }
+        Ok(())
+    }
+
+    unsafe fn remove_from_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), RemoveError> {
+        let start = start.as_ptr() as usize;
+        let end = start.checked_add(size).ok_or(RemoveError::InUse)?;
+
+        match self.mutable().remove_range(start, end) {
+            true => Ok(()),
+            false => Err(RemoveError::InUse),
+        }
+    }
+
+    fn total_bytes(&self) -> usize { self.mutable().total() }
+
+    fn allocated_bytes(&self) -> usize { self.mutable().allocated() }
+
+    fn stats(&self) -> HeapStats {
+        let mut stats = HeapStats::new();
+        stats.total_bytes = self.total_bytes();
+        stats.allocated_bytes = self.allocated_bytes();
+        self.counters.fill(&mut stats);
+
+        let mut histogram = [0; HISTOGRAM_SIZE];
+        self.mutable().free_blocks(&mut histogram);
+        stats.free_blocks = Some(histogram);
+        stats.largest_free_block = self.mutable().largest_free();
+        stats
+    }
+
+
+    unsafe fn reallocate<T: Sized + Default>(&mut self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+        if size == ptr.len() { return Ok(ptr) }
+
+        let old = layout_arr::<T>(ptr.len());
+        let new = AllocError::array_layout::<T>(size)?;
+
+        if self.resize_counted(ptr.cast(), old, new.size()).is_ok() {
+            self.counters.realloc(true);
+            return Ok(NonNull::slice_from_raw_parts(ptr.cast(), size));
+        }
+
+        let data: NonNull<T> = self.alloc_counted(new)?.cast();
+
+        unsafe {
+            core::ptr::copy(ptr.as_ptr() as *mut T, data.as_ptr(), core::cmp::min(size, ptr.len()));
+        }
+        self.dealloc_counted(ptr.cast(), old);
+
+        self.counters.realloc(false);
+        Ok(NonNull::slice_from_raw_parts(data, size))
+
+    }
+
+    unsafe fn resize_in_place(&mut self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> Result<(), ()> {
+        self.resize_counted(ptr, layout, new_size)
+    }
+
+    unsafe fn delete_array<T: Sized>(&mut self, mut ptr: NonNull<[T]>) {
+        unsafe {
+            drop_in_place(ptr.as_mut());
+        }
+        self.dealloc_counted(ptr.cast(), layout_arr::<T>(ptr.len()));
+    }
+
+
+}
+
+
+/// Creates `Layout` for an array of `T`
+/// - does not check whether `size` is not zero
+pub(crate) fn layout_arr<T: Sized>(size: usize) -> Layout {
+    unsafe {
+        Layout::from_size_align_unchecked(size_of::<T>() * size, align_of::<T>())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,139 @@
+//! Defines the `AllocError` type returned by the fallible allocation functions
+//! - the error carries the requested `Layout` (if it could be constructed) and the reason of the failure
+
+
+use core::alloc::Layout;
+use core::fmt;
+
+
+/// Reason why an allocation failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocErrorKind {
+    /// The heap has no block large enough for the request
+    OutOfMemory,
+    /// The request has size zero and the function does not allocate zero-sized blocks
+    ZeroSized,
+    /// The requested size does not fit `isize` (or computing it overflowed)
+    CapacityOverflow,
+    /// The requested align is not a power of two (or not supported by the allocator)
+    InvalidAlignment,
+}
+
+impl AllocErrorKind {
+    /// Returns short description of the reason
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::OutOfMemory => "out of memory",
+            Self::ZeroSized => "zero-sized allocation",
+            Self::CapacityOverflow => "capacity overflow",
+            Self::InvalidAlignment => "invalid alignment",
+        }
+    }
+}
+
+impl fmt::Display for AllocErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Error returned when an allocation fails
+/// - `layout()` is the requested layout, it is `None` if the layout itself could not be constructed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError {
+    layout: Option<Layout>,
+    kind: AllocErrorKind,
+}
+
+/// Error returned by the `try_reserve` family of functions
+pub type TryReserveError = AllocError;
+
+impl AllocError {
+
+    /// Constructs new `AllocError`
+    #[inline]
+    pub const fn new(kind: AllocErrorKind, layout: Option<Layout>) -> Self {
+        Self { layout, kind }
+    }
+
+    /// The heap could not satisfy `layout`
+    #[inline]
+    pub const fn out_of_memory(layout: Layout) -> Self {
+        Self::new(AllocErrorKind::OutOfMemory, Some(layout))
+    }
+
+    /// The request for `layout` has size zero
+    #[inline]
+    pub const fn zero_sized(layout: Layout) -> Self {
+        Self::new(AllocErrorKind::ZeroSized, Some(layout))
+    }
+
+    /// The request for an array of `T` has zero elements
+    #[inline]
+    pub const fn zero_sized_array<T>() -> Self {
+        Self::zero_sized(Layout::new::<[T; 0]>())
+    }
+
+    /// The requested size overflowed
+    #[inline]
+    pub const fn capacity_overflow() -> Self {
+        Self::new(AllocErrorKind::CapacityOverflow, None)
+    }
+
+    /// The requested align is invalid
+    #[inline]
+    pub const fn invalid_alignment() -> Self {
+        Self::new(AllocErrorKind::InvalidAlignment, None)
+    }
+
+    /// Returns the requested layout
+    /// - returns `None` if the layout could not be constructed
+    #[inline]
+    pub const fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    /// Returns the reason of the failure
+    #[inline]
+    pub const fn kind(&self) -> AllocErrorKind {
+        self.kind
+    }
+
+    /// Constructs `Layout` for an array of `size` elements of `T`
+    /// - returns `Err(CapacityOverflow)` if the size of the array does not fit `isize`
+    #[inline]
+    pub const fn array_layout<T>(size: usize) -> Result<Layout, Self> {
+        match Layout::array::<T>(size) {
+            Ok(layout) => Ok(layout),
+            Err(_) => Err(Self::capacity_overflow()),
+        }
+    }
+
+    /// Constructs `Layout` from `size` and `align`
+    /// - returns `Err(InvalidAlignment)` if `align` is not a power of two
+    /// - returns `Err(CapacityOverflow)` if `size` (rounded up to `align`) does not fit `isize`
+    #[inline]
+    pub const fn layout_from(size: usize, align: usize) -> Result<Layout, Self> {
+        if !align.is_power_of_two() {
+            return Err(Self::invalid_alignment());
+        }
+        match Layout::from_size_align(size, align) {
+            Ok(layout) => Ok(layout),
+            Err(_) => Err(Self::capacity_overflow()),
+        }
+    }
+
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.kind.as_str())?;
+        if let Some(layout) = self.layout {
+            write!(f, " (size {}, align {})", layout.size(), layout.align())?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for AllocError {}
@@ -6,28 +6,51 @@
 //! This module should not be modified
 //! 
 //! # Exports to `ministd`
-//! The only thing this crate should export is the `Heap` type with `MinistdAllocator`
+//! The only thing this crate should export is the `Heap` type with `MinistdAllocator` (and the `HeapStats` report and the `AllocError` type)
 //! - The allocator also has to expose the `const fn new() -> Self` method to construct it
 
 
 //  use the default allocator
-#[cfg(feature = "default")]
+#[cfg(all(feature = "default", not(feature = "slab")))]
 mod default;
 
-#[cfg(feature = "default")]
+#[cfg(all(feature = "default", not(feature = "slab")))]
 pub(crate) use default as alloc;
 
 
+//  use the slab allocator
+//  - takes priority over the default allocator
+#[cfg(feature = "slab")]
+mod slab;
+
+#[cfg(feature = "slab")]
+pub(crate) use slab as alloc;
+
+
 
 //  use the custom allocator
-#[cfg(not(feature = "default"))]
+#[cfg(not(any(feature = "default", feature = "slab")))]
 pub(crate) mod custom;
 
-#[cfg(not(feature = "default"))]
+#[cfg(not(any(feature = "default", feature = "slab")))]
 pub(crate) use custom as alloc;
 
 
 pub(crate) mod ministd_allocator_trait;
-pub use ministd_allocator_trait::MinistdAllocator;
+pub use ministd_allocator_trait::{MinistdAllocator, RemoveError};
+
+pub mod error;
+pub use error::{AllocError, AllocErrorKind, TryReserveError};
+
+pub mod stats;
+pub use stats::HeapStats;
+
+//  wrap the active allocator with red zones and live allocation tracking
+#[cfg(feature = "debug-heap")]
+pub mod debug;
+
+#[cfg(feature = "debug-heap")]
+pub use debug::Heap;
 
+#[cfg(not(feature = "debug-heap"))]
 pub use alloc::Heap;
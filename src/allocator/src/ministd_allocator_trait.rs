@@ -4,6 +4,17 @@ use core::alloc::{GlobalAlloc, Layout};
 use core::mem::MaybeUninit;
 use core::ptr::NonNull;
 
+use crate::{AllocError, HeapStats};
+
+/// Error returned by the `remove_from_heap()` function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveError {
+    /// The allocator is not able to remove memory from the heap
+    Unsupported,
+    /// Some memory in the range is still allocated
+    InUse,
+}
+
 pub trait MinistdAllocator where Self: Sized + GlobalAlloc {
 
     /// Error type returned by the `add_to_heap()` function
@@ -14,28 +25,29 @@ pub trait MinistdAllocator where Self: Sized + GlobalAlloc {
     unsafe fn allocate<T: Sized>(&mut self, val: T) -> Result<NonNull<T>, T>;
 
     /// Allocates uninitialized data for an instance of `T`
-    unsafe fn allocate_uninit<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, ()>;
+    /// - returns `Err` (with the requested layout and the reason) if allocation fails
+    unsafe fn allocate_uninit<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError>;
 
 
     /// Allocates data for an instance of `T` and sets all bytes to zero
-    unsafe fn allocate_zeroed<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, ()>;
+    unsafe fn allocate_zeroed<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError>;
 
     /// Allocates an array of type `T`
-    /// - returns `Err` if `size` is zero
-    unsafe fn allocate_array<T: Sized + Clone>(&mut self, size: usize, val: T) -> Result<NonNull<[T]>, ()>;
+    /// - returns `Err(ZeroSized)` if `size` is zero
+    unsafe fn allocate_array<T: Sized + Clone>(&mut self, size: usize, val: T) -> Result<NonNull<[T]>, AllocError>;
 
     /// Allocates an array and uses the closure to determine the value of each element
-    /// - returns `Err` if `size` is zero
-    unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&mut self, size: usize, f: &mut F) -> Result<NonNull<[T]>, ()>;
+    /// - returns `Err(ZeroSized)` if `size` is zero
+    unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&mut self, size: usize, f: &mut F) -> Result<NonNull<[T]>, AllocError>;
 
     /// Allocates an uninitialized array
-    /// - returns `Err` if `size` is zero
-    unsafe fn allocate_array_uninit<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, ()>;
+    /// - returns `Err(ZeroSized)` if `size` is zero
+    unsafe fn allocate_array_uninit<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError>;
 
 
     /// Allocates an array of type `T` and sets all bytes to zero
-    /// - returns `Err` if size is zero
-    unsafe fn allocate_array_zeroed<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, ()>;
+    /// - returns `Err(ZeroSized)` if `size` is zero
+    unsafe fn allocate_array_zeroed<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError>;
 
     /// Deallocates the pointer and `drop`s the inner value if needed
     /// - The pointer must be allocated with the `allocate()` function or has the exact memory layout as `T`
@@ -47,8 +59,18 @@ pub trait MinistdAllocator where Self: Sized + GlobalAlloc {
 
     /// Reallocates array into new buffer
     /// - does not drop any elements
-    /// - returns `Err` if `size` is zero
-    unsafe fn reallocate<T: Sized + Default>(&mut self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, ()>;
+    /// - returns `Err(ZeroSized)` if `size` is zero
+    /// - resizes the buffer in place if possible (see `resize_in_place()`)
+    unsafe fn reallocate<T: Sized + Default>(&mut self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, AllocError>;
+
+    /// Resizes the block at `ptr` to `new_size` bytes without moving it
+    /// - the block keeps its align (`layout.align()`)
+    /// - returns `Err` if the block cannot be resized in place, the heap is not changed in that case
+    /// - allocators that cannot resize blocks always return `Err` (the default)
+    unsafe fn resize_in_place(&mut self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> Result<(), ()> {
+        let _ = (ptr, layout, new_size);
+        Err(())
+    }
 
 
 
@@ -56,10 +78,23 @@ pub trait MinistdAllocator where Self: Sized + GlobalAlloc {
     /// - only virtual addresses should be used
     unsafe fn add_to_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), Self::AddError>;
 
+    /// Removes range of addresses from the heap
+    /// - the range must have been added with `add_to_heap()` (whole or its part)
+    /// - returns `Err(InUse)` if any memory in the range is allocated, the heap is not changed in that case
+    /// - allocators that cannot remove memory return `Err(Unsupported)` (the default)
+    unsafe fn remove_from_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), RemoveError> {
+        let _ = (start, size);
+        Err(RemoveError::Unsupported)
+    }
+
     /// Returns actual number of bytes in the heap
     fn total_bytes(&self) -> usize;
 
     /// Returns number of bytes that are allocated
     fn allocated_bytes(&self) -> usize;
 
+    /// Returns snapshot of the heap state
+    /// - see `HeapStats` for the provided information
+    fn stats(&self) -> HeapStats;
+
 }
\ No newline at end of file
@@ -0,0 +1,252 @@
+//! Size-class caches of the slab backend
+//! - every size class owns slabs (buddy blocks) that are cut into equally sized objects
+//! - the slab header is stored at the start of the slab, so it can be found from any object address
+
+
+use core::ptr::{NonNull, null_mut};
+
+use super::pages::{PageAllocator, PAGE_SIZE};
+
+
+/// Object sizes served by slabs (in bytes)
+/// - larger allocations go straight to the page allocator
+pub(crate) const CLASSES: [usize; 7] = [16, 32, 64, 128, 256, 512, 1024];
+
+/// Minimal number of objects in one slab
+/// - larger classes use multi-page slabs to keep the header overhead low
+const MIN_OBJECTS: usize = 16;
+
+
+/// Header of a free object
+struct FreeObject {
+    next: *mut FreeObject,
+}
+
+/// Header placed at the start of every slab
+#[repr(C)]
+struct Slab {
+    next: *mut Slab,
+    prev: *mut Slab,
+    free: *mut FreeObject,
+    used: usize,
+}
+
+
+#[derive(Copy, Clone)]
+pub(crate) struct SizeClass {
+    /// Size of one object
+    size: usize,
+    /// Order of one slab in the page allocator
+    order: usize,
+    /// Slabs with at least one free object
+    partial: *mut Slab,
+    /// Number of slabs in the `partial` list that have no object in use
+    empty: usize,
+    /// Number of slabs owned by this class
+    slabs: usize,
+}
+
+impl SizeClass {
+
+    pub(crate) const fn new(size: usize) -> Self {
+        let slab = if size * MIN_OBJECTS > PAGE_SIZE { size * MIN_OBJECTS } else { PAGE_SIZE };
+        Self {
+            size,
+            order: (slab / PAGE_SIZE).trailing_zeros() as usize,
+            partial: null_mut(),
+            empty: 0,
+            slabs: 0,
+        }
+    }
+
+    /// Returns index of the smallest size class that fits `size` bytes aligned to `align`
+    /// - returns `None` if the allocation is too large for slabs
+    pub(crate) const fn index_for(size: usize, align: usize) -> Option<usize> {
+        let size = if size > align { size } else { align };
+        let mut i = 0;
+        while i < CLASSES.len() {
+            if CLASSES[i] >= size {
+                return Some(i);
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Returns the object size of this class
+    #[inline(always)]
+    pub(crate) const fn size(&self) -> usize { self.size }
+
+    /// Returns number of bytes taken from the page allocator by this class
+    #[inline(always)]
+    pub(crate) const fn slab_bytes(&self) -> usize {
+        self.slabs * self.slab_size()
+    }
+
+    /// Returns number of free objects in the slabs of this class
+    pub(crate) fn free_objects(&self) -> usize {
+        let mut free = 0;
+        let mut slab = self.partial;
+        while !slab.is_null() {
+            unsafe {
+                free += self.capacity() - (*slab).used;
+                slab = (*slab).next;
+            }
+        }
+        free
+    }
+
+    /// Returns the size of one slab (in bytes)
+    #[inline(always)]
+    const fn slab_size(&self) -> usize {
+        PageAllocator::block_size(self.order)
+    }
+
+    /// Returns offset of the first object in a slab
+    #[inline(always)]
+    const fn first_offset(&self) -> usize {
+        size_of::<Slab>().next_multiple_of(self.size)
+    }
+
+    /// Returns number of objects in one slab
+    #[inline(always)]
+    const fn capacity(&self) -> usize {
+        (self.slab_size() - self.first_offset()) / self.size
+    }
+
+    /// Allocates one object
+    /// - takes new slab from `pages` if all slabs are full
+    pub(crate) fn alloc(&mut self, pages: &mut PageAllocator) -> Option<NonNull<u8>> {
+
+        if self.partial.is_null() {
+            self.grow(pages)?;
+        }
+
+        unsafe {
+            let slab = &mut *self.partial;
+
+            if slab.used == 0 {
+                self.empty -= 1;
+            }
+
+            let object = slab.free;
+            slab.free = (*object).next;
+            slab.used += 1;
+
+            if slab.used == self.capacity() {
+                self.unlink(slab);
+            }
+
+            NonNull::new(object as *mut u8)
+        }
+    }
+
+    /// Returns object back to its slab
+    /// - releases the slab if it is empty and this class already has another empty slab
+    pub(crate) unsafe fn dealloc(&mut self, ptr: NonNull<u8>, pages: &mut PageAllocator) {
+
+        let slab = (ptr.as_ptr() as usize & !(self.slab_size() - 1)) as *mut Slab;
+
+        unsafe {
+            let s = &mut *slab;
+
+            if s.used == self.capacity() {
+                //  the slab was full - make it available again
+                self.link(slab);
+            }
+
+            let object = ptr.as_ptr() as *mut FreeObject;
+            (*object).next = s.free;
+            s.free = object;
+            s.used -= 1;
+
+            if s.used == 0 {
+                if self.empty > 0 {
+                    self.unlink(slab);
+                    pages.dealloc(NonNull::new_unchecked(slab as *mut u8), self.order);
+                    self.slabs -= 1;
+                } else {
+                    self.empty += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns all empty slabs back to `pages`
+    pub(crate) fn release_empty(&mut self, pages: &mut PageAllocator) {
+        let mut slab = self.partial;
+        while !slab.is_null() {
+            unsafe {
+                let next = (*slab).next;
+                if (*slab).used == 0 {
+                    self.unlink(slab);
+                    pages.dealloc(NonNull::new_unchecked(slab as *mut u8), self.order);
+                    self.slabs -= 1;
+                }
+                slab = next;
+            }
+        }
+        self.empty = 0;
+    }
+
+    /// Takes new slab from `pages` and cuts it into objects
+    fn grow(&mut self, pages: &mut PageAllocator) -> Option<()> {
+
+        let base = pages.alloc(self.order)?.as_ptr();
+
+        unsafe {
+            //  link all objects into the free list
+            let mut free: *mut FreeObject = null_mut();
+            for i in (0..self.capacity()).rev() {
+                let object = base.add(self.first_offset() + i * self.size) as *mut FreeObject;
+                (*object).next = free;
+                free = object;
+            }
+
+            let slab = base as *mut Slab;
+            slab.write(Slab {
+                next: null_mut(),
+                prev: null_mut(),
+                free,
+                used: 0,
+            });
+
+            self.link(slab);
+        }
+
+        self.empty += 1;
+        self.slabs += 1;
+
+        Some(())
+    }
+
+    /// Inserts slab at the start of the `partial` list
+    unsafe fn link(&mut self, slab: *mut Slab) {
+        unsafe {
+            (*slab).prev = null_mut();
+            (*slab).next = self.partial;
+            if !self.partial.is_null() {
+                (*self.partial).prev = slab;
+            }
+        }
+        self.partial = slab;
+    }
+
+    /// Removes slab from the `partial` list
+    unsafe fn unlink(&mut self, slab: *mut Slab) {
+        unsafe {
+            let s = &mut *slab;
+            if s.prev.is_null() {
+                self.partial = s.next;
+            } else {
+                (*s.prev).next = s.next;
+            }
+            if !s.next.is_null() {
+                (*s.next).prev = s.prev;
+            }
+            s.next = null_mut();
+            s.prev = null_mut();
+        }
+    }
+
+}
@@ -0,0 +1,394 @@
+//! The slab allocator backend
+//! - small allocations (up to 1 KiB) are served from per-size-class slabs
+//!   - this avoids the power-of-two rounding of the buddy allocator for the most common allocation sizes (`Rc`, `Arc`, `Box`, ...)
+//! - large allocations are served by page-granular buddy blocks
+//!   - they are resized in place by merging with free buddies or splitting off their upper halves
+//! - all memory is given to the allocator with `MinistdAllocator::add_to_heap`
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::MaybeUninit;
+use core::ptr::{NonNull, copy_nonoverlapping, drop_in_place, null_mut};
+use core::cell::UnsafeCell;
+
+use crate::{AllocError, MinistdAllocator, RemoveError};
+use crate::stats::{HeapStats, Counters, HISTOGRAM_SIZE};
+
+mod pages;
+mod cache;
+
+use pages::PageAllocator;
+use cache::{SizeClass, CLASSES};
+
+
+/// The inner state of the slab allocator
+struct Slabs {
+    pages: PageAllocator,
+    classes: [SizeClass; CLASSES.len()],
+    /// Bytes given out by size classes (rounded up to the object size)
+    in_objects: usize,
+    counters: Counters,
+}
+
+impl Slabs {
+
+    const fn new() -> Self {
+        let mut classes = [SizeClass::new(0); CLASSES.len()];
+        let mut i = 0;
+        while i < CLASSES.len() {
+            classes[i] = SizeClass::new(CLASSES[i]);
+            i += 1;
+        }
+
+        Self {
+            pages: PageAllocator::new(),
+            classes,
+            in_objects: 0,
+            counters: Counters::new(),
+        }
+    }
+
+    fn alloc(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+
+        let ptr = match SizeClass::index_for(layout.size(), layout.align()) {
+            Some(class) => {
+                let class = &mut self.classes[class];
+                let ptr = class.alloc(&mut self.pages).ok_or(AllocError::out_of_memory(layout))?;
+                self.in_objects += class.size();
+                ptr
+            },
+            None => {
+                let order = PageAllocator::order_for(layout.size(), layout.align()).ok_or(AllocError::out_of_memory(layout))?;
+                self.pages.alloc(order).ok_or(AllocError::out_of_memory(layout))?
+            }
+        };
+
+        self.counters.alloc(self.allocated());
+
+        Ok(ptr)
+    }
+
+    fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+
+        if let Some(class) = SizeClass::index_for(layout.size(), layout.align()) {
+            let class = &mut self.classes[class];
+            self.in_objects -= class.size();
+            unsafe { class.dealloc(ptr, &mut self.pages); }
+        } else if let Some(order) = PageAllocator::order_for(layout.size(), layout.align()) {
+            unsafe { self.pages.dealloc(ptr, order); }
+        } else {
+            return;
+        }
+
+        self.counters.free();
+    }
+
+    /// Resizes the block without moving it
+    /// - objects stay in place if the new size falls into the same size class
+    /// - page blocks grow or shrink in the page allocator
+    /// - returns `Err` if the block cannot be resized in place
+    fn resize(&mut self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> Result<(), ()> {
+
+        let align = layout.align();
+
+        match (SizeClass::index_for(layout.size(), align), SizeClass::index_for(new_size, align)) {
+            (Some(class), Some(new_class)) if class == new_class => {},
+            (None, None) => {
+                let order = PageAllocator::order_for(layout.size(), align).ok_or(())?;
+                let new_order = PageAllocator::order_for(new_size, align).ok_or(())?;
+
+                if new_order > order {
+                    self.pages.grow(ptr, order, new_order)?;
+                } else if new_order < order {
+                    unsafe { self.pages.shrink(ptr, order, new_order); }
+                }
+            },
+            _ => return Err(()),
+        }
+
+        self.counters.resize(self.allocated());
+        Ok(())
+    }
+
+    /// Returns number of bytes that are given out to the users of the heap
+    /// - pages that hold slabs are not counted, only the objects in them
+    fn allocated(&self) -> usize {
+        let mut slabs = 0;
+        for class in self.classes.iter() {
+            slabs += class.slab_bytes();
+        }
+        self.pages.allocated() - slabs + self.in_objects
+    }
+
+    fn stats(&self) -> HeapStats {
+        let mut stats = HeapStats::new();
+        stats.total_bytes = self.pages.total();
+        stats.allocated_bytes = self.allocated();
+        self.counters.fill(&mut stats);
+
+        let mut histogram = [0; HISTOGRAM_SIZE];
+        self.pages.free_blocks(&mut histogram);
+
+        let mut largest = self.pages.largest_free();
+        for class in self.classes.iter() {
+            let free = class.free_objects();
+            histogram[class.size().trailing_zeros() as usize] += free;
+            if free != 0 && largest.is_none_or(|l| l < class.size()) {
+                largest = Some(class.size());
+            }
+        }
+
+        stats.free_blocks = Some(histogram);
+        stats.largest_free_block = largest;
+        stats
+    }
+
+}
+
+
+pub struct Heap {
+    heap: UnsafeCell<Slabs>
+}
+
+//  the heap only holds pointers into memory that it owns
+unsafe impl Send for Heap {}
+
+
+impl Heap {
+    /// Gets mutable reference to the inner value
+    const fn mutable(&self) -> &mut Slabs {
+        unsafe { &mut *self.heap.get() }
+    }
+
+    pub const fn new() -> Self {
+        Self {
+            heap: UnsafeCell::new(Slabs::new())
+        }
+    }
+}
+
+
+unsafe impl GlobalAlloc for Heap {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.mutable().alloc(layout) {
+            Ok(p) => p.as_ptr(),
+            Err(_) => null_mut(),
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        match self.mutable().alloc(layout) {
+            Ok(p) => {
+                unsafe { core::ptr::write_bytes(p.as_ptr(), 0, layout.size()) };
+                p.as_ptr()
+            },
+            Err(_) => null_mut()
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr.is_null() {
+            return
+        }
+        self.mutable().dealloc(unsafe { NonNull::new_unchecked(ptr) }, layout);
+    }
+
+    /// Resizes the block in place if possible, otherwise moves it to new block
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+
+        if let Some(block) = NonNull::new(ptr) && self.mutable().resize(block, layout, new_size).is_ok() {
+            self.mutable().counters.realloc(true);
+            return ptr;
+        }
+
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        let new = match self.mutable().alloc(new_layout) {
+            Ok(ptr) => ptr.as_ptr(),
+            Err(_) => return null_mut()
+        };
+
+        let count = core::cmp::min(new_size, layout.size());
+
+        unsafe {
+            copy_nonoverlapping(ptr, new, count);
+            self.dealloc(ptr, layout);
+        }
+
+        self.mutable().counters.realloc(false);
+        new
+
+    }
+
+}
+
+impl MinistdAllocator for Heap {
+    type AddError = ();
+
+    unsafe fn allocate<T: Sized>(&mut self, val: T) -> Result<NonNull<T>, T> {
+        let data: NonNull<T> = match self.mutable().alloc(Layout::new::<T>()) {
+            Ok(ptr) => ptr.cast(),
+            Err(_) => return Err(val)
+        };
+
+        unsafe { data.write(val); }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_uninit<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        Ok(self.mutable().alloc(Layout::new::<T>())?.cast())
+    }
+
+    unsafe fn allocate_zeroed<T: Sized>(&mut self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        let ptr = self.mutable().alloc(Layout::new::<T>())?.cast();
+        unsafe { ptr.write_bytes(0, 1) }
+        Ok(ptr)
+    }
+
+    unsafe fn allocate_array<T: Sized + Clone>(&mut self, size: usize, val: T) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let data = NonNull::slice_from_raw_parts(self.mutable().alloc(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(val.clone());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&mut self, size: usize, f: &mut F) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()); }
+
+        let data = NonNull::slice_from_raw_parts(self.mutable().alloc(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(f());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(data)
+    }
+
+    unsafe fn allocate_array_uninit<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        Ok(NonNull::slice_from_raw_parts(self.mutable().alloc(AllocError::array_layout::<T>(size)?)?.cast::<MaybeUninit<T>>(), size))
+    }
+
+    unsafe fn allocate_array_zeroed<T: Sized>(&mut self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let ptr = NonNull::slice_from_raw_parts(self.mutable().alloc(AllocError::array_layout::<T>(size)?)?.cast(), size);
+
+        unsafe {
+            ptr.cast::<MaybeUninit<T>>().write_bytes(0, size);
+        }
+
+        Ok(ptr)
+    }
+
+    unsafe fn delete<T: Sized>(&mut self, ptr: NonNull<T>) {
+        unsafe { drop_in_place(ptr.as_ptr()); }
+        self.mutable().dealloc(ptr.cast(), Layout::new::<T>());
+    }
+
+    unsafe fn add_to_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), Self::AddError> {
+
+        if size == 0 { return Err(()) }
+
+        let start = start.as_ptr() as usize;
+        let end = start.checked_add(size).ok_or(())?;
+
+        //  the region has to contain at least one whole page
+        match unsafe { self.mutable().pages.add(start, end) } {
+            0 => Err(()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Empty slabs cached by the size classes are released first
+    unsafe fn remove_from_heap(&mut self, start: NonNull<u8>, size: usize) -> Result<(), RemoveError> {
+        let heap = self.mutable();
+
+        for class in heap.classes.iter_mut() {
+            class.release_empty(&mut heap.pages);
+        }
+
+        let start = start.as_ptr() as usize;
+        let end = start.checked_add(size).ok_or(RemoveError::InUse)?;
+
+        match heap.pages.remove_range(start, end) {
+            true => Ok(()),
+            false => Err(RemoveError::InUse),
+        }
+    }
+
+    fn total_bytes(&self) -> usize { self.mutable().pages.total() }
+
+    fn allocated_bytes(&self) -> usize { self.mutable().allocated() }
+
+    /// The free-block histogram contains both free pages (by buddy order) and free objects (by size class)
+    fn stats(&self) -> HeapStats { self.mutable().stats() }
+
+
+    unsafe fn reallocate<T: Sized + Default>(&mut self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, AllocError> {
+
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+        if size == ptr.len() { return Ok(ptr) }
+
+        let old = layout_arr::<T>(ptr.len());
+        let new = AllocError::array_layout::<T>(size)?;
+
+        if self.mutable().resize(ptr.cast(), old, new.size()).is_ok() {
+            self.mutable().counters.realloc(true);
+            return Ok(NonNull::slice_from_raw_parts(ptr.cast(), size));
+        }
+
+        let data: NonNull<T> = self.mutable().alloc(new)?.cast();
+
+        unsafe {
+            core::ptr::copy(ptr.as_ptr() as *mut T, data.as_ptr(), core::cmp::min(size, ptr.len()));
+        }
+        self.mutable().dealloc(ptr.cast(), old);
+
+        self.mutable().counters.realloc(false);
+        Ok(NonNull::slice_from_raw_parts(data, size))
+
+    }
+
+    unsafe fn resize_in_place(&mut self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> Result<(), ()> {
+        self.mutable().resize(ptr, layout, new_size)
+    }
+
+    unsafe fn delete_array<T: Sized>(&mut self, mut ptr: NonNull<[T]>) {
+        unsafe {
+            drop_in_place(ptr.as_mut());
+        }
+        self.mutable().dealloc(ptr.cast(), layout_arr::<T>(ptr.len()));
+    }
+
+
+}
+
+
+/// Creates `Layout` for an array of `T`
+/// - does not check whether `size` is not zero
+pub(crate) fn layout_arr<T: Sized>(size: usize) -> Layout {
+    unsafe {
+        Layout::from_size_align_unchecked(size_of::<T>() * size, align_of::<T>())
+    }
+}
@@ -0,0 +1,242 @@
+//! Defines the `HeapStats` report returned by `MinistdAllocator::stats()`
+
+use core::cell::Cell;
+use core::fmt;
+
+
+/// Number of entries in the free-block histogram
+/// - one entry for each power of two
+pub const HISTOGRAM_SIZE: usize = usize::BITS as usize;
+
+
+/// Snapshot of the heap state
+/// - fields that the backend is not able to provide are `None`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Number of bytes managed by the heap
+    pub total_bytes: usize,
+    /// Number of bytes that are allocated
+    pub allocated_bytes: usize,
+    /// Highest value of `allocated_bytes` so far
+    pub peak_bytes: usize,
+    /// Number of successful allocations
+    pub allocations: usize,
+    /// Number of deallocations
+    pub frees: usize,
+    /// Number of reallocations
+    pub reallocations: usize,
+    /// Number of reallocations that resized the block without moving it
+    pub in_place_reallocations: usize,
+    /// Number of allocations that failed
+    /// - counted by `ministd`, backends leave it at `0`
+    pub failed_allocations: usize,
+    /// Number of `oom` handler invocations
+    /// - counted by `ministd`, backends leave it at `0`
+    pub oom_invocations: usize,
+    /// Size of the largest free block (in bytes)
+    pub largest_free_block: Option<usize>,
+    /// Number of free blocks by their size
+    /// - `free_blocks[n]` is the number of free blocks of `2^n` bytes
+    /// - the blocks are buddy orders or size classes depending on the backend
+    pub free_blocks: Option<[usize; HISTOGRAM_SIZE]>,
+}
+
+impl HeapStats {
+
+    pub const fn new() -> Self {
+        Self {
+            total_bytes: 0,
+            allocated_bytes: 0,
+            peak_bytes: 0,
+            allocations: 0,
+            frees: 0,
+            reallocations: 0,
+            in_place_reallocations: 0,
+            failed_allocations: 0,
+            oom_invocations: 0,
+            largest_free_block: None,
+            free_blocks: None,
+        }
+    }
+
+    /// Returns number of bytes that are not allocated
+    #[inline]
+    pub const fn free_bytes(&self) -> usize {
+        self.total_bytes.saturating_sub(self.allocated_bytes)
+    }
+
+    /// Returns number of allocations that were not freed yet
+    #[inline]
+    pub const fn live_allocations(&self) -> usize {
+        self.allocations.saturating_sub(self.frees)
+    }
+
+    /// Returns external fragmentation in percents
+    /// - `0` means that all free memory is in one block
+    /// - returns `None` if the largest free block is unknown or there is no free memory
+    pub const fn fragmentation(&self) -> Option<usize> {
+        let free = self.free_bytes();
+        match self.largest_free_block {
+            Some(largest) if free != 0 => Some(100 - (if largest > free { free } else { largest }) * 100 / free),
+            _ => None,
+        }
+    }
+
+}
+
+impl Default for HeapStats {
+    fn default() -> Self { Self::new() }
+}
+
+
+impl fmt::Display for HeapStats {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "heap: {} / {} allocated ({} free, peak {})",
+            Size(self.allocated_bytes), Size(self.total_bytes), Size(self.free_bytes()), Size(self.peak_bytes))?;
+        writeln!(f, "  allocations: {}, frees: {}, live: {}",
+            self.allocations, self.frees, self.live_allocations())?;
+        writeln!(f, "  reallocations: {} ({} in place)",
+            self.reallocations, self.in_place_reallocations)?;
+        writeln!(f, "  failed allocations: {}, oom handler runs: {}",
+            self.failed_allocations, self.oom_invocations)?;
+
+        match self.largest_free_block {
+            Some(largest) => match self.fragmentation() {
+                Some(frag) => writeln!(f, "  largest free block: {} (fragmentation {}%)", Size(largest), frag)?,
+                None => writeln!(f, "  largest free block: {}", Size(largest))?,
+            },
+            None => writeln!(f, "  largest free block: unavailable")?,
+        }
+
+        match &self.free_blocks {
+            Some(histogram) => {
+                write!(f, "  free blocks:")?;
+                let mut empty = true;
+                for (order, count) in histogram.iter().enumerate() {
+                    if *count != 0 {
+                        write!(f, "\n    {:>8}: {}", Size(1 << order), count)?;
+                        empty = false;
+                    }
+                }
+                if empty {
+                    write!(f, " none")?;
+                }
+                Ok(())
+            },
+            None => write!(f, "  free blocks: unavailable"),
+        }
+    }
+
+}
+
+
+/// Formats number of bytes with binary suffix
+struct Size(usize);
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+        let mut unit = 0;
+        let mut value = self.0;
+        while unit < UNITS.len() - 1 && value >= 1024 && value % 1024 == 0 {
+            value /= 1024;
+            unit += 1;
+        }
+
+        //  pad the whole value, not just the number
+        let mut buf = [0u8; 32];
+        let mut w = Writer { buf: &mut buf, len: 0 };
+        fmt::write(&mut w, format_args!("{} {}", value, UNITS[unit]))?;
+        let len = w.len;
+        f.pad(unsafe { core::str::from_utf8_unchecked(&buf[..len]) })
+    }
+}
+
+/// Writes formatted text into a fixed buffer
+struct Writer<'l> {
+    buf: &'l mut [u8],
+    len: usize,
+}
+
+impl fmt::Write for Writer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+
+/// Counters kept by the backends
+/// - uses `Cell` since the `GlobalAlloc` methods only take `&self`
+pub(crate) struct Counters {
+    allocations: Cell<usize>,
+    frees: Cell<usize>,
+    reallocations: Cell<usize>,
+    in_place: Cell<usize>,
+    peak: Cell<usize>,
+}
+
+impl Counters {
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            allocations: Cell::new(0),
+            frees: Cell::new(0),
+            reallocations: Cell::new(0),
+            in_place: Cell::new(0),
+            peak: Cell::new(0),
+        }
+    }
+
+    /// Records successful allocation
+    /// - `allocated` is the number of allocated bytes after the allocation
+    #[inline]
+    pub(crate) fn alloc(&self, allocated: usize) {
+        self.allocations.set(self.allocations.get() + 1);
+        if allocated > self.peak.get() {
+            self.peak.set(allocated);
+        }
+    }
+
+    /// Records deallocation
+    #[inline]
+    pub(crate) fn free(&self) {
+        self.frees.set(self.frees.get() + 1);
+    }
+
+    /// Records block resized without moving it
+    /// - `allocated` is the number of allocated bytes after the resize
+    #[inline]
+    pub(crate) fn resize(&self, allocated: usize) {
+        if allocated > self.peak.get() {
+            self.peak.set(allocated);
+        }
+    }
+
+    /// Records reallocation
+    /// - `in_place` tells whether the block was resized without moving it
+    #[inline]
+    pub(crate) fn realloc(&self, in_place: bool) {
+        self.reallocations.set(self.reallocations.get() + 1);
+        if in_place {
+            self.in_place.set(self.in_place.get() + 1);
+        }
+    }
+
+    /// Writes the counters into `stats`
+    pub(crate) fn fill(&self, stats: &mut HeapStats) {
+        stats.allocations = self.allocations.get();
+        stats.frees = self.frees.get();
+        stats.reallocations = self.reallocations.get();
+        stats.in_place_reallocations = self.in_place.get();
+        stats.peak_bytes = self.peak.get();
+    }
+
+}
@@ -0,0 +1,479 @@
+//	init/memmap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Boot memory map
+//! - built from the `(base, length, kind)` entries that Limine, Multiboot2 and UEFI provide
+//!   - the raw type codes are classified with `MemoryKind::from_limine`, `from_multiboot2` and `from_uefi`
+//! - overlapping entries are resolved by kind, the more restrictive one wins (see [`MemoryKind`])
+//!   - the kernel image and reserved ranges are clipped out of the usable memory the same way
+//! - adjacent entries of the same kind are merged
+//! - usable and reclaimable memory is shrunk to whole pages, all other kinds are grown to whole pages
+//! - the map can be printed as a table with `println!("{}", map)`
+//!
+//! # Example
+//! ```
+//! let mut map = MemoryMap::from_entries(limine_map.iter().map(|e|
+//!     (e.base as usize, e.length as usize, MemoryKind::from_limine(e.entry_type))))?;
+//! map.reserve(initrd.phys, initrd.size)?;
+//! println!("{}", map);
+//! ```
+
+
+use core::fmt;
+
+use crate::mem::{Region, PAGE_SIZE};
+use crate::mem::frame::FrameError;
+#[cfg(all(feature="allocator", feature="spin"))]
+use crate::mem::alloc::RegionError;
+
+
+/// Maximal number of entries of the merged map
+pub const MAX_MAP_ENTRIES: usize = 128;
+
+/// Maximal number of ranges the map is built from (bootloader entries, clipped and taken ranges)
+pub const MAX_MAP_RANGES: usize = 192;
+
+/// Maximal number of reserved ranges in one [`MapConfig`]
+pub const MAX_RESERVED: usize = 16;
+
+
+/// Kind of physical memory
+/// - ordered from the least to the most restrictive kind, the more restrictive kind wins where ranges overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryKind {
+    /// Free memory
+    Usable,
+    /// Memory used by the bootloader, free once its structures are not needed
+    Reclaimable,
+    /// ACPI tables, free once they are parsed
+    Acpi,
+    /// ACPI non-volatile storage, must be preserved
+    AcpiNvs,
+    /// Memory given to the heap (by [`init::memory_map`](super::memory_map))
+    Heap,
+    /// Bitmap of the frame allocator (by [`init::memory_map`](super::memory_map))
+    FrameBitmap,
+    /// Firmware, MMIO or memory reserved by the caller
+    Reserved,
+    /// The kernel image
+    Kernel,
+    /// Broken memory
+    Bad,
+}
+
+impl MemoryKind {
+
+    /// Classifies Limine memory map entry type
+    pub const fn from_limine(kind: u64) -> Self {
+        match kind {
+            0 => Self::Usable,
+            2 => Self::Acpi,
+            3 => Self::AcpiNvs,
+            4 => Self::Bad,
+            5 => Self::Reclaimable,
+            6 => Self::Kernel,
+            _ => Self::Reserved,    //  reserved, framebuffer
+        }
+    }
+
+    /// Classifies Multiboot2 memory map entry type
+    pub const fn from_multiboot2(kind: u32) -> Self {
+        match kind {
+            1 => Self::Usable,
+            3 => Self::Acpi,
+            4 => Self::AcpiNvs,
+            5 => Self::Bad,
+            _ => Self::Reserved,
+        }
+    }
+
+    /// Classifies UEFI memory descriptor type
+    /// - loader and boot services memory is reclaimable (after `ExitBootServices`)
+    pub const fn from_uefi(kind: u32) -> Self {
+        match kind {
+            1..=4 => Self::Reclaimable,
+            7 => Self::Usable,
+            8 => Self::Bad,
+            9 => Self::Acpi,
+            10 => Self::AcpiNvs,
+            _ => Self::Reserved,    //  runtime services, MMIO, PAL code, persistent memory
+        }
+    }
+
+    /// Returns short description of the kind
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Usable => "usable",
+            Self::Reclaimable => "reclaimable",
+            Self::Acpi => "ACPI tables",
+            Self::AcpiNvs => "ACPI NVS",
+            Self::Heap => "heap",
+            Self::FrameBitmap => "frame bitmap",
+            Self::Reserved => "reserved",
+            Self::Kernel => "kernel",
+            Self::Bad => "bad memory",
+        }
+    }
+
+    /// Checks if the memory may be handed out once its current user is done with it
+    /// - `Usable` and `Reclaimable` memory is shrunk to whole pages, other kinds are grown
+    #[inline]
+    pub const fn is_free(&self) -> bool {
+        matches!(self, Self::Usable | Self::Reclaimable)
+    }
+
+}
+
+impl fmt::Display for MemoryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+
+/// Error returned by the memory map operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+    /// The map has too many entries or ranges (see `MAX_MAP_ENTRIES` and `MAX_MAP_RANGES`)
+    Full,
+    /// No usable range is large enough
+    NoMemory,
+    /// The heap refused its region
+    #[cfg(all(feature="allocator", feature="spin"))]
+    Heap(RegionError),
+    /// The frame allocator could not be initialized
+    Frames(FrameError),
+}
+
+impl MapError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "memory map is full",
+            Self::NoMemory => "not enough usable memory",
+            #[cfg(all(feature="allocator", feature="spin"))]
+            Self::Heap(e) => e.as_str(),
+            Self::Frames(e) => e.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<FrameError> for MapError {
+    fn from(e: FrameError) -> Self {
+        Self::Frames(e)
+    }
+}
+
+
+/// One entry of the merged memory map
+/// - addresses are physical
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapEntry {
+    pub base: usize,
+    pub size: usize,
+    pub kind: MemoryKind,
+}
+
+impl MapEntry {
+
+    /// Returns the first address after the entry
+    #[inline]
+    pub const fn end(&self) -> usize {
+        self.base + self.size
+    }
+
+    /// Returns the entry as `Region`
+    /// - the virtual address is `base + phys_offset` (the higher half direct map)
+    #[inline]
+    pub const fn region<const ALIGN: usize>(&self, phys_offset: usize) -> Region<ALIGN> {
+        Region::new(self.base.wrapping_add(phys_offset), self.base, self.size)
+    }
+
+}
+
+
+/// One range the map is built from (`end` is exclusive)
+#[derive(Clone, Copy)]
+struct Range {
+    base: usize,
+    end: usize,
+    kind: MemoryKind,
+}
+
+impl Range {
+    const EMPTY: Self = Self { base: 0, end: 0, kind: MemoryKind::Usable };
+}
+
+
+/// Merged map of the physical memory
+/// - entries are sorted by address and do not overlap, memory that no entry covers is unknown
+pub struct MemoryMap {
+    ranges: [Range; MAX_MAP_RANGES],
+    ranges_len: usize,
+    entries: [MapEntry; MAX_MAP_ENTRIES],
+    len: usize,
+}
+
+impl MemoryMap {
+
+    /// Constructs empty map
+    pub const fn new() -> Self {
+        Self {
+            ranges: [Range::EMPTY; MAX_MAP_RANGES],
+            ranges_len: 0,
+            entries: [MapEntry { base: 0, size: 0, kind: MemoryKind::Usable }; MAX_MAP_ENTRIES],
+            len: 0,
+        }
+    }
+
+    /// Constructs map from the `(base, length, kind)` entries of the bootloader
+    /// - returns `Err(Full)` if there are too many entries
+    pub fn from_entries<I, K>(entries: I) -> Result<Self, MapError>
+    where I: IntoIterator<Item = (usize, usize, K)>, K: Into<MemoryKind> {
+        let mut this = Self::new();
+
+        for (base, size, kind) in entries {
+            this.push_range(base, size, kind.into())?;
+        }
+
+        this.rebuild()?;
+        Ok(this)
+    }
+
+    /// Adds range of memory to the map
+    /// - the range overrides less restrictive kinds it overlaps with
+    /// - returns `Err(Full)` if the map is full, the map is not changed then
+    pub fn add(&mut self, base: usize, size: usize, kind: MemoryKind) -> Result<(), MapError> {
+        let ranges_len = self.ranges_len;
+        self.push_range(base, size, kind)?;
+
+        if let Err(e) = self.rebuild() {
+            self.ranges_len = ranges_len;
+            self.rebuild()?;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Clips range out of the usable memory
+    /// - same as `add(base, size, MemoryKind::Reserved)`
+    #[inline]
+    pub fn reserve(&mut self, base: usize, size: usize) -> Result<(), MapError> {
+        self.add(base, size, MemoryKind::Reserved)
+    }
+
+    /// Takes `size` bytes (rounded up to whole pages) of usable memory and marks them as `kind`
+    /// - the smallest usable entry that is large enough is used, the memory is taken from its end
+    /// - returns physical address of the taken memory
+    /// - returns `Err(NoMemory)` if no usable entry is large enough
+    pub fn take(&mut self, size: usize, kind: MemoryKind) -> Result<usize, MapError> {
+        let size = size.checked_next_multiple_of(PAGE_SIZE).ok_or(MapError::NoMemory)?;
+
+        let entry = self.iter()
+            .filter(|e| e.kind == MemoryKind::Usable && e.size >= size)
+            .min_by_key(|e| e.size)
+            .ok_or(MapError::NoMemory)?;
+
+        let base = entry.end() - size;
+        self.add(base, size, kind)?;
+
+        Ok(base)
+    }
+
+    /// Returns the entries of the map
+    #[inline]
+    pub fn entries(&self) -> &[MapEntry] {
+        &self.entries[..self.len]
+    }
+
+    /// Returns iterator over the entries of the map
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, MapEntry> {
+        self.entries().iter()
+    }
+
+    /// Returns number of bytes of the given kind
+    pub fn total(&self, kind: MemoryKind) -> usize {
+        self.iter().filter(|e| e.kind == kind).map(|e| e.size).sum()
+    }
+
+    /// Returns the largest entry of the given kind
+    pub fn largest(&self, kind: MemoryKind) -> Option<MapEntry> {
+        self.iter().filter(|e| e.kind == kind).max_by_key(|e| e.size).copied()
+    }
+
+
+    /// Records range, rounds it to whole pages
+    fn push_range(&mut self, base: usize, size: usize, kind: MemoryKind) -> Result<(), MapError> {
+        let end = base.saturating_add(size);
+
+        let (base, end) = if kind.is_free() {
+            (base.checked_next_multiple_of(PAGE_SIZE).unwrap_or(end), end / PAGE_SIZE * PAGE_SIZE)
+        } else {
+            (base / PAGE_SIZE * PAGE_SIZE, end.checked_next_multiple_of(PAGE_SIZE).unwrap_or(usize::MAX / PAGE_SIZE * PAGE_SIZE))
+        };
+
+        if base >= end {
+            return Ok(());
+        }
+
+        let range = self.ranges.get_mut(self.ranges_len).ok_or(MapError::Full)?;
+        *range = Range { base, end, kind };
+        self.ranges_len += 1;
+
+        Ok(())
+    }
+
+    /// Builds the merged entries from the ranges
+    /// - walks the boundaries of the ranges in order, each piece between two boundaries gets the most restrictive kind
+    fn rebuild(&mut self) -> Result<(), MapError> {
+        self.len = 0;
+
+        let ranges = &self.ranges[..self.ranges_len];
+
+        let Some(mut cur) = ranges.iter().map(|r| r.base).min() else {
+            return Ok(());
+        };
+
+        //  next boundary after `cur`
+        let next_boundary = |cur: usize| ranges.iter()
+            .flat_map(|r| [r.base, r.end])
+            .filter(|&b| b > cur)
+            .min();
+
+        while let Some(next) = next_boundary(cur) {
+            let kind = ranges.iter()
+                .filter(|r| r.base <= cur && cur < r.end)
+                .map(|r| r.kind)
+                .max();
+
+            if let Some(kind) = kind {
+                match self.entries[..self.len].last_mut() {
+                    Some(last) if last.kind == kind && last.end() == cur => last.size += next - cur,
+                    _ => {
+                        let entry = self.entries.get_mut(self.len).ok_or(MapError::Full)?;
+                        *entry = MapEntry { base: cur, size: next - cur, kind };
+                        self.len += 1;
+                    },
+                }
+            }
+
+            cur = next;
+        }
+
+        Ok(())
+    }
+
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for MemoryMap {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {:<18}  {:<18}  {:>12}  kind", "base", "end", "size")?;
+
+        for entry in self.iter() {
+            writeln!(f, "  {:#018x}  {:#018x}  {:>8} KiB  {}", entry.base, entry.end(), entry.size / 1024, entry.kind)?;
+        }
+
+        write!(f, "  usable: {} KiB, reclaimable: {} KiB, heap: {} KiB",
+            self.total(MemoryKind::Usable) / 1024,
+            self.total(MemoryKind::Reclaimable) / 1024,
+            self.total(MemoryKind::Heap) / 1024)
+    }
+
+}
+
+impl fmt::Debug for MemoryMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+
+/// Options of [`init::memory_map`](super::memory_map)
+/// - const builder, e.g. `MapConfig::new(hhdm_offset).kernel(kernel_phys, kernel_size).heap_size(64 * MB)`
+#[derive(Debug, Clone, Copy)]
+pub struct MapConfig {
+    phys_offset: usize,
+    heap_size: usize,
+    kernel: (usize, usize),
+    reserved: [(usize, usize); MAX_RESERVED],
+    reserved_len: usize,
+}
+
+impl MapConfig {
+
+    /// Default size of the heap (in bytes)
+    pub const DEFAULT_HEAP_SIZE: usize = 16 * crate::mem::MB;
+
+    /// Constructs the default options
+    /// - all physical memory must be mapped at virtual address `phys + phys_offset` (e.g. the Limine HHDM)
+    pub const fn new(phys_offset: usize) -> Self {
+        Self {
+            phys_offset,
+            heap_size: Self::DEFAULT_HEAP_SIZE,
+            kernel: (0, 0),
+            reserved: [(0, 0); MAX_RESERVED],
+            reserved_len: 0,
+        }
+    }
+
+    /// Sets size of the heap
+    /// - the heap gets at most the largest usable entry
+    pub const fn heap_size(mut self, bytes: usize) -> Self {
+        self.heap_size = bytes;
+        self
+    }
+
+    /// Sets physical location of the kernel image
+    /// - if it is not set, `mem::kernel::LAYOUT` is used (see `Layout::set_phys`)
+    pub const fn kernel(mut self, phys: usize, size: usize) -> Self {
+        self.kernel = (phys, size);
+        self
+    }
+
+    /// Adds physical range that must not be handed out (initrd, framebuffer, ...)
+    /// - **panics** if more than `MAX_RESERVED` ranges are added
+    pub const fn reserve(mut self, phys: usize, size: usize) -> Self {
+        assert!(self.reserved_len < MAX_RESERVED, "too many reserved ranges");
+        self.reserved[self.reserved_len] = (phys, size);
+        self.reserved_len += 1;
+        self
+    }
+
+    /// Returns the offset of the direct map
+    pub const fn get_phys_offset(&self) -> usize {
+        self.phys_offset
+    }
+
+    /// Returns size of the heap
+    pub const fn get_heap_size(&self) -> usize {
+        self.heap_size
+    }
+
+    /// Returns physical location of the kernel image as `(phys, size)`
+    pub const fn get_kernel(&self) -> (usize, usize) {
+        self.kernel
+    }
+
+    /// Returns the reserved ranges as `(phys, size)`
+    pub fn get_reserved(&self) -> &[(usize, usize)] {
+        &self.reserved[..self.reserved_len]
+    }
+
+}
@@ -0,0 +1,108 @@
+//! Provides all the initialization functions needed to make the `ministd` work
+
+
+use core::ptr::NonNull;
+
+#[cfg(all(feature="allocator", feature="spin"))]
+use crate::mem::{Region, alloc, PAGE_ALIGN};
+#[cfg(feature = "spin")]
+use crate::mem::kernel;
+#[cfg(feature = "renderer")]
+use ::renderer::MinistdRenderer;
+
+pub mod memmap;
+pub use memmap::{MemoryMap, MemoryKind, MapEntry, MapError, MapConfig};
+
+/// initializes allocator (heap)
+#[cfg(all(feature="allocator", feature="spin"))]
+#[inline]
+pub fn allocator(region: Region<PAGE_ALIGN>) -> Result<(), Option<&'static str>> {
+    alloc::init(region)
+}
+
+/// initializes heap and frame allocator from the boot memory map
+/// - `entries` are the `(base, length, kind)` entries of the bootloader (see [`memmap`])
+/// - the kernel image and the reserved ranges of `config` are clipped out of the usable memory
+///   - if `config` sets no kernel image, the one of `mem::kernel::LAYOUT` is used (if its physical address is set)
+/// - the heap gets `config.get_heap_size()` bytes (at most the largest usable entry)
+/// - the frame allocator gets the rest of the usable memory, its bitmap is taken from the usable memory as well
+///   - it is not initialized if no usable memory is left
+/// - returns the final map, which can be printed as a table
+/// - returns `Err` if the map does not fit (see `memmap::MAX_MAP_ENTRIES`) or the allocators refuse the memory
+///
+/// # Safety
+/// - the entries must describe the physical memory correctly
+/// - all physical memory must be mapped at `phys + config.get_phys_offset()`
+/// - reclaimable memory is not used, so the boot structures stay valid
+#[cfg(all(feature="allocator", feature="spin"))]
+pub unsafe fn memory_map<I, K>(entries: I, config: &MapConfig) -> Result<MemoryMap, MapError>
+where I: IntoIterator<Item = (usize, usize, K)>, K: Into<MemoryKind> {
+    use crate::mem::frame::{self, FrameError, FRAME_SIZE, MAX_FRAME_REGIONS};
+
+    let mut map = MemoryMap::from_entries(entries)?;
+
+    let (kernel, kernel_size) = match config.get_kernel() {
+        (_, 0) => kernel::LAYOUT.read().phys_range().unwrap_or((0, 0)),
+        range => range,
+    };
+    map.add(kernel, kernel_size, MemoryKind::Kernel)?;
+    for &(base, size) in config.get_reserved() {
+        map.reserve(base, size)?;
+    }
+
+    let offset = config.get_phys_offset();
+
+    let largest = map.largest(MemoryKind::Usable).ok_or(MapError::NoMemory)?;
+    let heap_size = core::cmp::min(config.get_heap_size(), largest.size);
+    let heap = map.take(heap_size, MemoryKind::Heap)?;
+
+    unsafe { alloc::ALLOCATOR.add_to_heap(Region::new(heap.wrapping_add(offset), heap, heap_size)) }
+        .map_err(MapError::Heap)?;
+
+    //  the usable entries as frame regions
+    let usable = |map: &MemoryMap| -> Result<([Region<FRAME_SIZE>; MAX_FRAME_REGIONS], usize), MapError> {
+        let mut regions = [Region::empty(); MAX_FRAME_REGIONS];
+        let mut len = 0;
+        for entry in map.iter().filter(|e| e.kind == MemoryKind::Usable) {
+            *regions.get_mut(len).ok_or(FrameError::TooManyRegions)? = entry.region(offset);
+            len += 1;
+        }
+        Ok((regions, len))
+    };
+
+    let (regions, len) = usable(&map)?;
+    let words = frame::bitmap_words(&regions[..len]);
+    if words == 0 {
+        return Ok(map);
+    }
+
+    //  taking the bitmap only shrinks the span of the usable memory, so `words` are still enough
+    let bitmap = map.take(words * size_of::<u64>(), MemoryKind::FrameBitmap)?;
+    let (regions, len) = usable(&map)?;
+
+    if len != 0 {
+        let bitmap = unsafe { core::slice::from_raw_parts_mut(bitmap.wrapping_add(offset) as *mut u64, words) };
+        frame::init(bitmap, &regions[..len])?;
+    }
+
+    Ok(map)
+}
+
+/// initializes renderer
+/// - needed to print text to the screen
+#[cfg(all(feature="renderer", feature="spin"))]
+#[inline]
+pub fn renderer(fb: NonNull<u32>, width: usize, height: usize) -> Result<(), ()> {
+    crate::RENDERER.lock().init(fb, width, height)
+}
+
+/// initializes metadata about kernel memory layout
+/// - available in the `ministd::mem::kernel` module
+/// - needs the `kernel-layout` feature and the symbols of the linker script (see `mem::kernel::LINKER_SCRIPT`),
+///   the layout stays empty without it
+/// - the physical addresses are set by `kernel::LAYOUT.write().set_phys(phys)`
+#[cfg(feature = "spin")]
+pub fn memory() {
+    kernel::LAYOUT.write().init();
+}
+
@@ -2,12 +2,15 @@
 //	this file originally belonged to baseOS project
 //		an OS template on which to build
 
+#[cfg(all(target_arch = "x86_64", not(feature = "testing")))]
 use core::arch::asm;
 
 pub use inner::*;
 
 
-#[cfg(not(target_arch = "x86_64"))]
+//  stubs for other architectures and for the hosted build (feature `testing`)
+//  - port I/O and `cli`/`sti` would fault in a user-space process
+#[cfg(any(not(target_arch = "x86_64"), feature = "testing"))]
 mod inner {
     /// Does not support other architectures that x86_64
     pub fn outb(_: u16, _: u8) {}
@@ -30,15 +33,29 @@ mod inner {
     /// Does not support other architectures that x86_64
     pub fn inq(_: u16) -> u64 { 0 }
 
+    /// Does not support other architectures that x86_64
+    pub fn wait() {}
+
     pub mod int {
         /// Does not support other architectures that x86_64
         pub fn disable() {}
         /// Does not support other architectures that x86_64
         pub fn enable() {}
+        /// Does not support other architectures that x86_64
+        pub fn are_enabled() -> bool { false }
+    }
+
+    pub mod tlb {
+        /// Does not support other architectures that x86_64
+        pub fn flush(_: usize) {}
+        /// Does not support other architectures that x86_64
+        pub fn flush_all() {}
+        /// Does not support other architectures that x86_64
+        pub fn cr3() -> usize { 0 }
     }
 }
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", not(feature = "testing")))]
 mod inner {
     use core::arch::asm;
     /// Sends byte to a specified port
@@ -160,6 +177,52 @@ mod inner {
         //#[cfg(not(feature = "testing"))]
         unsafe { asm!("sti", options(nostack, nomem)); }
     }
+
+    /// Checks if interrupts are enabled (the `IF` flag is set)
+    #[inline(always)]
+    pub fn are_enabled() -> bool {
+        let flags: u64;
+        unsafe { asm!("pushfq", "pop {}", out(reg) flags, options(nomem, preserves_flags)); }
+        flags & (1 << 9) != 0
+    }
 }
 
+    pub mod tlb {
+        use core::arch::asm;
+
+        /// Invalidates the TLB entry of the page containing `addr` (`invlpg`)
+        #[inline(always)]
+        pub fn flush(addr: usize) {
+            unsafe { asm!("invlpg [{}]", in(reg) addr, options(nostack, preserves_flags)); }
+        }
+
+        /// `PGE` bit of `cr4` (global pages enabled)
+        const CR4_PGE: usize = 1 << 7;
+
+        /// Invalidates all TLB entries, global ones included
+        /// - toggles `cr4.PGE` if global pages are enabled, reloads `cr3` otherwise
+        #[inline(always)]
+        pub fn flush_all() {
+            let cr4: usize;
+            unsafe { asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags)); }
+
+            if cr4 & CR4_PGE != 0 {
+                unsafe {
+                    asm!("mov cr4, {}", in(reg) cr4 & !CR4_PGE, options(nostack, preserves_flags));
+                    asm!("mov cr4, {}", in(reg) cr4, options(nostack, preserves_flags));
+                }
+            } else {
+                unsafe { asm!("mov {0}, cr3", "mov cr3, {0}", out(reg) _, options(nostack, preserves_flags)); }
+            }
+        }
+
+        /// Returns the value of `cr3` (physical address of the active top-level page table and flags)
+        #[inline(always)]
+        pub fn cr3() -> usize {
+            let cr3: usize;
+            unsafe { asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags)); }
+            cr3
+        }
+    }
+
 }
\ No newline at end of file
@@ -41,6 +41,8 @@ pub use mem::string::ToString;
 
 #[cfg(all(feature="vector", feature="allocator", feature="spin"))]
 pub use mem::vec::{self, Vec};
+#[cfg(all(feature="vector", feature="allocator", feature="spin"))]
+pub use mem::vec_deque::{self, VecDeque};
 
 #[cfg(all(feature="box", feature="allocator", feature="spin"))]
 pub use mem::boxed::Box;
@@ -48,7 +50,7 @@ pub use mem::boxed::Box;
 pub use mem::array::Array;
 
 #[cfg(all(feature="allocator", feature="spin"))]
-pub use mem::alloc::{self, ALLOCATOR, Allocator};
+pub use mem::alloc::{self, ALLOCATOR, Allocator, AllocRef, Global, AllocError, TryReserveError};
 
 #[cfg(all(feature="rc", feature="allocator", feature="spin"))]
 pub use mem::rc::Rc;
@@ -60,6 +62,7 @@ pub use borrow::*;
 pub use spin;
 use proc_macro;
 
+//  `#[oom]` defines the last-resort oom handler, it is only called with the `oom-handler` feature
 pub use proc_macro::{/*entry, */oom, /*region_finder, testing, test_only*/};
 
 //  remote crates
@@ -74,20 +77,26 @@ pub mod assert {
 pub use spin::{Mutex, MutexGuard,
     RwLock, RwLockReadGuard, RwLockWriteGuard, RwLockUpgradableGuard,
     Lazy, Barrier, Once};
+#[cfg(feature="spin")]
+pub use sync::{IrqMutex, IrqMutexGuard};
 
 #[cfg(all(feature="hashmap", feature="allocator", feature="spin"))]
 pub use hashbrown::{HashMap, HashSet, HashTable};
 
+#[cfg(not(feature = "testing"))]
 use core::arch::asm;
+#[cfg(not(feature = "testing"))]
 use core::hint::spin_loop;
 pub use core::convert::{Infallible, From, TryFrom, Into, TryInto};
 
 
 #[cfg(feature = "allocator")]
-pub type HeapRef<'l> = spin::MutexGuard<'l, allocator::Heap>;
+pub type HeapRef<'l> = sync::IrqMutexGuard<'l, allocator::Heap>;
 
 
 /// disables interrupts and halts the CPU
+/// - panics in the hosted build (feature `testing`), there is no CPU to halt
+#[cfg(not(feature = "testing"))]
 pub fn hang() -> ! {
     loop {
         io::int::disable();
@@ -96,6 +105,13 @@ pub fn hang() -> ! {
     }
 }
 
+/// disables interrupts and halts the CPU
+/// - panics in the hosted build (feature `testing`), there is no CPU to halt
+#[cfg(feature = "testing")]
+pub fn hang() -> ! {
+    panic!("hang() called in the hosted build");
+}
+
 
 /// Allows cloning if failure is possible
 pub trait TryClone {
@@ -0,0 +1,164 @@
+//	mem/alloc/alloc_ref.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Allocator parameter of the collections
+//! - every collection (`Vec`, `String`, `Box`, `Array`, `Rc`, `Arc`, `DynamicBuffer`) takes an allocator as its last generic parameter
+//! - the parameter defaults to [`Global`] (the `ALLOCATOR`), so `Vec<T>` still allocates on the kernel heap
+//!
+//! # Using another allocator
+//! ```
+//! let arena: MyArena = ...;
+//! let mut v: Vec<u32, 0, 0, &MyArena> = Vec::new_in(&arena);
+//! v.push(1);
+//! ```
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr::{copy_nonoverlapping, write_bytes, NonNull};
+
+use crate::ALLOCATOR;
+use super::AllocError;
+
+
+/// Allocator that can be used by the collections
+/// - stable-Rust replacement of `core::alloc::Allocator`
+/// - returns `Err` (with the requested layout and the reason) if allocation fails
+///
+/// # Safety
+/// - memory returned by `allocate` must stay valid until it is passed to `deallocate`
+/// - memory may be deallocated through any copy (or reference) of the allocator it was allocated with
+/// - returned memory must satisfy both size and align of the `layout`
+pub unsafe trait AllocRef {
+
+    /// Allocates memory described by `layout`
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// Allocates memory described by `layout` and sets all bytes to zero
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocate(layout)?;
+        unsafe { write_bytes(ptr.as_ptr(), 0, layout.size()); }
+        Ok(ptr)
+    }
+
+    /// Deallocates memory
+    ///
+    /// # Safety
+    /// - `ptr` must be allocated by this allocator with the same `layout`
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Moves memory to larger block
+    /// - the old block is deallocated on success and left untouched on failure
+    ///
+    /// # Safety
+    /// - `ptr` must be allocated by this allocator with the `old` layout
+    /// - `new.size()` must be greater or equal to `old.size()`
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe { move_block(self, ptr, old, new, old.size()) }
+    }
+
+    /// Moves memory to smaller block
+    /// - the old block is deallocated on success and left untouched on failure
+    ///
+    /// # Safety
+    /// - `ptr` must be allocated by this allocator with the `old` layout
+    /// - `new.size()` must be smaller or equal to `old.size()`
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe { move_block(self, ptr, old, new, new.size()) }
+    }
+
+}
+
+unsafe impl<A: AllocRef + ?Sized> AllocRef for &A {
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate_zeroed(layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).grow(ptr, old, new) }
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).shrink(ptr, old, new) }
+    }
+
+}
+
+
+/// The global allocator (`ALLOCATOR`) as `AllocRef`
+/// - default allocator of all collections
+/// - has no members, so it takes no memory in the collections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl AllocRef for Global {
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        NonNull::new(unsafe { ALLOCATOR.alloc(layout) }).ok_or(AllocError::out_of_memory(layout))
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        NonNull::new(unsafe { ALLOCATOR.alloc_zeroed(layout) }).ok_or(AllocError::out_of_memory(layout))
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { ALLOCATOR.dealloc(ptr.as_ptr(), layout) }
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        if new.align() != old.align() {
+            return unsafe { move_block(self, ptr, old, new, old.size()) };
+        }
+        NonNull::new(unsafe { ALLOCATOR.realloc(ptr.as_ptr(), old, new.size()) }).ok_or(AllocError::out_of_memory(new))
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        if new.align() != old.align() {
+            return unsafe { move_block(self, ptr, old, new, new.size()) };
+        }
+        NonNull::new(unsafe { ALLOCATOR.realloc(ptr.as_ptr(), old, new.size()) }).ok_or(AllocError::out_of_memory(new))
+    }
+
+}
+
+/// Moves `count` bytes to new block and deallocates the old one
+#[cfg_attr(feature = "alloc-tracking", track_caller)]
+unsafe fn move_block<A: AllocRef + ?Sized>(alloc: &A, ptr: NonNull<u8>, old: Layout, new: Layout, count: usize) -> Result<NonNull<u8>, AllocError> {
+    let data = alloc.allocate(new)?;
+    unsafe {
+        copy_nonoverlapping(ptr.as_ptr(), data.as_ptr(), count);
+        alloc.deallocate(ptr, old);
+    }
+    Ok(data)
+}
@@ -0,0 +1,147 @@
+//	mem/alloc/fault.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Allocation failure injection (the `fault-injection` feature)
+//! - makes the `ALLOCATOR` return null on purpose, so that the `try_*` paths of the collections can be tested
+//! - one [`Fault`] is active at a time, injecting a new one replaces the old one
+//! - injected failures return null right away (the reclaim handlers and the `#[oom]` handler do not run)
+//! - growing `realloc` calls can fail as well, shrinking ones never do
+//!
+//! # Example
+//! ```
+//! let mut v = Vec::from([1, 2, 3]);
+//! v.shrink_to_fit();
+//!
+//! let _fault = fault::inject(Fault::Nth(1));
+//! assert!(v.try_push(4).is_err());
+//! assert_eq!(v, [1, 2, 3]);
+//! ```
+
+
+use core::alloc::Layout;
+use core::sync::atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering};
+
+use super::{HEAP, MinistdAllocator};
+
+
+/// Kind of injected failures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fault {
+    /// Fails the Nth allocation from now (`1` fails the next one)
+    /// - allocations before and after it succeed
+    Nth(usize),
+    /// Fails every allocation with probability `p` (`0.0..=1.0`)
+    /// - the random numbers come from a PRNG seeded with `seed`, so runs are reproducible
+    Probability { p: f64, seed: u64 },
+    /// Fails every allocation that would make the heap hold more than this many bytes
+    /// - uses the same count as `ALLOCATOR.allocated_bytes()`
+    Budget(usize),
+}
+
+/// Guard returned by [`inject`]
+/// - stops the injection when dropped
+#[must_use = "the injection stops once the guard is dropped"]
+pub struct FaultGuard {
+    _private: (),
+}
+
+impl Drop for FaultGuard {
+    #[inline]
+    fn drop(&mut self) {
+        disable();
+    }
+}
+
+
+const OFF: u8 = 0;
+const NTH: u8 = 1;
+const PROBABILITY: u8 = 2;
+const BUDGET: u8 = 3;
+
+/// Active kind of failures
+static MODE: AtomicU8 = AtomicU8::new(OFF);
+/// Countdown (`Nth`), threshold out of `2^32` (`Probability`) or byte limit (`Budget`)
+static ARG: AtomicU64 = AtomicU64::new(0);
+/// State of the PRNG
+static STATE: AtomicU64 = AtomicU64::new(0);
+/// Number of injected failures
+static INJECTED: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Starts injecting allocation failures
+/// - returns guard that stops the injection when dropped
+pub fn inject(fault: Fault) -> FaultGuard {
+    MODE.store(OFF, Ordering::Release);
+
+    let mode = match fault {
+        Fault::Nth(n) => {
+            ARG.store(n as u64, Ordering::Relaxed);
+            NTH
+        },
+        Fault::Probability { p, seed } => {
+            let p = if p.is_nan() { 0.0 } else { p.clamp(0.0, 1.0) };
+            ARG.store((p * (1u64 << 32) as f64) as u64, Ordering::Relaxed);
+            //  xorshift gets stuck on zero
+            STATE.store(if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed }, Ordering::Relaxed);
+            PROBABILITY
+        },
+        Fault::Budget(bytes) => {
+            ARG.store(bytes as u64, Ordering::Relaxed);
+            BUDGET
+        },
+    };
+
+    MODE.store(mode, Ordering::Release);
+
+    FaultGuard { _private: () }
+}
+
+/// Stops injecting allocation failures
+/// - the guard of the active injection does this when dropped
+#[inline]
+pub fn disable() {
+    MODE.store(OFF, Ordering::Release);
+}
+
+/// Returns number of failures injected so far
+#[inline]
+pub fn injected() -> usize {
+    INJECTED.load(Ordering::Relaxed)
+}
+
+
+/// Decides if the allocation has to fail
+/// - must not be called while holding the `HEAP` lock
+pub(crate) fn should_fail(layout: Layout) -> bool {
+    let fail = match MODE.load(Ordering::Acquire) {
+        NTH => ARG.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1)) == Ok(1),
+        PROBABILITY => next_random() >> 32 < ARG.load(Ordering::Relaxed),
+        BUDGET => {
+            let allocated = HEAP.lock().allocated_bytes() as u64;
+            allocated.saturating_add(layout.size() as u64) > ARG.load(Ordering::Relaxed)
+        },
+        _ => return false,
+    };
+
+    if fail {
+        INJECTED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fail
+}
+
+/// Returns next number of the xorshift64* generator
+fn next_random() -> u64 {
+    let step = |mut x: u64| {
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        x
+    };
+
+    let old = STATE.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x))).unwrap_or_default();
+
+    step(old).wrapping_mul(0x2545_f491_4f6c_dd1d)
+}
@@ -0,0 +1,74 @@
+//	mem/alloc/forbid.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Sections of code that must not allocate
+//! - interrupt handlers, the scheduler or code running with a lock the oom handlers need can mark themselves with [`forbid_alloc`]
+//! - allocating while the returned guard lives fails a debug assertion (builds without `debug_assertions` only count the guards)
+//! - the guards are counted per CPU if the CPU-id provider is set (see [`percpu::set_cpu_id_provider`]), otherwise globally
+//!
+//! # Example
+//! ```
+//! fn timer_handler() {
+//!     let _no_alloc = alloc::forbid_alloc();
+//!     TICKS.fetch_add(1, Ordering::Relaxed);
+//! }
+//! ```
+//!
+//! [`percpu::set_cpu_id_provider`]: super::percpu::set_cpu_id_provider
+
+
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::percpu::{self, MAX_CPUS};
+
+
+/// Number of live guards of each CPU
+/// - the last slot is shared by CPUs without their own slot (and by all CPUs before the CPU-id provider is set)
+static FORBIDDEN: [AtomicUsize; MAX_CPUS + 1] = [const { AtomicUsize::new(0) }; MAX_CPUS + 1];
+
+
+/// Guard returned by [`forbid_alloc`]
+/// - allocation is allowed again once all guards of the CPU are dropped
+/// - the guard cannot leave the CPU (thread) that created it
+#[must_use = "allocation is allowed again once the guard is dropped"]
+pub struct NoAllocGuard {
+    slot: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for NoAllocGuard {
+    #[inline]
+    fn drop(&mut self) {
+        FORBIDDEN[self.slot].fetch_sub(1, Ordering::Release);
+    }
+}
+
+
+/// Forbids allocation on the current CPU until the returned guard is dropped
+/// - guards nest
+#[inline]
+pub fn forbid_alloc() -> NoAllocGuard {
+    let slot = slot();
+    FORBIDDEN[slot].fetch_add(1, Ordering::Acquire);
+
+    NoAllocGuard { slot, _not_send: PhantomData }
+}
+
+/// Checks if allocation is forbidden on the current CPU
+#[inline]
+pub fn alloc_forbidden() -> bool {
+    FORBIDDEN[slot()].load(Ordering::Acquire) != 0
+}
+
+
+/// Returns slot of the current CPU
+#[inline]
+fn slot() -> usize {
+    match percpu::cpu_id() {
+        Some(id) if id < MAX_CPUS => id,
+        _ => MAX_CPUS,
+    }
+}
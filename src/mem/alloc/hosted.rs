@@ -0,0 +1,47 @@
+//	mem/alloc/hosted.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Heap of the hosted build (feature `testing`)
+//! - the crate can be linked into a normal Linux program (e.g. `cargo test --features testing`)
+//!   - `ALLOCATOR` is not the `#[global_allocator]`, so it does not clash with the allocator of `std`
+//!   - the collections still allocate through `ALLOCATOR`, so they are tested against the real heap
+//! - the heap is seeded with `HOSTED_HEAP_SIZE` bytes of a static buffer on the first allocation
+//!   - more memory can be added with `ALLOCATOR.add_to_heap` as usual
+
+
+use core::cell::UnsafeCell;
+
+use crate::mem::{Region, MB, PAGE_ALIGN};
+use crate::spin::Once;
+
+
+/// Size of the static buffer the heap is seeded with (in bytes)
+pub const HOSTED_HEAP_SIZE: usize = 64 * MB;
+
+
+#[repr(C, align(4096))]
+struct Buffer(UnsafeCell<[u8; HOSTED_HEAP_SIZE]>);
+
+//  the buffer is only handed to the heap, which does its own locking
+unsafe impl Sync for Buffer {}
+
+static BUFFER: Buffer = Buffer(UnsafeCell::new([0; HOSTED_HEAP_SIZE]));
+
+static SEEDED: Once = Once::new();
+
+
+/// Gives the static buffer to the heap (only the first call does anything)
+/// - called by the allocator before every allocation
+#[inline]
+pub fn seed() {
+    SEEDED.call_once(|| {
+        let addr = BUFFER.0.get() as usize;
+        let region = Region::<PAGE_ALIGN>::new(addr, addr, HOSTED_HEAP_SIZE);
+
+        if let Err(e) = unsafe { super::ALLOCATOR.add_to_heap(region) } {
+            panic!("failed to seed the hosted heap: {}", e);
+        }
+    });
+}
@@ -0,0 +1,583 @@
+//	mem/heap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//  this file implements features of the buddy_system_allocator
+
+//! This is your kernel heap module
+//! - allocates/deallocates memory
+
+
+/*pub use allocator::Heap;
+pub use allocator::MinistdAllocator as Allocator;
+use spin::Mutex;
+
+pub type LockedHeap = spin::Mutex<Heap>;
+
+pub static ALLOCATOR: Mutex<()> = Mutex::new(());*/
+
+//#[global_allocator]
+//pub static ALLOCATOR: LockedHeap = LockedHeap::new(empty!(););
+
+//static ALLOCATOR: 
+
+//pub use buddy_system_allocator as allocator;
+pub use allocator::{Heap, MinistdAllocator, HeapStats, RemoveError};
+pub use allocator::{AllocError, AllocErrorKind, TryReserveError};
+pub use core::alloc::GlobalAlloc;
+pub use core::alloc::Layout;
+use core::mem::MaybeUninit;
+use core::ptr::drop_in_place;
+use core::ptr::{copy_nonoverlapping, null_mut, NonNull};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::mem::*;
+
+use crate::sync::IrqMutex;
+use crate::convert::Align;
+
+pub mod alloc_ref;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod forbid;
+#[cfg(feature = "testing")]
+pub mod hosted;
+pub mod oom;
+pub mod percpu;
+pub mod regions;
+#[cfg(feature = "alloc-tracking")]
+pub mod tracking;
+
+pub use alloc_ref::{AllocRef, Global};
+pub use forbid::{forbid_alloc, alloc_forbidden, NoAllocGuard};
+pub use regions::RegionError;
+use regions::REGIONS;
+
+//pub type Heap = allocator::Heap<32>;
+
+/// The default Allocator type for ministd
+/// - has no members on purpose to prevent taking any memory with the use of `Global` allocator in other crates
+pub struct Allocator;
+
+impl Allocator {
+    pub(crate) const fn new() -> Self { Self { } }
+
+    /// Allocates memory from the per-CPU caches or the heap
+    /// 
+    /// if allocation fails:
+    /// - returns all objects in the per-CPU caches to the heap and tries again
+    /// - runs the registered reclaim handlers (see [`oom`]), tries again after each one that freed memory
+    /// - runs the `#[oom]` handler (feature `oom-handler`)
+    ///   - success: try allocation again
+    ///   - failure: returns null
+    /// 
+    /// **panics** (debug builds) if allocation is forbidden on the current CPU (see [`forbid`])
+    unsafe fn heap_alloc(&self, layout: Layout) -> *mut u8 {
+
+        debug_assert!(!alloc_forbidden(), "allocation of {} bytes where allocating is forbidden", layout.size());
+
+        #[cfg(feature = "testing")]
+        hosted::seed();
+
+        let layout = percpu::normalize(layout);
+
+        if let Some(ptr) = percpu::alloc(layout) {
+            return ptr.as_ptr();
+        }
+
+        unsafe {
+            let ptr = HEAP.lock().alloc(layout);
+
+            if ptr.is_null() {
+                //  the caches must be flushed without holding the heap lock
+                percpu::flush_all();
+
+                let ptr = HEAP.lock().alloc(layout);
+                if !ptr.is_null() {
+                    return ptr;
+                }
+
+                OOM_INVOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+                //  the handlers free memory, so they run without the heap lock
+                //  - freed objects may land in the per-CPU caches, flush them before every retry
+                let reclaimed = oom::reclaim(layout, || {
+                    percpu::flush_all();
+                    NonNull::new(HEAP.lock().alloc(layout))
+                });
+                if let Some(ptr) = reclaimed {
+                    return ptr.as_ptr();
+                }
+
+                #[cfg(feature = "oom-handler")]
+                let ptr = {
+                    let mut guard = HEAP.lock();
+                    match __ministd_oom_handler(&mut guard, &self) {
+                        Ok(_) => guard.alloc(layout),
+                        Err(_) => null_mut()
+                    }
+                };
+
+                #[cfg(not(feature = "oom-handler"))]
+                let ptr: *mut u8 = null_mut();
+
+                if ptr.is_null() {
+                    FAILED_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                }
+
+                return ptr;
+            }
+
+            ptr
+        }
+    }
+}
+
+//  Representation of the `MinistdAllocator` trait
+//  - all methods go through `GlobalAlloc` so that small allocations are served by the per-CPU caches
+impl Allocator {
+
+    /// Tries to allocate data of type `T`
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate<T: Sized>(&self, val: T) -> Result<NonNull<T>, T> {
+        match NonNull::new(unsafe { self.alloc(Layout::new::<T>()) }) {
+            Some(ptr) => {
+                let ptr = ptr.cast();
+                unsafe { ptr.write(val); }
+                Ok(ptr)
+            },
+            None => Err(val),
+        }
+    }
+
+    /// Tries to allocate uninitialized data
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_uninit<T: Sized>(&self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        let layout = Layout::new::<T>();
+        match NonNull::new(unsafe { self.alloc(layout) }) {
+            Some(ptr) => Ok(ptr.cast()),
+            None => Err(AllocError::out_of_memory(layout)),
+        }
+    }
+
+    /// Tries to allocate data and sets all bytes to zero
+    /// - runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_zeroed<T: Sized>(&self) -> Result<NonNull<MaybeUninit<T>>, AllocError> {
+        let layout = Layout::new::<T>();
+        match NonNull::new(unsafe { self.alloc_zeroed(layout) }) {
+            Some(ptr) => Ok(ptr.cast()),
+            None => Err(AllocError::out_of_memory(layout)),
+        }
+    }
+
+    /// Allocates an array and uses of type T
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_array<T: Sized + Clone>(&self, size: usize, val: T) -> Result<NonNull<[T]>, AllocError> {
+        let data = unsafe { self.allocate_array_uninit::<T>(size)? };
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(val.clone());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(NonNull::slice_from_raw_parts(data.cast(), size))
+    }
+    
+    /// Allocates and array and uses the closure to determine the value of each element
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_array_with<T: Sized, F: FnMut() -> T>(&self, size: usize, f: &mut F) -> Result<NonNull<[T]>, AllocError> {
+        let data = unsafe { self.allocate_array_uninit::<T>(size)? };
+
+        unsafe {
+            let mut ptr: NonNull<T> = data.cast();
+            for _ in 0..size {
+                ptr.write(f());
+                ptr = ptr.add(1);
+            }
+        }
+
+        Ok(NonNull::slice_from_raw_parts(data.cast(), size))
+    }
+
+    /// Allocates uninitialized array of `T`
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_array_uninit<T: Sized>(&self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+
+        match NonNull::new(unsafe { self.alloc(layout) }) {
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr.cast(), size)),
+            None => Err(AllocError::out_of_memory(layout)),
+        }
+    }
+
+    /// Allocates uninitialized array of `T` with all bytes set to `0`
+    /// - Runs the `oom` handler upon failure, then tries again
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn allocate_array_zeroed<T: Sized>(&self, size: usize) -> Result<NonNull<[MaybeUninit<T>]>, AllocError> {
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+
+        match NonNull::new(unsafe { self.alloc_zeroed(layout) }) {
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr.cast(), size)),
+            None => Err(AllocError::out_of_memory(layout)),
+        }
+    }
+
+    /// Deallocates the pointer and `drop`s the inner value if needed
+    #[inline]
+    pub unsafe fn delete<T: Sized>(&self, ptr: NonNull<T>) {
+        unsafe {
+            drop_in_place(ptr.as_ptr());
+            self.dealloc(ptr.cast().as_ptr(), Layout::new::<T>());
+        }
+    }
+
+    /// Deallocates the pointer (whether it is pointing to an array or not)
+    /// - `drop`s the data in the pointer
+    pub unsafe fn deallocate_layout<T: Sized>(&self, mut ptr: NonNull<T>, layout: Layout) {
+        unsafe {
+            drop_in_place(ptr.as_mut());
+            self.dealloc(ptr.cast().as_ptr(), layout);
+        }
+    }
+
+    /// Deallocates the array and `drop`s each element if needed
+    #[inline]
+    pub unsafe fn delete_array<T: Sized>(&mut self, mut ptr: NonNull<[T]>) {
+        unsafe {
+            drop_in_place(ptr.as_mut());
+            self.dealloc(ptr.cast().as_ptr(), layout_arr::<T>(ptr.len()));
+        }
+    }
+
+    /// Reallocates array into new buffer, running the `oom` handler upon failure, tries again if needed
+    /// - Does not drop eny elements
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub unsafe fn reallocate<T: Sized + Default>(&self, ptr: NonNull<[T]>, size: usize) -> Result<NonNull<[T]>, AllocError> {
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+        if size == ptr.len() { return Ok(ptr) }
+
+        let new = AllocError::array_layout::<T>(size)?;
+
+        let data = unsafe { self.realloc(ptr.cast().as_ptr(), layout_arr::<T>(ptr.len()), new.size()) };
+
+        match NonNull::new(data) {
+            Some(data) => Ok(NonNull::slice_from_raw_parts(data.cast(), size)),
+            None => Err(AllocError::out_of_memory(new)),
+        }
+    }
+
+
+    /// Adds memory region to the allocator
+    /// - the region is recorded in the region set (see [`regions`])
+    /// - returns `Err` if the region is empty, overlaps with another region or the allocator refuses it
+    pub unsafe fn add_to_heap(&self, region: Region<PAGE_ALIGN>) -> Result<(), RegionError> {
+        let mut set = REGIONS.lock();
+
+        set.check(&region)?;
+
+        let start = NonNull::new(region.virt() as *mut u8).ok_or(RegionError::Empty)?;
+
+        unsafe { HEAP.lock().add_to_heap(start, region.size()) }.map_err(|_| RegionError::Rejected)?;
+
+        set.insert(region);
+
+        Ok(())
+    }
+
+    /// Removes memory region from the allocator
+    /// - `region` must be the same as the one given to `add_to_heap()`
+    /// - returns `Err(InUse)` if the region has live allocations
+    ///   - objects in the per-CPU caches are returned to the heap first
+    ///
+    /// # Safety
+    /// - the memory of the region must not be used after it is removed (e.g. through dangling pointers to freed blocks)
+    pub unsafe fn remove_from_heap(&self, region: Region<PAGE_ALIGN>) -> Result<(), RegionError> {
+        let mut set = REGIONS.lock();
+
+        let index = set.find(&region).ok_or(RegionError::NotFound)?;
+
+        percpu::flush_all();
+
+        let start = NonNull::new(region.virt() as *mut u8).ok_or(RegionError::Empty)?;
+
+        unsafe { HEAP.lock().remove_from_heap(start, region.size()) }.map_err(RegionError::from)?;
+
+        set.remove(index);
+
+        Ok(())
+    }
+
+    /// Shrinks memory region of the allocator to `size` bytes
+    /// - the end of the region is removed from the heap
+    /// - `size` is aligned up to `PAGE_ALIGN`
+    /// - removes the whole region if `size` is zero
+    /// - returns `Err(Invalid)` if `size` is not smaller than the region
+    /// - returns `Err(InUse)` if the removed part has live allocations
+    ///
+    /// # Safety
+    /// - the removed part must not be used after the call (e.g. through dangling pointers to freed blocks)
+    pub unsafe fn shrink_region(&self, region: Region<PAGE_ALIGN>, size: usize) -> Result<(), RegionError> {
+        let size = size.align(PAGE_ALIGN);
+
+        if size == 0 {
+            return unsafe { self.remove_from_heap(region) };
+        }
+
+        let mut set = REGIONS.lock();
+
+        let index = set.find(&region).ok_or(RegionError::NotFound)?;
+
+        if size >= region.size() {
+            return Err(RegionError::Invalid);
+        }
+
+        percpu::flush_all();
+
+        let tail = NonNull::new(unsafe { region.virt().add(size) } as *mut u8).ok_or(RegionError::Empty)?;
+
+        unsafe { HEAP.lock().remove_from_heap(tail, region.size() - size) }.map_err(RegionError::from)?;
+
+        set.get_mut(index).resize(size);
+
+        Ok(())
+    }
+
+    /// Returns actual number of bytes in the heap
+    #[inline]
+    pub fn total_bytes(&self) -> usize {
+        #[cfg(feature = "testing")]
+        hosted::seed();
+        HEAP.lock().total_bytes()
+    }
+
+    /// Returns number of bytes that are allocated
+    /// - objects in the per-CPU caches are counted as allocated
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        #[cfg(feature = "testing")]
+        hosted::seed();
+        HEAP.lock().allocated_bytes()
+    }
+
+    /// Returns snapshot of the heap state
+    /// - objects in the per-CPU caches are counted as allocated
+    /// - can be printed with `println!("{}", ALLOCATOR.stats())`
+    pub fn stats(&self) -> HeapStats {
+        #[cfg(feature = "testing")]
+        hosted::seed();
+        let mut stats = HEAP.lock().stats();
+        stats.failed_allocations = FAILED_ALLOCATIONS.load(Ordering::Relaxed);
+        stats.oom_invocations = OOM_INVOCATIONS.load(Ordering::Relaxed);
+        stats.reallocations = REALLOCATIONS.load(Ordering::Relaxed);
+        stats.in_place_reallocations = IN_PLACE_REALLOCATIONS.load(Ordering::Relaxed);
+        stats
+    }
+
+}
+
+
+
+unsafe impl GlobalAlloc for Allocator {
+
+    /// allocates new data on the heap
+    /// - small allocations are served by the per-CPU caches (see [`percpu`])
+    /// - the allocation is recorded with the caller location with the `alloc-tracking` feature (see [`tracking`])
+    /// 
+    /// if allocation fails:
+    /// - returns all objects in the per-CPU caches to the heap and tries again
+    /// - runs the registered reclaim handlers (see [`oom`])
+    /// - runs the `#[oom]` handler (feature `oom-handler`)
+    ///   - success: try allocation again
+    ///   - failure: returns null
+    /// 
+    /// returns null without trying if a failure is injected (feature `fault-injection`, see [`fault`])
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+
+        #[cfg(feature = "fault-injection")]
+        if fault::should_fail(layout) {
+            return null_mut();
+        }
+
+        let ptr = unsafe { self.heap_alloc(layout) };
+
+        #[cfg(feature = "alloc-tracking")]
+        if !ptr.is_null() {
+            tracking::record(ptr, layout, core::panic::Location::caller());
+        }
+
+        ptr
+    }
+
+    /// same as `alloc` but zeroes the allocated buffer
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+
+        let data = unsafe { self.alloc(layout) };
+
+        if data.is_null() {
+            null_mut()
+        } else {
+            unsafe {
+                core::ptr::write_bytes(data, 0, layout.size())
+            }
+            data
+        }
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let Some(ptr) = NonNull::new(ptr) else {
+            return
+        };
+
+        #[cfg(feature = "alloc-tracking")]
+        tracking::forget(ptr.as_ptr());
+
+        let layout = percpu::normalize(layout);
+
+        if !percpu::dealloc(ptr, layout) {
+            unsafe { HEAP.lock().dealloc(ptr.as_ptr(), layout); }
+        }
+
+        #[cfg(feature = "debug-heap")]
+        report_violation();
+    }
+
+    /// reallocates memory
+    /// - does not deallocate the old buffer if allocation fails
+    /// - returns the same pointer if both layouts fall into the same size class
+    /// - tries to resize the block in the heap without moving it, copies only if that fails
+    /// 
+    /// used layout: `Layout::from_size_unchecked(new_size, layout.align())`
+    /// 
+    /// **panics** (debug builds) if allocation is forbidden on the current CPU (see [`forbid`])
+    /// 
+    /// returns null if a failure is injected and the block grows (feature `fault-injection`, see [`fault`])
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+
+        debug_assert!(!alloc_forbidden(), "reallocation to {} bytes where allocating is forbidden", new_size);
+
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        #[cfg(feature = "fault-injection")]
+        if new_size > layout.size() && fault::should_fail(new_layout) {
+            return null_mut();
+        }
+
+        REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+        let (old, new) = (percpu::normalize(layout), percpu::normalize(new_layout));
+
+        let in_place = old == new || match NonNull::new(ptr) {
+            Some(block) => unsafe { HEAP.lock().resize_in_place(block, old, new.size()) }.is_ok(),
+            None => false,
+        };
+
+        if in_place {
+            IN_PLACE_REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "alloc-tracking")]
+            tracking::resize(ptr, new_size);
+            return ptr;
+        }
+        
+        //  not through `alloc`, the injected failure was already decided
+        let new = unsafe {
+            self.heap_alloc(new_layout)
+        };
+
+        if new.is_null() {
+            return null_mut();
+        }
+
+        #[cfg(feature = "alloc-tracking")]
+        tracking::record(new, new_layout, core::panic::Location::caller());
+
+        let count = core::cmp::min(new_size, layout.size());
+        unsafe {
+            copy_nonoverlapping(ptr, new, count);
+            self.dealloc(ptr, layout);
+        }
+        new
+    }
+
+}
+
+
+/// This is the global allocator for BaseOS
+/// 
+/// It is used by all structures that are working with heap as the default allocator
+/// - it is not the `#[global_allocator]` in the hosted build (feature `testing`, see [`hosted`])
+#[cfg_attr(not(feature = "testing"), global_allocator)]
+pub static ALLOCATOR: Allocator = Allocator::new();
+
+///// This is the Heap used by the `ALLOCATOR`
+/// - interrupts are disabled while the lock is held, so interrupt handlers can allocate
+pub(crate) static HEAP: IrqMutex<Heap> = IrqMutex::new(Heap::new());
+    // use Vec later
+
+/// Number of allocations that returned null
+static FAILED_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of `oom` handler invocations
+static OOM_INVOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of `realloc` calls
+static REALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of `realloc` calls that did not move the block
+static IN_PLACE_REALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+
+#[cfg(feature = "oom-handler")]
+unsafe extern "Rust" {
+
+    //  functions defined by the developer in the main crate
+
+    //pub(crate) fn __region_finder() -> Result<Region, Option<&'static str>>;
+    //pub(crate) fn out_of_memory_handler(heap: &mut MutexGuard<Heap>, allocator: &Allocator) -> Result<(), ()>;
+
+    /// The last-resort handler defined with `#[ministd::oom]`
+    /// - runs with the heap lock held, after all registered reclaim handlers failed
+    pub(crate) fn __ministd_oom_handler(heap: &mut crate::HeapRef, alloc: &crate::Allocator) -> Result<(), ()>;
+}
+
+
+
+
+/// Initializes heap by giving the allocator memory region
+/// 
+/// You can list the regions of the heap with the [`regions::regions`] function
+pub(crate) fn init(region: Region<PAGE_ALIGN>) -> Result<(), Option<&'static str>> {
+
+    unsafe { ALLOCATOR.add_to_heap(region) }.map_err(|e| Some(e.as_str()))
+
+}
+
+/// Reports heap corruption detected by the debug heap
+/// - must not be called while holding the `HEAP` lock (the report allocates)
+#[cfg(feature = "debug-heap")]
+fn report_violation() {
+    let violation = HEAP.lock().take_violation();
+    if let Some(violation) = violation {
+        crate::panic_fmt!("heap corruption: {}", violation);
+    }
+}
+
+pub(crate) const fn layout_arr<T: Sized>(size: usize) -> Layout {
+    unsafe {
+        Layout::from_size_align_unchecked(size_of::<T>() * size, align_of::<T>())
+    }
+}
\ No newline at end of file
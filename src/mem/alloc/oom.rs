@@ -0,0 +1,205 @@
+//	mem/alloc/oom.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Registry of out-of-memory (reclaim) handlers
+//! - subsystems that keep memory they can give back (page caches, glyph caches, ...) register a reclaim callback
+//! - when an allocation fails, the allocator walks the callbacks from the highest priority to the lowest
+//!   and retries the allocation after every callback that reports freed memory
+//! - callbacks run **without** the heap lock, so they free memory through the usual `Box`/`Vec`/`ALLOCATOR` paths
+//! - callbacks may run on several CPUs at once, allocations inside of a callback never run the callbacks again
+//! - the `#[ministd::oom]` function (feature `oom-handler`) runs only after all callbacks failed, as the last resort
+//!
+//! # Example
+//! ```
+//! fn shrink_glyphs(layout: Layout) -> usize {
+//!     GLYPHS.lock().evict(layout.size())
+//! }
+//!
+//! let id = oom::register("glyph cache", 10, shrink_glyphs)?;
+//! // ...
+//! oom::unregister(id);
+//! ```
+
+
+use core::alloc::Layout;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::sync::IrqMutex;
+use super::percpu::{self, MAX_CPUS};
+
+
+/// Maximal number of registered handlers
+pub const MAX_OOM_HANDLERS: usize = 16;
+
+
+/// Reclaim callback
+/// - receives the layout of the failed allocation
+/// - returns number of bytes it freed (`0` if it could not free anything)
+/// - must not hold any lock that the allocating code may hold
+pub type OomCallback = fn(Layout) -> usize;
+
+
+/// Identifies registered handler, returned by [`register`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OomHandlerId(usize);
+
+
+/// Error returned by [`register`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OomError {
+    /// The registry is full (see `MAX_OOM_HANDLERS`)
+    Full,
+}
+
+impl OomError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "too many oom handlers",
+        }
+    }
+}
+
+impl fmt::Display for OomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Registered handler
+#[derive(Clone, Copy)]
+struct Entry {
+    id: usize,
+    name: &'static str,
+    priority: i32,
+    callback: OomCallback,
+}
+
+/// Description of a registered handler, returned by [`handlers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OomHandlerInfo {
+    pub id: OomHandlerId,
+    pub name: &'static str,
+    pub priority: i32,
+}
+
+
+/// Handlers sorted by priority (the highest first)
+struct Registry {
+    entries: [Option<Entry>; MAX_OOM_HANDLERS],
+    len: usize,
+}
+
+static REGISTRY: IrqMutex<Registry> = IrqMutex::new(Registry {
+    entries: [None; MAX_OOM_HANDLERS],
+    len: 0,
+});
+
+/// Source of the handler ids
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Set while the handlers run on a CPU, allocations inside of the handlers do not run them again
+/// - one flag for each CPU, so a failed allocation on one CPU does not skip the handlers on the others
+/// - CPUs without an id (see [`percpu::set_cpu_id_provider`]) share the last flag
+static RECLAIMING: [AtomicBool; MAX_CPUS + 1] = [const { AtomicBool::new(false) }; MAX_CPUS + 1];
+
+
+/// Registers reclaim callback
+/// - handlers with higher `priority` run first, handlers with the same priority run in the order of registration
+/// - the handlers run once per CPU at a time, which needs the CPU-id provider (see [`percpu::set_cpu_id_provider`])
+///   - without it all CPUs count as one, an allocation that fails while the handlers run on another CPU does not run them
+/// - returns `Err(Full)` if `MAX_OOM_HANDLERS` handlers are registered
+pub fn register(name: &'static str, priority: i32, callback: OomCallback) -> Result<OomHandlerId, OomError> {
+    let mut registry = REGISTRY.lock();
+
+    if registry.len == MAX_OOM_HANDLERS {
+        return Err(OomError::Full);
+    }
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    //  keep the entries sorted, the new entry goes after the entries with the same priority
+    let len = registry.len;
+    let index = registry.entries[..len].iter()
+        .position(|e| e.is_some_and(|e| e.priority < priority))
+        .unwrap_or(len);
+
+    registry.entries.copy_within(index..len, index + 1);
+    registry.entries[index] = Some(Entry { id, name, priority, callback });
+    registry.len += 1;
+
+    Ok(OomHandlerId(id))
+}
+
+/// Removes handler from the registry
+/// - returns `false` if the handler is not registered
+pub fn unregister(id: OomHandlerId) -> bool {
+    let mut registry = REGISTRY.lock();
+    let len = registry.len;
+
+    let Some(index) = registry.entries[..len].iter().position(|e| e.is_some_and(|e| e.id == id.0)) else {
+        return false;
+    };
+
+    registry.entries.copy_within(index + 1..len, index);
+    registry.entries[len - 1] = None;
+    registry.len -= 1;
+
+    true
+}
+
+/// Returns number of registered handlers
+pub fn count() -> usize {
+    REGISTRY.lock().len
+}
+
+/// Returns registered handlers in the order they run
+pub fn handlers() -> impl Iterator<Item = OomHandlerInfo> {
+    let entries = REGISTRY.lock().entries;
+
+    entries.into_iter().flatten().map(|e| OomHandlerInfo {
+        id: OomHandlerId(e.id),
+        name: e.name,
+        priority: e.priority,
+    })
+}
+
+
+/// Runs the handlers until `retry` succeeds
+/// - `retry` runs after every handler that freed some memory
+/// - returns `None` if no handler freed enough memory (or the handlers are already running on this CPU)
+pub(crate) fn reclaim<T>(layout: Layout, mut retry: impl FnMut() -> Option<T>) -> Option<T> {
+
+    let reclaiming = &RECLAIMING[percpu::cpu_id().map_or(MAX_CPUS, |id| id.min(MAX_CPUS))];
+
+    if reclaiming.swap(true, Ordering::Acquire) {
+        return None;
+    }
+    let _reclaiming = Reclaiming(reclaiming);
+
+    //  copy the entries, so handlers can (un)register other handlers
+    let entries = REGISTRY.lock().entries;
+
+    let mut ret = None;
+    for entry in entries.iter().flatten() {
+        if (entry.callback)(layout) > 0 && let Some(r) = retry() {
+            ret = Some(r);
+            break;
+        }
+    }
+
+    ret
+}
+
+/// Clears the `RECLAIMING` flag of the CPU on drop, even if a handler panics
+struct Reclaiming(&'static AtomicBool);
+
+impl Drop for Reclaiming {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,278 @@
+//	mem/alloc/percpu.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Per-CPU allocation caches
+//! - small allocations and frees are served from a CPU-local stash of free objects (magazine)
+//!   - magazines are refilled from and drained to the global `HEAP` in batches of `BATCH` objects
+//!   - common-path allocations never touch the global `HEAP` lock
+//! - caching is disabled until the CPU-id provider is set with [`set_cpu_id_provider`]
+//! - caching is always disabled with the `debug-heap` feature (the debug heap has to see every free with its exact layout)
+//!
+//! # Size classes
+//! Cacheable layouts are rounded up to one of the size classes (`16` to `512` bytes)
+//! - every cacheable layout is normalized by [`normalize`] before it reaches the heap
+//!   - the heap therefore sees the same layout for allocation and deallocation no matter if the object went through the cache or not
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr::{NonNull, null_mut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::spin::Mutex;
+use super::HEAP;
+
+
+/// Maximal number of CPUs that have their own cache
+/// - CPUs with larger id always use the global heap
+pub const MAX_CPUS: usize = 16;
+
+/// Object sizes served by the caches (in bytes)
+pub const CLASSES: [usize; 6] = [16, 32, 64, 128, 256, 512];
+
+/// Number of objects that fit one magazine
+pub const MAGAZINE_SIZE: usize = 32;
+
+/// Number of objects moved between magazine and the heap at once
+pub const BATCH: usize = 16;
+
+
+/// Stash of free objects of one size class
+struct Magazine {
+    objects: [*mut u8; MAGAZINE_SIZE],
+    count: usize,
+}
+
+impl Magazine {
+    const fn new() -> Self {
+        Self {
+            objects: [null_mut(); MAGAZINE_SIZE],
+            count: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn pop(&mut self) -> Option<NonNull<u8>> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        NonNull::new(self.objects[self.count])
+    }
+
+    #[inline(always)]
+    fn push(&mut self, ptr: NonNull<u8>) {
+        self.objects[self.count] = ptr.as_ptr();
+        self.count += 1;
+    }
+
+    #[inline(always)]
+    const fn is_full(&self) -> bool { self.count == MAGAZINE_SIZE }
+}
+
+
+/// Cache of one CPU
+struct CpuCache {
+    magazines: [Magazine; CLASSES.len()],
+}
+
+//  the cache only holds objects owned by the heap
+unsafe impl Send for CpuCache {}
+
+impl CpuCache {
+    const fn new() -> Self {
+        Self {
+            magazines: [const { Magazine::new() }; CLASSES.len()],
+        }
+    }
+
+    /// Moves up to `count` objects from the magazine back to the heap
+    fn drain(&mut self, class: usize, count: usize) {
+        let layout = class_layout(class);
+        let magazine = &mut self.magazines[class];
+        let heap = HEAP.lock();
+        for _ in 0..count {
+            match magazine.pop() {
+                Some(ptr) => unsafe { heap.dealloc(ptr.as_ptr(), layout) },
+                None => break,
+            }
+        }
+    }
+
+    /// Fills the magazine with up to `BATCH` objects from the heap
+    /// - returns `false` if the heap could not provide any object
+    fn refill(&mut self, class: usize) -> bool {
+        let layout = class_layout(class);
+        let magazine = &mut self.magazines[class];
+        let heap = HEAP.lock();
+        for _ in 0..BATCH {
+            match NonNull::new(unsafe { heap.alloc(layout) }) {
+                Some(ptr) => magazine.push(ptr),
+                None => break,
+            }
+        }
+        magazine.count != 0
+    }
+
+    /// Returns all cached objects back to the heap
+    fn flush(&mut self) {
+        for class in 0..CLASSES.len() {
+            self.drain(class, MAGAZINE_SIZE);
+        }
+    }
+}
+
+
+static CACHES: [Mutex<CpuCache>; MAX_CPUS] = [const { Mutex::new(CpuCache::new()) }; MAX_CPUS];
+
+/// Address of the CPU-id provider (`0` if not set)
+static CPU_ID: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Sets function that returns id of the current CPU
+/// - enables the per-CPU caches
+/// - the function must be cheap and must not allocate (it runs on every allocation)
+/// - ids must be unique for each CPU, CPUs with id `>= MAX_CPUS` bypass the caches
+pub fn set_cpu_id_provider(provider: fn() -> usize) {
+    CPU_ID.store(provider as usize, Ordering::Release);
+}
+
+/// Disables the per-CPU caches and returns all cached objects back to the heap
+pub fn disable() {
+    CPU_ID.store(0, Ordering::Release);
+    flush_all();
+}
+
+/// Checks if the per-CPU caches are enabled
+#[inline]
+pub fn enabled() -> bool {
+    CPU_ID.load(Ordering::Acquire) != 0
+}
+
+/// Returns all objects cached by the current CPU back to the heap
+/// - does nothing if the caches are disabled
+pub fn flush() {
+    if let Some(mut cache) = current() {
+        cache.flush();
+    }
+}
+
+/// Returns all objects cached by all CPUs back to the heap
+/// - caches that are in use at the moment are skipped
+pub fn flush_all() {
+    for cache in CACHES.iter() {
+        if let Some(mut cache) = cache.try_lock() {
+            cache.flush();
+        }
+    }
+}
+
+/// Returns number of bytes that sit in the per-CPU caches
+/// - these bytes are counted as allocated by the heap
+/// - caches that are in use at the moment are skipped
+pub fn cached_bytes() -> usize {
+    let mut bytes = 0;
+    for cache in CACHES.iter() {
+        if let Some(cache) = cache.try_lock() {
+            for (class, magazine) in cache.magazines.iter().enumerate() {
+                bytes += magazine.count * CLASSES[class];
+            }
+        }
+    }
+    bytes
+}
+
+
+/// Returns the layout the heap uses for `layout`
+/// - cacheable layouts are rounded up to their size class
+/// - other layouts are returned unchanged
+#[inline]
+pub(crate) const fn normalize(layout: Layout) -> Layout {
+    if cfg!(feature = "debug-heap") {
+        return layout;
+    }
+    match class_of(layout) {
+        Some(class) => class_layout(class),
+        None => layout,
+    }
+}
+
+/// Tries to allocate object from the cache of the current CPU
+/// - `layout` must be normalized
+/// - returns `None` if the allocation has to be served by the heap
+pub(crate) fn alloc(layout: Layout) -> Option<NonNull<u8>> {
+    let class = class_of(layout)?;
+    let mut cache = current()?;
+
+    if let Some(ptr) = cache.magazines[class].pop() {
+        return Some(ptr);
+    }
+
+    if !cache.refill(class) {
+        return None;
+    }
+
+    cache.magazines[class].pop()
+}
+
+/// Tries to return object to the cache of the current CPU
+/// - `layout` must be normalized
+/// - returns `false` if the object has to be returned to the heap
+pub(crate) fn dealloc(ptr: NonNull<u8>, layout: Layout) -> bool {
+    let Some(class) = class_of(layout) else {
+        return false;
+    };
+    let Some(mut cache) = current() else {
+        return false;
+    };
+
+    if cache.magazines[class].is_full() {
+        cache.drain(class, BATCH);
+    }
+
+    cache.magazines[class].push(ptr);
+    true
+}
+
+
+/// Returns id of the current CPU
+/// - returns `None` if the CPU-id provider is not set
+pub(crate) fn cpu_id() -> Option<usize> {
+    let provider = CPU_ID.load(Ordering::Acquire);
+    if provider == 0 {
+        return None;
+    }
+
+    let provider: fn() -> usize = unsafe { core::mem::transmute(provider) };
+    Some(provider())
+}
+
+/// Locks cache of the current CPU
+/// - returns `None` if the caches are disabled, the CPU has no cache or the cache is in use (for example by interrupted code)
+fn current() -> Option<spin::MutexGuard<'static, CpuCache>> {
+    if cfg!(feature = "debug-heap") {
+        return None;
+    }
+
+    CACHES.get(cpu_id()?)?.try_lock()
+}
+
+/// Returns index of the size class for `layout`
+const fn class_of(layout: Layout) -> Option<usize> {
+    let size = if layout.size() > layout.align() { layout.size() } else { layout.align() };
+
+    if size > CLASSES[CLASSES.len() - 1] {
+        return None;
+    }
+
+    let size = if size < CLASSES[0] { CLASSES[0] } else { size.next_power_of_two() };
+
+    Some((size.trailing_zeros() - CLASSES[0].trailing_zeros()) as usize)
+}
+
+/// Returns layout of objects of some class
+#[inline(always)]
+const fn class_layout(class: usize) -> Layout {
+    unsafe { Layout::from_size_align_unchecked(CLASSES[class], CLASSES[class]) }
+}
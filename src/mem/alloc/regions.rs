@@ -0,0 +1,197 @@
+//	mem/alloc/regions.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Set of memory regions given to the heap
+//! - every region added with `Allocator::add_to_heap` (or `init::allocator`) is recorded here
+//! - regions can be listed with [`regions`]
+
+
+use core::fmt;
+
+use crate::mem::{Region, PAGE_ALIGN};
+use crate::sync::IrqMutex;
+
+
+/// Maximal number of regions the heap can consist of
+pub const MAX_REGIONS: usize = 32;
+
+
+/// Error returned by the heap region operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionError {
+    /// The region has zero size or null address
+    Empty,
+    /// The region set is full (see `MAX_REGIONS`)
+    Full,
+    /// The same region was already added
+    Duplicate,
+    /// The region overlaps with already added region
+    Overlap(Region<PAGE_ALIGN>),
+    /// The region was not added to the heap
+    NotFound,
+    /// Some memory in the region is still allocated
+    InUse,
+    /// The allocator backend is not able to remove memory
+    Unsupported,
+    /// The allocator backend refused the region
+    Rejected,
+    /// The requested size does not shrink the region
+    Invalid,
+}
+
+impl RegionError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Empty => "heap region is empty",
+            Self::Full => "too many heap regions",
+            Self::Duplicate => "heap region was already added",
+            Self::Overlap(_) => "heap region overlaps with another heap region",
+            Self::NotFound => "heap region was not added to the heap",
+            Self::InUse => "heap region has live allocations",
+            Self::Unsupported => "allocator cannot remove heap regions",
+            Self::Rejected => "allocator refused the heap region",
+            Self::Invalid => "new size of the heap region is not smaller",
+        }
+    }
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap(other) => write!(f, "{} ({:#x} - {:#x})",
+                self.as_str(), other.virt() as usize, other.virt() as usize + other.size()),
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+impl From<allocator::RemoveError> for RegionError {
+    fn from(value: allocator::RemoveError) -> Self {
+        match value {
+            allocator::RemoveError::Unsupported => Self::Unsupported,
+            allocator::RemoveError::InUse => Self::InUse,
+        }
+    }
+}
+
+
+/// Fixed-size set of heap regions
+#[derive(Clone, Copy)]
+pub(crate) struct RegionSet {
+    regions: [Region<PAGE_ALIGN>; MAX_REGIONS],
+    len: usize,
+}
+
+impl RegionSet {
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            regions: [Region::empty(); MAX_REGIONS],
+            len: 0,
+        }
+    }
+
+    /// Checks whether `region` can be added to the set
+    pub(crate) fn check(&self, region: &Region<PAGE_ALIGN>) -> Result<(), RegionError> {
+        if region.size() == 0 || region.virt().is_null() {
+            return Err(RegionError::Empty);
+        }
+
+        let start = region.virt() as usize;
+        let end = start.checked_add(region.size()).ok_or(RegionError::Empty)?;
+
+        for other in self.as_slice() {
+            let other_start = other.virt() as usize;
+            let other_end = other_start + other.size();
+
+            if other_start == start && other_end == end {
+                return Err(RegionError::Duplicate);
+            }
+            if start < other_end && other_start < end {
+                return Err(RegionError::Overlap(*other));
+            }
+        }
+
+        if self.len == MAX_REGIONS {
+            return Err(RegionError::Full);
+        }
+
+        Ok(())
+    }
+
+    /// Adds `region` to the set
+    /// - `check()` must succeed for the region
+    pub(crate) fn insert(&mut self, region: Region<PAGE_ALIGN>) {
+        self.regions[self.len] = region;
+        self.len += 1;
+    }
+
+    /// Returns index of the region that starts at the same address and has the same size
+    pub(crate) fn find(&self, region: &Region<PAGE_ALIGN>) -> Option<usize> {
+        self.as_slice().iter().position(|r| r.virt() == region.virt() && r.size() == region.size())
+    }
+
+    /// Removes region at `index`
+    pub(crate) fn remove(&mut self, index: usize) {
+        self.regions.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+    }
+
+    /// Returns mutable reference to region at `index`
+    #[inline]
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut Region<PAGE_ALIGN> {
+        &mut self.regions[..self.len][index]
+    }
+
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[Region<PAGE_ALIGN>] {
+        &self.regions[..self.len]
+    }
+
+}
+
+
+/// Regions of the heap
+pub(crate) static REGIONS: IrqMutex<RegionSet> = IrqMutex::new(RegionSet::new());
+
+
+/// Returns iterator over regions of the heap
+/// - iterates over a snapshot, regions added or removed later are not visible
+pub fn regions() -> Regions {
+    Regions {
+        set: *REGIONS.lock(),
+        index: 0,
+    }
+}
+
+/// Returns number of regions of the heap
+pub fn count() -> usize {
+    REGIONS.lock().len
+}
+
+
+/// Iterator over a snapshot of the heap regions
+pub struct Regions {
+    set: RegionSet,
+    index: usize,
+}
+
+impl Iterator for Regions {
+    type Item = Region<PAGE_ALIGN>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let region = *self.set.as_slice().get(self.index)?;
+        self.index += 1;
+        Some(region)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.set.len - self.index;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Regions {}
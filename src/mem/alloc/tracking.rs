@@ -0,0 +1,345 @@
+//	mem/alloc/tracking.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Allocation tracking and leak reports (the `alloc-tracking` feature)
+//! - every live allocation is recorded with its size, align and call site (`#[track_caller]` location)
+//! - allocations are kept in a fixed-size side table (`TABLE_SIZE` entries)
+//!   - allocations that do not fit are counted, but not recorded
+//!
+//! # Finding leaks
+//! ```
+//! let snapshot = tracking::snapshot();
+//! subsystem::init();
+//! subsystem::teardown();
+//! println!("{}", snapshot.leaks());
+//! ```
+//! - note that `Box::leak`, `Vec::leak`, `String::leak` and `panic_fmt!` leak on purpose
+
+
+use core::alloc::Layout;
+use core::fmt;
+use core::panic::Location;
+
+use crate::sync::IrqMutex;
+
+
+/// Number of allocations that can be recorded
+/// - must be a power of two
+pub const TABLE_SIZE: usize = 4096;
+
+/// Maximal number of call sites in one report
+/// - allocations from other sites are summed up in `LeakReport::other`
+pub const REPORT_SITES: usize = 64;
+
+
+/// One recorded allocation
+#[derive(Clone, Copy)]
+struct Entry {
+    /// Address of the allocation (`0` if the entry is free)
+    ptr: usize,
+    size: usize,
+    align: usize,
+    location: &'static Location<'static>,
+    /// Sequence number of the allocation
+    seq: u64,
+}
+
+impl Entry {
+    const EMPTY: Self = Self { ptr: 0, size: 0, align: 0, location: Location::caller(), seq: 0 };
+}
+
+
+/// Hash table of live allocations (open addressing with linear probing)
+struct Table {
+    entries: [Entry; TABLE_SIZE],
+    /// Number of recorded allocations
+    len: usize,
+    /// Number of live allocations that did not fit the table
+    dropped: usize,
+    /// Sequence number of the next allocation
+    seq: u64,
+}
+
+impl Table {
+
+    const fn new() -> Self {
+        Self {
+            entries: [Entry::EMPTY; TABLE_SIZE],
+            len: 0,
+            dropped: 0,
+            seq: 0,
+        }
+    }
+
+    #[inline(always)]
+    const fn hash(ptr: usize) -> usize {
+        (ptr >> 4).wrapping_mul(0x9e37_79b9_7f4a_7c15u64 as usize) >> (usize::BITS - TABLE_SIZE.trailing_zeros())
+    }
+
+    fn insert(&mut self, ptr: usize, layout: Layout, location: &'static Location<'static>) {
+        let seq = self.seq;
+        self.seq += 1;
+
+        //  keep one entry free so that lookups always terminate
+        if self.len >= TABLE_SIZE - 1 {
+            self.dropped += 1;
+            return;
+        }
+
+        let mut i = Self::hash(ptr);
+        while self.entries[i].ptr != 0 {
+            i = (i + 1) % TABLE_SIZE;
+        }
+
+        self.entries[i] = Entry { ptr, size: layout.size(), align: layout.align(), location, seq };
+        self.len += 1;
+    }
+
+    /// Returns index of the entry of `ptr`
+    /// - returns `None` if the allocation was not recorded
+    fn find(&self, ptr: usize) -> Option<usize> {
+        let mut i = Self::hash(ptr);
+        loop {
+            match self.entries[i].ptr {
+                0 => return None,
+                p if p == ptr => return Some(i),
+                _ => i = (i + 1) % TABLE_SIZE,
+            }
+        }
+    }
+
+    /// Changes the size of the recorded allocation, it keeps its sequence number and call site
+    fn resize(&mut self, ptr: usize, size: usize) {
+        if let Some(i) = self.find(ptr) {
+            self.entries[i].size = size;
+        }
+    }
+
+    fn remove(&mut self, ptr: usize) {
+        let Some(i) = self.find(ptr) else {
+            //  the allocation was not recorded
+            self.dropped = self.dropped.saturating_sub(1);
+            return;
+        };
+
+        self.entries[i] = Entry::EMPTY;
+        self.len -= 1;
+
+        //  move the following entries back so the probe sequences stay unbroken
+        let mut hole = i;
+        let mut j = (i + 1) % TABLE_SIZE;
+        while self.entries[j].ptr != 0 {
+            let home = Self::hash(self.entries[j].ptr);
+            //  the entry can move to the hole if its home is not in (hole, j]
+            let between = if hole <= j { hole < home && home <= j } else { hole < home || home <= j };
+            if !between {
+                self.entries[hole] = self.entries[j];
+                self.entries[j] = Entry::EMPTY;
+                hole = j;
+            }
+            j = (j + 1) % TABLE_SIZE;
+        }
+    }
+
+    /// Groups live allocations made since `since` by their call site
+    fn report(&self, since: u64) -> LeakReport {
+        let mut report = LeakReport::new();
+        report.unrecorded = self.dropped;
+
+        for entry in self.entries.iter() {
+            if entry.ptr == 0 || entry.seq < since {
+                continue;
+            }
+
+            let sites = &mut report.sites[..report.len];
+            match sites.iter_mut().find(|s| core::ptr::eq(s.location, entry.location) || *s.location == *entry.location) {
+                Some(site) => {
+                    site.count += 1;
+                    site.bytes += entry.size;
+                },
+                None if report.len < REPORT_SITES => {
+                    report.sites[report.len] = Site { location: entry.location, count: 1, bytes: entry.size };
+                    report.len += 1;
+                },
+                None => {
+                    report.other.count += 1;
+                    report.other.bytes += entry.size;
+                }
+            }
+        }
+
+        //  largest leaks first
+        report.sites[..report.len].sort_unstable_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        report
+    }
+
+}
+
+
+static TABLE: IrqMutex<Table> = IrqMutex::new(Table::new());
+
+
+/// Records new allocation
+#[inline]
+pub(crate) fn record(ptr: *mut u8, layout: Layout, location: &'static Location<'static>) {
+    TABLE.lock().insert(ptr as usize, layout, location);
+}
+
+/// Removes allocation from the records
+#[inline]
+pub(crate) fn forget(ptr: *mut u8) {
+    TABLE.lock().remove(ptr as usize);
+}
+
+/// Updates the size of allocation that was resized in place
+/// - the allocation stays attributed to its original call site and snapshot
+#[inline]
+pub(crate) fn resize(ptr: *mut u8, size: usize) {
+    TABLE.lock().resize(ptr as usize, size);
+}
+
+
+/// Marks point in time to compare the heap with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    seq: u64,
+}
+
+impl Snapshot {
+    /// Returns allocations made after the snapshot that are still live
+    pub fn leaks(&self) -> LeakReport {
+        TABLE.lock().report(self.seq)
+    }
+}
+
+/// Takes snapshot of the heap
+/// - use `Snapshot::leaks()` later to list allocations that were not freed
+pub fn snapshot() -> Snapshot {
+    Snapshot { seq: TABLE.lock().seq }
+}
+
+/// Returns all live allocations grouped by their call site
+pub fn report() -> LeakReport {
+    TABLE.lock().report(0)
+}
+
+/// Copies live allocations into `buf`
+/// - returns number of copied allocations
+/// - allocations are copied in no particular order
+pub fn allocations(buf: &mut [Allocation]) -> usize {
+    let table = TABLE.lock();
+    let mut copied = 0;
+    for (entry, slot) in table.entries.iter().filter(|e| e.ptr != 0).zip(buf.iter_mut()) {
+        *slot = Allocation {
+            ptr: entry.ptr,
+            layout: unsafe { Layout::from_size_align_unchecked(entry.size, entry.align) },
+            location: entry.location,
+        };
+        copied += 1;
+    }
+    copied
+}
+
+/// Returns number of live allocations
+/// - includes allocations that did not fit the table
+pub fn live() -> usize {
+    let table = TABLE.lock();
+    table.len + table.dropped
+}
+
+
+/// One live allocation
+#[derive(Debug, Clone, Copy)]
+pub struct Allocation {
+    pub ptr: usize,
+    pub layout: Layout,
+    pub location: &'static Location<'static>,
+}
+
+impl Allocation {
+    /// Placeholder to initialize buffers for `allocations()`
+    pub const EMPTY: Self = Self { ptr: 0, layout: Layout::new::<()>(), location: Location::caller() };
+}
+
+/// Live allocations made at one call site
+#[derive(Debug, Clone, Copy)]
+pub struct Site {
+    pub location: &'static Location<'static>,
+    /// Number of live allocations
+    pub count: usize,
+    /// Number of bytes in the live allocations
+    pub bytes: usize,
+}
+
+/// Live allocations grouped by call site
+/// - sorted by the number of bytes (largest first)
+/// - is a copy of the records, so it can be printed without holding any lock
+#[derive(Clone)]
+pub struct LeakReport {
+    sites: [Site; REPORT_SITES],
+    len: usize,
+    /// Allocations from sites that did not fit the report
+    pub other: Site,
+    /// Number of live allocations that did not fit the table
+    pub unrecorded: usize,
+}
+
+impl LeakReport {
+
+    const fn new() -> Self {
+        const EMPTY: Site = Site { location: Location::caller(), count: 0, bytes: 0 };
+        Self {
+            sites: [EMPTY; REPORT_SITES],
+            len: 0,
+            other: EMPTY,
+            unrecorded: 0,
+        }
+    }
+
+    /// Returns the call sites
+    #[inline]
+    pub fn sites(&self) -> &[Site] {
+        &self.sites[..self.len]
+    }
+
+    /// Returns number of allocations in the report
+    pub fn count(&self) -> usize {
+        self.sites().iter().map(|s| s.count).sum::<usize>() + self.other.count
+    }
+
+    /// Returns number of bytes in the report
+    pub fn bytes(&self) -> usize {
+        self.sites().iter().map(|s| s.bytes).sum::<usize>() + self.other.bytes
+    }
+
+    /// Checks if there are no live allocations in the report
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0 && self.unrecorded == 0
+    }
+
+}
+
+impl fmt::Display for LeakReport {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} live allocations ({} bytes)", self.count(), self.bytes())?;
+
+        for site in self.sites() {
+            write!(f, "\n  {}:{}: {} allocations, {} bytes", site.location.file(), site.location.line(), site.count, site.bytes)?;
+        }
+
+        if self.other.count != 0 {
+            write!(f, "\n  other sites: {} allocations, {} bytes", self.other.count, self.other.bytes)?;
+        }
+
+        if self.unrecorded != 0 {
+            write!(f, "\n  not recorded (table full): {} allocations", self.unrecorded)?;
+        }
+
+        Ok(())
+    }
+
+}
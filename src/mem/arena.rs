@@ -0,0 +1,309 @@
+//	mem/arena.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Bump (arena) allocator
+//! - allocations are carved linearly out of one memory block, so they are fast and do not fragment the heap
+//! - memory is given back all at once by `Arena::reset` or up to a `Checkpoint` by `Arena::rewind`
+//! - values placed in the arena are **never dropped**, only their memory is reused
+//!
+//! # Example
+//! ```
+//! let mut arena = Arena::with_capacity(4 * KB);
+//!
+//! let table = arena.alloc_slice(&[1u32, 2, 3]);
+//! let name = arena.alloc_str("RSDT");
+//!
+//! let mark = arena.checkpoint();
+//! let scratch: Vec<u8, 0, 0, &Arena> = Vec::new_in(&arena);
+//! drop(scratch);
+//! arena.rewind(mark);
+//! ```
+
+
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+
+use crate::mem::Region;
+use crate::mem::alloc::{AllocError, AllocRef};
+use crate::mem::boxed::Box;
+
+
+/// Position in an [`Arena`] returned by `Arena::checkpoint`
+/// - rewinding to it frees everything allocated after it was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    top: usize,
+}
+
+
+/// Bump allocator over a `Region` or a boxed buffer
+/// - hands out references that live as long as the borrow of the arena
+///   - the references are mutable although the arena is borrowed shared, every allocation bumps `top` past the returned memory, so they never alias
+/// - `reset` and `rewind` take `&mut self`, so no reference to the freed memory can outlive them
+/// - can be used as the allocator of the collections (`&Arena` implements [`AllocRef`])
+pub struct Arena {
+    start: usize,
+    end: usize,
+    top: Cell<usize>,
+
+    /// Keeps the boxed buffer alive, `None` for arenas made from a `Region`
+    _buf: Option<Box<[MaybeUninit<u8>]>>,
+}
+
+#[allow(clippy::mut_from_ref)]
+impl Arena {
+
+    /// Constructs new `Arena` using `buf` as its memory
+    pub fn new(mut buf: Box<[MaybeUninit<u8>]>) -> Self {
+        let start = buf.as_mut_ptr() as *mut u8 as usize;
+        let end = start + buf.len();
+
+        Self {
+            start,
+            end,
+            top: Cell::new(start),
+            _buf: Some(buf),
+        }
+    }
+
+    /// Constructs new `Arena` with `size` bytes allocated on the heap
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity(size: usize) -> Self {
+        Self::new(Box::new_uninit_slice(size))
+    }
+
+    /// Tries to construct new `Arena` with `size` bytes allocated on the heap
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity(size: usize) -> Result<Self, AllocError> {
+        Ok(Self::new(Box::try_new_uninit_slice(size)?))
+    }
+
+    /// Constructs new `Arena` using the memory described by `region`
+    ///
+    /// # Safety
+    /// - the virtual memory of `region` must be mapped, writable and unused for the whole life of the arena
+    pub unsafe fn from_region<const ALIGN: usize>(region: Region<ALIGN>) -> Self {
+        let start = region.virt() as usize;
+
+        Self {
+            start,
+            end: start + region.size(),
+            top: Cell::new(start),
+            _buf: None,
+        }
+    }
+
+
+    /// Allocates memory for `layout`
+    /// - returns `Err` if there is not enough space left
+    pub fn alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let top = self.top.get();
+
+        let addr = top.checked_add(layout.align() - 1).ok_or(AllocError::out_of_memory(layout))? & !(layout.align() - 1);
+        let new_top = addr.checked_add(layout.size()).ok_or(AllocError::out_of_memory(layout))?;
+
+        if new_top > self.end {
+            return Err(AllocError::out_of_memory(layout));
+        }
+
+        self.top.set(new_top);
+
+        //  `start` is never null, so neither is `addr`
+        Ok(unsafe { NonNull::new_unchecked(addr as *mut u8) })
+    }
+
+    /// Moves `val` into the arena
+    /// - **panics** if there is not enough space left
+    #[inline]
+    pub fn alloc<T>(&self, val: T) -> &mut T {
+        match self.try_alloc(val) {
+            Ok(r) => r,
+            Err(e) => panic!("Arena: {}", e),
+        }
+    }
+
+    /// Tries to move `val` into the arena
+    /// - returns `Err` if there is not enough space left
+    pub fn try_alloc<T>(&self, val: T) -> Result<&mut T, AllocError> {
+        let ptr = self.alloc_layout(Layout::new::<T>())?.cast::<T>();
+
+        unsafe {
+            ptr.write(val);
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Copies `slice` into the arena
+    /// - **panics** if there is not enough space left
+    #[inline]
+    pub fn alloc_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        match self.try_alloc_slice(slice) {
+            Ok(r) => r,
+            Err(e) => panic!("Arena: {}", e),
+        }
+    }
+
+    /// Tries to copy `slice` into the arena
+    /// - returns `Err` if there is not enough space left
+    pub fn try_alloc_slice<T: Copy>(&self, slice: &[T]) -> Result<&mut [T], AllocError> {
+        let layout = Layout::for_value(slice);
+        let ptr = self.alloc_layout(layout)?.cast::<T>();
+
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), ptr.as_ptr(), slice.len());
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), slice.len()))
+        }
+    }
+
+    /// Allocates slice of `len` elements, each constructed by `f(index)`
+    /// - **panics** if there is not enough space left
+    #[inline]
+    pub fn alloc_slice_with<T, F: FnMut(usize) -> T>(&self, len: usize, f: F) -> &mut [T] {
+        match self.try_alloc_slice_with(len, f) {
+            Ok(r) => r,
+            Err(e) => panic!("Arena: {}", e),
+        }
+    }
+
+    /// Tries to allocate slice of `len` elements, each constructed by `f(index)`
+    /// - returns `Err` if there is not enough space left
+    pub fn try_alloc_slice_with<T, F: FnMut(usize) -> T>(&self, len: usize, mut f: F) -> Result<&mut [T], AllocError> {
+        let layout = AllocError::array_layout::<T>(len)?;
+        let ptr = self.alloc_layout(layout)?.cast::<T>();
+
+        unsafe {
+            for i in 0..len {
+                ptr.add(i).write(f(i));
+            }
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), len))
+        }
+    }
+
+    /// Copies `s` into the arena
+    /// - **panics** if there is not enough space left
+    #[inline]
+    pub fn alloc_str(&self, s: &str) -> &mut str {
+        match self.try_alloc_str(s) {
+            Ok(r) => r,
+            Err(e) => panic!("Arena: {}", e),
+        }
+    }
+
+    /// Tries to copy `s` into the arena
+    /// - returns `Err` if there is not enough space left
+    pub fn try_alloc_str(&self, s: &str) -> Result<&mut str, AllocError> {
+        let bytes = self.try_alloc_slice(s.as_bytes())?;
+        Ok(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
+    }
+
+
+    /// Returns the current position in the arena
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { top: self.top.get() }
+    }
+
+    /// Frees everything allocated after `checkpoint` was taken
+    /// - values in the freed memory are not dropped
+    /// - **panics** if `checkpoint` does not belong to this arena
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        assert!(checkpoint.top >= self.start && checkpoint.top <= self.end, "Arena: checkpoint does not belong to this arena");
+        self.top.set(checkpoint.top);
+    }
+
+    /// Frees all memory of the arena
+    /// - values in the arena are not dropped
+    #[inline]
+    pub fn reset(&mut self) {
+        self.top.set(self.start);
+    }
+
+    /// Runs `f` and frees everything it allocated in the arena
+    /// - the result of `f` cannot borrow from the arena
+    pub fn scope<R, F: FnOnce(&Self) -> R>(&mut self, f: F) -> R {
+        let checkpoint = self.checkpoint();
+        let ret = f(self);
+        self.rewind(checkpoint);
+        ret
+    }
+
+
+    /// Returns the size of the arena in bytes
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns the number of used bytes (including align padding)
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.top.get() - self.start
+    }
+
+    /// Returns the number of bytes left
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.end - self.top.get()
+    }
+
+    /// Returns whether `ptr` with `size` bytes is the last allocation of the arena
+    #[inline]
+    fn is_last(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        ptr.as_ptr() as usize + size == self.top.get()
+    }
+
+}
+
+
+/// The arena as the allocator of collections
+/// - deallocating the last allocation gives its memory back, other deallocations do nothing
+/// - the last allocation grows and shrinks in place
+unsafe impl AllocRef for Arena {
+
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        self.alloc_layout(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if self.is_last(ptr, layout.size()) {
+            self.top.set(ptr.as_ptr() as usize);
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        let addr = ptr.as_ptr() as usize;
+
+        if self.is_last(ptr, old.size()) && addr & (new.align() - 1) == 0 {
+            let new_top = addr.checked_add(new.size()).ok_or(AllocError::out_of_memory(new))?;
+            if new_top <= self.end {
+                self.top.set(new_top);
+                return Ok(ptr);
+            }
+        }
+
+        let data = self.alloc_layout(new)?;
+        unsafe { ptr::copy_nonoverlapping(ptr.as_ptr(), data.as_ptr(), old.size()); }
+        Ok(data)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        if ptr.as_ptr() as usize & (new.align() - 1) != 0 {
+            let data = self.alloc_layout(new)?;
+            unsafe { ptr::copy_nonoverlapping(ptr.as_ptr(), data.as_ptr(), new.size()); }
+            return Ok(data);
+        }
+
+        if self.is_last(ptr, old.size()) {
+            self.top.set(ptr.as_ptr() as usize + new.size());
+        }
+        Ok(ptr)
+    }
+
+}
@@ -6,10 +6,11 @@
 //! The `Box` for arrays and slices - the `ministd::Box` cannot yet allocate arrays and slices
 
 use core::alloc::Layout;
+use core::cmp::Ordering;
 use core::mem::{ManuallyDrop, MaybeUninit};
-use core::ptr::{drop_in_place, NonNull};
-use core::alloc::GlobalAlloc;
-use crate::mem::alloc::ALLOCATOR;
+use core::ptr::{self, drop_in_place, NonNull};
+use crate::mem::alloc::{AllocError, AllocRef, Global};
+use crate::mem::sort;
 use crate::TryClone;
 use core::ops::{Bound::*, Index, IndexMut, RangeBounds};
 use core::slice;
@@ -25,14 +26,91 @@ pub const fn uninit<T: Sized, const LEN: usize>() -> [T; LEN] {
 }
 
 /// Array is Box-like structure used to allocate arrays
+/// - the array is allocated by the allocator `A` (see [`AllocRef`])
 #[repr(C)]
-pub struct Array<T: Sized> {
+pub struct Array<T: Sized, A: AllocRef = Global> {
     data: NonNull<T>,
     size: usize,
+    alloc: A,
 }
 
 impl<T: Sized> Array<T> {
 
+    /// Allocates array on the heap and sets all values to `f()`
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_with<F>(f: F, size: usize) -> Self
+    where F: Fn(usize) -> T {
+        Self::new_with_in(f, size, Global)
+    }
+
+    /// Allocates array on the heap and sets all values to `f()`
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_with<F>(f: F, size: usize) -> Result<Self, AllocError>
+    where F: Fn(usize) -> T {
+        Self::try_new_with_in(f, size, Global)
+    }
+
+
+    /// Allocates array on the heap and checks for values returned by `f()`
+    /// - **panics** if allocation or `f()` fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_with_checked<F, E>(f: F, size: usize) -> Self
+    where F: Fn(usize) -> Result<T, E> {
+        Self::new_with_checked_in(f, size, Global)
+    }
+
+
+    /// Tries to allocate array on the heap and checks for values returned by `f()`
+    /// - return `Err` if allocation or `f()` fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_with_checked<F, E: From<AllocError>>(f: F, size: usize) -> Result<Self, E>
+    where F: Fn(usize) -> Result<T, E> {
+        Self::try_new_with_checked_in(f, size, Global)
+    }
+
+
+    /// Allocates array on heap, returning it unitialized
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit(size: usize) -> Array<MaybeUninit<T>> {
+        Self::new_uninit_in(size, Global)
+    }
+
+    /// Tries to allocate array on heap while returning it uninitialized
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit(size: usize) -> Result<Array<MaybeUninit<T>>, AllocError> {
+        Self::try_new_uninit_in(size, Global)
+    }
+
+    /// Allocates array on heap while forcing all bytes to 0
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed(size: usize) -> Array<MaybeUninit<T>> {
+        Self::new_zeroed_in(size, Global)
+    }
+
+    /// Tries to allocate array on heap while forcing all bytes to 0
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed(size: usize) -> Result<Array<MaybeUninit<T>>, AllocError> {
+        Self::try_new_zeroed_in(size, Global)
+    }
+
+}
+
+impl<T: Sized, A: AllocRef> Array<T, A> {
+
     #[inline]
     fn handle_bounds<R>(&self, range: &R) -> (usize, usize)
     where R: RangeBounds<usize> {
@@ -54,166 +132,141 @@ impl<T: Sized> Array<T> {
         unsafe { Layout::from_size_align_unchecked(size_of::<T>() * size, align_of::<T>()) }
     }
 
-    /// Allocates array on the heap and sets all values to `f()`
+    /// Allocates array in `alloc` and sets all values to `f()`
     /// - **panics** if allocation fails
-    pub fn new_with<F>(f: F, size: usize) -> Self
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_with_in<F>(f: F, size: usize, alloc: A) -> Self
     where F: Fn(usize) -> T {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut T;
-
-        assert!(!data.is_null(), "failed to allocate memory for Array");
-
-        for (i, item) in unsafe { slice::from_raw_parts_mut(data, size) }.iter_mut().enumerate() {
-            *item = f(i);
-        }
-
-        Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size: size,
+        match Self::try_new_with_in(f, size, alloc) {
+            Ok(arr) => arr,
+            Err(e) => panic!("failed to allocate memory for Array: {}", e),
         }
     }
 
-    /// Allocates array on the heap and sets all values to `f()`
+    /// Allocates array in `alloc` and sets all values to `f()`
     /// - returns `Err` if allocation fails
-    pub fn try_new_with<F>(f: F, size: usize) -> Result<Self, ()>
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_with_in<F>(f: F, size: usize, alloc: A) -> Result<Self, AllocError>
     where F: Fn(usize) -> T {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut T;
-
-        if data.is_null() {
-            return Err(());
-        }
+        let data = alloc.allocate(AllocError::array_layout::<T>(size)?)?.cast::<T>();
 
-        for (i, item) in unsafe { slice::from_raw_parts_mut(data, size) }.iter_mut().enumerate() {
-            *item = f(i);
+        for i in 0..size {
+            unsafe { data.add(i).write(f(i)); }
         }
 
         Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             size,
+            alloc,
         })
     }
 
 
-    /// Allocates array on the heap and checks for values returned by `f()`
+    /// Allocates array in `alloc` and checks for values returned by `f()`
     /// - **panics** if allocation or `f()` fails
-    pub fn new_with_checked<F, E>(f: F, size: usize) -> Self
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_with_checked_in<F, E>(f: F, size: usize, alloc: A) -> Self
     where F: Fn(usize) -> Result<T, E> {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut T;
-
-        assert!(!data.is_null(), "failed to allocate data for Array");
-
-        let slice = unsafe { slice::from_raw_parts_mut(data, size) };
-
-        for (i, item) in slice.iter_mut().enumerate() {
-            *item = match f(i) {
-                Ok(i) => i,
+        let Ok(data) = alloc.allocate(Self::layout(size)) else {
+            panic!("failed to allocate data for Array");
+        };
+        let data = data.cast::<T>();
+
+        for i in 0..size {
+            match f(i) {
+                Ok(item) => unsafe { data.add(i).write(item) },
                 Err(_) => panic!("failed to create instance of T"),
-            };
+            }
         }
 
         Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             size,
+            alloc,
         }
     }
 
 
-    /// Tries to allocate array on the heap and checks for values returned by `f()`
+    /// Tries to allocate array in `alloc` and checks for values returned by `f()`
     /// - return `Err` if allocation or `f()` fails
-    pub fn try_new_with_checked<F, E: Default>(f: F, size: usize) -> Result<Self, E>
+    ///   - drops all already created values and deallocates buffer
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_with_checked_in<F, E: From<AllocError>>(f: F, size: usize, alloc: A) -> Result<Self, E>
     where F: Fn(usize) -> Result<T, E> {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut T;
-
-        if data.is_null() {
-            return Err(E::default());
-        }
-
-        let slice = unsafe { slice::from_raw_parts_mut(data, size) };
-
-        for (i, item) in slice.iter_mut().enumerate() {
-            *item = f(i)?;
+        let data = alloc.allocate(AllocError::array_layout::<T>(size)?)?.cast::<T>();
+
+        for i in 0..size {
+            match f(i) {
+                Ok(item) => unsafe { data.add(i).write(item) },
+                Err(e) => unsafe {
+                    drop_in_place(slice::from_raw_parts_mut(data.as_ptr(), i));
+                    alloc.deallocate(data.cast(), Self::layout(size));
+                    return Err(e);
+                },
+            }
         }
 
         Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             size,
+            alloc,
         })
 
     }
 
 
-    /// Allocates array on heap, returning it unitialized
+    /// Allocates array in `alloc`, returning it unitialized
     /// - **panics** if allocation fails
-    pub fn new_uninit(size: usize) -> Array<MaybeUninit<T>> {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut MaybeUninit<T>;
-
-        assert!(!data.is_null(), "failed to allocate memory for Array");
-
-        Array {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size,
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit_in(size: usize, alloc: A) -> Array<MaybeUninit<T>, A> {
+        match Self::try_new_uninit_in(size, alloc) {
+            Ok(arr) => arr,
+            Err(e) => panic!("failed to allocate memory for Array: {}", e),
         }
     }
 
-    /// Tries to allocate array on heap while returning it uninitialized
+    /// Tries to allocate array in `alloc` while returning it uninitialized
     /// - returns `Err` if allocation fails
-    pub fn try_new_uninit(size: usize) -> Result<Array<MaybeUninit<T>>, ()> {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(size))
-        } as *mut MaybeUninit<T>;
-
-        if data.is_null() {
-            return Err(());
-        }
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_in(size: usize, alloc: A) -> Result<Array<MaybeUninit<T>, A>, AllocError> {
+        let data = alloc.allocate(AllocError::array_layout::<T>(size)?)?;
 
         Ok(Array {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data: data.cast(),
             size,
+            alloc,
         })
     }
 
-    /// Allocates array on heap while forcing all bytes to 0
+    /// Allocates array in `alloc` while forcing all bytes to 0
     /// - **panics** if allocation fails
-    pub fn new_zeroed(size: usize) -> Array<MaybeUninit<T>> {
-        let data = unsafe {
-            ALLOCATOR.alloc_zeroed(Self::layout(size))
-        } as *mut MaybeUninit<T>;
-
-        assert!(!data.is_null(), "failed to allocate memory for Array");
-
-        Array {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size,
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed_in(size: usize, alloc: A) -> Array<MaybeUninit<T>, A> {
+        match Self::try_new_zeroed_in(size, alloc) {
+            Ok(arr) => arr,
+            Err(e) => panic!("failed to allocate memory for Array: {}", e),
         }
-
     }
 
-    /// Tries to allocate array on heap while forcing all bytes to 0
+    /// Tries to allocate array in `alloc` while forcing all bytes to 0
     /// - returns `Err` if allocation fails
-    pub fn try_new_zeroed(size: usize) -> Result<Array<MaybeUninit<T>>, ()> {
-        let data = unsafe {
-            ALLOCATOR.alloc_zeroed(Self::layout(size))
-        } as *mut MaybeUninit<T>;
-
-        if data.is_null() {
-            return Err(());
-        }
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed_in(size: usize, alloc: A) -> Result<Array<MaybeUninit<T>, A>, AllocError> {
+        let data = alloc.allocate_zeroed(AllocError::array_layout::<T>(size)?)?;
 
         Ok(Array {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data: data.cast(),
             size,
+            alloc,
         })
     }
 
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// Returns iterator to Array
     #[inline(always)]
     pub fn iter<'l>(&'l self) -> core::slice::Iter<'l, T> {
@@ -229,21 +282,22 @@ impl<T: Sized> Array<T> {
 
 }
 
-impl<T: Sized> Array<MaybeUninit<T>> {
+impl<T: Sized, A: AllocRef> Array<MaybeUninit<T>, A> {
 
     /// Tells the compiler that data are initialized
     #[inline]
-    pub unsafe fn assume_init(self) -> Array<T> {
+    pub unsafe fn assume_init(self) -> Array<T, A> {
         let m = ManuallyDrop::new(self);
         Array {
-            data: unsafe { NonNull::new_unchecked(m.data.as_ptr() as *mut T) },
+            data: m.data.cast(),
             size: m.size,
+            alloc: unsafe { ptr::read(&m.alloc) },
         }
     }
 
 }
 
-impl<T: Sized> Array<T> {
+impl<T: Sized, A: AllocRef> Array<T, A> {
 
     /// Returns number of Elements in the array
     #[inline(always)]
@@ -324,137 +378,363 @@ impl<T: Sized> Array<T> {
         self.data.as_ptr()
     }
 
-    /// Constructs `Vec<T>` from this `Array`
-    #[cfg(all(feature="allocator", feature="vector"))]
-    pub fn into_vec<const STEP: usize>(self) -> Vec<T, STEP> {
-        let m = ManuallyDrop::new(self);
-        unsafe { Vec::from_parts(m.data, m.len(), m.len()) }
-    }
-
 }
 
-impl<T: Sized + Clone> Array<T> {
+impl<T: Sized, A: AllocRef> Array<T, A> {
 
-    /// Allocates array on the heap while copying all elements from the slice
-    /// - **panics** if allocation or `T::clone()` fails
-    pub fn from_slice(slice: &[T]) -> Self {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(slice.len()))
-        } as *mut T;
-
-        assert!(!data.is_null(), "failed to allocate memory for Array");
+    /// Sorts the array with stable merge sort
+    /// - allocates scratch space for `len / 2` elements from `ALLOCATOR` (not from `A`), arrays up to 20 elements are sorted without allocating
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort(&mut self)
+    where T: Ord {
+        self.sort_by(T::cmp);
+    }
 
-        let s = unsafe { slice::from_raw_parts_mut(data, slice.len()) };
+    /// Tries to sort the array with stable merge sort
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort(&mut self) -> Result<(), AllocError>
+    where T: Ord {
+        self.try_sort_by(T::cmp)
+    }
 
-        for (i, item) in s.iter_mut().enumerate() {
-            *item = slice[i].clone();
+    /// Sorts the array with stable merge sort using `compare`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+        if let Err(e) = self.try_sort_by(compare) {
+            panic!("failed to allocate memory for Array: {}", e);
         }
+    }
 
-        Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size: slice.len(),
-        }
+    /// Tries to sort the array with stable merge sort using `compare`
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort_by<F>(&mut self, mut compare: F) -> Result<(), AllocError>
+    where F: FnMut(&T, &T) -> Ordering {
+        sort::merge_sort(self.as_mut_slice(), &mut |a, b| compare(a, b) == Ordering::Less)
+    }
 
+    /// Sorts the array with stable merge sort by the keys returned by `f`
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort_by_key<K: Ord, F>(&mut self, mut f: F)
+    where F: FnMut(&T) -> K {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
     }
-}
 
+    /// Tries to sort the array with stable merge sort by the keys returned by `f`
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort_by_key<K: Ord, F>(&mut self, mut f: F) -> Result<(), AllocError>
+    where F: FnMut(&T) -> K {
+        self.try_sort_by(|a, b| f(a).cmp(&f(b)))
+    }
 
-impl<T: Sized + TryClone> Array<T>
-where T::Error: Default {
-    /// Tries to allocate array on the heap while copying ell elements from the slice
-    /// - returns `Err` if allocation or `T::try_clone()` fails
-    ///   - drops all already copied values and deallocates buffer
-    pub fn try_from_slice(slice: &[T]) -> Result<Self, T::Error> {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(slice.len()))
-        } as *mut T;
+    /// Sorts the array in place, the order of equal elements may change
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable(&mut self)
+    where T: Ord {
+        self.as_mut_slice().sort_unstable();
+    }
 
-        if data.is_null() {
-            return Err(T::Error::default());
-        }
+    /// Sorts the array in place using `compare`, the order of equal elements may change
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+        self.as_mut_slice().sort_unstable_by(compare);
+    }
 
-        let s = unsafe { slice::from_raw_parts_mut(data, slice.len()) };
+    /// Sorts the array in place by the keys returned by `f`, the order of equal elements may change
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable_by_key<K: Ord, F>(&mut self, f: F)
+    where F: FnMut(&T) -> K {
+        self.as_mut_slice().sort_unstable_by_key(f);
+    }
 
-        for (i, item) in s.iter_mut().enumerate() {
-            *item = slice[i].try_clone()?;
-        }
+    /// Retains only the elements for which `f` returns `true`, in their order
+    /// - the array is reallocated to its new length
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn retain<F>(&mut self, mut f: F)
+    where F: FnMut(&T) -> bool {
+        self.retain_mut(|e| f(e));
+    }
 
-        Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size: slice.len(),
+    /// Tries to retain only the elements for which `f` returns `true`, in their order
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_retain<F>(&mut self, mut f: F) -> Result<(), AllocError>
+    where F: FnMut(&T) -> bool {
+        self.try_retain_mut(|e| f(e))
+    }
+
+    /// Retains only the elements for which `f` returns `true`, `f` may modify the elements
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn retain_mut<F>(&mut self, f: F)
+    where F: FnMut(&mut T) -> bool {
+        if let Err(e) = self.try_retain_mut(f) {
+            panic!("failed to allocate memory for Array: {}", e);
+        }
+    }
+
+    /// Tries to retain only the elements for which `f` returns `true`, `f` may modify the elements
+    /// - returns `Err` if allocation fails, the array is not changed then
+    /// - `f` has already been called for every element then, its modifications stay
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_retain_mut<F>(&mut self, mut f: F) -> Result<(), AllocError>
+    where F: FnMut(&mut T) -> bool {
+        self.try_keep_marked(|v, keep| {
+            let mut kept = 0;
+            for (e, k) in v.iter_mut().zip(keep.iter_mut()) {
+                *k = f(e);
+                kept += *k as usize;
+            }
+            kept
         })
     }
-}
 
+    /// Removes consecutive repeated elements, keeps the first one of each run
+    /// - the array is reallocated to its new length
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn dedup(&mut self)
+    where T: PartialEq {
+        self.dedup_by(|a, b| a == b);
+    }
 
+    /// Tries to remove consecutive repeated elements
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_dedup(&mut self) -> Result<(), AllocError>
+    where T: PartialEq {
+        self.try_dedup_by(|a, b| a == b)
+    }
 
+    /// Removes consecutive elements for which `same_bucket` returns `true`
+    /// - `same_bucket(a, b)` is called with the element `a` and the last kept element `b` before it
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where F: FnMut(&mut T, &mut T) -> bool {
+        if let Err(e) = self.try_dedup_by(same_bucket) {
+            panic!("failed to allocate memory for Array: {}", e);
+        }
+    }
+
+    /// Tries to remove consecutive elements for which `same_bucket` returns `true`
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_dedup_by<F>(&mut self, mut same_bucket: F) -> Result<(), AllocError>
+    where F: FnMut(&mut T, &mut T) -> bool {
+        self.try_keep_marked(|v, keep| {
+            let mut last = 0;
+            keep[0] = true;
+            for (i, k) in keep.iter_mut().enumerate().skip(1) {
+                let (front, back) = v.split_at_mut(i);
+                if !same_bucket(&mut back[0], &mut front[last]) {
+                    *k = true;
+                    last = i;
+                }
+            }
+            keep.iter().filter(|k| **k).count()
+        })
+    }
 
+    /// Removes consecutive elements that have the same key
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn dedup_by_key<K: PartialEq, F>(&mut self, mut key: F)
+    where F: FnMut(&mut T) -> K {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
 
+    /// Tries to remove consecutive elements that have the same key
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_dedup_by_key<K: PartialEq, F>(&mut self, mut key: F) -> Result<(), AllocError>
+    where F: FnMut(&mut T) -> K {
+        self.try_dedup_by(|a, b| key(a) == key(b))
+    }
 
+    /// Inserts `val` into the sorted array, keeping it sorted
+    /// - `val` goes after the elements equal to it, returns its index
+    /// - the array is reallocated to its new length
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn insert_sorted(&mut self, val: T) -> usize
+    where T: Ord {
+        self.insert_sorted_by(val, T::cmp)
+    }
 
+    /// Tries to insert `val` into the sorted array, keeping it sorted
+    /// - returns index of `val` or `Err(val)` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_insert_sorted(&mut self, val: T) -> Result<usize, T>
+    where T: Ord {
+        self.try_insert_sorted_by(val, T::cmp)
+    }
 
+    /// Inserts `val` into the array sorted by `compare`, keeping it sorted
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn insert_sorted_by<F>(&mut self, val: T, compare: F) -> usize
+    where F: FnMut(&T, &T) -> Ordering {
+        match self.try_insert_sorted_by(val, compare) {
+            Ok(index) => index,
+            Err(_) => panic!("failed to allocate memory for Array"),
+        }
+    }
+
+    /// Tries to insert `val` into the array sorted by `compare`, keeping it sorted
+    /// - returns index of `val` or `Err(val)` if allocation fails, the array is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_insert_sorted_by<F>(&mut self, val: T, compare: F) -> Result<usize, T>
+    where F: FnMut(&T, &T) -> Ordering {
+        let index = sort::upper_bound(self.as_slice(), &val, compare);
+        let len = self.len();
+
+        let Ok(new) = AllocError::array_layout::<T>(len + 1) else {
+            return Err(val);
+        };
+        let Ok(data) = (unsafe { self.alloc.grow(self.data.cast(), Self::layout(len), new) }) else {
+            return Err(val);
+        };
 
+        unsafe {
+            let ptr = data.cast::<T>().as_ptr().add(index);
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(val);
+        }
+        self.data = data.cast();
+        self.size = len + 1;
 
-impl<T: Sized + Clone> Clone for Array<T> {
-    fn clone(&self) -> Self {
+        Ok(index)
+    }
 
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(self.len()))
-        } as *mut T;
+    /// Keeps the elements marked by `mark`, drops the others and moves the kept ones to new allocation
+    /// - `mark` is called with the elements and cleared marks, sets `true` for the ones to keep and returns their count
+    /// - no element is moved before both allocations succeed
+    /// - returns `Err` if allocation fails, the array is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn try_keep_marked<F>(&mut self, mark: F) -> Result<(), AllocError>
+    where F: FnOnce(&mut [T], &mut [bool]) -> usize {
+        let len = self.len();
+        if len == 0 {
+            return Ok(());
+        }
 
-        assert!(!data.is_null(), "failed to allocate memoyr for Array");
+        let marks = sort::Scratch::<bool>::new(len)?;
+        let keep = unsafe {
+            ptr::write_bytes(marks.as_ptr(), 0, len);
+            slice::from_raw_parts_mut(marks.as_ptr(), len)
+        };
+        let kept = mark(self.as_mut_slice(), keep);
+        if kept == len {
+            return Ok(());
+        }
 
-        let new = unsafe { slice::from_raw_parts_mut(data, self.len()) };
-        let slice = self.as_slice();
+        let data = self.alloc.allocate(AllocError::array_layout::<T>(kept)?)?.cast::<T>();
+        let old = core::mem::replace(&mut self.data, data);
+        self.size = kept;
 
-        for (i, item) in new.iter_mut().enumerate() {
-            *item = slice[i].clone();
+        unsafe {
+            let mut dst = data.as_ptr();
+            for (i, _) in keep.iter().enumerate().filter(|(_, k)| **k) {
+                ptr::copy_nonoverlapping(old.add(i).as_ptr(), dst, 1);
+                dst = dst.add(1);
+            }
+            //  the array owns only the kept elements now, a panicking drop leaks the rest
+            for (i, _) in keep.iter().enumerate().filter(|(_, k)| !**k) {
+                drop_in_place(old.add(i).as_ptr());
+            }
+            self.alloc.deallocate(old.cast(), Self::layout(len));
         }
+        Ok(())
+    }
 
-        Array {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size: self.size
-        }
+}
+
+impl<T: Sized> Array<T> {
 
+    /// Constructs `Vec<T>` from this `Array`
+    #[cfg(all(feature="allocator", feature="vector"))]
+    pub fn into_vec<const STEP: usize>(self) -> Vec<T, STEP> {
+        let m = ManuallyDrop::new(self);
+        unsafe { Vec::from_parts(m.data, m.len(), m.len()) }
     }
+
 }
 
+impl<T: Sized + Clone> Array<T> {
+
+    /// Allocates array on the heap while copying all elements from the slice
+    /// - **panics** if allocation or `T::clone()` fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn from_slice(slice: &[T]) -> Self {
+        Self::new_with(|i| slice[i].clone(), slice.len())
+    }
+}
+
+
+impl<T: Sized + TryClone> Array<T>
+where T::Error: From<AllocError> {
+    /// Tries to allocate array on the heap while copying ell elements from the slice
+    /// - returns `Err` if allocation or `T::try_clone()` fails
+    ///   - allocation failure is converted into `T::Error`
+    ///   - drops all already copied values and deallocates buffer
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_from_slice(slice: &[T]) -> Result<Self, T::Error> {
+        Self::try_new_with_checked(|i| slice[i].try_clone(), slice.len())
+    }
+}
+
+
 
-impl<T: Sized + TryClone> TryClone for Array<T>
-where T::Error: Default {
 
-    type Error = T::Error;
 
-    fn try_clone(&self) -> Result<Self, Self::Error>
-        where Self: Sized, Self::Error: Default {
-        let data = unsafe {
-            ALLOCATOR.alloc(Self::layout(self.size))
-        } as *mut T;
 
-        if data.is_null() {
-            return Err(T::Error::default());
-        }
 
-        let new = unsafe { slice::from_raw_parts_mut(data, self.len()) };
+
+
+
+impl<T: Sized + Clone, A: AllocRef + Clone> Clone for Array<T, A> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn clone(&self) -> Self {
         let slice = self.as_slice();
+        Self::new_with_in(|i| slice[i].clone(), self.len(), self.alloc.clone())
+    }
+}
 
-        for (i, item) in new.iter_mut().enumerate() {
-            *item = match slice[i].try_clone() {
-                Ok(i) => i,
-                Err(_) => unsafe {
-                    //  deallocate memory
-                    drop_in_place(slice::from_raw_parts_mut(self.data.as_ptr(), i));
-                    ALLOCATOR.dealloc(self.data.as_ptr() as *mut u8, Self::layout(self.len()));
-                    return Err(T::Error::default());
-                },
-            };
-        }
 
-        Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            size: self.len(),
-        })
+impl<T: Sized + TryClone, A: AllocRef + Clone> TryClone for Array<T, A>
+where T::Error: From<AllocError> {
 
+    type Error = T::Error;
+
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn try_clone(&self) -> Result<Self, Self::Error>
+        where Self: Sized {
+        let slice = self.as_slice();
+        Self::try_new_with_checked_in(|i| slice[i].try_clone(), self.len(), self.alloc.clone())
     }
 }
 
@@ -467,29 +747,29 @@ where T::Error: Default {
 
 
 
-impl<T: Sized> Drop for Array<T> {
+impl<T: Sized, A: AllocRef> Drop for Array<T, A> {
     fn drop(&mut self) {
         unsafe {
             drop_in_place(self.as_mut_slice());
-            ALLOCATOR.dealloc(self.data.as_ptr() as *mut u8, Self::layout(self.size));
+            self.alloc.deallocate(self.data.cast(), Self::layout(self.size));
         }
     }
 }
 
-impl<T: Sized> Deref for Array<T> {
+impl<T: Sized, A: AllocRef> Deref for Array<T, A> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
         unsafe { slice::from_raw_parts(self.data.as_ptr(), self.size) }
     }
 }
 
-impl<T: Sized> DerefMut for Array<T> {
+impl<T: Sized, A: AllocRef> DerefMut for Array<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { slice::from_raw_parts_mut(self.data.as_ptr(), self.size) }
     }
 }
 
-impl<T: Sized> Index<usize> for Array<T> {
+impl<T: Sized, A: AllocRef> Index<usize> for Array<T, A> {
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
         match self.at(index) {
@@ -499,7 +779,7 @@ impl<T: Sized> Index<usize> for Array<T> {
     }
 }
 
-impl<T: Sized> IndexMut<usize> for Array<T> {
+impl<T: Sized, A: AllocRef> IndexMut<usize> for Array<T, A> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         match self.at_mut(index) {
             Some(m) => m,
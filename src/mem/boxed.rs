@@ -8,9 +8,10 @@
 //! 
 //! **TODO**: Try to implement array/slice allocation directly in box
 
-use core::{alloc::{GlobalAlloc, Layout}, any::Any, fmt::{Debug, Display, Pointer}, hash::Hash, iter, mem::{ManuallyDrop, MaybeUninit}, ops::{Deref, DerefMut}, option::Iter, pin::{Pin, pin}, ptr::{NonNull, drop_in_place}};
+use core::{alloc::Layout, any::Any, fmt::{Debug, Display, Pointer}, hash::Hash, mem::{ManuallyDrop, MaybeUninit}, ops::{Deref, DerefMut}, pin::Pin, ptr::{self, NonNull, drop_in_place}};
 
-use crate::{ALLOCATOR, String, ToOwned, ToString, TryClone, alloc::layout_arr};
+use crate::{ALLOCATOR, TryClone, alloc::layout_arr};
+use crate::mem::alloc::{AllocError, AllocRef, Global};
 
 
 
@@ -22,13 +23,16 @@ use crate::{ALLOCATOR, String, ToOwned, ToString, TryClone, alloc::layout_arr};
 /// This implementation of `std::Box`-like structure prioritizes using **stable** language features
 /// - The `Layout` of allocated data is stored within the structure to make it simpler to develop
 ///   - This enlarges the `ministd::Box` layout by another 8 bytes: the final layout of `ministd::Box` is the `24` bytes
+/// - The allocator `A` is stored in the structure as well (see [`AllocRef`])
+///   - the default `Global` allocator takes no memory
 #[repr(C)]
-pub struct Box<T: ?Sized> {
+pub struct Box<T: ?Sized, A: AllocRef = Global> {
     ptr: NonNull<T>,
     layout: Layout,
+    alloc: A,
 }
 
-impl<T: ?Sized> Box<T> {
+impl<T: ?Sized, A: AllocRef> Box<T, A> {
     /// Returns `NonNull` pointer to the allocated data
     /// - The pointer is **dangling** if `T` is zero sized
     pub const fn as_non_null(&self) -> NonNull<T> { self.ptr }
@@ -46,9 +50,20 @@ impl<T: ?Sized> Box<T> {
     /// Identifies the `Layout` used by `Box` for an instance of (unsized) `T`
     pub const fn layout_unsized(val: &T) -> Layout { Layout::for_value(val) }
 
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A { &self.alloc }
+
+    /// Destructs the `Box` without dropping anything
+    #[inline]
+    fn into_parts(self) -> (NonNull<T>, Layout, A) {
+        let m = ManuallyDrop::new(self);
+        (m.ptr, m.layout, unsafe { ptr::read(&m.alloc) })
+    }
+
 }
 
-impl<T: Sized> Box<[T]> {
+impl<T: Sized, A: AllocRef> Box<[T], A> {
 
     /// Identifies the `Layout` used by `Box` for an array of (sized) type `T`
     pub const fn layout_arr(size: usize) -> Layout {
@@ -62,23 +77,26 @@ impl<T: Sized> Box<T> {
     /// Allocates memory on the heap and the places `x` into it
     /// - `panic`s on allocation failure
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new(x: T) -> Box<T> {
-        Box::alloc(x).expect("failed to allocate memory")
+        Box::alloc(x, Global).expect("failed to allocate memory")
     }
 
     /// Constructs new `Box` with uninitialized contents
     /// - `panic`s on allocation failure
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_uninit() -> Box<MaybeUninit<T>> {
-        Box::alloc_uninit().expect("failed to allocate memory")
+        Box::alloc_uninit(Global).expect("failed to allocate memory")
     }
 
     /// Constructs new `Box` with uninitialized memory
     /// - All bytes are set to `0`
     /// - `panic`s on allocation failure
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_zeroed() -> Box<MaybeUninit<T>> {
-        Box::alloc_zeroed().expect("failed to allocate memory")
+        Box::alloc_zeroed(Global).expect("failed to allocate memory")
     }
 
     /// Constructs new `Pin<Box>`
@@ -91,90 +109,160 @@ impl<T: Sized> Box<T> {
     /// Tries to allocate memory on the heap and place `x` into it
     /// - `drop`s `x` on allocation failure and returns `Err`
     #[inline]
-    pub fn try_new(x: T) -> Result<Box<T>, ()> {
-        Box::alloc(x)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new(x: T) -> Result<Box<T>, AllocError> {
+        Box::alloc(x, Global)
     }
 
     /// Contructs new `Box` with uninitialized contents
     /// - Returns `Err` on allocation error
     #[inline]
-    pub fn try_new_uninit() -> Result<Box<MaybeUninit<T>>, ()> {
-        Box::alloc_uninit()
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit() -> Result<Box<MaybeUninit<T>>, AllocError> {
+        Box::alloc_uninit(Global)
     }
 
     /// Constructs new `Box` with uninitialized contents
     /// - All byes are set to `0`
     /// - Returns `Err` on allocation failure
     #[inline]
-    pub fn try_new_zeroed() -> Result<Box<MaybeUninit<T>>, ()> {
-        Box::alloc_zeroed()
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed() -> Result<Box<MaybeUninit<T>>, AllocError> {
+        Box::alloc_zeroed(Global)
     }
 
     /// Constructs new `Pin<Box>`
     /// - `drop`s `x` on allocation failure and returns `Err`
-    pub fn try_pin(x: T) -> Result<Pin<Box<T>>, ()>
+    pub fn try_pin(x: T) -> Result<Pin<Box<T>>, AllocError>
     where T: Unpin {
         Ok(Pin::new(Box::try_new(x)?))
     }
 
 }
 
+impl<T: Sized, A: AllocRef> Box<T, A> {
+
+    /// Allocates memory in `alloc` and the places `x` into it
+    /// - `panic`s on allocation failure
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_in(x: T, alloc: A) -> Box<T, A> {
+        Box::alloc(x, alloc).expect("failed to allocate memory")
+    }
+
+    /// Constructs new `Box` with uninitialized contents in `alloc`
+    /// - `panic`s on allocation failure
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit_in(alloc: A) -> Box<MaybeUninit<T>, A> {
+        Box::alloc_uninit(alloc).expect("failed to allocate memory")
+    }
+
+    /// Constructs new `Box` with uninitialized memory in `alloc`
+    /// - All bytes are set to `0`
+    /// - `panic`s on allocation failure
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed_in(alloc: A) -> Box<MaybeUninit<T>, A> {
+        Box::alloc_zeroed(alloc).expect("failed to allocate memory")
+    }
+
+    /// Tries to allocate memory in `alloc` and place `x` into it
+    /// - `drop`s `x` on allocation failure and returns `Err`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_in(x: T, alloc: A) -> Result<Box<T, A>, AllocError> {
+        Box::alloc(x, alloc)
+    }
+
+    /// Contructs new `Box` with uninitialized contents in `alloc`
+    /// - Returns `Err` on allocation error
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_in(alloc: A) -> Result<Box<MaybeUninit<T>, A>, AllocError> {
+        Box::alloc_uninit(alloc)
+    }
+
+    /// Constructs new `Box` with uninitialized contents in `alloc`
+    /// - All byes are set to `0`
+    /// - Returns `Err` on allocation failure
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed_in(alloc: A) -> Result<Box<MaybeUninit<T>, A>, AllocError> {
+        Box::alloc_zeroed(alloc)
+    }
+
+}
+
 impl<T: Sized> Box<[T]> {
 
     /// Constructs new `Box` with uninitialized contents
     /// - `panic`s on allocation failure
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_uninit_slice(size: usize) -> Box<[MaybeUninit<T>]> {
         Box {
             ptr: unsafe { ALLOCATOR.allocate_array_uninit(size).expect("failed to allocate memory") },
             layout: layout_arr::<T>(size),
+            alloc: Global,
         }
     }
 
     /// Constructs new `Box` for an array with all bytes set to `0`
     /// - `panic`s on allocation failure
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_zeroed_slice(size: usize) -> Box<[MaybeUninit<T>]> {
         Box {
             ptr: unsafe { ALLOCATOR.allocate_array_zeroed(size).expect("failed to allocate memory") },
-            layout: layout_arr::<T>(size)
+            layout: layout_arr::<T>(size),
+            alloc: Global,
         }
     }
 
     /// Tries to construct new `Box` with uninitialized contents
-    pub fn try_new_uninit_slice(size: usize) -> Result<Box<[MaybeUninit<T>]>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_slice(size: usize) -> Result<Box<[MaybeUninit<T>]>, AllocError> {
         Ok(Box {
             ptr: unsafe { ALLOCATOR.allocate_array_uninit(size)? },
-            layout: layout_arr::<T>(size)
+            layout: layout_arr::<T>(size),
+            alloc: Global,
         })
     }
 
     /// Tries to construct new `Box` with all bytes set to `0`
-    pub fn try_new_zeroed_slice(size: usize) -> Result<Box<[MaybeUninit<T>]>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed_slice(size: usize) -> Result<Box<[MaybeUninit<T>]>, AllocError> {
         Ok(Box {
             ptr: unsafe { ALLOCATOR.allocate_array_zeroed(size)? },
             layout: layout_arr::<T>(size),
+            alloc: Global,
         })
     }
 
     /// Allocates an array, uses the closure to determine the value of each element
     /// - `panic`s on allocation failure
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_slice_with<F: FnMut() -> T>(size: usize, mut f: F) -> Box<[T]> {
         Box {
             ptr: unsafe { ALLOCATOR.allocate_array_with(size, &mut f).expect("failed to allocate memory") },
             layout: layout_arr::<T>(size),
+            alloc: Global,
         }
     }
 
 
     /// Tries to allocate an array, uses the closure to determine the value of each element
-    pub fn try_new_slice_with<F: FnMut() -> T>(size: usize, mut f: F) -> Result<Box<[T]>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_slice_with<F: FnMut() -> T>(size: usize, mut f: F) -> Result<Box<[T]>, AllocError> {
         Ok(Box {
             ptr: unsafe { ALLOCATOR.allocate_array_with(size, &mut f)? },
-            layout: layout_arr::<T>(size)
+            layout: layout_arr::<T>(size),
+            alloc: Global,
         })
     }
 
     /// Allocates an array, uses the `default` value for each element
     /// - `panic`s on allocation failure
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_slice_default(size: usize) -> Box<[T]>
     where T: Default {
         Box {
@@ -187,13 +275,15 @@ impl<T: Sized> Box<[T]> {
 
                 NonNull::slice_from_raw_parts(arr.cast(), size)
             },
-            layout: layout_arr::<T>(size)
+            layout: layout_arr::<T>(size),
+            alloc: Global,
         }
     }
 
     /// Tries to allocate an array, uses the `default` value for each element
     /// - Returns `Err` on allocation failure
-    pub fn try_new_slice_default(size: usize) -> Result<Box<[T]>, ()>
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_slice_default(size: usize) -> Result<Box<[T]>, AllocError>
     where T: Default {
         Ok(Box {
             ptr: unsafe {
@@ -205,45 +295,12 @@ impl<T: Sized> Box<[T]> {
 
                 NonNull::slice_from_raw_parts(arr.cast(), size)
             },
-            layout: layout_arr::<T>(size)
+            layout: layout_arr::<T>(size),
+            alloc: Global,
         })
     }
 
-    /// Tries to convert the inner slice into and array of `N` elements
-    /// - Returns `None` if `N` is not exaclty equal to the size of the slice
-    pub fn into_array<const N: usize>(self) -> Option<Box<[T; N]>> {
-        if N == self.ptr.len() {
-
-            let Box { ptr, layout } = *ManuallyDrop::new(self);
-
-            Some(Box {
-                ptr: ptr.cast(),
-                layout,
-            })
-
-        } else {
-            unsafe { ManuallyDrop::drop(&mut ManuallyDrop::new(self)) };
-            None
-        }
-    }
-
-    /// Converts the inner slice into an array of `N` elements
-    /// 
-    /// # **Safety**
-    /// The caller is to guarantee that the length of the inner slice is exaclty equal to `N`
-    /// - Other use will result in undefined behaviour
-    pub const unsafe fn into_array_unchecked<const N: usize>(self) -> Box<[T; N]> {
-        let ptr = self.ptr;
-        let layout = self.layout;
-
-        let _ = ManuallyDrop::new(self);
-
-        Box {
-            ptr: ptr.cast(),
-            layout
-        }
-    }
-
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn from_slice<'a, U: Sized>(slice: &'a [U]) -> Self
     where T: From<&'a U> {
         let ptr = unsafe {
@@ -255,11 +312,13 @@ impl<T: Sized> Box<[T]> {
 
         Box {
             ptr,
-            layout: layout_arr::<T>(slice.len())
+            layout: layout_arr::<T>(slice.len()),
+            alloc: Global,
         }
 
     }
 
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn clone_from_slice(slice: &[T]) -> Self
     where T: Clone {
 
@@ -271,69 +330,127 @@ impl<T: Sized> Box<[T]> {
 
         Box {
             ptr,
-            layout: layout_arr::<T>(slice.len())
+            layout: layout_arr::<T>(slice.len()),
+            alloc: Global,
         }
 
     }
 
 }
 
-impl<T: Sized> Box<MaybeUninit<T>> {
+impl<T: Sized, A: AllocRef> Box<[T], A> {
+
+    /// Constructs new `Box` with uninitialized contents in `alloc`
+    /// - `panic`s on allocation failure
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit_slice_in(size: usize, alloc: A) -> Box<[MaybeUninit<T>], A> {
+        Self::try_new_uninit_slice_in(size, alloc).expect("failed to allocate memory")
+    }
+
+    /// Tries to construct new `Box` with uninitialized contents in `alloc`
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_slice_in(size: usize, alloc: A) -> Result<Box<[MaybeUninit<T>], A>, AllocError> {
+        if size == 0 { return Err(AllocError::zero_sized_array::<T>()) }
+
+        let layout = AllocError::array_layout::<T>(size)?;
+        let ptr = alloc.allocate(layout)?;
+
+        Ok(Box {
+            ptr: NonNull::slice_from_raw_parts(ptr.cast(), size),
+            layout,
+            alloc,
+        })
+    }
+
+    /// Tries to convert the inner slice into and array of `N` elements
+    /// - Returns `None` if `N` is not exaclty equal to the size of the slice
+    pub fn into_array<const N: usize>(self) -> Option<Box<[T; N], A>> {
+        if N == self.ptr.len() {
+
+            let (ptr, layout, alloc) = self.into_parts();
+
+            Some(Box {
+                ptr: ptr.cast(),
+                layout,
+                alloc,
+            })
+
+        } else {
+            unsafe { ManuallyDrop::drop(&mut ManuallyDrop::new(self)) };
+            None
+        }
+    }
+
+    /// Converts the inner slice into an array of `N` elements
+    /// 
+    /// # **Safety**
+    /// The caller is to guarantee that the length of the inner slice is exaclty equal to `N`
+    /// - Other use will result in undefined behaviour
+    pub unsafe fn into_array_unchecked<const N: usize>(self) -> Box<[T; N], A> {
+        let (ptr, layout, alloc) = self.into_parts();
+
+        Box {
+            ptr: ptr.cast(),
+            layout,
+            alloc,
+        }
+    }
+
+}
+
+impl<T: Sized, A: AllocRef> Box<MaybeUninit<T>, A> {
 
     /// Converts to `Box<T>`
     /// 
     /// # **Safety**
     /// As with `MaybeUninit::assume_init``, it is up to the caller to guarantee that the value really is in an initialized state. Calling this when the content is not yet fully initialized causes immediate undefined behavior
-    pub const unsafe fn assume_init(self) -> Box<T> {
-        let ptr = self.ptr;
-        let layout = self.layout;
+    pub unsafe fn assume_init(self) -> Box<T, A> {
+        let (ptr, layout, alloc) = self.into_parts();
 
-        let _ = ManuallyDrop::new(self);
         Box {
             ptr: ptr.cast(),
             layout,
+            alloc,
         }
     }
 
     /// Safely converts to `Box<T>` by initializing the inner data
-    pub fn write(mut self, val: T) -> Box<T> {
+    pub fn write(mut self, val: T) -> Box<T, A> {
         
         unsafe { self.ptr.as_mut().write(val) };
 
-        let m = ManuallyDrop::new(self);
-        let Box { ptr, layout } = *m;
+        let (ptr, layout, alloc) = self.into_parts();
 
         Box {
             ptr: ptr.cast(),
             layout,
+            alloc,
         }
     }
 
 }
 
-impl<T: Sized> Box<[MaybeUninit<T>]> {
+impl<T: Sized, A: AllocRef> Box<[MaybeUninit<T>], A> {
 
     /// Converts to `Box<[T]>`
     /// 
     /// # **Safety**
     ///  As with `MaybeUninit::assume_init``, it is up to the caller to guarantee that the value really is in an initialized state. Calling this when the content is not yet fully initialized causes immediate undefined behavior
-    pub const unsafe fn assume_init(self) -> Box<[T]> {
+    pub unsafe fn assume_init(self) -> Box<[T], A> {
 
-        let ptr = self.ptr;
-        let layout = self.layout;
-
-        let _ = ManuallyDrop::new(self);
+        let (ptr, layout, alloc) = self.into_parts();
 
         Box {
             ptr: NonNull::slice_from_raw_parts(ptr.cast(), ptr.len()),
             layout,
+            alloc,
         }
     }
 
     /// Safely converts to `Box<[T]> by initializing the inner array
-    pub fn write(self, value: T) -> Box<[T]>
+    pub fn write(self, value: T) -> Box<[T], A>
     where T: Clone {
-        let Box { mut ptr, layout} = *ManuallyDrop::new(self);
+        let (mut ptr, layout, alloc) = self.into_parts();
 
         unsafe {
             for i in ptr.as_mut() {
@@ -344,20 +461,22 @@ impl<T: Sized> Box<[MaybeUninit<T>]> {
         Box {
             ptr: NonNull::slice_from_raw_parts(ptr.cast(), ptr.len()),
             layout,
+            alloc,
         }
     }
 
 }
 
-impl Box<dyn Any> {
+impl<A: AllocRef> Box<dyn Any, A> {
 
     /// Attempts to downcast the box to a concrete type
-    pub fn downcast<T: Any>(self) -> Result<Box<T>, Self> {
+    pub fn downcast<T: Any>(self) -> Result<Box<T, A>, Self> {
         if self.is::<T>() {
-            let Box { ptr, layout } = *ManuallyDrop::new(self);
+            let (ptr, layout, alloc) = self.into_parts();
             Ok(Box {
                 ptr: ptr.cast(),
                 layout,
+                alloc,
             })
         } else {
             Err(self)
@@ -368,25 +487,27 @@ impl Box<dyn Any> {
     /// 
     /// # **Safety**
     /// It is up to the caller to guarantee that the inner value is an instance of `T`. Calling this on an `Box` that does not store `dyn T` will result in immediate undefined behaviour
-    pub unsafe fn downcast_unchecked<T: Any>(self) -> Box<T> {
-        let Box { ptr, layout } = *ManuallyDrop::new(self);
+    pub unsafe fn downcast_unchecked<T: Any>(self) -> Box<T, A> {
+        let (ptr, layout, alloc) = self.into_parts();
         Box {
             ptr: ptr.cast(),
-            layout
+            layout,
+            alloc,
         }
     }
 
 }
 
-impl Box<dyn Any + Send> {
+impl<A: AllocRef> Box<dyn Any + Send, A> {
 
     /// Attempts to downcast the box to a concrete type
-    pub fn downcast<T: Any + Send>(self) -> Result<Box<T>, Self> {
+    pub fn downcast<T: Any + Send>(self) -> Result<Box<T, A>, Self> {
         if self.is::<T>() {
-            let Box { ptr, layout } = *ManuallyDrop::new(self);
+            let (ptr, layout, alloc) = self.into_parts();
             Ok(Box {
                 ptr: ptr.cast(),
                 layout,
+                alloc,
             })
         } else {
             Err(self)
@@ -397,11 +518,12 @@ impl Box<dyn Any + Send> {
     /// 
     /// # **Safety**
     /// It is up to the caller to guarantee that the inner value is an instance of `T`. Calling this on an `Box` that does not store `dyn T` will result in immediate undefined behaviour
-    pub unsafe fn downcast_unchecked<T: Any>(self) -> Box<T> {
-        let Box { ptr, layout } = *ManuallyDrop::new(self);
+    pub unsafe fn downcast_unchecked<T: Any>(self) -> Box<T, A> {
+        let (ptr, layout, alloc) = self.into_parts();
         Box {
             ptr: ptr.cast(),
-            layout
+            layout,
+            alloc,
         }
     }
 
@@ -415,7 +537,7 @@ impl<T: ?Sized> Box<T> {
     /// # **Safety**
     /// The pointer must be allocated by the `ministd::ALLOCATOR` allocator using the passed `Layout`
     pub unsafe fn from_raw(ptr: *mut T, layout: Layout) -> Box<T> {
-        Box { ptr: NonNull::new(ptr).expect("pointer is null"), layout, }
+        Box { ptr: NonNull::new(ptr).expect("pointer is null"), layout, alloc: Global }
     }
 
     /// Constructs `Box` from an `NonNull` pointer and `Layout`
@@ -423,18 +545,30 @@ impl<T: ?Sized> Box<T> {
     /// # **Safety**
     /// The pointer must be allocated by the `ministd::ALLOCATOR` allocator using the specified `Layout`
     pub const unsafe fn from_non_null(ptr: NonNull<T>, layout: Layout) -> Box<T> {
-        Box { ptr, layout }
+        Box { ptr, layout, alloc: Global }
+    }
+
+}
+
+impl<T: ?Sized, A: AllocRef> Box<T, A> {
+
+    /// Constructs `Box` from an `NonNull` pointer, `Layout` and allocator
+    /// 
+    /// # **Safety**
+    /// The pointer must be allocated by `alloc` using the specified `Layout`
+    pub const unsafe fn from_non_null_in(ptr: NonNull<T>, layout: Layout, alloc: A) -> Box<T, A> {
+        Box { ptr, layout, alloc }
     }
 
     /// Destructs the `Box` and returns its pointer and layout
     pub fn into_raw(self) -> (*mut T, Layout) {
-        let Box { ptr, layout } = *ManuallyDrop::new(self);
+        let (ptr, layout, _) = self.into_parts();
         (ptr.as_ptr(), layout)
     }
 
     /// Destructs the `Box` and returns its pointer and layout
     pub fn into_non_null(self) -> (NonNull<T>, Layout) {
-        let Box { ptr, layout } = *ManuallyDrop::new(self);
+        let (ptr, layout, _) = self.into_parts();
         (ptr, layout)
     }
 
@@ -449,87 +583,98 @@ impl<T: ?Sized> Box<T> {
     /// Leaks the memory and returns mutable reference to the data
     /// - You can choose the lifetime of the reference
     pub unsafe fn leak<'l>(self) -> &'l mut T {
-        let Box { mut ptr, .. } = *ManuallyDrop::new(self);
+        let (mut ptr, ..) = self.into_parts();
         unsafe { ptr.as_mut() }
     }
 
     /// Leaks the memory and returns mutable reference to the data and its layout
     /// - You can choose the lifetime of the reference
     pub unsafe fn leak_with_layout<'l>(self) -> (&'l mut T, Layout) {
-        let Box { mut ptr, layout } = *ManuallyDrop::new(self);
+        let (mut ptr, layout, _) = self.into_parts();
         (unsafe { ptr.as_mut() }, layout)
     }
 
     /// `Pin`s the `Box`
     pub fn into_pin(self) -> Pin<Self>
     where T: Unpin {
-        let Box { ptr, layout } = *ManuallyDrop::new(self);
-        Pin::new(Box { ptr, layout })
+        Pin::new(self)
     }
 
 }
 
 
 
-impl<T: Sized> Box<T> {
+impl<T: Sized, A: AllocRef> Box<T, A> {
 
-    /// Allocates memory for `x` and places it into it
+    /// Allocates memory for `x` in `alloc` and places it into it
     /// - Does not allocate data if `T` is zero sized
     /// - `x` is `drop`ped on allocation failure
-    fn alloc(x: T) -> Result<Box<T>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn alloc(x: T, alloc: A) -> Result<Box<T, A>, AllocError> {
         if size_of::<T>() == 0 {
             Ok(Box {
                 ptr: NonNull::dangling(),
-                layout: unsafe { Layout::from_size_align_unchecked(0, 0) }
+                layout: unsafe { Layout::from_size_align_unchecked(0, 0) },
+                alloc,
             })
         } else {
+            let ptr = alloc.allocate(Layout::new::<T>())?.cast::<T>();
+            unsafe { ptr.write(x); }
+
             Ok(Box{
-                ptr: unsafe { ALLOCATOR.allocate(x).map_err(|v| drop(v) ) }?,
+                ptr,
                 layout: Layout::new::<T>(),
+                alloc,
             })
         }
     }
 
 
-    /// Allocates uninitialized memory for an instance of `T`
+    /// Allocates uninitialized memory for an instance of `T` in `alloc`
     /// - Does not allocate data if `T` is zero sized
-    fn alloc_uninit() -> Result<Box<MaybeUninit<T>>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn alloc_uninit(alloc: A) -> Result<Box<MaybeUninit<T>, A>, AllocError> {
         if size_of::<T>() == 0 {
             Ok(Box {
                 ptr: NonNull::dangling(),
                 layout: unsafe {
                     Layout::from_size_align_unchecked(0, 0)
-                }
+                },
+                alloc,
             })
         } else {
             Ok(Box {
-                ptr: unsafe { ALLOCATOR.allocate_uninit() }?,
-                layout: Layout::new::<T>()
+                ptr: alloc.allocate(Layout::new::<T>())?.cast(),
+                layout: Layout::new::<T>(),
+                alloc,
             })
         }
     }
 
-    /// Allocates zeroed memory for an instance of `T`
+    /// Allocates zeroed memory for an instance of `T` in `alloc`
     /// - Does not allocate data if `T` is zero sized
-    fn alloc_zeroed() -> Result<Box<MaybeUninit<T>>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn alloc_zeroed(alloc: A) -> Result<Box<MaybeUninit<T>, A>, AllocError> {
         if size_of::<T>() == 0 {
             Ok(Box {
                 ptr: NonNull::dangling(),
                 layout: unsafe {
                     Layout::from_size_align_unchecked(0, 0)
-                }
+                },
+                alloc,
             })
         } else {
             Ok(Box {
-                ptr: unsafe { ALLOCATOR.allocate_zeroed() }?,
-                layout: Layout::new::<T>()
+                ptr: alloc.allocate_zeroed(Layout::new::<T>())?.cast(),
+                layout: Layout::new::<T>(),
+                alloc,
             })
         }
     }
 
 }
 
-impl<T: ?Sized> Box<T> {
+impl<T: ?Sized, A: AllocRef> Box<T, A> {
     /// Returns `true` if the `Box` did not allocate any memory
     /// - `size_of::<T>() == 0`
     pub fn is_dangling(&self) -> bool { self.layout.size() == 0}
@@ -538,47 +683,48 @@ impl<T: ?Sized> Box<T> {
 
 
 
-impl<T: ?Sized> AsMut<T> for Box<T> {
+impl<T: ?Sized, A: AllocRef> AsMut<T> for Box<T, A> {
     fn as_mut(&mut self) -> &mut T {
         unsafe { self.ptr.as_mut() }
     }
 }
 
-impl<T: ?Sized> AsRef<T> for Box<T> {
+impl<T: ?Sized, A: AllocRef> AsRef<T> for Box<T, A> {
     fn as_ref(&self) -> &T {
         unsafe { self.ptr.as_ref() }
     }
 }
 
-impl<T: ?Sized> crate::Borrow<T> for Box<T> {
+impl<T: ?Sized, A: AllocRef> crate::Borrow<T> for Box<T, A> {
     fn borrow(&self) -> &T {
         unsafe { self.ptr.as_ref() }
     }
 }
 
-impl<T: ?Sized> crate::BorrowMut<T> for Box<T> {
+impl<T: ?Sized, A: AllocRef> crate::BorrowMut<T> for Box<T, A> {
     fn borrow_mut(&mut self) -> &mut T {
         unsafe { self.ptr.as_mut() }
     }
 }
 
-impl<T: ?Sized + Clone> Clone for Box<T> {
+impl<T: ?Sized + Clone, A: AllocRef + Clone> Clone for Box<T, A> {
     fn clone(&self) -> Self {
-        Self::new(self.as_ref().clone())
+        Self::new_in(self.as_ref().clone(), self.alloc.clone())
     }
 }
 
-impl<T: Sized + TryClone> TryClone for Box<T> {
-    type Error = ();
+impl<T: Sized + TryClone, A: AllocRef + Clone> TryClone for Box<T, A>
+where T::Error: From<AllocError> {
+    type Error = T::Error;
     fn try_clone(&self) -> Result<Self, Self::Error>
         where Self: Sized {
-        Box::try_new(self.as_ref().try_clone().map_err(|_| () )?)
+        Ok(Box::try_new_in(self.as_ref().try_clone()?, self.alloc.clone())?)
     }
 }
 
-impl<T: Sized + Default> Default for Box<T> {
+impl<T: Sized + Default, A: AllocRef + Default> Default for Box<T, A> {
     fn default() -> Self {
-        Self::new(T::default())
+        Self::new_in(T::default(), A::default())
     }
 }
 
@@ -588,13 +734,13 @@ impl<T: Sized + Default + Unpin> Default for Pin<Box<T>> {
     }
 }
 
-impl<T: Display> Display for Box<T> {
+impl<T: Display, A: AllocRef> Display for Box<T, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_ref())
     }
 }
 
-impl<T: Debug> Debug for Box<T> {
+impl<T: Debug, A: AllocRef> Debug for Box<T, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if f.alternate() {
             write!(f, "Box {{ ptr: {:p}, layout: {:?}, value: {:?} }}", self.ptr, self.layout, self.as_ref())
@@ -604,7 +750,7 @@ impl<T: Debug> Debug for Box<T> {
     }
 }
 
-impl<T: ?Sized> Deref for Box<T> {
+impl<T: ?Sized, A: AllocRef> Deref for Box<T, A> {
     type Target = T;
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
@@ -612,7 +758,7 @@ impl<T: ?Sized> Deref for Box<T> {
     }
 }
 
-impl<T: ?Sized> DerefMut for Box<T> {
+impl<T: ?Sized, A: AllocRef> DerefMut for Box<T, A> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut T {
         unsafe { self.ptr.as_mut() }
@@ -620,18 +766,19 @@ impl<T: ?Sized> DerefMut for Box<T> {
 }
 
 
-impl<T: ?Sized> Drop for Box<T> {
+impl<T: ?Sized, A: AllocRef> Drop for Box<T, A> {
     fn drop(&mut self) {
         if !self.is_dangling() {
             unsafe {
                 drop_in_place(self.ptr.as_mut());
-                ALLOCATOR.dealloc(self.ptr.as_ptr().cast(), self.layout);
+                self.alloc.deallocate(self.ptr.cast(), self.layout);
             }
         }
     }
 }
 
 impl<T: Clone> From<&[T]> for Box<[T]> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn from(value: &[T]) -> Self {
         let ptr = unsafe {
             let mut other = value.iter();
@@ -642,7 +789,8 @@ impl<T: Clone> From<&[T]> for Box<[T]> {
 
         Box {
             ptr,
-            layout: layout_arr::<T>(value.len())
+            layout: layout_arr::<T>(value.len()),
+            alloc: Global,
         }
     }
 }
@@ -670,7 +818,7 @@ impl<T: Sized, const N: usize> From<[T; N]> for Box<[T]> {
 //  From<Box<[T]>> for Cow<'l, [T]>
 //impl<T: ?Sized + Display> ToString for Box<T>
 
-impl<T: Hash> Hash for Box<T> {
+impl<T: Hash, A: AllocRef> Hash for Box<T, A> {
     #[inline]
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.as_ref().hash(state);
@@ -678,7 +826,7 @@ impl<T: Hash> Hash for Box<T> {
 }
 
 
-impl<'l, T: Sized> IntoIterator for &'l Box<[T]> {
+impl<'l, T: Sized, A: AllocRef> IntoIterator for &'l Box<[T], A> {
     type IntoIter = core::slice::Iter<'l, T>;
     type Item = &'l T;
     #[inline]
@@ -688,7 +836,7 @@ impl<'l, T: Sized> IntoIterator for &'l Box<[T]> {
 }
 
 
-impl<T, U> PartialEq<U> for Box<T>
+impl<T, U, A: AllocRef> PartialEq<U> for Box<T, A>
 where T: PartialEq<U> + ?Sized {
     #[inline]
     fn eq(&self, other: &U) -> bool {
@@ -701,7 +849,7 @@ where T: PartialEq<U> + ?Sized {
     }
 }
 
-impl<T, U> PartialOrd<U> for Box<T>
+impl<T, U, A: AllocRef> PartialOrd<U> for Box<T, A>
 where T: PartialOrd<U> + ?Sized {
     #[inline]
     fn partial_cmp(&self, other: &U) -> Option<core::cmp::Ordering> {
@@ -722,23 +870,15 @@ where T: PartialOrd<U> + ?Sized {
 
 }
 
-impl<T: ?Sized> Pointer for Box<T> {
+impl<T: ?Sized, A: AllocRef> Pointer for Box<T, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:p}", self.ptr)
     }
 }
 
-unsafe impl<T: ?Sized + Sync> Sync for Box<T> {}
-
-unsafe impl<T: ?Sized + Send> Send for Box<T> {}
-
-impl<T: ?Sized + Clone> ToOwned for Box<T> {
-    type Owned = T;
-    fn to_owned(&self) -> Self::Owned {
-        self.as_ref().clone()
-    }
-}
+unsafe impl<T: ?Sized + Sync, A: AllocRef + Sync> Sync for Box<T, A> {}
 
+unsafe impl<T: ?Sized + Send, A: AllocRef + Send> Send for Box<T, A> {}
 
 
 
@@ -769,6 +909,7 @@ impl<T: Sized> Box<T> {
 
     /// Allocates memory on heap with `val` value
     /// - panics if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new(val: T) -> Self {
         Self {
             data: match unsafe { ALLOCATOR.allocate(val) } {
@@ -780,6 +921,7 @@ impl<T: Sized> Box<T> {
 
     /// Tries to allocate memory with some value
     /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn try_new(val: T) -> Result<Self, ()> {
         Ok(Self {
             data: unsafe { ALLOCATOR.allocate(val).map_err(|v| {drop(v); ()} )? },
@@ -788,6 +930,7 @@ impl<T: Sized> Box<T> {
 
     /// Allocates memory on heap and leaves it uninitialized
     /// - panics if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_uninit() -> Box<MaybeUninit<T>> {
         let data = unsafe {
             ALLOCATOR.alloc(Self::layout())
@@ -802,6 +945,7 @@ impl<T: Sized> Box<T> {
 
     /// Tries to allocate memory on heap and leaves it uninitialized
     /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn try_new_uninit() -> Result<Box<MaybeUninit<T>>, ()> {
         let data = unsafe {
             ALLOCATOR.alloc(Self::layout())
@@ -818,6 +962,7 @@ impl<T: Sized> Box<T> {
 
     /// Allocates memory on heap and forces all bytes to 0
     /// - panics if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_zeroed() -> Box<MaybeUninit<T>> {
         let data = unsafe {
             ALLOCATOR.alloc(Self::layout())
@@ -832,6 +977,7 @@ impl<T: Sized> Box<T> {
 
     /// Tries to allocate memory while forcing all bytes to 0
     /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn try_new_zeroed() -> Result<Box<MaybeUninit<T>>, ()> {
         let data = unsafe {
             ALLOCATOR.alloc(Self::layout())
@@ -938,6 +1084,7 @@ impl<T> AsMut<T> for Box<T> {
 }
 
 impl<T: Clone> Clone for Box<T> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn clone(&self) -> Self {
         let val = self.as_ref().clone();
         Self {
@@ -954,6 +1101,7 @@ where T: Sized + TryClone, T::Error: Default {
 
     type Error = T::Error;
 
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn try_clone(&self) -> Result<Self, Self::Error>
     where Self: Sized, Self::Error: Default {
         
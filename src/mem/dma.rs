@@ -0,0 +1,659 @@
+//	mem/dma.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! DMA-capable allocations
+//! - buffers are carved out of a dedicated pool of `Region`s registered with [`add_region`]
+//!   - the regions must be mapped, physically contiguous and not used for anything else
+//! - every buffer is physically contiguous and knows its physical address (`phys_addr()`)
+//! - [`DmaConstraints`] describe where the buffer may lie
+//!   - align of the physical address, physical address limit (`below`) and a boundary the buffer must not cross
+//!
+//! # Example
+//! ```
+//! unsafe { dma::add_region(Region::new(virt, phys, 256 * KB))? };
+//!
+//! //  floppy/ISA DMA: below 16 MiB, must not cross a 64 KiB boundary
+//! let buffer: DmaVec<u8> = DmaVec::with_capacity(512, DmaConstraints::ISA);
+//! controller.set_address(buffer.phys_addr());
+//!
+//! let ring = DmaBox::new([Descriptor::EMPTY; 32], DmaConstraints::new().align(4 * KB));
+//! ```
+
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+use crate::mem::{Region, MB, GB, PAGE_SIZE};
+use crate::sync::IrqMutex;
+
+
+/// Maximal number of regions in the DMA pool
+pub const MAX_DMA_REGIONS: usize = 16;
+
+/// Maximal number of free extents the DMA pool can keep track of
+/// - allocations that would fragment the pool further fail with `Err(Fragmented)`
+pub const MAX_DMA_EXTENTS: usize = 128;
+
+/// Every buffer is rounded up to (and aligned to) this many bytes
+pub const DMA_GRANULE: usize = 16;
+
+
+/// Error returned by the DMA pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaError {
+    /// The region has zero size or null address
+    Empty,
+    /// The pool has `MAX_DMA_REGIONS` regions already
+    Full,
+    /// The region overlaps with a region of the pool (physically or virtually)
+    Overlap,
+    /// The virtual and the physical address of the region have different offsets in their pages
+    Misaligned,
+    /// No free memory satisfies the request
+    OutOfMemory,
+    /// The free memory is split into too many pieces (see `MAX_DMA_EXTENTS`)
+    Fragmented,
+    /// The requested align or boundary is not a power of two, or the align is larger than the boundary
+    InvalidAlignment,
+    /// The buffer is larger than the boundary it must not cross
+    TooLarge,
+}
+
+impl DmaError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Empty => "DMA region is empty",
+            Self::Full => "too many DMA regions",
+            Self::Overlap => "DMA region overlaps with another DMA region",
+            Self::Misaligned => "DMA region is mapped at a different page offset",
+            Self::OutOfMemory => "out of DMA memory",
+            Self::Fragmented => "DMA memory is too fragmented",
+            Self::InvalidAlignment => "DMA align or boundary is invalid",
+            Self::TooLarge => "DMA buffer is larger than its boundary",
+        }
+    }
+}
+
+impl fmt::Display for DmaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Placement requirements of a DMA buffer
+/// - all values are physical
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmaConstraints {
+    align: usize,
+    boundary: Option<usize>,
+    limit: usize,
+}
+
+impl DmaConstraints {
+
+    /// Buffer for the ISA DMA controller
+    /// - below 16 MiB, does not cross a 64 KiB boundary
+    pub const ISA: Self = Self::new().below(16 * MB).boundary(64 * 1024);
+
+    /// Buffer for devices with 32-bit addressing
+    /// - below 4 GiB
+    pub const DMA32: Self = Self::new().below(4 * GB);
+
+    /// No requirements (other than `DMA_GRANULE` align)
+    pub const fn new() -> Self {
+        Self {
+            align: DMA_GRANULE,
+            boundary: None,
+            limit: usize::MAX,
+        }
+    }
+
+    /// The physical address of the buffer has to be aligned to `align` bytes
+    /// - must be a power of two
+    pub const fn align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// The buffer must not cross a multiple of `boundary` bytes
+    /// - must be a power of two and must not be smaller than the align
+    pub const fn boundary(mut self, boundary: usize) -> Self {
+        self.boundary = Some(boundary);
+        self
+    }
+
+    /// The whole buffer has to lie below the physical address `limit`
+    pub const fn below(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns the required align
+    #[inline(always)]
+    pub const fn get_align(&self) -> usize { self.align }
+
+    /// Returns the boundary the buffer must not cross
+    #[inline(always)]
+    pub const fn get_boundary(&self) -> Option<usize> { self.boundary }
+
+    /// Returns the physical address limit
+    #[inline(always)]
+    pub const fn get_limit(&self) -> usize { self.limit }
+
+    /// Checks the values of the constraints
+    const fn check(&self) -> Result<(), DmaError> {
+        if !self.align.is_power_of_two() {
+            return Err(DmaError::InvalidAlignment);
+        }
+        if let Some(boundary) = self.boundary && (!boundary.is_power_of_two() || self.align > boundary) {
+            return Err(DmaError::InvalidAlignment);
+        }
+        Ok(())
+    }
+
+}
+
+impl Default for DmaConstraints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Region of the pool
+#[derive(Clone, Copy)]
+struct Window {
+    virt: usize,
+    phys: usize,
+    size: usize,
+}
+
+/// Free physical memory `[phys, phys + size)` inside of the window `window`
+#[derive(Clone, Copy)]
+struct Extent {
+    phys: usize,
+    size: usize,
+    window: usize,
+}
+
+/// Regions of the pool and their free memory
+struct DmaPool {
+    windows: [Window; MAX_DMA_REGIONS],
+    windows_len: usize,
+    /// Free extents sorted by their physical address
+    extents: [Extent; MAX_DMA_EXTENTS],
+    extents_len: usize,
+    total: usize,
+    free: usize,
+}
+
+impl DmaPool {
+
+    const fn new() -> Self {
+        Self {
+            windows: [Window { virt: 0, phys: 0, size: 0 }; MAX_DMA_REGIONS],
+            windows_len: 0,
+            extents: [Extent { phys: 0, size: 0, window: 0 }; MAX_DMA_EXTENTS],
+            extents_len: 0,
+            total: 0,
+            free: 0,
+        }
+    }
+
+    fn add(&mut self, window: Window) -> Result<(), DmaError> {
+        //  only whole granules are used
+        let skip = window.phys.next_multiple_of(DMA_GRANULE) - window.phys;
+        let size = window.size.saturating_sub(skip) & !(DMA_GRANULE - 1);
+        let window = Window { virt: window.virt + skip, phys: window.phys + skip, size };
+
+        if window.size == 0 || window.virt == 0 {
+            return Err(DmaError::Empty);
+        }
+        if window.phys.checked_add(window.size).is_none() || window.virt.checked_add(window.size).is_none() {
+            return Err(DmaError::Empty);
+        }
+        //  buffers are aligned by their physical address, the virtual one has to follow it
+        if (window.virt ^ window.phys) & (PAGE_SIZE - 1) != 0 {
+            return Err(DmaError::Misaligned);
+        }
+
+        for other in &self.windows[..self.windows_len] {
+            let phys = window.phys < other.phys + other.size && other.phys < window.phys + window.size;
+            let virt = window.virt < other.virt + other.size && other.virt < window.virt + window.size;
+            if phys || virt {
+                return Err(DmaError::Overlap);
+            }
+        }
+
+        if self.windows_len == MAX_DMA_REGIONS {
+            return Err(DmaError::Full);
+        }
+        if self.extents_len == MAX_DMA_EXTENTS {
+            return Err(DmaError::Fragmented);
+        }
+
+        self.windows[self.windows_len] = window;
+        self.insert(Extent { phys: window.phys, size: window.size, window: self.windows_len });
+        self.windows_len += 1;
+        self.total += window.size;
+        self.free += window.size;
+
+        Ok(())
+    }
+
+    /// Allocates `size` bytes
+    /// - returns the virtual and the physical address
+    /// - both addresses are aligned to `align`, only the physical one to the align of `constraints`
+    fn alloc(&mut self, size: usize, align: usize, constraints: &DmaConstraints) -> Result<(NonNull<u8>, usize), DmaError> {
+        constraints.check()?;
+
+        let size = Self::round(size);
+        let virt_align = align;
+        let align = core::cmp::max(core::cmp::max(align, constraints.align), DMA_GRANULE);
+
+        if let Some(boundary) = constraints.boundary && size > boundary {
+            return Err(DmaError::TooLarge);
+        }
+
+        for index in 0..self.extents_len {
+            let extent = self.extents[index];
+            //  the windows agree with their physical memory only up to the page size
+            let window = self.windows[extent.window];
+            if (window.virt ^ window.phys) & (virt_align - 1) != 0 {
+                continue;
+            }
+            let Some(phys) = Self::place(&extent, size, align, constraints) else {
+                continue;
+            };
+
+            let end = extent.phys + extent.size;
+            let before = Extent { phys: extent.phys, size: phys - extent.phys, window: extent.window };
+            let after = Extent { phys: phys + size, size: end - (phys + size), window: extent.window };
+
+            //  splitting the extent in the middle needs one more slot
+            if before.size != 0 && after.size != 0 && self.extents_len == MAX_DMA_EXTENTS {
+                return Err(DmaError::Fragmented);
+            }
+
+            self.remove(index);
+            if before.size != 0 {
+                self.insert(before);
+            }
+            if after.size != 0 {
+                self.insert(after);
+            }
+            self.free -= size;
+
+            let virt = window.virt + (phys - window.phys);
+
+            //  windows never start at null
+            return Ok((unsafe { NonNull::new_unchecked(virt as *mut u8) }, phys));
+        }
+
+        Err(DmaError::OutOfMemory)
+    }
+
+    /// Returns the lowest physical address in `extent` that satisfies the request
+    fn place(extent: &Extent, size: usize, align: usize, constraints: &DmaConstraints) -> Option<usize> {
+        let mut phys = extent.phys.checked_next_multiple_of(align)?;
+
+        if let Some(boundary) = constraints.boundary
+            && phys / boundary != (phys + size - 1) / boundary {
+            //  move to the next boundary, it is aligned because `check` makes sure `align <= boundary`
+            phys = (phys / boundary + 1).checked_mul(boundary)?.checked_next_multiple_of(align)?;
+        }
+
+        let end = phys.checked_add(size)?;
+        if end > extent.phys + extent.size || end > constraints.limit {
+            return None;
+        }
+
+        Some(phys)
+    }
+
+    /// Returns `size` bytes at `phys` back to the pool
+    fn free(&mut self, phys: usize, size: usize) {
+        let size = Self::round(size);
+
+        let window = self.windows[..self.windows_len].iter()
+            .position(|w| phys >= w.phys && phys + size <= w.phys + w.size)
+            .expect("DmaPool: freed buffer does not belong to the pool");
+
+        let mut extent = Extent { phys, size, window };
+
+        //  merge with the neighbours from the same window
+        let index = self.extents[..self.extents_len].partition_point(|e| e.phys < phys);
+
+        if index < self.extents_len {
+            let next = self.extents[index];
+            debug_assert!(phys + size <= next.phys, "DmaPool: double free");
+            if next.window == window && phys + size == next.phys {
+                extent.size += next.size;
+                self.remove(index);
+            }
+        }
+        if index > 0 {
+            let prev = &mut self.extents[index - 1];
+            debug_assert!(prev.phys + prev.size <= phys, "DmaPool: double free");
+            if prev.window == window && prev.phys + prev.size == phys {
+                prev.size += extent.size;
+                self.free += size;
+                return;
+            }
+        }
+
+        //  the extent cannot be merged and there is no slot left, the memory is lost
+        if self.extents_len == MAX_DMA_EXTENTS {
+            self.total -= size;
+            return;
+        }
+
+        self.insert(extent);
+        self.free += size;
+    }
+
+    /// Inserts extent, keeps them sorted
+    /// - there has to be a free slot
+    fn insert(&mut self, extent: Extent) {
+        let index = self.extents[..self.extents_len].partition_point(|e| e.phys < extent.phys);
+        self.extents.copy_within(index..self.extents_len, index + 1);
+        self.extents[index] = extent;
+        self.extents_len += 1;
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.extents.copy_within(index + 1..self.extents_len, index);
+        self.extents_len -= 1;
+    }
+
+    /// Returns the number of bytes the pool uses for a buffer of `size` bytes
+    #[inline(always)]
+    const fn round(size: usize) -> usize {
+        if size == 0 { DMA_GRANULE } else { size.next_multiple_of(DMA_GRANULE) }
+    }
+
+}
+
+
+static POOL: IrqMutex<DmaPool> = IrqMutex::new(DmaPool::new());
+
+
+/// Adds memory to the DMA pool
+/// - returns `Err(Overlap)` if the region overlaps with a region of the pool
+/// - returns `Err(Misaligned)` if the virtual and the physical address of `region` differ in their page offsets
+/// - returns `Err(Full)` if the pool has `MAX_DMA_REGIONS` regions
+///
+/// # Safety
+/// - the virtual memory of `region` must be mapped to its physical memory for the rest of the kernel's life
+/// - the memory must not be used by anything else
+pub unsafe fn add_region<const ALIGN: usize>(region: Region<ALIGN>) -> Result<(), DmaError> {
+    let mut pool = POOL.lock();
+
+    pool.add(Window { virt: region.virt() as usize, phys: region.phys(), size: region.size() })
+}
+
+/// Returns number of bytes in the DMA pool
+pub fn total_bytes() -> usize {
+    POOL.lock().total
+}
+
+/// Returns number of free bytes in the DMA pool
+pub fn free_bytes() -> usize {
+    POOL.lock().free
+}
+
+
+/// Value placed in DMA-capable memory
+/// - the memory is physically contiguous, `phys_addr()` returns its physical address
+pub struct DmaBox<T> {
+    ptr: NonNull<T>,
+    phys: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for DmaBox<T> {}
+unsafe impl<T: Sync> Sync for DmaBox<T> {}
+
+impl<T> DmaBox<T> {
+
+    /// Moves `val` into DMA memory that satisfies `constraints`
+    /// - **panics** if allocation fails
+    pub fn new(val: T, constraints: DmaConstraints) -> Self {
+        match Self::try_new(val, constraints) {
+            Ok(b) => b,
+            Err(e) => panic!("DmaBox: {}", e),
+        }
+    }
+
+    /// Tries to move `val` into DMA memory that satisfies `constraints`
+    /// - returns `Err` if the pool has no memory that satisfies them
+    pub fn try_new(val: T, constraints: DmaConstraints) -> Result<Self, DmaError> {
+        let (ptr, phys) = POOL.lock().alloc(size_of::<T>(), align_of::<T>(), &constraints)?;
+        let ptr = ptr.cast::<T>();
+
+        unsafe { ptr.write(val); }
+
+        Ok(Self { ptr, phys, _marker: PhantomData })
+    }
+
+    /// Returns the physical address of the value
+    #[inline(always)]
+    pub const fn phys_addr(&self) -> usize {
+        self.phys
+    }
+
+    /// Returns the virtual address of the value
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Moves the value out and frees the memory
+    pub fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        let val = unsafe { this.ptr.read() };
+        POOL.lock().free(this.phys, size_of::<T>());
+        val
+    }
+
+}
+
+impl<T> Deref for DmaBox<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for DmaBox<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DmaBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Drop for DmaBox<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()); }
+        POOL.lock().free(self.phys, size_of::<T>());
+    }
+}
+
+
+/// Fixed-capacity vector in DMA-capable memory
+/// - the buffer never moves, so the physical address stays valid for the whole life of the vector
+/// - `push` fails once the capacity is reached
+pub struct DmaVec<T> {
+    ptr: NonNull<T>,
+    phys: usize,
+    len: usize,
+    capacity: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for DmaVec<T> {}
+unsafe impl<T: Sync> Sync for DmaVec<T> {}
+
+impl<T> DmaVec<T> {
+
+    /// Constructs empty vector with room for `capacity` elements in DMA memory that satisfies `constraints`
+    /// - **panics** if allocation fails
+    pub fn with_capacity(capacity: usize, constraints: DmaConstraints) -> Self {
+        match Self::try_with_capacity(capacity, constraints) {
+            Ok(v) => v,
+            Err(e) => panic!("DmaVec: {}", e),
+        }
+    }
+
+    /// Tries to construct empty vector with room for `capacity` elements in DMA memory that satisfies `constraints`
+    /// - returns `Err` if the pool has no memory that satisfies them
+    pub fn try_with_capacity(capacity: usize, constraints: DmaConstraints) -> Result<Self, DmaError> {
+        let size = size_of::<T>().checked_mul(capacity).ok_or(DmaError::TooLarge)?;
+        let (ptr, phys) = POOL.lock().alloc(size, align_of::<T>(), &constraints)?;
+
+        Ok(Self { ptr: ptr.cast(), phys, len: 0, capacity, _marker: PhantomData })
+    }
+
+    /// Constructs vector of `len` zeroed elements
+    /// - **panics** if allocation fails
+    ///
+    /// # Safety
+    /// - all-zero bytes must be a valid value of `T`
+    pub unsafe fn zeroed(len: usize, constraints: DmaConstraints) -> Self {
+        let mut this = Self::with_capacity(len, constraints);
+        unsafe {
+            ptr::write_bytes(this.ptr.as_ptr(), 0, len);
+            this.set_len(len);
+        }
+        this
+    }
+
+    /// Constructs vector with copy of `slice`
+    /// - **panics** if allocation fails
+    pub fn from_slice(slice: &[T], constraints: DmaConstraints) -> Self
+    where T: Copy {
+        let mut this = Self::with_capacity(slice.len(), constraints);
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), this.ptr.as_ptr(), slice.len());
+            this.set_len(slice.len());
+        }
+        this
+    }
+
+    /// Returns the physical address of the first element
+    #[inline(always)]
+    pub const fn phys_addr(&self) -> usize {
+        self.phys
+    }
+
+    /// Returns the physical address of the element at `index`
+    /// - the element does not have to be initialized
+    /// - returns `None` if `index >= capacity`
+    #[inline]
+    pub const fn phys_addr_of(&self, index: usize) -> Option<usize> {
+        if index < self.capacity { Some(self.phys + index * size_of::<T>()) } else { None }
+    }
+
+    /// Returns the number of elements
+    #[inline(always)]
+    pub const fn len(&self) -> usize { self.len }
+
+    /// Checks if the vector is empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of elements the vector can hold
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize { self.capacity }
+
+    /// Returns pointer to the buffer
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *const T { self.ptr.as_ptr() }
+
+    /// Returns mutable pointer to the buffer
+    #[inline(always)]
+    pub const fn as_mut_ptr(&mut self) -> *mut T { self.ptr.as_ptr() }
+
+    /// Sets the length of the vector
+    ///
+    /// # Safety
+    /// - `len <= capacity`
+    /// - elements up to `len` must be initialized
+    #[inline(always)]
+    pub unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    /// Appends `val` to the end of the vector
+    /// - returns `Err(val)` if the vector is full
+    pub fn push(&mut self, val: T) -> Result<(), T> {
+        if self.len == self.capacity {
+            return Err(val);
+        }
+        unsafe { self.ptr.add(self.len).write(val); }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last element
+    /// - returns `None` if the vector is empty
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    /// Drops all elements
+    pub fn clear(&mut self) {
+        let len = self.len;
+        self.len = 0;
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), len)); }
+    }
+
+}
+
+impl<T> Deref for DmaVec<T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for DmaVec<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DmaVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Drop for DmaVec<T> {
+    fn drop(&mut self) {
+        self.clear();
+        POOL.lock().free(self.phys, size_of::<T>() * self.capacity);
+    }
+}
@@ -9,15 +9,10 @@
 
 use core::marker::PhantomData;
 use core::mem::ManuallyDrop;
-use core::ptr::{copy_nonoverlapping, null_mut, NonNull};
-use core::alloc::{Layout, GlobalAlloc};
-use crate::{ALLOCATOR, TryClone};
-
-/// returns the minimum of 3 values
-#[inline(always)]
-fn min_3(v1: usize, v2: usize, v3: usize) -> usize {
-    core::cmp::min(core::cmp::min(v1, v2), v3)
-}
+use core::ptr::{null_mut, NonNull};
+use core::alloc::Layout;
+use crate::mem::alloc::{AllocError, AllocRef, Global, TryReserveError};
+use crate::TryClone;
 
 
 
@@ -30,10 +25,11 @@ fn min_3(v1: usize, v2: usize, v3: usize) -> usize {
 /// ## Memory layout
 /// The `DynamicBuffer` has standardized memory layout:
 /// ```rust
-/// pub struct DynamicBuffer<T, STEP, ALIGN> {
+/// pub struct DynamicBuffer<T, STEP, ALIGN, A> {
 ///     data: NonNull::<u8>,
 ///     cap: u32,
 ///     pub size: u32,
+///     alloc: A,
 /// }
 /// ```
 /// 
@@ -50,16 +46,96 @@ fn min_3(v1: usize, v2: usize, v3: usize) -> usize {
 /// 3. `ALIGN` - defines custom alignment of the data
 ///     - set to 0 to use `align_of::<T>()`
 ///     - if used value is invalid, the `DynamicB uffer` will use the closest valid value
+/// 4. `A` - the allocator (see [`AllocRef`])
+///     - uses the global `ALLOCATOR` by default
 #[repr(C)]
-pub struct DynamicBuffer<T: Sized, const STEP: usize, const ALIGN: usize = 0> {
+pub struct DynamicBuffer<T: Sized, const STEP: usize, const ALIGN: usize = 0, A: AllocRef = Global> {
     data: NonNull::<u8>,
     cap: u32,
     pub size: u32,
+    alloc: A,
     _marker: PhantomData<T>,
 }
 
 impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALIGN> {
 
+    /// Constructs empty DynamicBuffer with no allocated data
+    pub const fn empty() -> Self {
+        Self::empty_in(Global)
+    }
+
+    pub(crate) const fn from_raw(data: NonNull<T>, cap: u32, size: u32) -> DynamicBuffer<T, STEP, ALIGN> {
+        Self::from_raw_in(data, cap, size, Global)
+    }
+
+    /// Constructs `DynamicBuffer<T>` with some elements allocated
+    /// - **panics** if allocation fails
+    /// - `size = 0`
+    /// - `capacity` is aligned to `STEP`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated
+    /// - returns `Err` if allocation fails
+    /// - `size = 0`
+    /// - `capacity` is aligned to `STEP`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
+    /// Constructs `DynamicBuffer<T>` with some elements allocated
+    /// - **panics** if allocation fails
+    /// - `size = 0`
+    /// - `capacity` is not aligned to `STEP`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_exact_capacity(capacity: usize) -> Self {
+        Self::with_exact_capacity_in(capacity, Global)
+    }
+
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated
+    /// - returns `Err` if allocation fails
+    /// - `size = 0`
+    /// - `capacity` is not aligned to `STEP`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_exact_capacity(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_exact_capacity_in(capacity, Global)
+    }
+
+    /// Constructs `DynamicBuffer<T>` with some elements allocated and zeroed memory
+    /// - **panics** if allocation fails
+    /// - `size = 0`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_zeroed(capacity: usize) -> Self {
+        Self::with_capacity_zeroed_in(capacity, Global)
+    }
+
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated and zeroed memory
+    /// - returns `Err` if allocation fails
+    /// - `size = 0`
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_zeroed(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_capacity_zeroed_in(capacity, Global)
+    }
+
+    /// Constructs new `DynamicBuffer` from raw parts
+    /// - **warning**: may be potentially unsafe
+    pub fn from_raw_parts(ptr: NonNull<T>, layout: Layout) -> Self {
+        Self::from_raw_parts_in(ptr, layout, Global)
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DynamicBuffer<T, STEP, ALIGN, A> {
+
 
     /// The real alignment of the data
     const ALGN: usize = if ALIGN < align_of::<T>() {
@@ -82,187 +158,156 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
         Self::layout_for_exact(self.capacity())
     }
 
-    /// Constructs empty DynamicBuffer with no allocated data
-    pub const fn empty() -> Self {
+    /// Constructs empty DynamicBuffer with no allocated data in `alloc`
+    pub const fn empty_in(alloc: A) -> Self {
         Self {
             data: NonNull::dangling(),
             cap: 0,
             size: 0,
+            alloc,
             _marker: PhantomData,
         }
     }
 
-    pub(crate) const fn from_raw(data: NonNull<T>, cap: u32, size: u32) -> DynamicBuffer<T, STEP, ALIGN> {
+    pub(crate) const fn from_raw_in(data: NonNull<T>, cap: u32, size: u32, alloc: A) -> Self {
         Self {
-            data: unsafe { NonNull::new_unchecked(data.as_ptr() as *mut u8) },
+            data: data.cast(),
             cap,
             size,
+            alloc,
             _marker: PhantomData,
         }
     }
 
-    /// Constructs `DynamicBuffer<T>` with some elements allocated
+    /// Constructs `DynamicBuffer<T>` with some elements allocated in `alloc`
     /// - **panics** if allocation fails
     /// - `size = 0`
     /// - `capacity` is aligned to `STEP`
-    pub fn with_capacity(capacity: usize) -> Self {
-        let cap = Self::new_capacity(capacity);
-
-        let l = Self::layout_for_exact(cap);
-
-        let data = unsafe { ALLOCATOR.alloc(l) };
-
-        assert!(!data.is_null(), "failed to allocate data");
-
-        Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            cap: cap as u32,
-            size: 0,
-            _marker: PhantomData
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        match Self::try_with_capacity_in(capacity, alloc) {
+            Ok(s) => s,
+            Err(e) => panic!("failed to allocate data: {}", e),
         }
     }
 
-    /// Tries to construct `DynamicBuffer<T>` with some elements allocated
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated in `alloc`
     /// - returns `Err` if allocation fails
     /// - `size = 0`
     /// - `capacity` is aligned to `STEP`
-    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
         let cap = Self::new_capacity(capacity);
 
-        let l = Self::layout_for_exact(cap);
-
-        let data = unsafe { ALLOCATOR.alloc(l) };
-
-        if data.is_null() {
-            return Err(());
-        }
+        let data = alloc.allocate(Self::try_layout_for(cap)?)?;
 
         Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             cap: cap as u32,
             size: 0,
+            alloc,
             _marker: PhantomData
         })
     }
 
 
-    /// Constructs `DynamicBuffer<T>` with some elements allocated
+    /// Constructs `DynamicBuffer<T>` with some elements allocated in `alloc`
     /// - **panics** if allocation fails
     /// - `size = 0`
     /// - `capacity` is not aligned to `STEP`
-    pub fn with_exact_capacity(capacity: usize) -> Self {
-        let l = Self::layout_for_exact(capacity);
-
-        let data = unsafe { ALLOCATOR.alloc(l) };
-
-        assert!(!data.is_null(), "failed to allocate data");
-
-        Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            cap: capacity as u32,
-            size: 0,
-            _marker: PhantomData
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_exact_capacity_in(capacity: usize, alloc: A) -> Self {
+        match Self::try_with_exact_capacity_in(capacity, alloc) {
+            Ok(s) => s,
+            Err(e) => panic!("failed to allocate data: {}", e),
         }
     }
 
-    /// Tries to construct `DynamicBuffer<T>` with some elements allocated
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated in `alloc`
     /// - returns `Err` if allocation fails
     /// - `size = 0`
     /// - `capacity` is not aligned to `STEP`
-    pub fn try_with_exact_capacity(capacity: usize) -> Result<Self, ()> {
-        let l = Self::layout_for_exact(capacity);
-
-        let data = unsafe { ALLOCATOR.alloc(l) };
-
-        if data.is_null() {
-            return Err(());
-        }
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_exact_capacity_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
+        let data = alloc.allocate(Self::try_layout_for(capacity)?)?;
 
         Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             cap: capacity as u32,
             size: 0,
+            alloc,
             _marker: PhantomData
         })
     }
 
-    /// Constructs `DynamicBuffer<T>` with some elements allocated and zeroed memory
+    /// Constructs `DynamicBuffer<T>` with some elements allocated in `alloc` and zeroed memory
     /// - **panics** if allocation fails
     /// - `size = 0`
-    pub fn with_capacity_zeroed(capacity: usize) -> Self {
-        let cap = Self::new_capacity(capacity);
-
-        let l = Self::layout_for_exact(cap);
-        
-        let data = unsafe { ALLOCATOR.alloc_zeroed(l) };
-
-        assert!(!data.is_null(), "failed to allocate data");
-
-        Self {
-            data: unsafe { NonNull::new_unchecked(data) },
-            cap: cap as u32,
-            size: 0,
-            _marker: PhantomData
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_zeroed_in(capacity: usize, alloc: A) -> Self {
+        match Self::try_with_capacity_zeroed_in(capacity, alloc) {
+            Ok(s) => s,
+            Err(e) => panic!("failed to allocate data: {}", e),
         }
     }
 
-    /// Tries to construct `DynamicBuffer<T>` with some elements allocated and zeroed memory
+    /// Tries to construct `DynamicBuffer<T>` with some elements allocated in `alloc` and zeroed memory
     /// - returns `Err` if allocation fails
     /// - `size = 0`
-    pub fn try_with_capacity_zeroed(capacity: usize) -> Result<Self, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_zeroed_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
         let cap = Self::new_capacity(capacity);
 
-        let l = Self::layout_for_exact(cap);
-
-        let data = unsafe { ALLOCATOR.alloc(l) };
-
-        if data.is_null() {
-            return Err(());
-        }
+        let data = alloc.allocate_zeroed(Self::try_layout_for(cap)?)?;
 
         Ok(Self {
-            data: unsafe { NonNull::new_unchecked(data) },
+            data,
             cap: cap as u32,
             size: 0,
+            alloc,
             _marker: PhantomData
         })
     }
 
+    /// Moves the buffer to new allocation with `cap` elements
+    /// - an existing buffer is grown or shrunk by the allocator, which resizes it in place if it can
+    /// - the whole old buffer is copied if the allocator moves it
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn relocate(&mut self, cap: usize) -> Result<(), TryReserveError> {
+
+        let layout = Self::try_layout_for(cap)?;
+
+        let new = if self.capacity() == 0 || cap == 0 || size_of::<T>() == 0 {
+            //  `grow` and `shrink` do not take empty blocks
+            let new = self.alloc.allocate(layout)?;
+            if self.capacity() > 0 {
+                unsafe { self.alloc.deallocate(self.data, self.layout()); }
+            }
+            new
+        } else if cap > self.capacity() {
+            unsafe { self.alloc.grow(self.data, self.layout(), layout)? }
+        } else {
+            unsafe { self.alloc.shrink(self.data, self.layout(), layout)? }
+        };
+
+        self.data = new;
+        self.cap = cap as u32;
+
+        Ok(())
+    }
+
     /// Resizes (reallocates) the buffer to certain size
     /// - `size` is aligned to `STEP`
     /// - **no elements are dropped**
     /// - **no-op** if `capacity` would be the same`
     /// - if `self.is_empty()` allocates new data
     /// - **panics** if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
+    /// - **copies the whole old block to the new location** (only the part that fits if the buffer shrinks)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn resize(&mut self, size: usize) {
-
-        if self.capacity() == size {
-            return
-        }
-
-        let wanted = Self::new_capacity(size);
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        assert!(!new.is_null(), "failed to allocate memory");
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
+        if let Err(e) = self.try_resize(size) {
+            panic!("failed to allocate memory: {}", e);
         }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
     }
 
 
@@ -273,39 +318,15 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
     /// - **no-op** if `capacity` would be the same`
     /// - if `self.is_empty()` allocates new data
     /// - returns `Err` if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    pub fn try_resize(&mut self, size: usize) -> Result<(), ()> {
+    /// - **copies the whole old block to the new location** (only the part that fits if the buffer shrinks)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_resize(&mut self, size: usize) -> Result<(), TryReserveError> {
 
         if self.capacity() == size {
             return Ok(())
         }
 
-        let wanted = Self::new_capacity(size);
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        if new.is_null() {
-            return Err(());
-        }
-
-        if self.capacity() > 0 {
-
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
-        }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
-        Ok(())
+        self.relocate(Self::new_capacity(size))
 
     }
 
@@ -314,68 +335,28 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
     /// - **no elements are dropped**
     /// - if `self.is_empty()` allocates new data
     /// - **panics** if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
+    /// - **copies the whole old block to the new location** (only the part that fits if the buffer shrinks)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn resize_exact(&mut self, size: usize) {
-
-        if size == self.capacity() {
-            return
-        }
-
-        let layout = Self::layout_for_exact(size);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        assert!(!new.is_null(), "failed to allocate memory");
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), size);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
+        if let Err(e) = self.try_resize_exact(size) {
+            panic!("failed to allocate memory: {}", e);
         }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = size as u32;
-
     }
 
     /// Tries to resize (reallocate) the buffer to exact size
     /// - **no-op** if `capacity` would be the same`
     /// - **no elements are dropped**
     /// - if `self.is_empty()` allocates new data
-    /// - **panics** if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
-    pub fn try_resize_exact(&mut self, size: usize) -> Result<(), ()> {
-
-        let layout = Self::layout_for_exact(size);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        if new.is_null() {
-            return Err(());
-        }
+    /// - returns `Err` if allocation fails
+    /// - **copies the whole old block to the new location** (only the part that fits if the buffer shrinks)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_resize_exact(&mut self, size: usize) -> Result<(), TryReserveError> {
 
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), size);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
+        if size == self.capacity() {
+            return Ok(())
         }
 
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = size as u32;
-
-        Ok(())
+        self.relocate(size)
 
     }
 
@@ -383,142 +364,52 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
     /// Expands the `capacity` by `STEP` elements
     /// - this function always reallocates memory
     /// - **panics** if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
+    /// - **copies the whole old block to the new location**
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn expand(&mut self) {
-
-        let wanted = Self::next_capacity(self.capacity());
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        assert!(!new.is_null(), "failed to allocate memory");
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
+        if let Err(e) = self.try_expand() {
+            panic!("failed to allocate memory: {}", e);
         }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
     }
 
     /// Tries to expand the `capacity` by `STEP` elements
     /// - this function always reallocates memory
     /// - returns `Err` if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
-    pub fn try_expand(&mut self) -> Result<(), ()> {
-
-        let wanted = Self::next_capacity(self.capacity());
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        if new.is_null() {
-            return Err(());
-        }
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
-        }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
-        Ok(())
-
+    /// - **copies the whole old block to the new location**
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_expand(&mut self) -> Result<(), TryReserveError> {
+        self.relocate(Self::next_capacity(self.capacity()))
     }
 
     /// Expands the `capacity` by `STEP * steps` elements
     /// - this function always reallocates memory
     /// - **panics** if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
+    /// - **copies the whole old block to the new location**
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn expand_by(&mut self, steps: usize) {
-
-        let wanted = Self::next_capacity(self.capacity() + (STEP * steps));
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        assert!(!new.is_null(), "failed to allocate memory");
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
+        if let Err(e) = self.try_expand_by(steps) {
+            panic!("failed to allocate memory: {}", e);
         }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
     }
 
     /// Tries to expanf the `capacity` by `STEP * steps` elements
     /// - this function always reallocates memory
     /// - returns `Err` if allocation fails
-    /// - **Copies exactly `self.size` elements to the new location**
-    ///   - Copies all elements if `self.size > self.capacity()`
-    pub fn try_expand_by(&mut self, steps: usize) -> Result<(), ()> {
-
-        let wanted = Self::next_capacity(self.capacity() + (STEP * steps));
-
-        let layout = Self::layout_for_exact(wanted);
-
-        let new = unsafe { ALLOCATOR.alloc(layout) };
-
-        if new.is_null() {
-            return Err(());
-        }
-
-        if self.capacity() > 0 {
-            if self.size > 0 {
-                unsafe {
-                    //  eliminate buffer overflow
-                    let copy_size = min_3(self.size as usize, self.capacity(), wanted);
-                    copy_nonoverlapping(self.data.as_ptr(), new, copy_size * size_of::<T>());
-                }
-            }
-            unsafe { ALLOCATOR.dealloc(self.data.as_ptr(), self.layout()); }
-        }
-
-        self.data = unsafe { NonNull::new_unchecked(new) };
-        self.cap = wanted as u32;
-
-        Ok(())
-
+    /// - **copies the whole old block to the new location**
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_expand_by(&mut self, steps: usize) -> Result<(), TryReserveError> {
+        self.relocate(Self::next_capacity(self.capacity() + (STEP * steps)))
     }
 
     /// Constructs new `DynamicBuffer` from raw parts
+    /// - `ptr` must be allocated by `alloc`
     /// - **warning**: may be potentially unsafe
-    pub fn from_raw_parts(ptr: NonNull<T>, layout: Layout) -> Self {
+    pub fn from_raw_parts_in(ptr: NonNull<T>, layout: Layout, alloc: A) -> Self {
         Self {
-            data: unsafe { NonNull::new_unchecked(ptr.as_ptr() as *mut u8) },
+            data: ptr.cast(),
             cap: (layout.size()/size_of::<T>()) as u32,
             size: 0,
+            alloc,
             _marker: PhantomData
         }
     }
@@ -546,11 +437,17 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
         }
     }
 
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
 
 }
 
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DynamicBuffer<T, STEP, ALIGN, A> {
 
     /// Returns the `STEP` generic for this instance
     pub const fn step(&self) -> usize { STEP }
@@ -589,6 +486,18 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
         unsafe { Layout::from_size_align_unchecked(size_of::<T>() * capacity, Self::align_of()) }
     }
 
+    /// Describes memory layout for some capacity without aligning to `STEP`
+    /// - returns `Err(CapacityOverflow)` if the capacity does not fit the buffer or the size overflows `isize`
+    pub const fn try_layout_for(capacity: usize) -> Result<Layout, TryReserveError> {
+        if capacity > u32::MAX as usize {
+            return Err(AllocError::capacity_overflow());
+        }
+        match size_of::<T>().checked_mul(capacity) {
+            Some(size) => AllocError::layout_from(size, Self::align_of()),
+            None => Err(AllocError::capacity_overflow()),
+        }
+    }
+
     /// aligns the capacity up to next generic `STEP`
     /// - result is greater than `STEP`
     /// - returns number of elements
@@ -645,71 +554,46 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DynamicBuffer<T, STEP, ALI
 }
 
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Drop for DynamicBuffer<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for DynamicBuffer<T, STEP, ALIGN, A> {
     fn drop(&mut self) {
         if self.capacity() > 0 {
             unsafe {
-                ALLOCATOR.dealloc(self.data.as_ptr(), self.layout());
+                self.alloc.deallocate(self.data, self.layout());
             }
         }
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Clone for DynamicBuffer<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef + Clone> Clone for DynamicBuffer<T, STEP, ALIGN, A> {
     /// `DynamicBuffer::clone()` does **not copy** any data
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn clone(&self) -> Self {
-        if self.capacity() == 0 {
-            Self {
-                data: NonNull::dangling(),
-                cap: 0,
-                size: 0,
-                _marker: PhantomData,
-            }
-        } else {
-            let data = unsafe {
-                ALLOCATOR.alloc(self.layout())
-            };
-
-            assert!(!data.is_null(), "failed to allocate memory");
-
-            Self {
-                data: unsafe { NonNull::new_unchecked(data) },
-                cap: self.capacity() as u32,
-                size: self.size,
-                _marker: PhantomData,
-            }
+        match self.try_clone() {
+            Ok(s) => s,
+            Err(e) => panic!("failed to allocate memory: {}", e),
         }
     }
 }
 
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> TryClone for DynamicBuffer<T, STEP, ALIGN> {
-    type Error = ();
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef + Clone> TryClone for DynamicBuffer<T, STEP, ALIGN, A> {
+    type Error = AllocError;
     /// `DynamicBuffer::try_clone()` does **not copy** any data
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn try_clone(&self) -> Result<Self, Self::Error>
-    where Self: Sized, Self::Error: Default {
+    where Self: Sized {
 
         if self.capacity() == 0 {
-            Ok(Self {
-                data: NonNull::dangling(),
-                cap: 0,
-                size: 0,
-                _marker: PhantomData,
-            })
+            Ok(Self::empty_in(self.alloc.clone()))
         } else {
 
-            let data = unsafe {
-                ALLOCATOR.alloc(self.layout())
-            };
-
-            if data.is_null() {
-                return Err(());
-            }
+            let data = self.alloc.allocate(self.layout())?;
 
             Ok(Self {
-                data: unsafe { NonNull::new_unchecked(data) },
+                data,
                 cap: self.capacity() as u32,
                 size: self.size,
+                alloc: self.alloc.clone(),
                 _marker: PhantomData,
             })
         }
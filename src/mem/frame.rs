@@ -0,0 +1,530 @@
+//	mem/frame.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Physical page frame allocator
+//! - tracks `FRAME_SIZE` frames of the usable physical memory in a bitmap (one bit for each frame, `1` = used)
+//! - the bitmap is memory given by the caller (`bitmap_words` tells how much is needed), so no heap is needed
+//! - only physical addresses are handled, the memory of the frames is never touched
+//!   - the allocator can therefore be tested on the host with synthetic regions
+//! - the global allocator is initialized by [`init`] and used through [`alloc_frame`], [`alloc_contiguous`] and [`free_frame`]
+//!
+//! # Example
+//! ```
+//! static mut BITMAP: [u64; 1024] = [0; 1024];    //  tracks up to 256 MiB
+//!
+//! let usable = [Region::new(0, 0x10_0000, 16 * MB), Region::new(0, 0x200_0000, 64 * MB)];
+//! frame::init(unsafe { &mut *&raw mut BITMAP }, &usable)?;
+//!
+//! let frame = frame::alloc_frame()?;
+//! let dma = frame::alloc_contiguous(16, 64 * KB)?;
+//! frame::free_frame(frame)?;
+//! ```
+
+
+use core::fmt;
+
+use crate::mem::{Region, PAGE_SIZE};
+
+
+/// Size of one frame (in bytes)
+pub const FRAME_SIZE: usize = PAGE_SIZE;
+
+/// Maximal number of usable regions the allocator can be constructed from
+pub const MAX_FRAME_REGIONS: usize = 64;
+
+/// Number of frames tracked by one bitmap word
+const WORD_BITS: usize = u64::BITS as usize;
+
+
+/// Physical page frame
+/// - holds the physical address of the frame, it is always aligned to `FRAME_SIZE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Frame(usize);
+
+impl Frame {
+
+    /// Returns frame that contains the physical address `addr`
+    #[inline(always)]
+    pub const fn containing(addr: usize) -> Self {
+        Self(addr & !(FRAME_SIZE - 1))
+    }
+
+    /// Returns frame that starts at the physical address `addr`
+    /// - returns `None` if `addr` is not aligned to `FRAME_SIZE`
+    #[inline]
+    pub const fn from_addr(addr: usize) -> Option<Self> {
+        if addr.is_multiple_of(FRAME_SIZE) { Some(Self(addr)) } else { None }
+    }
+
+    /// Returns the physical address of the frame
+    #[inline(always)]
+    pub const fn addr(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the number of the frame (`addr / FRAME_SIZE`)
+    #[inline(always)]
+    pub const fn number(&self) -> usize {
+        self.0 / FRAME_SIZE
+    }
+
+}
+
+
+/// Error returned by the frame allocator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// No free frame (or no free run of frames) is left
+    OutOfFrames,
+    /// The requested align is not a power of two
+    InvalidAlignment,
+    /// The bitmap is too small for the given regions (see `bitmap_words`)
+    BitmapTooSmall,
+    /// None of the regions contains a whole frame
+    NoMemory,
+    /// More than `MAX_FRAME_REGIONS` regions were given
+    TooManyRegions,
+    /// The frame is outside of the tracked memory
+    OutOfRange,
+    /// The frame is not allocated (double free)
+    NotAllocated,
+    /// The global frame allocator is not initialized
+    Uninitialized,
+    /// The global frame allocator is already initialized
+    AlreadyInitialized,
+}
+
+impl FrameError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::OutOfFrames => "out of physical frames",
+            Self::InvalidAlignment => "frame align is not a power of two",
+            Self::BitmapTooSmall => "frame bitmap is too small",
+            Self::NoMemory => "no usable physical memory",
+            Self::TooManyRegions => "too many usable physical regions",
+            Self::OutOfRange => "frame is outside of the tracked memory",
+            Self::NotAllocated => "frame is not allocated",
+            Self::Uninitialized => "frame allocator is not initialized",
+            Self::AlreadyInitialized => "frame allocator is already initialized",
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Snapshot of the frame allocator state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// Number of usable frames
+    pub total_frames: usize,
+    /// Number of frames that are free
+    pub free_frames: usize,
+    /// Lowest value of `free_frames` so far
+    pub min_free_frames: usize,
+    /// Number of successful allocations (a contiguous run counts once)
+    pub allocations: usize,
+    /// Number of frees (a contiguous run counts once)
+    pub frees: usize,
+    /// Number of allocations that failed
+    pub failed_allocations: usize,
+}
+
+impl FrameStats {
+
+    /// Returns number of allocated frames
+    #[inline(always)]
+    pub const fn used_frames(&self) -> usize {
+        self.total_frames - self.free_frames
+    }
+
+    /// Returns number of usable bytes
+    #[inline(always)]
+    pub const fn total_bytes(&self) -> usize {
+        self.total_frames * FRAME_SIZE
+    }
+
+    /// Returns number of free bytes
+    #[inline(always)]
+    pub const fn free_bytes(&self) -> usize {
+        self.free_frames * FRAME_SIZE
+    }
+
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frames: {} free of {} (min {}), {} allocations, {} frees, {} failed",
+            self.free_frames, self.total_frames, self.min_free_frames,
+            self.allocations, self.frees, self.failed_allocations)
+    }
+}
+
+
+/// Returns the frame-aligned part of `region` as `(first frame number, end frame number)`
+/// - returns `None` if the region does not contain a whole frame
+#[inline]
+fn frames_of<const ALIGN: usize>(region: &Region<ALIGN>) -> Option<(usize, usize)> {
+    let start = region.phys().checked_add(FRAME_SIZE - 1)? / FRAME_SIZE;
+    let end = region.phys().checked_add(region.size())? / FRAME_SIZE;
+
+    if start < end { Some((start, end)) } else { None }
+}
+
+/// Returns the range of frame numbers covered by `regions`
+fn span<const ALIGN: usize>(regions: &[Region<ALIGN>]) -> Option<(usize, usize)> {
+    regions.iter()
+        .filter_map(frames_of)
+        .reduce(|(s0, e0), (s1, e1)| (core::cmp::min(s0, s1), core::cmp::max(e0, e1)))
+}
+
+/// Returns number of bitmap words needed to track `regions`
+/// - the bitmap spans from the lowest to the highest usable frame, holes between the regions are included
+pub fn bitmap_words<const ALIGN: usize>(regions: &[Region<ALIGN>]) -> usize {
+    match span(regions) {
+        Some((start, end)) => (end - start).div_ceil(WORD_BITS),
+        None => 0,
+    }
+}
+
+
+/// Bitmap frame allocator
+/// - frames outside of the usable regions (holes) are marked as used forever
+pub struct FrameAllocator<'b> {
+    bitmap: &'b mut [u64],
+    /// Number of the first tracked frame
+    base: usize,
+    /// Number of tracked frames (including holes)
+    frames: usize,
+    /// Usable ranges of frame numbers, frees outside of them are refused
+    ranges: [(usize, usize); MAX_FRAME_REGIONS],
+    ranges_len: usize,
+    /// Index of the first word that may contain a free frame
+    hint: usize,
+    stats: FrameStats,
+}
+
+impl<'b> FrameAllocator<'b> {
+
+    /// Constructs allocator over the usable physical `regions`
+    /// - only whole frames of the regions are used, the virtual addresses of the regions are ignored
+    /// - overlapping regions are allowed
+    /// - returns `Err(BitmapTooSmall)` if `bitmap` has less than `bitmap_words(regions)` words
+    /// - returns `Err(NoMemory)` if no region contains a whole frame
+    /// - returns `Err(TooManyRegions)` if more than `MAX_FRAME_REGIONS` regions contain a whole frame
+    pub fn new<const ALIGN: usize>(bitmap: &'b mut [u64], regions: &[Region<ALIGN>]) -> Result<Self, FrameError> {
+        let (start, end) = span(regions).ok_or(FrameError::NoMemory)?;
+
+        if bitmap.len() < bitmap_words(regions) {
+            return Err(FrameError::BitmapTooSmall);
+        }
+
+        let mut ranges = [(0, 0); MAX_FRAME_REGIONS];
+        let mut ranges_len = 0;
+        for range in regions.iter().filter_map(frames_of) {
+            *ranges.get_mut(ranges_len).ok_or(FrameError::TooManyRegions)? = range;
+            ranges_len += 1;
+        }
+
+        bitmap.fill(u64::MAX);
+
+        let mut this = Self {
+            bitmap,
+            base: start,
+            frames: end - start,
+            ranges,
+            ranges_len,
+            hint: 0,
+            stats: FrameStats::default(),
+        };
+
+        for (first, end) in ranges[..ranges_len].iter().copied() {
+            for frame in first - start..end - start {
+                if this.is_used(frame) {
+                    this.set_free(frame);
+                    this.stats.total_frames += 1;
+                }
+            }
+        }
+
+        this.stats.free_frames = this.stats.total_frames;
+        this.stats.min_free_frames = this.stats.total_frames;
+
+        Ok(this)
+    }
+
+    /// Marks all frames of `region` as used
+    /// - used for memory inside of the usable regions that is already taken (the kernel image, boot structures, ...)
+    /// - frames outside of the tracked memory and frames that are already used are skipped
+    /// - returns number of frames that were reserved
+    pub fn reserve<const ALIGN: usize>(&mut self, region: Region<ALIGN>) -> usize {
+        let start = region.phys() / FRAME_SIZE;
+        let end = region.phys().saturating_add(region.size()).div_ceil(FRAME_SIZE);
+
+        let mut reserved = 0;
+        for number in core::cmp::max(start, self.base)..core::cmp::min(end, self.base + self.frames) {
+            let frame = number - self.base;
+            if !self.is_used(frame) {
+                self.set_used(frame);
+                reserved += 1;
+            }
+        }
+
+        self.take(reserved);
+        reserved
+    }
+
+
+    /// Allocates one frame
+    /// - returns `Err(OutOfFrames)` if no frame is free
+    pub fn alloc_frame(&mut self) -> Result<Frame, FrameError> {
+        let words = self.frames.div_ceil(WORD_BITS);
+
+        for index in self.hint..words {
+            let word = self.bitmap[index];
+            if word == u64::MAX {
+                continue;
+            }
+
+            let frame = index * WORD_BITS + word.trailing_ones() as usize;
+            if frame >= self.frames {
+                break;
+            }
+
+            self.hint = index;
+            self.set_used(frame);
+            self.take(1);
+            self.stats.allocations += 1;
+
+            return Ok(self.frame_at(frame));
+        }
+
+        self.hint = words;
+        self.stats.failed_allocations += 1;
+        Err(FrameError::OutOfFrames)
+    }
+
+    /// Allocates `n` physically contiguous frames, the address of the first one is aligned to `align` bytes
+    /// - `align` smaller than `FRAME_SIZE` is treated as `FRAME_SIZE`
+    /// - returns the first frame of the run
+    /// - returns `Err(InvalidAlignment)` if `align` is not a power of two
+    /// - returns `Err(OutOfFrames)` if no free run is large enough (or `n` is zero)
+    pub fn alloc_contiguous(&mut self, n: usize, align: usize) -> Result<Frame, FrameError> {
+        if !align.is_power_of_two() {
+            return Err(FrameError::InvalidAlignment);
+        }
+
+        let step = core::cmp::max(align / FRAME_SIZE, 1);
+
+        if n == 0 {
+            self.stats.failed_allocations += 1;
+            return Err(FrameError::OutOfFrames);
+        }
+
+        //  the first frame whose physical address is aligned
+        let mut candidate = self.base.next_multiple_of(step) - self.base;
+
+        //  a run longer than the address space can not fit
+        while let Some(end) = candidate.checked_add(n).filter(|end| *end <= self.frames) {
+            match (candidate..end).rev().find(|frame| self.is_used(*frame)) {
+                //  no aligned run can contain the used frame, skip past it
+                Some(used) => candidate = (self.base + used + 1).next_multiple_of(step) - self.base,
+                None => {
+                    for frame in candidate..end {
+                        self.set_used(frame);
+                    }
+                    self.take(n);
+                    self.stats.allocations += 1;
+
+                    return Ok(self.frame_at(candidate));
+                },
+            }
+        }
+
+        self.stats.failed_allocations += 1;
+        Err(FrameError::OutOfFrames)
+    }
+
+    /// Frees one frame
+    /// - returns `Err(OutOfRange)` if the frame is not tracked by the allocator
+    /// - returns `Err(NotAllocated)` if the frame is free or lies in a hole between the usable regions
+    #[inline]
+    pub fn free_frame(&mut self, frame: Frame) -> Result<(), FrameError> {
+        self.free_contiguous(frame, 1)
+    }
+
+    /// Frees `n` contiguous frames starting with `first`
+    /// - nothing is freed if any of the frames cannot be freed
+    /// - returns `Err(OutOfRange)` if any frame is not tracked by the allocator
+    /// - returns `Err(NotAllocated)` if any frame is free or lies in a hole between the usable regions
+    pub fn free_contiguous(&mut self, first: Frame, n: usize) -> Result<(), FrameError> {
+        let start = first.number().checked_sub(self.base).ok_or(FrameError::OutOfRange)?;
+        let end = start.checked_add(n).ok_or(FrameError::OutOfRange)?;
+
+        if end > self.frames {
+            return Err(FrameError::OutOfRange);
+        }
+        if (start..end).any(|frame| !self.is_usable(frame)) {
+            return Err(FrameError::NotAllocated);
+        }
+        if (start..end).any(|frame| !self.is_used(frame)) {
+            return Err(FrameError::NotAllocated);
+        }
+
+        for frame in start..end {
+            self.set_free(frame);
+        }
+
+        self.hint = core::cmp::min(self.hint, start / WORD_BITS);
+        self.stats.free_frames += n;
+        self.stats.frees += 1;
+
+        Ok(())
+    }
+
+
+    /// Checks if `frame` is tracked and free
+    pub fn is_free(&self, frame: Frame) -> bool {
+        match frame.number().checked_sub(self.base) {
+            Some(index) if index < self.frames => !self.is_used(index),
+            _ => false,
+        }
+    }
+
+    /// Returns statistics of the allocator
+    #[inline(always)]
+    pub const fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Returns the number of the longest run of free frames
+    /// - walks the whole bitmap
+    pub fn largest_free_run(&self) -> usize {
+        let (mut largest, mut current) = (0, 0);
+
+        for frame in 0..self.frames {
+            if self.is_used(frame) {
+                current = 0;
+            } else {
+                current += 1;
+                largest = core::cmp::max(largest, current);
+            }
+        }
+
+        largest
+    }
+
+
+    /// Converts index into the bitmap to `Frame`
+    #[inline(always)]
+    const fn frame_at(&self, index: usize) -> Frame {
+        Frame((self.base + index) * FRAME_SIZE)
+    }
+
+    /// Checks if frame at `index` lies in one of the usable regions
+    fn is_usable(&self, index: usize) -> bool {
+        let number = self.base + index;
+        self.ranges[..self.ranges_len].iter().any(|(start, end)| (*start..*end).contains(&number))
+    }
+
+    #[inline(always)]
+    fn is_used(&self, index: usize) -> bool {
+        self.bitmap[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    #[inline(always)]
+    fn set_used(&mut self, index: usize) {
+        self.bitmap[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    #[inline(always)]
+    fn set_free(&mut self, index: usize) {
+        self.bitmap[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+    }
+
+    /// Counts `n` newly used frames
+    #[inline(always)]
+    fn take(&mut self, n: usize) {
+        self.stats.free_frames -= n;
+        self.stats.min_free_frames = core::cmp::min(self.stats.min_free_frames, self.stats.free_frames);
+    }
+
+}
+
+
+#[cfg(feature = "spin")]
+pub use global::*;
+
+/// The global frame allocator
+#[cfg(feature = "spin")]
+mod global {
+    use super::*;
+    use crate::sync::IrqMutex;
+
+    static FRAMES: IrqMutex<Option<FrameAllocator<'static>>> = IrqMutex::new(None);
+
+    /// Initializes the global frame allocator over the usable physical `regions`
+    /// - see [`FrameAllocator::new`]
+    /// - returns `Err(AlreadyInitialized)` if the allocator is already initialized
+    pub fn init<const ALIGN: usize>(bitmap: &'static mut [u64], regions: &[Region<ALIGN>]) -> Result<(), FrameError> {
+        let mut frames = FRAMES.lock();
+
+        if frames.is_some() {
+            return Err(FrameError::AlreadyInitialized);
+        }
+
+        *frames = Some(FrameAllocator::new(bitmap, regions)?);
+        Ok(())
+    }
+
+    /// Runs `f` with the global frame allocator
+    /// - returns `Err(Uninitialized)` if the allocator is not initialized
+    /// - interrupts are disabled while `f` runs
+    pub fn with<R>(f: impl FnOnce(&mut FrameAllocator<'static>) -> R) -> Result<R, FrameError> {
+        FRAMES.lock().as_mut().map(f).ok_or(FrameError::Uninitialized)
+    }
+
+    /// Marks all frames of `region` as used in the global frame allocator
+    /// - see [`FrameAllocator::reserve`]
+    pub fn reserve<const ALIGN: usize>(region: Region<ALIGN>) -> Result<usize, FrameError> {
+        with(|frames| frames.reserve(region))
+    }
+
+    /// Allocates one frame from the global frame allocator
+    #[inline]
+    pub fn alloc_frame() -> Result<Frame, FrameError> {
+        with(|frames| frames.alloc_frame())?
+    }
+
+    /// Allocates `n` contiguous frames aligned to `align` bytes from the global frame allocator
+    /// - see [`FrameAllocator::alloc_contiguous`]
+    #[inline]
+    pub fn alloc_contiguous(n: usize, align: usize) -> Result<Frame, FrameError> {
+        with(|frames| frames.alloc_contiguous(n, align))?
+    }
+
+    /// Returns frame to the global frame allocator
+    #[inline]
+    pub fn free_frame(frame: Frame) -> Result<(), FrameError> {
+        with(|frames| frames.free_frame(frame))?
+    }
+
+    /// Returns `n` contiguous frames to the global frame allocator
+    #[inline]
+    pub fn free_contiguous(first: Frame, n: usize) -> Result<(), FrameError> {
+        with(|frames| frames.free_contiguous(first, n))?
+    }
+
+    /// Returns statistics of the global frame allocator
+    /// - returns `None` if the allocator is not initialized
+    pub fn stats() -> Option<FrameStats> {
+        with(|frames| frames.stats()).ok()
+    }
+}
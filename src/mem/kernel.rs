@@ -3,12 +3,99 @@
 //		an OS template on which to build
 
 //! Marks position and size of each section of the kernel
+//! - the sections are found through the symbols of the linker script (`__KERNEL_START`, `__TEXT_START` ... `__BSS_END`)
+//!   - `link/kernel.ld` is a template that defines them (also available as [`LINKER_SCRIPT`])
+//!   - the symbols are only linked with the `kernel-layout` feature, the layout stays empty without it
+//! - `ministd::init::memory()` fills [`LAYOUT`], the physical addresses are set by [`Layout::set_phys`]
+//! - once the kernel is mapped by the `Mapper`, [`Layout::protect`] makes text read-only and executable,
+//!   rodata read-only and the rest non-executable
+//!
+//! # Example
+//! ```
+//! //  build.rs of the kernel
+//! std::fs::copy(ministd_dir.join("link/kernel.ld"), out_dir.join("kernel.ld"))?;
+//! println!("cargo:rustc-link-arg=-T{}", out_dir.join("kernel.ld").display());
+//!
+//! //  kernel
+//! ministd::init::memory();
+//! LAYOUT.write().set_phys(kernel_address.physical_base as usize);
+//! LAYOUT.read().protect(&mut mapper, PageFlags::GLOBAL)?;
+//! ```
 
-/*use super::Region;
+use core::fmt;
+
+use super::Region;
+use super::paging::{FrameSource, Mapper, PageFlags, PagingError};
+#[cfg(feature = "kernel-layout")]
+use super::PAGE_SIZE;
+#[cfg(feature = "spin")]
 use crate::RwLock;
 
+
+/// Template linker script for x86_64 higher half kernels
+/// - places the kernel at `0xffffffff80000000`, each section starts and ends at a page boundary
+/// - defines all symbols the [`Layout`] is read from
+pub const LINKER_SCRIPT: &str = include_str!("../../link/kernel.ld");
+
+
+/// Section of the kernel image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Code (`.text`)
+    Text,
+    /// Constants (`.rodata`)
+    Rodata,
+    /// Initialized statics (`.data`)
+    Data,
+    /// Dynamic linking info (`.dynamic`), empty for static kernels
+    Dynamic,
+    /// Zeroed statics (`.bss`)
+    Bss,
+}
+
+impl Section {
+
+    /// All sections in the order of the linker script
+    pub const ALL: [Self; 5] = [Self::Text, Self::Rodata, Self::Data, Self::Dynamic, Self::Bss];
+
+    /// Returns page flags the section is protected with
+    /// - text is read-only and executable, rodata read-only, the rest writable
+    /// - only text is executable
+    pub const fn flags(&self) -> PageFlags {
+        match self {
+            Self::Text => PageFlags::EMPTY,
+            Self::Rodata => PageFlags::NO_EXECUTE,
+            Self::Data | Self::Dynamic | Self::Bss => PageFlags::from_bits(PageFlags::WRITABLE.bits() | PageFlags::NO_EXECUTE.bits()),
+        }
+    }
+
+    /// Returns name of the section
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => ".text",
+            Self::Rodata => ".rodata",
+            Self::Data => ".data",
+            Self::Dynamic => ".dynamic",
+            Self::Bss => ".bss",
+        }
+    }
+
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Position and size of the kernel image and its sections
+/// - the regions are page aligned
+/// - physical addresses are `0` until [`Layout::set_phys`] is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Layout {
     pub kernel: Region,
+    pub text: Region,
     pub rodata: Region,
     pub data: Region,
     pub dynamic: Region,
@@ -16,9 +103,12 @@ pub struct Layout {
 }
 
 impl Layout {
+
+    /// Constructs empty layout
     pub const fn new() -> Self {
         Self {
             kernel: Region::empty(),
+            text: Region::empty(),
             rodata: Region::empty(),
             data: Region::empty(),
             dynamic: Region::empty(),
@@ -26,66 +116,147 @@ impl Layout {
         }
     }
 
+    /// Returns page aligned region from `start` to `end`
     #[inline]
-    fn transform(var: &usize) -> usize {
-        (var as *const usize) as usize
+    #[cfg(feature = "kernel-layout")]
+    const fn span(start: usize, end: usize) -> Region {
+        let start = start & !(PAGE_SIZE - 1);
+        Region::new(start, 0, end.saturating_sub(start))
+    }
+
+    /// Constructs layout from the symbols of the linker script
+    /// - the symbols must be defined (see [`LINKER_SCRIPT`]), otherwise the kernel does not link
+    #[cfg(feature = "kernel-layout")]
+    pub fn from_symbols() -> Self {
+        let addr = |symbol: *const u8| symbol.addr();
+
+        Self {
+            kernel: Self::span(addr(&raw const __KERNEL_START), addr(&raw const __KERNEL_END)),
+            text: Self::span(addr(&raw const __TEXT_START), addr(&raw const __TEXT_END)),
+            rodata: Self::span(addr(&raw const __RODATA_START), addr(&raw const __RODATA_END)),
+            data: Self::span(addr(&raw const __DATA_START), addr(&raw const __DATA_END)),
+            dynamic: Self::span(addr(&raw const __DYNAMIC_START), addr(&raw const __DYNAMIC_END)),
+            bss: Self::span(addr(&raw const __BSS_START), addr(&raw const __BSS_END)),
+        }
     }
 
+    /// Fills the layout from the symbols of the linker script
+    /// - does nothing without the `kernel-layout` feature
     pub fn init(&mut self) {
+        #[cfg(feature = "kernel-layout")] {
+            *self = Self::from_symbols();
+        }
+    }
+
+    /// Checks if the layout was not filled
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.kernel.size() == 0
+    }
+
+    /// Returns region of the section
+    pub const fn get(&self, section: Section) -> Region {
+        match section {
+            Section::Text => self.text,
+            Section::Rodata => self.rodata,
+            Section::Data => self.data,
+            Section::Dynamic => self.dynamic,
+            Section::Bss => self.bss,
+        }
+    }
 
-        let kstart = Self::transform(unsafe { &__KERNEL_START });
-        let kend = Self::transform(unsafe { &__KERNEL_END });
-        self.kernel = Region::new(kstart, 0, kend - kstart);
+    /// Returns the sections with their regions, empty sections are skipped
+    pub fn sections(&self) -> impl Iterator<Item = (Section, Region)> + '_ {
+        Section::ALL.into_iter()
+            .map(|section| (section, self.get(section)))
+            .filter(|(_, region)| region.size() != 0)
+    }
 
-        let rostart = Self::transform(unsafe { &__RODATA_START });
-        let roend = Self::transform(unsafe { &__RODATA_END });
-        self.rodata = Region::new(rostart, 0, roend - rostart);
+    /// Returns the section that contains the virtual address
+    pub fn section_of(&self, virt: usize) -> Option<Section> {
+        self.sections()
+            .find(|(_, region)| region.virt().addr() <= virt && virt - region.virt().addr() < region.size())
+            .map(|(section, _)| section)
+    }
 
-        let dstart = Self::transform(unsafe { &__DATA_START });
-        let dend = Self::transform(unsafe { &__DATA_END });
-        self.data = Region::new(dstart, 0, dend - dstart);
+    /// Sets physical addresses of all regions
+    /// - `phys` is the physical address of `__KERNEL_START` (e.g. from the Limine kernel address request)
+    /// - the image must be physically contiguous
+    pub fn set_phys(&mut self, phys: usize) {
+        let base = self.kernel.virt().addr();
+        for region in [&mut self.kernel, &mut self.text, &mut self.rodata, &mut self.data, &mut self.dynamic, &mut self.bss] {
+            if region.size() != 0 {
+                region.reallocate(phys + (region.virt().addr() - base));
+            }
+        }
+    }
 
-        let dystart = Self::transform(unsafe { &__DYNAMIC_START });
-        let dyend = Self::transform(unsafe { &__DYNAMIC_END });
-        self.dynamic = Region::new(dystart, 0, dyend - dystart);
+    /// Returns physical location of the kernel image as `(phys, size)`
+    /// - returns `None` if the layout is empty or the physical address was not set
+    pub const fn phys_range(&self) -> Option<(usize, usize)> {
+        match self.is_empty() || self.kernel.phys() == 0 {
+            true => None,
+            false => Some((self.kernel.phys(), self.kernel.size())),
+        }
+    }
 
-        let bstart = Self::transform(unsafe { &__BSS_START });
-        let bend = Self::transform(unsafe { &__BSS_END });
-        self.dynamic = Region::new(bstart, 0, bend - bstart);
-        
+    /// Changes protection of the mapped sections (see [`Section::flags`])
+    /// - the kernel must be mapped with 4 KiB pages, or huge pages that do not cross the sections
+    /// - `extra` flags are added to all sections (e.g. `PageFlags::GLOBAL`)
+    /// - returns `Err` of the first section that fails, the sections before it are already changed
+    pub fn protect<S: FrameSource>(&self, mapper: &mut Mapper<S>, extra: PageFlags) -> Result<(), PagingError> {
+        for (section, region) in self.sections() {
+            mapper.protect(region, section.flags() | extra)?;
+        }
+        Ok(())
+    }
 
+}
 
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<9} {:<18}  {:<18}  {:>10}", "section", "virt", "phys", "size")?;
+        for (name, region) in [("kernel", self.kernel)].into_iter().chain(self.sections().map(|(s, r)| (s.as_str(), r))) {
+            writeln!(f, "{:<9} {:#018x}  {:#018x}  {:>6} KiB", name, region.virt().addr(), region.phys(), region.size() / 1024)?;
+        }
+        Ok(())
+    }
 }
 
+
 /// Stores kernel memory layout metadata
 /// - position and size of all linker sections
 /// - physical addresses are unknown by default
-/// 
+///
 /// **NOTE**: do not forget to call the `ministd::init::memory()` to fully initialize the data
+#[cfg(feature = "spin")]
 pub static LAYOUT: RwLock<Layout> = RwLock::new(Layout::new());
 
 
 //  variables that are used to identify each sections
+#[cfg(feature = "kernel-layout")]
 unsafe extern "C" {
-    pub(crate) static __KERNEL_START: usize;
-    pub(crate) static __KERNEL_END: usize;
-
-    pub(crate) static __TEXT_START: usize;
-    pub(crate) static __TEXT_END: usize;
+    static __KERNEL_START: u8;
+    static __KERNEL_END: u8;
 
-    pub(crate) static __RODATA_START: usize;
-    pub(crate) static __RODATA_END: usize;
+    static __TEXT_START: u8;
+    static __TEXT_END: u8;
 
-    pub(crate) static __DATA_START: usize;
-    pub(crate) static __DATA_END: usize;
+    static __RODATA_START: u8;
+    static __RODATA_END: u8;
 
-    pub(crate) static __DYNAMIC_START: usize;
-    pub(crate) static __DYNAMIC_END: usize;
+    static __DATA_START: u8;
+    static __DATA_END: u8;
 
-    pub(crate) static __BSS_START: usize;
-    pub(crate) static __BSS_END: usize;
+    static __DYNAMIC_START: u8;
+    static __DYNAMIC_END: u8;
 
+    static __BSS_START: u8;
+    static __BSS_END: u8;
 }
-*/
\ No newline at end of file
@@ -17,7 +17,16 @@
 //!     3. `Vec<T>` - Modified version of the `std::Vec` giving control over overallocation and data align
 //!     4. `Rc` - Classic reference counter
 //!         - The `Weak` pointer is not yet implemented
+//!     5. `Arena` - Bump allocator for short-lived or scratch allocations
+//!     6. `Pool<T>` - Pool of objects of one type with O(1) allocation and free
+//!     7. `VecDeque<T>` - Double-ended queue on a ring buffer, grows like `Vec<T>`
 //! 4. `Region` struct - used by the allocator to mark used memory areas
+//!     - `RegionMap` keeps several tagged regions sorted and non-overlapping
+//! 5. `frame` - physical page frame allocator
+//! 6. `dma` - physically contiguous buffers with known physical address (`DmaBox<T>`, `DmaVec<T>`)
+//! 7. `paging` - x86_64 page tables and the `Mapper`
+//! 8. `kernel` - sections of the kernel image (`LAYOUT`) and a template linker script
+//! 9. `stack` - kernel stacks with guard pages, canaries and high-water marks
 
 
 /// Standard size of one **kilobyte** (1024 bytes)
@@ -44,6 +53,12 @@ mod readonly;
 pub use readonly::ReadOnly;
 
 pub mod kernel;
+pub mod region_map;
+pub mod frame;
+pub mod paging;
+pub mod stack;
+#[cfg(feature="spin")]
+pub mod dma;
 
 #[cfg(all(feature="allocator", feature="spin"))]
 pub mod alloc;
@@ -52,18 +67,36 @@ pub mod alloc;
 pub mod boxed;
 #[cfg(all(feature="box", feature="allocator", feature="spin"))]
 pub mod array;
+#[cfg(all(feature="box", feature="allocator", feature="spin"))]
+mod arena;
 #[cfg(all(feature="allocator", feature="spin"))]
 mod dynamic_buffer;
+#[cfg(all(feature="allocator", feature="spin"))]
+mod pool;
+#[cfg(all(feature="allocator", feature="spin"))]
+mod sort;
 
 #[cfg(all(feature="string", feature="allocator", feature="spin"))]
 pub mod string;
 #[cfg(all(feature="vector", feature="allocator", feature="spin"))]
 pub mod vec;
+#[cfg(all(feature="vector", feature="allocator", feature="spin"))]
+pub mod vec_deque;
 #[cfg(all(feature="rc", feature="allocator", feature="spin"))]
 pub mod rc;
 
 #[cfg(all(feature="allocator", feature="spin"))]
 pub use dynamic_buffer::DynamicBuffer;
+#[cfg(all(feature="vector", feature="allocator", feature="spin"))]
+pub use vec_deque::VecDeque;
+#[cfg(all(feature="box", feature="allocator", feature="spin"))]
+pub use arena::{Arena, Checkpoint};
+#[cfg(all(feature="allocator", feature="spin"))]
+pub use pool::{Pool, PoolBox, PoolStats};
+#[cfg(feature="spin")]
+pub use dma::{DmaBox, DmaVec, DmaConstraints};
+
+pub use region_map::{RegionMap, RegionTag};
 
 pub use crate::convert::Align;
 pub use core::mem::{ManuallyDrop, MaybeUninit};
@@ -82,7 +115,7 @@ pub use core::mem::{ManuallyDrop, MaybeUninit};
 /// Generic parameter: ALIGN
 /// - **forces the align** of addresses and size
 ///   - the `ministd::mem::Align` trait is used to align values
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Region<const ALIGN: usize = PAGE_ALIGN> {
     virt: usize,    //  usize is only inner representation
     phys: usize,
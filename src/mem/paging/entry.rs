@@ -0,0 +1,309 @@
+//	mem/paging/entry.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Page table entries and their flags
+
+
+use core::fmt;
+use core::ops::{BitAnd, BitOr, BitOrAssign, Not};
+
+use super::{ENTRIES, PAGE_SIZE};
+
+
+/// Flags of a page table entry
+/// - combined with `|`, e.g. `PageFlags::WRITABLE | PageFlags::NO_EXECUTE`
+/// - `PRESENT` is added by the `Mapper`, `HUGE` is managed by the `Mapper`
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct PageFlags(u64);
+
+impl PageFlags {
+    /// No flags (read-only, executable, kernel only, write-back)
+    pub const EMPTY: Self = Self(0);
+    /// The entry is valid
+    pub const PRESENT: Self = Self(1 << 0);
+    /// Writes are allowed
+    pub const WRITABLE: Self = Self(1 << 1);
+    /// Accessible from ring 3
+    pub const USER: Self = Self(1 << 2);
+    /// Write-through caching (see [`CacheMode`])
+    pub const WRITE_THROUGH: Self = Self(1 << 3);
+    /// Caching disabled (see [`CacheMode`])
+    pub const NO_CACHE: Self = Self(1 << 4);
+    /// Set by the CPU on access
+    pub const ACCESSED: Self = Self(1 << 5);
+    /// Set by the CPU on write (leaf entries only)
+    pub const DIRTY: Self = Self(1 << 6);
+    /// The entry maps 2 MiB or 1 GiB page instead of pointing to a table
+    pub const HUGE: Self = Self(1 << 7);
+    /// The TLB entry survives `cr3` reloads (needs `CR4.PGE`)
+    pub const GLOBAL: Self = Self(1 << 8);
+    /// Instruction fetches are not allowed (needs `EFER.NXE`)
+    pub const NO_EXECUTE: Self = Self(1 << 63);
+
+    /// Flags that can be set by the user of the `Mapper`
+    const USER_MASK: u64 = 0x8000_0000_0000_017f & !Self::HUGE.0;
+
+    /// Returns the raw bits
+    #[inline(always)]
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Constructs flags from raw bits
+    /// - bits that are not flags are dropped
+    #[inline(always)]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits & FLAGS_MASK)
+    }
+
+    /// Checks if all flags of `other` are set
+    #[inline(always)]
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the flags with the caching flags set to `mode`
+    #[inline]
+    pub const fn cache(self, mode: CacheMode) -> Self {
+        Self(self.0 & !(Self::WRITE_THROUGH.0 | Self::NO_CACHE.0) | mode.bits())
+    }
+
+    /// Returns cache mode of the flags
+    #[inline]
+    pub const fn cache_mode(&self) -> CacheMode {
+        CacheMode::from_bits(self.0)
+    }
+
+    /// Returns the flags without the flags managed by the `Mapper` and the CPU
+    #[inline(always)]
+    pub(crate) const fn user(self) -> Self {
+        Self(self.0 & Self::USER_MASK & !(Self::ACCESSED.0 | Self::DIRTY.0))
+    }
+
+}
+
+impl BitOr for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for PageFlags {
+    #[inline(always)]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn not(self) -> Self {
+        Self(!self.0 & FLAGS_MASK)
+    }
+}
+
+impl fmt::Debug for PageFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [(PageFlags, &str); 10] = [
+            (PageFlags::PRESENT, "P"), (PageFlags::WRITABLE, "W"), (PageFlags::USER, "U"),
+            (PageFlags::WRITE_THROUGH, "PWT"), (PageFlags::NO_CACHE, "PCD"), (PageFlags::ACCESSED, "A"),
+            (PageFlags::DIRTY, "D"), (PageFlags::HUGE, "PS"), (PageFlags::GLOBAL, "G"), (PageFlags::NO_EXECUTE, "NX"),
+        ];
+
+        let mut first = true;
+        for (flag, name) in NAMES {
+            if self.contains(flag) {
+                if !first {
+                    f.write_str(" | ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+
+        if first {
+            f.write_str("EMPTY")?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Caching of a page
+/// - uses the default PAT layout (the `PAT` bit of the entries is never set)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Normal memory
+    #[default]
+    WriteBack,
+    /// Writes go straight to the memory, reads are cached (framebuffers)
+    WriteThrough,
+    /// Not cached, can be overridden to write-combining by the MTRRs
+    UncachedMinus,
+    /// Not cached (MMIO)
+    Uncached,
+}
+
+impl CacheMode {
+
+    /// Returns the `PWT` and `PCD` bits of the mode
+    #[inline]
+    pub const fn bits(&self) -> u64 {
+        match self {
+            Self::WriteBack => 0,
+            Self::WriteThrough => PageFlags::WRITE_THROUGH.0,
+            Self::UncachedMinus => PageFlags::NO_CACHE.0,
+            Self::Uncached => PageFlags::WRITE_THROUGH.0 | PageFlags::NO_CACHE.0,
+        }
+    }
+
+    /// Returns the mode selected by the `PWT` and `PCD` bits
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        match (bits & PageFlags::WRITE_THROUGH.0 != 0, bits & PageFlags::NO_CACHE.0 != 0) {
+            (false, false) => Self::WriteBack,
+            (true, false) => Self::WriteThrough,
+            (false, true) => Self::UncachedMinus,
+            (true, true) => Self::Uncached,
+        }
+    }
+
+}
+
+
+/// Bits of an entry that hold the physical address
+const ADDR_MASK: u64 = 0x000f_ffff_ffff_f000;
+/// Bits of an entry that hold the flags
+const FLAGS_MASK: u64 = 0x8000_0000_0000_01ff;
+
+
+/// One entry of a page table
+/// - either points to the next table, maps a page (the last level or `HUGE`) or is unused
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct PageTableEntry(u64);
+
+impl PageTableEntry {
+
+    /// Unused entry
+    pub const UNUSED: Self = Self(0);
+
+    /// Constructs entry pointing to `phys`
+    /// - `phys` must be aligned to `PAGE_SIZE` (the low bits are dropped)
+    #[inline(always)]
+    pub const fn new(phys: usize, flags: PageFlags) -> Self {
+        Self(phys as u64 & ADDR_MASK | flags.0)
+    }
+
+    /// Returns the raw value
+    #[inline(always)]
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the physical address the entry points to
+    /// - for huge pages the address includes the `PAT` bit if it is set
+    #[inline(always)]
+    pub const fn addr(&self) -> usize {
+        (self.0 & ADDR_MASK) as usize
+    }
+
+    /// Returns flags of the entry
+    #[inline(always)]
+    pub const fn flags(&self) -> PageFlags {
+        PageFlags(self.0 & FLAGS_MASK)
+    }
+
+    /// Checks if the entry is valid
+    #[inline(always)]
+    pub const fn is_present(&self) -> bool {
+        self.0 & PageFlags::PRESENT.0 != 0
+    }
+
+    /// Checks if the entry maps huge page
+    #[inline(always)]
+    pub const fn is_huge(&self) -> bool {
+        self.0 & PageFlags::HUGE.0 != 0
+    }
+
+    /// Checks if the entry is zero
+    #[inline(always)]
+    pub const fn is_unused(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Sets the address and flags
+    #[inline(always)]
+    pub fn set(&mut self, phys: usize, flags: PageFlags) {
+        *self = Self::new(phys, flags);
+    }
+
+    /// Sets the flags, keeps the address
+    #[inline(always)]
+    pub fn set_flags(&mut self, flags: PageFlags) {
+        self.0 = self.0 & !FLAGS_MASK | flags.0;
+    }
+
+    /// Clears the entry
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.0 = 0;
+    }
+
+}
+
+impl fmt::Debug for PageTableEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PageTableEntry({:#x}, {:?})", self.addr(), self.flags())
+    }
+}
+
+
+/// One page table of any level
+#[derive(Clone)]
+#[repr(C, align(4096))]
+pub struct PageTable {
+    pub entries: [PageTableEntry; ENTRIES],
+}
+
+const _: () = assert!(size_of::<PageTable>() == PAGE_SIZE);
+
+impl PageTable {
+
+    /// Constructs table with all entries unused
+    pub const fn new() -> Self {
+        Self { entries: [PageTableEntry::UNUSED; ENTRIES] }
+    }
+
+    /// Clears all entries
+    #[inline]
+    pub fn zero(&mut self) {
+        self.entries.fill(PageTableEntry::UNUSED);
+    }
+
+    /// Checks if all entries are unused
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(PageTableEntry::is_unused)
+    }
+
+}
+
+impl Default for PageTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
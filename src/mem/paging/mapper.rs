@@ -0,0 +1,433 @@
+//	mem/paging/mapper.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Mapping of regions into a 4-level page table
+
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::io::tlb;
+use crate::mem::Region;
+use crate::mem::frame::{Frame, FrameAllocator};
+
+use super::{table_index, is_canonical, PageFlags, PageSize, PageTable, PageTableEntry, PagingError, LEVELS, PAGE_SIZE};
+
+
+/// Source of frames for the page tables
+pub trait FrameSource {
+    /// Returns physical address of a free frame for a new table
+    /// - the frame does not have to be zeroed
+    /// - returns `None` if there is no free frame
+    fn alloc_table(&mut self) -> Option<usize>;
+
+    /// Takes back frame of a table that is not used anymore
+    fn free_table(&mut self, phys: usize);
+}
+
+impl<S: FrameSource + ?Sized> FrameSource for &mut S {
+    #[inline]
+    fn alloc_table(&mut self) -> Option<usize> {
+        (**self).alloc_table()
+    }
+
+    #[inline]
+    fn free_table(&mut self, phys: usize) {
+        (**self).free_table(phys)
+    }
+}
+
+impl FrameSource for FrameAllocator<'_> {
+    fn alloc_table(&mut self) -> Option<usize> {
+        self.alloc_frame().ok().map(|frame| frame.addr())
+    }
+
+    fn free_table(&mut self, phys: usize) {
+        let _ = self.free_frame(Frame::containing(phys));
+    }
+}
+
+/// The global frame allocator as [`FrameSource`] (see [`frame`](crate::mem::frame))
+#[cfg(feature = "spin")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalFrames;
+
+#[cfg(feature = "spin")]
+impl FrameSource for GlobalFrames {
+    fn alloc_table(&mut self) -> Option<usize> {
+        crate::mem::frame::alloc_frame().ok().map(|frame| frame.addr())
+    }
+
+    fn free_table(&mut self, phys: usize) {
+        let _ = crate::mem::frame::free_frame(Frame::containing(phys));
+    }
+}
+
+
+/// Handler that flushes the TLBs of the other CPUs (`0` if not set)
+static SHOOTDOWN: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the TLB shootdown handler
+/// - called as `handler(virt, size)` after mappings were removed or changed and the TLB of the current CPU was flushed
+/// - the handler should make the other CPUs flush the range (e.g. through an IPI) and wait for them
+/// - `None` removes the handler (single CPU systems)
+pub fn set_shootdown_handler(handler: Option<fn(usize, usize)>) {
+    SHOOTDOWN.store(handler.map_or(0, |h| h as usize), Ordering::Release);
+}
+
+/// Flushes the range on the current CPU and runs the shootdown handler
+fn flush(virt: usize, size: usize) {
+    //  flushing many pages one by one is slower than flushing the whole TLB
+    //  - `flush_all` toggles `cr4.PGE`, so global pages are flushed as well
+    const MAX_INVLPG: usize = 64;
+
+    if size / PAGE_SIZE > MAX_INVLPG {
+        tlb::flush_all();
+    } else {
+        for page in (virt..virt + size).step_by(PAGE_SIZE) {
+            tlb::flush(page);
+        }
+    }
+
+    let handler = SHOOTDOWN.load(Ordering::Acquire);
+    if handler != 0 {
+        let handler: fn(usize, usize) = unsafe { core::mem::transmute(handler) };
+        handler(virt, size);
+    }
+}
+
+
+/// Result of [`Mapper::translate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Translation {
+    /// Physical address the virtual address maps to
+    pub phys: usize,
+    /// Flags of the page
+    pub flags: PageFlags,
+    /// Size of the page
+    pub size: PageSize,
+}
+
+
+/// Maps regions into a 4-level page table
+/// - the tables are accessed at `phys + phys_offset`
+/// - new tables are taken from the frame source, tables that become empty are given back
+pub struct Mapper<S: FrameSource> {
+    root: usize,
+    offset: usize,
+    source: S,
+    max_page: PageSize,
+}
+
+impl<S: FrameSource> Mapper<S> {
+
+    /// Constructs mapper of the table hierarchy rooted at physical address `root`
+    /// - `map` uses pages of up to 2 MiB (see `set_max_page_size`)
+    ///
+    /// # Safety
+    /// - `root` must be a valid top-level table (PML4) and all tables must be accessible at `phys + phys_offset`
+    /// - no one else may modify the tables while the mapper lives
+    pub const unsafe fn new(root: usize, phys_offset: usize, source: S) -> Self {
+        Self { root, offset: phys_offset, source, max_page: PageSize::Size2M }
+    }
+
+    /// Constructs mapper of the active table hierarchy (from `cr3`)
+    ///
+    /// # Safety
+    /// - same as `new`
+    pub unsafe fn active(phys_offset: usize, source: S) -> Self {
+        unsafe { Self::new(PageTableEntry::new(tlb::cr3(), PageFlags::EMPTY).addr(), phys_offset, source) }
+    }
+
+    /// Returns physical address of the top-level table
+    #[inline]
+    pub const fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Returns the frame source
+    #[inline]
+    pub fn source(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Consumes the mapper and returns the frame source
+    #[inline]
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Sets the largest page size used by `map`
+    /// - 1 GiB pages need `pdpe1gb` support of the CPU
+    #[inline]
+    pub fn set_max_page_size(&mut self, size: PageSize) {
+        self.max_page = size;
+    }
+
+
+    /// Maps `region` with the largest pages that fit
+    /// - the virtual and physical addresses and the size must be aligned to 4 KiB
+    /// - `PRESENT` is added to `flags`
+    /// - returns `Err(AlreadyMapped)` if some page is already mapped, nothing is mapped then
+    /// - returns `Err(NoFrames)` if the frame source is empty, nothing is mapped then
+    pub fn map<const ALIGN: usize>(&mut self, region: Region<ALIGN>, flags: PageFlags) -> Result<(), PagingError> {
+        let (virt, phys, size) = check(&region, PageSize::Size4K)?;
+
+        let mut offset = 0;
+        while offset < size {
+            let page = [PageSize::Size1G, PageSize::Size2M, PageSize::Size4K].into_iter()
+                .filter(|&page| page <= self.max_page)
+                .find(|page| {
+                    let bytes = page.bytes();
+                    (virt + offset) % bytes == 0 && (phys + offset) % bytes == 0 && size - offset >= bytes
+                })
+                .unwrap_or(PageSize::Size4K);
+
+            if let Err(e) = self.map_page(virt + offset, phys + offset, flags, page) {
+                self.rollback(virt, offset);
+                return Err(e);
+            }
+
+            offset += page.bytes();
+        }
+
+        Ok(())
+    }
+
+    /// Maps `region` with pages of the given size
+    /// - the virtual and physical addresses and the size must be aligned to `page`
+    /// - see `map`
+    pub fn map_with<const ALIGN: usize>(&mut self, region: Region<ALIGN>, flags: PageFlags, page: PageSize) -> Result<(), PagingError> {
+        let (virt, phys, size) = check(&region, page)?;
+
+        for offset in (0..size).step_by(page.bytes()) {
+            if let Err(e) = self.map_page(virt + offset, phys + offset, flags, page) {
+                self.rollback(virt, offset);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unmaps `region` (the physical address of the region is ignored)
+    /// - huge pages must be covered by the region whole
+    /// - tables that become empty are given back to the frame source
+    /// - returns `Err(NotMapped)` or `Err(HugePage)` before anything is unmapped
+    pub fn unmap<const ALIGN: usize>(&mut self, region: Region<ALIGN>) -> Result<(), PagingError> {
+        let (virt, _, size) = check(&region, PageSize::Size4K)?;
+
+        self.validate(virt, size)?;
+        self.unmap_range(virt, size);
+        flush(virt, size);
+
+        Ok(())
+    }
+
+    /// Changes flags of the pages of `region` (the physical address of the region is ignored)
+    /// - huge pages must be covered by the region whole
+    /// - returns `Err(NotMapped)` or `Err(HugePage)` before anything is changed
+    pub fn protect<const ALIGN: usize>(&mut self, region: Region<ALIGN>, flags: PageFlags) -> Result<(), PagingError> {
+        let (virt, _, size) = check(&region, PageSize::Size4K)?;
+
+        self.validate(virt, size)?;
+
+        let mut offset = 0;
+        while offset < size {
+            let (entry, page) = self.walk(virt + offset, flags.contains(PageFlags::USER)).ok_or(PagingError::NotMapped)?;
+
+            let huge = if page == PageSize::Size4K { PageFlags::EMPTY } else { PageFlags::HUGE };
+            unsafe { (*entry).set_flags(flags.user() | PageFlags::PRESENT | huge); }
+
+            offset += page.bytes();
+        }
+
+        flush(virt, size);
+
+        Ok(())
+    }
+
+    /// Returns the physical address, flags and page size `virt` maps to
+    /// - returns `None` if the address is not mapped
+    pub fn translate(&self, virt: usize) -> Option<Translation> {
+        if !is_canonical(virt) {
+            return None;
+        }
+
+        let (entry, size) = self.walk(virt, false)?;
+        let entry = unsafe { *entry };
+        let mask = size.bytes() - 1;
+
+        Some(Translation {
+            phys: (entry.addr() & !mask) + (virt & mask),
+            flags: entry.flags(),
+            size,
+        })
+    }
+
+    /// Returns the physical address `virt` maps to
+    #[inline]
+    pub fn translate_addr(&self, virt: usize) -> Option<usize> {
+        self.translate(virt).map(|t| t.phys)
+    }
+
+
+    /// Returns pointer to the table at physical address `phys`
+    #[inline(always)]
+    fn table(&self, phys: usize) -> *mut PageTable {
+        phys.wrapping_add(self.offset) as *mut PageTable
+    }
+
+    /// Finds the entry that maps `virt`
+    /// - sets `USER` on the tables on the way if `user` is true
+    fn walk(&self, virt: usize, user: bool) -> Option<(*mut PageTableEntry, PageSize)> {
+        let mut table = self.root;
+
+        for level in (1..=LEVELS).rev() {
+            let entry = unsafe { &mut (*self.table(table)).entries[table_index(virt, level)] };
+
+            if !entry.is_present() {
+                return None;
+            }
+            if level == 1 || entry.is_huge() {
+                return Some((entry, PageSize::from_level(level)?));
+            }
+            if user {
+                entry.set_flags(entry.flags() | PageFlags::USER);
+            }
+
+            table = entry.addr();
+        }
+
+        None
+    }
+
+    /// Maps one page, creates the missing tables
+    fn map_page(&mut self, virt: usize, phys: usize, flags: PageFlags, page: PageSize) -> Result<(), PagingError> {
+        let user = flags & PageFlags::USER;
+        let mut table = self.root;
+
+        for level in (page.level() + 1..=LEVELS).rev() {
+            let entry = unsafe { &mut (*self.table(table)).entries[table_index(virt, level)] };
+
+            if entry.is_present() {
+                if entry.is_huge() {
+                    return Err(PagingError::AlreadyMapped);
+                }
+                entry.set_flags(entry.flags() | user);
+            } else {
+                let new = self.source.alloc_table().ok_or(PagingError::NoFrames)?;
+                unsafe { (*self.table(new)).zero(); }
+                entry.set(new, PageFlags::PRESENT | PageFlags::WRITABLE | user);
+            }
+
+            table = entry.addr();
+        }
+
+        let entry = unsafe { &mut (*self.table(table)).entries[table_index(virt, page.level())] };
+
+        if entry.is_present() {
+            return Err(PagingError::AlreadyMapped);
+        }
+
+        let huge = if page == PageSize::Size4K { PageFlags::EMPTY } else { PageFlags::HUGE };
+        entry.set(phys, flags.user() | PageFlags::PRESENT | huge);
+
+        Ok(())
+    }
+
+    /// Checks that every page of the range is mapped and huge pages are covered whole
+    fn validate(&self, virt: usize, size: usize) -> Result<(), PagingError> {
+        let mut addr = virt;
+        while addr < virt + size {
+            let (_, page) = self.walk(addr, false).ok_or(PagingError::NotMapped)?;
+
+            let start = addr & !(page.bytes() - 1);
+            if start < virt || start + page.bytes() > virt + size {
+                return Err(PagingError::HugePage);
+            }
+
+            addr = start + page.bytes();
+        }
+
+        Ok(())
+    }
+
+    /// Clears the entries of the range and frees the tables that became empty
+    /// - pages that are not mapped are skipped
+    fn unmap_range(&mut self, virt: usize, size: usize) {
+        let mut addr = virt;
+        while addr < virt + size {
+            let next = match self.walk(addr, false) {
+                Some((entry, page)) => {
+                    unsafe { (*entry).clear(); }
+                    (addr & !(page.bytes() - 1)) + page.bytes()
+                },
+                None => addr + PAGE_SIZE,
+            };
+
+            //  a table can only become empty once all its entries in the range are cleared
+            if next >= virt + size || table_index(next, 1) == 0 {
+                self.prune(addr);
+            }
+
+            addr = next;
+        }
+    }
+
+    /// Unmaps the pages mapped by a failed `map` and frees the tables created for them
+    fn rollback(&mut self, virt: usize, size: usize) {
+        self.unmap_range(virt, size);
+        self.prune(virt + size);
+    }
+
+    /// Frees the empty tables on the path to `virt`, from the lowest level up
+    fn prune(&mut self, virt: usize) {
+        let mut path = [self.root; LEVELS];
+        let mut depth = 1;
+
+        while depth < LEVELS {
+            let entry = unsafe { (*self.table(path[depth - 1])).entries[table_index(virt, LEVELS + 1 - depth)] };
+            if !entry.is_present() || entry.is_huge() {
+                break;
+            }
+            path[depth] = entry.addr();
+            depth += 1;
+        }
+
+        //  the root is never freed
+        for i in (1..depth).rev() {
+            if !unsafe { (*self.table(path[i])).is_empty() } {
+                break;
+            }
+
+            unsafe { (*self.table(path[i - 1])).entries[table_index(virt, LEVELS + 1 - i)].clear(); }
+            self.source.free_table(path[i]);
+        }
+    }
+
+}
+
+
+/// Returns virtual address, physical address and size of the region
+/// - checks alignment to `page` and that the virtual range is canonical
+fn check<const ALIGN: usize>(region: &Region<ALIGN>, page: PageSize) -> Result<(usize, usize, usize), PagingError> {
+    let (virt, phys, size) = (region.virt() as usize, region.phys(), region.size());
+    let mask = page.bytes() - 1;
+
+    if virt & mask != 0 || phys & mask != 0 || size & mask != 0 {
+        return Err(PagingError::NotAligned);
+    }
+
+    if size == 0 {
+        return Ok((virt, phys, 0));
+    }
+
+    let last = virt.checked_add(size - 1).ok_or(PagingError::NonCanonical)?;
+    if !is_canonical(virt) || !is_canonical(last) || virt >> 47 != last >> 47 {
+        return Err(PagingError::NonCanonical);
+    }
+
+    Ok((virt, phys, size))
+}
@@ -0,0 +1,142 @@
+//	mem/paging/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! x86_64 4-level page tables
+//! - typed entries and flags ([`PageTableEntry`], [`PageFlags`], [`CacheMode`])
+//! - [`Mapper`] maps, unmaps, translates and changes protection of `Region`s in 4 KiB, 2 MiB and 1 GiB pages
+//!   - intermediate tables are allocated from a [`FrameSource`] (e.g. the global frame allocator)
+//!   - the tables are accessed through the direct map (`phys + phys_offset`), so they can be tested on the host
+//!     with tables in normal memory
+//! - the TLB of the current CPU is flushed by the `Mapper`, other CPUs are flushed by the shootdown handler
+//!   (see [`set_shootdown_handler`])
+//!
+//! # Example
+//! ```
+//! let mut mapper = unsafe { Mapper::active(hhdm_offset, GlobalFrames) };
+//!
+//! let mmio = Region::new(0xffff_9000_0000_0000, 0xfee0_0000, 4 * KB);
+//! mapper.map(mmio, (PageFlags::WRITABLE | PageFlags::NO_EXECUTE).cache(CacheMode::Uncached))?;
+//!
+//! assert_eq!(mapper.translate_addr(0xffff_9000_0000_0020), Some(0xfee0_0020));
+//! ```
+
+
+mod entry;
+mod mapper;
+
+pub use entry::{PageFlags, CacheMode, PageTableEntry, PageTable};
+pub use mapper::{Mapper, FrameSource, Translation, set_shootdown_handler};
+#[cfg(feature = "spin")]
+pub use mapper::GlobalFrames;
+
+use core::fmt;
+
+use crate::mem::PAGE_SIZE;
+
+
+/// Number of entries in one table
+pub const ENTRIES: usize = 512;
+
+/// Number of table levels (PML4, PDPT, PD, PT)
+pub const LEVELS: usize = 4;
+
+
+/// Size of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PageSize {
+    /// 4 KiB page mapped by the last level (PT)
+    Size4K,
+    /// 2 MiB page mapped by the page directory (PD)
+    Size2M,
+    /// 1 GiB page mapped by the PDPT (needs `pdpe1gb` support)
+    Size1G,
+}
+
+impl PageSize {
+
+    /// Returns size of the page (in bytes)
+    #[inline]
+    pub const fn bytes(&self) -> usize {
+        match self {
+            Self::Size4K => 4096,
+            Self::Size2M => 2 * 1024 * 1024,
+            Self::Size1G => 1024 * 1024 * 1024,
+        }
+    }
+
+    /// Returns level of the table that maps the page (`1` = PT)
+    #[inline]
+    pub const fn level(&self) -> usize {
+        match self {
+            Self::Size4K => 1,
+            Self::Size2M => 2,
+            Self::Size1G => 3,
+        }
+    }
+
+    /// Returns page size mapped by the table level
+    #[inline]
+    pub const fn from_level(level: usize) -> Option<Self> {
+        match level {
+            1 => Some(Self::Size4K),
+            2 => Some(Self::Size2M),
+            3 => Some(Self::Size1G),
+            _ => None,
+        }
+    }
+
+}
+
+
+/// Error returned by the `Mapper`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagingError {
+    /// The addresses or size are not aligned to the page size
+    NotAligned,
+    /// The virtual address is not canonical (bits 47..63 differ)
+    NonCanonical,
+    /// Some page of the region is already mapped
+    AlreadyMapped,
+    /// Some page of the region is not mapped
+    NotMapped,
+    /// The region covers only part of a huge page
+    HugePage,
+    /// The frame source has no frame for a new table
+    NoFrames,
+}
+
+impl PagingError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotAligned => "region is not aligned to the page size",
+            Self::NonCanonical => "virtual address is not canonical",
+            Self::AlreadyMapped => "page is already mapped",
+            Self::NotMapped => "page is not mapped",
+            Self::HugePage => "region covers only part of a huge page",
+            Self::NoFrames => "no frame for a page table",
+        }
+    }
+}
+
+impl fmt::Display for PagingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Returns index into the table of `level` for the virtual address
+#[inline(always)]
+pub const fn table_index(virt: usize, level: usize) -> usize {
+    (virt >> (12 + 9 * (level - 1))) & (ENTRIES - 1)
+}
+
+/// Checks if the virtual address is canonical (48-bit addresses)
+#[inline(always)]
+pub const fn is_canonical(virt: usize) -> bool {
+    let high = virt >> 47;
+    high == 0 || high == (usize::MAX >> 47)
+}
@@ -0,0 +1,414 @@
+//	mem/pool.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Fixed-size object pool
+//! - objects of one type are allocated from page-sized chunks taken from the `ALLOCATOR`
+//! - free slots are kept in an intrusive free list, so both allocation and free are O(1)
+//! - chunks are given back to the `ALLOCATOR` only when the pool is dropped
+//!
+//! # Example
+//! ```
+//! static TASKS: Pool<Task> = Pool::new();
+//!
+//! let task = TASKS.alloc(Task::new());
+//! println!("{}", TASKS.stats());
+//! drop(task);     //  the slot goes back to `TASKS`
+//! ```
+
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt::{self, Debug, Display};
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, null_mut, NonNull};
+
+use crate::ALLOCATOR;
+use crate::mem::alloc::{AllocError, TryReserveError};
+use crate::mem::PAGE_SIZE;
+use crate::spin::Mutex;
+
+
+/// One slot of the pool, either free (link to the next free slot) or holding a value
+union Slot<T> {
+    next: *mut Slot<T>,
+    value: ManuallyDrop<T>,
+}
+
+/// Header at the start of every chunk
+struct ChunkHeader {
+    next: *mut ChunkHeader,
+}
+
+
+/// Snapshot of the pool state
+/// - can be printed with `println!("{}", pool.stats())`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Size of one object (in bytes)
+    pub object_size: usize,
+    /// Number of slots in all chunks
+    pub capacity: usize,
+    /// Number of slots holding a value
+    pub used: usize,
+    /// Highest value of `used` so far
+    pub peak: usize,
+    /// Number of chunks taken from the `ALLOCATOR`
+    pub chunks: usize,
+    /// Size of one chunk (in bytes)
+    pub chunk_bytes: usize,
+    /// Number of successful allocations
+    pub allocations: usize,
+    /// Number of slots returned to the pool
+    pub frees: usize,
+    /// Number of allocations that failed
+    pub failed_allocations: usize,
+}
+
+impl PoolStats {
+
+    /// Returns number of free slots
+    #[inline]
+    pub const fn available(&self) -> usize {
+        self.capacity - self.used
+    }
+
+    /// Returns number of bytes taken from the `ALLOCATOR`
+    #[inline]
+    pub const fn total_bytes(&self) -> usize {
+        self.chunks * self.chunk_bytes
+    }
+
+}
+
+impl Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "pool: {} / {} slots used ({} free, peak {})",
+            self.used, self.capacity, self.available(), self.peak)?;
+        writeln!(f, "  object size: {} bytes, chunks: {} x {} bytes",
+            self.object_size, self.chunks, self.chunk_bytes)?;
+        write!(f, "  allocations: {}, frees: {}, failed allocations: {}",
+            self.allocations, self.frees, self.failed_allocations)
+    }
+}
+
+
+/// State of the pool protected by the lock
+struct PoolInner<T> {
+    free: *mut Slot<T>,
+    chunks: *mut ChunkHeader,
+    stats: PoolStats,
+}
+
+unsafe impl<T: Send> Send for PoolInner<T> {}
+
+
+/// Pool of objects of type `T`
+/// - grows by one chunk (at least one page) whenever it runs out of free slots
+/// - values are returned in [`PoolBox`], which gives the slot back on drop
+pub struct Pool<T> {
+    inner: Mutex<PoolInner<T>>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Sync for Pool<T> {}
+
+impl<T> Pool<T> {
+
+    /// Offset of the first slot in a chunk
+    const SLOTS_OFFSET: usize = {
+        let align = align_of::<Slot<T>>();
+        (size_of::<ChunkHeader>() + align - 1) & !(align - 1)
+    };
+
+    /// Number of slots in one chunk
+    const CHUNK_SLOTS: usize = {
+        let size = if size_of::<Slot<T>>() == 0 { 1 } else { size_of::<Slot<T>>() };
+        let slots = PAGE_SIZE.saturating_sub(Self::SLOTS_OFFSET) / size;
+        if slots == 0 { 1 } else { slots }
+    };
+
+    /// Layout of one chunk
+    const CHUNK_LAYOUT: Layout = {
+        let align = if align_of::<Slot<T>>() > align_of::<ChunkHeader>() { align_of::<Slot<T>>() } else { align_of::<ChunkHeader>() };
+        match Layout::from_size_align(Self::SLOTS_OFFSET + Self::CHUNK_SLOTS * size_of::<Slot<T>>(), align) {
+            Ok(layout) => layout,
+            Err(_) => panic!("Pool: object is too large"),
+        }
+    };
+
+    /// Constructs new empty `Pool`
+    /// - does not allocate
+    pub const fn new() -> Self {
+        Self {
+            inner: Mutex::new(PoolInner {
+                free: null_mut(),
+                chunks: null_mut(),
+                stats: PoolStats {
+                    object_size: size_of::<T>(),
+                    capacity: 0,
+                    used: 0,
+                    peak: 0,
+                    chunks: 0,
+                    chunk_bytes: Self::CHUNK_LAYOUT.size(),
+                    allocations: 0,
+                    frees: 0,
+                    failed_allocations: 0,
+                },
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Moves `val` into the pool
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn alloc(&self, val: T) -> PoolBox<'_, T> {
+        match self.try_alloc(val) {
+            Ok(b) => b,
+            Err(e) => panic!("Pool: {}", e),
+        }
+    }
+
+    /// Tries to move `val` into the pool
+    /// - returns `Err` if the pool has no free slot and a new chunk cannot be allocated
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_alloc(&self, val: T) -> Result<PoolBox<'_, T>, AllocError> {
+        let mut inner = self.inner.lock();
+
+        if inner.free.is_null() && let Err(e) = Self::grow(&mut inner) {
+            inner.stats.failed_allocations += 1;
+            return Err(e);
+        }
+
+        let slot = inner.free;
+        unsafe {
+            inner.free = (*slot).next;
+            ptr::write(&raw mut (*slot).value, ManuallyDrop::new(val));
+        }
+
+        inner.stats.used += 1;
+        inner.stats.allocations += 1;
+        if inner.stats.used > inner.stats.peak {
+            inner.stats.peak = inner.stats.used;
+        }
+
+        Ok(PoolBox {
+            ptr: unsafe { NonNull::new_unchecked(slot.cast::<T>()) },
+            pool: self,
+        })
+    }
+
+    /// Makes sure at least `additional` slots are free
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn reserve(&self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("Pool: {}", e);
+        }
+    }
+
+    /// Tries to make sure at least `additional` slots are free
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve(&self, additional: usize) -> Result<(), TryReserveError> {
+        let mut inner = self.inner.lock();
+        while inner.stats.capacity - inner.stats.used < additional {
+            Self::grow(&mut inner)?;
+        }
+        Ok(())
+    }
+
+    /// Returns number of slots in the pool
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.lock().stats.capacity
+    }
+
+    /// Returns number of slots holding a value
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.inner.lock().stats.used
+    }
+
+    /// Returns number of free slots
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.inner.lock().stats.available()
+    }
+
+    /// Returns snapshot of the pool state
+    #[inline]
+    pub fn stats(&self) -> PoolStats {
+        self.inner.lock().stats
+    }
+
+    /// Allocates new chunk and puts its slots to the free list
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn grow(inner: &mut PoolInner<T>) -> Result<(), AllocError> {
+        let chunk = unsafe { ALLOCATOR.alloc(Self::CHUNK_LAYOUT) };
+        if chunk.is_null() {
+            return Err(AllocError::out_of_memory(Self::CHUNK_LAYOUT));
+        }
+
+        unsafe {
+            let header = chunk.cast::<ChunkHeader>();
+            header.write(ChunkHeader { next: inner.chunks });
+            inner.chunks = header;
+
+            //  link the slots in reverse, so the first slot is used first
+            let slots = chunk.add(Self::SLOTS_OFFSET).cast::<Slot<T>>();
+            for i in (0..Self::CHUNK_SLOTS).rev() {
+                let slot = slots.add(i);
+                (*slot).next = inner.free;
+                inner.free = slot;
+            }
+        }
+
+        inner.stats.chunks += 1;
+        inner.stats.capacity += Self::CHUNK_SLOTS;
+
+        Ok(())
+    }
+
+    /// Puts the slot back to the free list
+    /// - the value must already be dropped or moved out
+    unsafe fn release(&self, ptr: NonNull<T>) {
+        let mut inner = self.inner.lock();
+        let slot = ptr.as_ptr().cast::<Slot<T>>();
+
+        unsafe { (*slot).next = inner.free; }
+        inner.free = slot;
+
+        inner.stats.used -= 1;
+        inner.stats.frees += 1;
+    }
+
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Pool<T> {
+    /// Gives all chunks back to the `ALLOCATOR`
+    /// - no `PoolBox` can outlive the pool, so all slots are free at this point
+    fn drop(&mut self) {
+        let inner = self.inner.get_mut();
+        let mut chunk = inner.chunks;
+
+        while !chunk.is_null() {
+            unsafe {
+                let next = (*chunk).next;
+                ALLOCATOR.dealloc(chunk.cast(), Self::CHUNK_LAYOUT);
+                chunk = next;
+            }
+        }
+    }
+}
+
+impl<T> Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.stats(), f)
+    }
+}
+
+
+/// Pointer to a value allocated in a [`Pool`]
+/// - drops the value and returns the slot to the pool on drop
+pub struct PoolBox<'pool, T> {
+    ptr: NonNull<T>,
+    pool: &'pool Pool<T>,
+}
+
+unsafe impl<T: Send> Send for PoolBox<'_, T> {}
+unsafe impl<T: Sync> Sync for PoolBox<'_, T> {}
+
+impl<'pool, T> PoolBox<'pool, T> {
+
+    /// Moves the value out and returns the slot to the pool
+    pub fn into_inner(this: Self) -> T {
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let val = this.ptr.read();
+            this.pool.release(this.ptr);
+            val
+        }
+    }
+
+    /// Leaks the value, its slot is never returned to the pool
+    pub fn leak(this: Self) -> &'pool mut T {
+        let this = ManuallyDrop::new(this);
+        unsafe { &mut *this.ptr.as_ptr() }
+    }
+
+    /// Returns the pool this value belongs to
+    #[inline]
+    pub fn pool(this: &Self) -> &'pool Pool<T> {
+        this.pool
+    }
+
+}
+
+impl<T> Deref for PoolBox<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for PoolBox<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> AsRef<T> for PoolBox<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for PoolBox<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T> Drop for PoolBox<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            self.pool.release(self.ptr);
+        }
+    }
+}
+
+impl<T: Debug> Debug for PoolBox<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for PoolBox<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for PoolBox<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for PoolBox<'_, T> {}
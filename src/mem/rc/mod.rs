@@ -5,12 +5,11 @@
 //! The classic reference counted pointer `Rc`
 //! - This implementation does not offer the `Weak` pointer yet
 
-use core::alloc::GlobalAlloc;
 use core::borrow::{Borrow, BorrowMut};
 use core::fmt::{Debug, Display, Pointer};
 use core::marker::PhantomData;
 use core::mem::{ManuallyDrop, MaybeUninit};
-use core::ptr::{drop_in_place, write_bytes};
+use core::ptr::{self, drop_in_place};
 use core::{cell::Cell, ptr::NonNull};
 
 mod rc_inner;
@@ -18,11 +17,14 @@ mod rc_inner;
 pub(crate) use rc_inner::*;
 
 use crate::{alloc::*, TryClone, Cow, ToOwned};
+use crate::mem::alloc::{AllocError, AllocRef, Global};
 
 /// A single-threaded reference-counting pointer
 /// - With no `Weak` pointer unfortunately
-pub struct Rc<T: Sized> {
+/// - the allocation is made by the allocator `A` (see [`AllocRef`])
+pub struct Rc<T: Sized, A: AllocRef = Global> {
     data: NonNull<RcInner<T>>,
+    alloc: A,
     _not_sync_not_send: PhantomData<Cell<()>>,    //  for !Send
 }
 
@@ -32,106 +34,174 @@ impl<T> Rc<T> {
 
     /// Constructs new `Rc<T>`
     /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new(val: T) -> Self {
-        Self {
-            data: unsafe { ALLOCATOR.allocate(RcInner::new(val)).map_err(|inner| {drop(inner)} ) }.expect("allocation failed"),
-            _not_sync_not_send: PhantomData
-        }
+        Self::new_in(val, Global)
     }
 
     /// Tries to construct new `Rc<T>`
     /// - returns `Err` if allocation fails
-    pub fn try_new(val: T) -> Result<Self, ()> {
-        Ok(Self {
-            data: unsafe { ALLOCATOR.allocate(RcInner::new(val)).map_err(|inner| drop(inner) )? },
-            _not_sync_not_send: PhantomData
-        })
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new(val: T) -> Result<Self, AllocError> {
+        Self::try_new_in(val, Global)
     }
 
     /// Constructs new `Rc<T>` with uninitalized content
     /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_uninit() -> Rc<MaybeUninit<T>> {
-
-        Rc {
-            data: unsafe {
-                let data = (ALLOCATOR.alloc(Layout::new::<RcInner<MaybeUninit<T>>>())
-                as *mut RcInner<MaybeUninit<T>>).as_mut().expect("allocation failed");
-
-                data.set_strong(1);
-                data.set_weak(1);
-                
-
-                NonNull::new_unchecked(data)
-            },
-            _not_sync_not_send: PhantomData
-        }
-
+        Self::new_uninit_in(Global)
     }
 
     /// Tries to construct new `Rc<T>` with uninitialized content
     /// - returns `Err` if allocation fails
-    pub fn try_new_uninit() -> Result<Rc<MaybeUninit<T>>, ()> {
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit() -> Result<Rc<MaybeUninit<T>>, AllocError> {
+        Self::try_new_uninit_in(Global)
+    }
 
-        Ok(Rc {
-            data: unsafe {
-                let data = (ALLOCATOR.alloc(Layout::new::<RcInner<MaybeUninit<T>>>())
-                as *mut RcInner<MaybeUninit<T>>).as_mut().ok_or(())?;
+    /// Constructs new `Rc<T>` with all bytes set to 0
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed() -> Rc<MaybeUninit<T>> {
+        Self::new_zeroed_in(Global)
+    }
 
-                data.set_strong(1);
-                data.set_weak(1);
+    /// Tries to construct new `Rc<T>` with all bytes set to 0
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed() -> Result<Rc<MaybeUninit<T>>, AllocError> {
+        Self::try_new_zeroed_in(Global)
+    }
 
-                NonNull::new_unchecked(data)
+    /// Converts the `Rc` to pointer **without deleting the strong reference**
+    /// - to prevent memory leak, this must be converted back to `Rc` by `Rc::from_raw`
+    /// - `self` will not be dropped
+    pub fn into_raw(self) -> *const T {
+        let ptr = self.inner().data_as_ptr();
+        _ = ManuallyDrop::new(self);
+        ptr
+    }
 
-            },
+    /// Converts raw pointer to `Rc`
+    /// 
+    /// safety:
+    /// - the pointer must be previously returned by `Rc::into_raw`
+    ///   - otherwise use of this function may result in undefined behaviour
+    /// - the strong reference counter is not increased
+    pub const fn from_raw(ptr: *const T) -> Self {
+        Self {
+            //  sub 8 (2 * sizeof(u32) ) to get the actual address of the allocated memory
+            //  - the pointer returned by the `Rc::into_raw` points to the data (T type), not the allocated memory
+            //  - see `RcInner` structure
+            data: unsafe { NonNull::new_unchecked(ptr.byte_offset(-8) as *mut RcInner<T> ) },
+            alloc: Global,
             _not_sync_not_send: PhantomData
-        })
-
+        }
     }
 
-    /// Constructs new `Rc<T>` with all bytes set to 0
-    /// - **panics** if allocation fails
-    pub fn new_zeroed() -> Rc<MaybeUninit<T>> {
+}
 
-        Rc {
-            data: unsafe {
-                let data = (ALLOCATOR.alloc(Layout::new::<RcInner<MaybeUninit<T>>>())
-                as *mut RcInner<MaybeUninit<T>>).as_mut().expect("allocation failed");
+impl<T, A: AllocRef> Rc<T, A> {
 
-                data.set_strong(1);
-                data.set_weak(1);
+    /// Constructs new `Rc<T>` in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_in(val: T, alloc: A) -> Self {
+        match Self::try_new_in(val, alloc) {
+            Ok(rc) => rc,
+            Err(e) => panic!("Rc: {}", e),
+        }
+    }
 
-                write_bytes(data.data_as_ptr(), 0, size_of::<T>());
-                
+    /// Tries to construct new `Rc<T>` in `alloc`
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_in(val: T, alloc: A) -> Result<Self, AllocError> {
+        let data = alloc.allocate(Layout::new::<RcInner<T>>())?.cast::<RcInner<T>>();
 
-                NonNull::new_unchecked(data)
+        unsafe { data.write(RcInner::new(val)); }
 
-            },
+        Ok(Self {
+            data,
+            alloc,
             _not_sync_not_send: PhantomData
-        }
+        })
+    }
 
+    /// Constructs new `Rc<T>` with uninitalized content in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit_in(alloc: A) -> Rc<MaybeUninit<T>, A> {
+        match Self::try_new_uninit_in(alloc) {
+            Ok(rc) => rc,
+            Err(e) => panic!("Rc: {}", e),
+        }
     }
 
-    /// Tries to construct new `Rc<T>` with all bytes set to 0
+    /// Tries to construct new `Rc<T>` with uninitialized content in `alloc`
     /// - returns `Err` if allocation fails
-    pub fn try_new_zeroed() -> Result<Rc<MaybeUninit<T>>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_in(alloc: A) -> Result<Rc<MaybeUninit<T>, A>, AllocError> {
+        let mut data = alloc.allocate(Layout::new::<RcInner<MaybeUninit<T>>>())?.cast::<RcInner<MaybeUninit<T>>>();
+
+        unsafe {
+            data.as_mut().set_strong(1);
+            data.as_mut().set_weak(1);
+        }
 
         Ok(Rc {
-            data: unsafe {
-                let data = (ALLOCATOR.alloc(Layout::new::<RcInner<MaybeUninit<T>>>())
-                as *mut RcInner<MaybeUninit<T>>).as_mut().ok_or(())?;
+            data,
+            alloc,
+            _not_sync_not_send: PhantomData
+        })
+    }
 
-                data.set_strong(1);
-                data.set_weak(1);
+    /// Constructs new `Rc<T>` with all bytes set to 0 in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed_in(alloc: A) -> Rc<MaybeUninit<T>, A> {
+        match Self::try_new_zeroed_in(alloc) {
+            Ok(rc) => rc,
+            Err(e) => panic!("Rc: {}", e),
+        }
+    }
 
-                write_bytes(data.data_as_ptr(), 0, size_of::<T>());
-                
+    /// Tries to construct new `Rc<T>` with all bytes set to 0 in `alloc`
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed_in(alloc: A) -> Result<Rc<MaybeUninit<T>, A>, AllocError> {
+        let mut data = alloc.allocate_zeroed(Layout::new::<RcInner<MaybeUninit<T>>>())?.cast::<RcInner<MaybeUninit<T>>>();
 
-                NonNull::new_unchecked(data)
+        unsafe {
+            data.as_mut().set_strong(1);
+            data.as_mut().set_weak(1);
+        }
 
-            },
+        Ok(Rc {
+            data,
+            alloc,
             _not_sync_not_send: PhantomData
         })
+    }
+
+    /// Reads the value out and deallocates the memory without dropping the value
+    /// - `self` must be the last strong reference
+    unsafe fn take(self) -> T {
+        let this = ManuallyDrop::new(self);
 
+        unsafe {
+            let ret = this.inner().data_as_ptr().read();
+            let alloc = ptr::read(&this.alloc);
+            alloc.deallocate(this.data.cast(), Layout::new::<RcInner<T>>());
+            ret
+        }
     }
 
     /// Returns the inner value, if the Rc has exactly one strong reference
@@ -139,76 +209,25 @@ impl<T> Rc<T> {
     /// - if returning -> deallocates the inner value
     pub fn try_unwrap(self) -> Result<T, Self> {
         
-        let mut this = ManuallyDrop::new(self);
-        let inner = this.inner_mut();
-        
-        if inner.strong() == 0 {
+        if self.inner().strong() == 0 {
 
-            let ret = unsafe { inner.data_as_ptr().read() };
-
-            unsafe {
-                ALLOCATOR.dealloc(inner.as_mut_ptr() as *mut u8, Layout::new::<RcInner<T>>());
-            }
-
-            //  do not drop `self`
-
-            Ok(ret)
+            Ok(unsafe { self.take() })
 
         } else {
-            Err((*this).clone())
-            //  `clone` does only create another `Rc` (no allocation)
+            Err(self)
         }
     }
 
     /// Destructs `self` and returns its value
     /// - returns `None` if there are more than one strong references
     pub fn into_inner(self) -> Option<T> {
-        let mut this = ManuallyDrop::new(self);
-        let inner = this.inner_mut();
-
-        if inner.strong() == 0 {
-            let ret = unsafe { inner.data_as_ptr().read() };
-
-            unsafe {
-                ALLOCATOR.dealloc(inner.as_mut_ptr() as *mut u8, Layout::new::<RcInner<T>>());
-            }
-
-            //  do not drop `self`
-            Some(ret)
-
+        if self.inner().strong() == 0 {
+            Some(unsafe { self.take() })
         } else {
-            //  no need to drop `self`
             None
         }
     }
 
-
-
-    /// Converts the `Rc` to pointer **without deleting the strong reference**
-    /// - to prevent memory leak, this must be converted back to `Rc` by `Rc::from_raw`
-    /// - `self` will not be dropped
-    pub fn into_raw(self) -> *const T {
-        let ptr = self.inner().data_as_ptr();
-        _ = ManuallyDrop::new(self);
-        ptr
-    }
-
-    /// Converts raw pointer to `Rc`
-    /// 
-    /// safety:
-    /// - the pointer must be previously returned by `Rc::into_raw`
-    ///   - otherwise use of this function may result in undefined behaviour
-    /// - the strong reference counter is not increased
-    pub const fn from_raw(ptr: *const T) -> Self {
-        Self {
-            //  sub 8 (2 * sizeof(u32) ) to get the actual address of the allocated memory
-            //  - the pointer returned by the `Rc::into_raw` points to the data (T type), not the allocated memory
-            //  - see `RcInner` structure
-            data: unsafe { NonNull::new_unchecked(ptr.byte_offset(-8) as *mut RcInner<T> ) },
-            _not_sync_not_send: PhantomData
-        }
-    }
-
     /*/// Creates new `Weak` pointer to this allocation
     #[inline]
     pub fn downgrade(&self) -> Weak<T> {
@@ -222,7 +241,7 @@ impl<T> Rc<T> {
     #[inline]
     pub fn get_mut(&mut self) -> Option<&mut T> {
         if self.strong_count() == 0 && self.weak_count() == 0 {
-            Some(unsafe { self.data.as_mut().data_mut() })
+            Some(self.inner_mut().data_mut())
         } else {
             None
         }
@@ -231,25 +250,31 @@ impl<T> Rc<T> {
     /// Returns mutable reference to the data without doing any checks
     /// - use `Rc::get_mut` if possible
     pub unsafe fn get_mut_unchecked(&mut self) -> &mut T {
-        unsafe { self.data.as_mut().data_mut() }
+        self.inner_mut().data_mut()
+    }
+
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        &self.alloc
     }
 
 }
 
-impl<T> Rc<MaybeUninit<T>> {
+impl<T, A: AllocRef> Rc<MaybeUninit<T>, A> {
 
-    pub const fn assume_init(self) -> Rc<T> {
-        let data = self.data;
-        _ = ManuallyDrop::new(self);
+    pub fn assume_init(self) -> Rc<T, A> {
+        let this = ManuallyDrop::new(self);
         Rc {
-            data: unsafe { NonNull::new_unchecked(data.as_ptr() as *mut RcInner<T>) },
+            data: this.data.cast(),
+            alloc: unsafe { ptr::read(&this.alloc) },
             _not_sync_not_send: PhantomData
         }
     }
 
 }
 
-impl<T> Rc<T> {
+impl<T, A: AllocRef> Rc<T, A> {
 
     /// Returns reference to the inner (allocated) value
     const fn inner(&self) -> &RcInner<T> {
@@ -295,74 +320,60 @@ impl<T> Rc<T> {
 
 }
 
-impl<T: Clone> Rc<T> {
+impl<T: Clone, A: AllocRef> Rc<T, A> {
     /// If we have the only reference to T then unwrap it. Otherwise, clone T and return the clone
     pub fn unwrap_or_clone(self) -> T {
         self.try_unwrap().unwrap_or_else(|rc| rc.inner().data().clone())
     }
 }
 
-impl<T: TryClone> Rc<T> {
+impl<T: TryClone, A: AllocRef> Rc<T, A> {
 
     /// If we have the only reference to T then unwrap it. Otherwise, try cloning T and return the clone
     pub fn unwrap_or_tryclone(self) -> Result<T, T::Error> {
 
-        let mut this = ManuallyDrop::new(self);
-        let inner = this.inner_mut();
-
-        
-        if inner.strong() == 0 {
-
-            let ret = unsafe { inner.data_as_ptr().read() };
-
-            unsafe {
-                ALLOCATOR.dealloc(inner.as_mut_ptr() as *mut u8, Layout::new::<RcInner<T>>());
-            }
-
-            //  do not drop `self`
-
-            Ok(ret)
-
+        if self.inner().strong() == 0 {
+            Ok(unsafe { self.take() })
         } else {
-            inner.data().try_clone()
-            //  `clone` does only create another `Rc` (no allocation)
+            self.inner().data().try_clone()
         }
 
     }
 
 }
 
-impl<T> Drop for Rc<T> {
+impl<T, A: AllocRef> Drop for Rc<T, A> {
     fn drop(&mut self) {
-        let inner = self.inner_mut();
+        let inner = self.inner();
         inner.dec_strong();
         if inner.strong() == 0 {
             unsafe {
-                //drop_in_place(inner.data_as_ptr());
-                ALLOCATOR.delete(NonNull::new_unchecked(inner.as_mut_ptr()));
+                drop_in_place(inner.data_as_ptr());
+                self.alloc.deallocate(self.data.cast(), Layout::new::<RcInner<T>>());
             }
         }
     }
 }
 
-impl<T> Clone for Rc<T> {
+impl<T, A: AllocRef + Clone> Clone for Rc<T, A> {
     #[inline]
     fn clone(&self) -> Self {
         self.inner().inc_strong();
         Self {
             data: self.data,
+            alloc: self.alloc.clone(),
             _not_sync_not_send: PhantomData
         }
     }
 }
 
-impl<T> Borrow<T> for Rc<T> {
+impl<T, A: AllocRef> Borrow<T> for Rc<T, A> {
     #[inline]
     fn borrow(&self) -> &T {
         &self.inner().data()
     }
 }
-impl<T> BorrowMut<T> for Rc<T> {
+impl<T, A: AllocRef> BorrowMut<T> for Rc<T, A> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut T {
         self.get_mut().expect("there are pointing references to this Rc")
@@ -370,28 +381,28 @@ impl<T> BorrowMut<T> for Rc<T> {
 }
 
 
-impl<T: Debug> Debug for Rc<T> {
+impl<T: Debug, A: AllocRef> Debug for Rc<T, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.inner().data().fmt(f)
     }
 }
 
-impl<T: Display> Display for Rc<T> {
+impl<T: Display, A: AllocRef> Display for Rc<T, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.inner().data().fmt(f)
     }
 }
 
-impl<T: Default> Default for Rc<T> {
+impl<T: Default, A: AllocRef + Default> Default for Rc<T, A> {
     #[inline]
     fn default() -> Self {
-        Rc::new(T::default())
+        Rc::new_in(T::default(), A::default())
     }
 }
 
-impl<T: PartialOrd> PartialOrd for Rc<T> {
+impl<T: PartialOrd, A: AllocRef> PartialOrd for Rc<T, A> {
     #[inline]
     fn ge(&self, other: &Self) -> bool {
         self.inner().data().ge(&other.inner().data())
@@ -419,7 +430,7 @@ impl<T: PartialOrd> PartialOrd for Rc<T> {
     
 }
 
-impl<T: PartialEq> PartialEq for Rc<T> {
+impl<T: PartialEq, A: AllocRef> PartialEq for Rc<T, A> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         self.inner().data().eq(&other.inner().data())
@@ -432,23 +443,23 @@ impl<T: PartialEq> PartialEq for Rc<T> {
 
 }
 
-impl<T: core::cmp::Eq> core::cmp::Eq for Rc<T> {}
+impl<T: core::cmp::Eq, A: AllocRef> core::cmp::Eq for Rc<T, A> {}
 
 
-impl<T: Ord> Ord for Rc<T> {
+impl<T: Ord, A: AllocRef> Ord for Rc<T, A> {
     #[inline]
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.inner().data().cmp(&other.inner().data())
     }
 }
 
-impl<T> Pointer for Rc<T> {
+impl<T, A: AllocRef> Pointer for Rc<T, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:p}", self.data )
     }
 }
 
-impl<T> Unpin for Rc<T> {}
+impl<T, A: AllocRef> Unpin for Rc<T, A> {}
 
 
 impl<'a, B> From<Cow<'a, B>> for Rc<B>
@@ -0,0 +1,434 @@
+//	mem/region_map.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Sorted map of tagged, non-overlapping regions
+//! - used for memory layout bookkeeping: the virtual address space of the kernel, the physical memory, ...
+//! - the regions are ordered and compared by their virtual address
+//!   - the physical address is carried along (`0` means the region has no physical address)
+//! - neighbouring regions with the same tag (and contiguous physical addresses) are merged
+//! - the capacity is fixed (`N` regions), so the map can be used before the heap exists
+//!
+//! # Example
+//! ```
+//! let mut layout: RegionMap<32> = RegionMap::new();
+//! layout.insert(Region::new(kernel_start, 0, kernel_size), RegionTag::KernelText)?;
+//! layout.insert(Region::new(fb_addr, 0, fb_size), RegionTag::Framebuffer)?;
+//!
+//! //  fails with `Err(Overlap(..))` if the heap overlaps the kernel or the framebuffer
+//! layout.insert(heap_region, RegionTag::Heap)?;
+//!
+//! let stack = layout.find_gap(64 * KB, PAGE_SIZE, KERNEL_BASE, usize::MAX);
+//! ```
+
+
+use core::fmt;
+
+use super::{Region, PAGE_ALIGN};
+
+
+/// Purpose of a region in the [`RegionMap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionTag {
+    /// Memory that can be handed out (see `RegionMap::carve`)
+    Free,
+    /// Code of the kernel
+    KernelText,
+    /// Data of the kernel (rodata, data, bss)
+    KernelData,
+    Heap,
+    Stack,
+    /// Memory-mapped device registers
+    Mmio,
+    Framebuffer,
+    /// DMA buffers
+    Dma,
+    /// Memory that must not be touched
+    Reserved,
+    /// Tag defined by the user
+    Other(u16),
+}
+
+impl RegionTag {
+    /// Returns short name of the tag
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Free => "free",
+            Self::KernelText => "kernel text",
+            Self::KernelData => "kernel data",
+            Self::Heap => "heap",
+            Self::Stack => "stack",
+            Self::Mmio => "MMIO",
+            Self::Framebuffer => "framebuffer",
+            Self::Dma => "DMA",
+            Self::Reserved => "reserved",
+            Self::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for RegionTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Other(id) => write!(f, "other ({})", id),
+            _ => f.pad(self.as_str()),
+        }
+    }
+}
+
+
+/// Error returned by the [`RegionMap`] operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionMapError {
+    /// The region has zero size or wraps around the address space
+    Invalid,
+    /// The map has no space for another region
+    Full,
+    /// The region overlaps with a region of the given tag
+    Overlap(RegionTag),
+    /// No free region is large enough
+    NoSpace,
+}
+
+impl RegionMapError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Invalid => "region is empty or wraps around",
+            Self::Full => "region map is full",
+            Self::Overlap(_) => "region overlaps with another region",
+            Self::NoSpace => "no free region is large enough",
+        }
+    }
+}
+
+impl fmt::Display for RegionMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap(tag) => write!(f, "{} ({})", self.as_str(), tag),
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+
+/// Region with its tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaggedRegion<const ALIGN: usize = PAGE_ALIGN> {
+    pub region: Region<ALIGN>,
+    pub tag: RegionTag,
+}
+
+impl<const ALIGN: usize> TaggedRegion<ALIGN> {
+
+    /// Returns the first virtual address
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.region.virt() as usize
+    }
+
+    /// Returns the first virtual address after the region
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.start() + self.region.size()
+    }
+
+    /// Returns the part of the region between `start` and `end`
+    /// - the physical address is moved along
+    fn slice(&self, start: usize, end: usize) -> Self {
+        let phys = match self.region.phys() {
+            0 => 0,
+            phys => phys + (start - self.start()),
+        };
+
+        Self { region: unsafe { Region::new_unchecked(start, phys, end - start) }, tag: self.tag }
+    }
+
+}
+
+
+/// Map of at most `N` tagged, non-overlapping regions sorted by address
+#[derive(Clone, Copy)]
+pub struct RegionMap<const N: usize, const ALIGN: usize = PAGE_ALIGN> {
+    entries: [TaggedRegion<ALIGN>; N],
+    len: usize,
+}
+
+impl<const N: usize, const ALIGN: usize> RegionMap<N, ALIGN> {
+
+    /// Constructs empty map
+    pub const fn new() -> Self {
+        Self {
+            entries: [TaggedRegion { region: Region::empty(), tag: RegionTag::Free }; N],
+            len: 0,
+        }
+    }
+
+    /// Returns number of regions
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the map has no regions
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximal number of regions
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Removes all regions
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the regions sorted by address
+    #[inline]
+    pub fn as_slice(&self) -> &[TaggedRegion<ALIGN>] {
+        &self.entries[..self.len]
+    }
+
+    /// Returns iterator over the regions sorted by address
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, TaggedRegion<ALIGN>> {
+        self.as_slice().iter()
+    }
+
+    /// Returns iterator over the regions with the given tag
+    pub fn iter_tag(&self, tag: RegionTag) -> impl Iterator<Item = &TaggedRegion<ALIGN>> {
+        self.iter().filter(move |e| e.tag == tag)
+    }
+
+    /// Returns the region containing `addr`
+    pub fn find(&self, addr: usize) -> Option<&TaggedRegion<ALIGN>> {
+        let i = self.as_slice().partition_point(|e| e.end() <= addr);
+        self.as_slice().get(i).filter(|e| e.start() <= addr)
+    }
+
+    /// Returns the first region that overlaps with `region`
+    pub fn overlaps(&self, region: Region<ALIGN>) -> Option<&TaggedRegion<ALIGN>> {
+        let (start, end) = bounds(&region).ok()?;
+        let i = self.first_after(start);
+        self.as_slice().get(i).filter(|e| e.start() < end)
+    }
+
+    /// Returns the parts of the regions that lie inside of `region`
+    pub fn intersect(&self, region: Region<ALIGN>) -> impl Iterator<Item = TaggedRegion<ALIGN>> + '_ {
+        let (start, end) = bounds(&region).unwrap_or((0, 0));
+        let first = self.first_after(start);
+
+        self.as_slice()[first..].iter()
+            .take_while(move |e| e.start() < end)
+            .map(move |e| e.slice(core::cmp::max(e.start(), start), core::cmp::min(e.end(), end)))
+    }
+
+
+    /// Inserts tagged region
+    /// - the region is merged with its neighbours if they have the same tag and contiguous physical addresses
+    /// - returns `Err(Overlap)` if the region overlaps with another region
+    /// - returns `Err(Full)` if the map is full
+    pub fn insert(&mut self, region: Region<ALIGN>, tag: RegionTag) -> Result<(), RegionMapError> {
+        let (start, end) = bounds(&region)?;
+        let new = TaggedRegion { region, tag };
+
+        let i = self.first_after(start);
+        if let Some(next) = self.as_slice().get(i).filter(|e| e.start() < end) {
+            return Err(RegionMapError::Overlap(next.tag));
+        }
+
+        let left = i > 0 && mergeable(&self.entries[i - 1], &new);
+        let right = i < self.len && mergeable(&new, &self.entries[i]);
+
+        match (left, right) {
+            (true, true) => {
+                let size = self.entries[i - 1].region.size() + region.size() + self.entries[i].region.size();
+                self.entries[i - 1].region.enlarge_unckecked(size - self.entries[i - 1].region.size());
+                self.remove_at(i);
+            },
+            (true, false) => self.entries[i - 1].region.enlarge_unckecked(region.size()),
+            (false, true) => {
+                let size = region.size() + self.entries[i].region.size();
+                self.entries[i] = TaggedRegion { region: unsafe { Region::new_unchecked(start, region.phys(), size) }, tag };
+            },
+            (false, false) => self.insert_at(i, new)?,
+        }
+
+        Ok(())
+    }
+
+    /// Removes `region` from the map
+    /// - regions that overlap only partially are shrunk or split
+    /// - returns `Err(Full)` if a region would have to be split in a full map, nothing is removed then
+    pub fn subtract(&mut self, region: Region<ALIGN>) -> Result<(), RegionMapError> {
+        let (start, end) = bounds(&region)?;
+
+        let mut i = self.first_after(start);
+        while i < self.len && self.entries[i].start() < end {
+            let entry = self.entries[i];
+
+            match (entry.start() < start, end < entry.end()) {
+                (true, true) => {
+                    if self.len == N {
+                        return Err(RegionMapError::Full);
+                    }
+                    self.entries[i] = entry.slice(entry.start(), start);
+                    self.insert_at(i + 1, entry.slice(end, entry.end()))?;
+                    return Ok(());
+                },
+                (true, false) => {
+                    self.entries[i] = entry.slice(entry.start(), start);
+                    i += 1;
+                },
+                (false, true) => {
+                    self.entries[i] = entry.slice(end, entry.end());
+                    return Ok(());
+                },
+                (false, false) => self.remove_at(i),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets tag of `region`, whatever was there before
+    /// - same as `subtract` followed by `insert`
+    /// - returns `Err(Full)` if the map is full, the map is not changed then
+    pub fn set(&mut self, region: Region<ALIGN>, tag: RegionTag) -> Result<(), RegionMapError> {
+        let backup = (self.entries, self.len);
+
+        let result = self.subtract(region).and_then(|_| self.insert(region, tag));
+        if result.is_err() {
+            (self.entries, self.len) = backup;
+        }
+
+        result
+    }
+
+    /// Takes `size` bytes aligned to `align` from the first `Free` region that is large enough and tags them
+    /// - `size` is rounded up to `ALIGN`, `align` must be a power of two
+    /// - returns the taken region (with the physical address moved along)
+    /// - returns `Err(NoSpace)` if no free region is large enough
+    pub fn carve(&mut self, size: usize, align: usize, tag: RegionTag) -> Result<Region<ALIGN>, RegionMapError> {
+        let size = size.checked_next_multiple_of(ALIGN).ok_or(RegionMapError::NoSpace)?;
+        let align = core::cmp::max(align, ALIGN);
+
+        let taken = self.iter_tag(RegionTag::Free).find_map(|e| {
+            let start = e.start().checked_next_multiple_of(align)?;
+            let end = start.checked_add(size)?;
+            (end <= e.end()).then(|| e.slice(start, end))
+        }).ok_or(RegionMapError::NoSpace)?;
+
+        self.set(taken.region, tag)?;
+
+        Ok(taken.region)
+    }
+
+    /// Returns start of the first gap between `lo` and `hi` that no region covers,
+    /// has `size` bytes and starts at a multiple of `align`
+    /// - `align` must be a power of two
+    /// - used to find free virtual address space
+    pub fn find_gap(&self, size: usize, align: usize, lo: usize, hi: usize) -> Option<usize> {
+        let mut start = lo.checked_next_multiple_of(align)?;
+
+        for entry in self.as_slice()[self.first_after(start)..].iter() {
+            let end = start.checked_add(size)?;
+            if end > hi {
+                return None;
+            }
+            if end <= entry.start() {
+                return Some(start);
+            }
+            start = core::cmp::max(start, entry.end()).checked_next_multiple_of(align)?;
+        }
+
+        let end = start.checked_add(size)?;
+        (end <= hi).then_some(start)
+    }
+
+
+    /// Returns index of the first region that ends after `addr`
+    #[inline]
+    fn first_after(&self, addr: usize) -> usize {
+        self.as_slice().partition_point(|e| e.end() <= addr)
+    }
+
+    fn insert_at(&mut self, i: usize, entry: TaggedRegion<ALIGN>) -> Result<(), RegionMapError> {
+        if self.len == N {
+            return Err(RegionMapError::Full);
+        }
+
+        self.entries.copy_within(i..self.len, i + 1);
+        self.entries[i] = entry;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn remove_at(&mut self, i: usize) {
+        self.entries.copy_within(i + 1..self.len, i);
+        self.len -= 1;
+    }
+
+}
+
+impl<const N: usize, const ALIGN: usize> Default for RegionMap<N, ALIGN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'l, const N: usize, const ALIGN: usize> IntoIterator for &'l RegionMap<N, ALIGN> {
+    type Item = &'l TaggedRegion<ALIGN>;
+    type IntoIter = core::slice::Iter<'l, TaggedRegion<ALIGN>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const N: usize, const ALIGN: usize> fmt::Debug for RegionMap<N, ALIGN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize, const ALIGN: usize> fmt::Display for RegionMap<N, ALIGN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {:<18}  {:<18}  {:<18}  tag", "start", "end", "phys")?;
+
+        for entry in self.iter() {
+            writeln!(f, "  {:#018x}  {:#018x}  {:#018x}  {}", entry.start(), entry.end(), entry.region.phys(), entry.tag)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Returns start and end of the region
+/// - returns `Err(Invalid)` if the region is empty or wraps around
+#[inline]
+fn bounds<const ALIGN: usize>(region: &Region<ALIGN>) -> Result<(usize, usize), RegionMapError> {
+    let start = region.virt() as usize;
+    match start.checked_add(region.size()) {
+        Some(end) if end > start => Ok((start, end)),
+        _ => Err(RegionMapError::Invalid),
+    }
+}
+
+/// Checks if `b` (right after `a`) can be merged into `a`
+#[inline]
+fn mergeable<const ALIGN: usize>(a: &TaggedRegion<ALIGN>, b: &TaggedRegion<ALIGN>) -> bool {
+    a.tag == b.tag && a.end() == b.start() && match (a.region.phys(), b.region.phys()) {
+        (0, 0) => true,
+        (0, _) | (_, 0) => false,
+        (pa, pb) => pa + a.region.size() == pb,
+    }
+}
@@ -0,0 +1,180 @@
+//	mem/sort.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Sorting and partitioning shared by `Vec` and `Array`
+//! - `core` has only the unstable sort, the stable one here takes its scratch space from `ALLOCATOR`
+//! - the functions are panic-safe: if the comparator panics, the slice still holds every element exactly once
+
+
+use core::alloc::Layout;
+use core::cmp::Ordering;
+use core::ptr::{copy_nonoverlapping, NonNull};
+
+use crate::mem::alloc::{AllocError, AllocRef, Global};
+
+
+/// Slices up to this length are sorted by insertion sort, which does not allocate
+pub(crate) const INSERTION_LEN: usize = 20;
+
+
+/// Sorts `v` with stable merge sort
+/// - allocates scratch space for `v.len() / 2` elements from `ALLOCATOR`
+/// - returns `Err` if allocation fails, `v` is not changed then
+pub(crate) fn merge_sort<T, F>(v: &mut [T], is_less: &mut F) -> Result<(), AllocError>
+where F: FnMut(&T, &T) -> bool {
+    if size_of::<T>() == 0 || v.len() < 2 {
+        return Ok(());
+    }
+    if v.len() <= INSERTION_LEN {
+        insertion_sort(v, is_less);
+        return Ok(());
+    }
+
+    let scratch = Scratch::<T>::new(v.len() / 2)?;
+    sort_with(v, scratch.ptr.as_ptr(), is_less);
+    Ok(())
+}
+
+/// Sorts `v` with stable insertion sort
+/// - the elements are only rotated after the comparisons, so a panicking comparator leaves `v` as it was
+pub(crate) fn insertion_sort<T, F>(v: &mut [T], is_less: &mut F)
+where F: FnMut(&T, &T) -> bool {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && is_less(&v[i], &v[j - 1]) {
+            j -= 1;
+        }
+        v[j..=i].rotate_right(1);
+    }
+}
+
+/// Moves the first element of every run of consecutive elements in the same bucket to the front, in their order
+/// - `same_bucket(a, b)` is called with the element `a` and the last kept element `b`
+/// - returns the number of kept elements, the duplicates are left at the back in unspecified order
+pub(crate) fn partition_dedup<T, F>(v: &mut [T], mut same_bucket: F) -> usize
+where F: FnMut(&mut T, &mut T) -> bool {
+    if v.len() < 2 {
+        return v.len();
+    }
+
+    let mut kept = 1;
+    for i in 1..v.len() {
+        let (front, back) = v.split_at_mut(i);
+        if !same_bucket(&mut back[0], &mut front[kept - 1]) {
+            v.swap(kept, i);
+            kept += 1;
+        }
+    }
+    kept
+}
+
+/// Returns index after the last element of sorted `v` that is not greater than `x`
+/// - the index where `x` is inserted to keep `v` sorted and the insertion stable
+pub(crate) fn upper_bound<T, F>(v: &[T], x: &T, mut compare: F) -> usize
+where F: FnMut(&T, &T) -> Ordering {
+    match v.binary_search_by(|e| match compare(e, x) {
+        Ordering::Greater => Ordering::Greater,
+        _ => Ordering::Less,
+    }) {
+        Ok(i) | Err(i) => i,
+    }
+}
+
+
+/// Recursive part of the merge sort
+/// - `buf` has space for at least `v.len() / 2` elements
+fn sort_with<T, F>(v: &mut [T], buf: *mut T, is_less: &mut F)
+where F: FnMut(&T, &T) -> bool {
+    if v.len() <= INSERTION_LEN {
+        insertion_sort(v, is_less);
+        return;
+    }
+
+    let mid = v.len() / 2;
+    sort_with(&mut v[..mid], buf, is_less);
+    sort_with(&mut v[mid..], buf, is_less);
+
+    //  the halves are already in order
+    if !is_less(&v[mid], &v[mid - 1]) {
+        return;
+    }
+    unsafe { merge(v, mid, buf, is_less) };
+}
+
+/// Merges sorted `v[..mid]` and `v[mid..]`
+/// - safety: `buf` must have space for `mid` elements and must not overlap `v`
+unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+where F: FnMut(&T, &T) -> bool {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+
+    unsafe {
+        copy_nonoverlapping(v, buf, mid);
+
+        //  the left half lives in `buf`, the hole in `v` is always as long as what is left of it
+        let mut hole = Hole { start: buf, end: buf.add(mid), dest: v };
+        let mut right = v.add(mid);
+        let right_end = v.add(len);
+
+        while hole.start < hole.end && right < right_end {
+            //  the left element goes first if the elements are equal, this keeps the sort stable
+            if is_less(&*right, &*hole.start) {
+                copy_nonoverlapping(right, hole.dest, 1);
+                right = right.add(1);
+            } else {
+                copy_nonoverlapping(hole.start, hole.dest, 1);
+                hole.start = hole.start.add(1);
+            }
+            hole.dest = hole.dest.add(1);
+        }
+        //  the rest of the right half is already in place, the drop of `hole` moves the rest of the left one
+    }
+}
+
+/// Elements of the left half that were not merged yet
+/// - copied back into `v` on drop, even if the comparator panics
+struct Hole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+/// Scratch space of the merge sort allocated from `ALLOCATOR`
+/// - never owns the elements, it is only deallocated on drop
+pub(crate) struct Scratch<T> {
+    ptr: NonNull<T>,
+    layout: Layout,
+}
+
+impl<T> Scratch<T> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub(crate) fn new(len: usize) -> Result<Self, AllocError> {
+        let layout = AllocError::array_layout::<T>(len)?;
+        let ptr = Global.allocate(layout)?.cast();
+
+        Ok(Self { ptr, layout })
+    }
+
+    /// Returns pointer to the uninitialized space
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+}
+
+impl<T> Drop for Scratch<T> {
+    fn drop(&mut self) {
+        unsafe { Global.deallocate(self.ptr.cast(), self.layout) };
+    }
+}
+
@@ -0,0 +1,449 @@
+//	mem/stack.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Kernel stacks with guard pages, canaries and high-water marks
+//! - [`Stacks`] hands out stacks from a virtual area, every stack has an unmapped guard page below it
+//!   - an overflow hits the guard page and page faults instead of corrupting the memory below
+//!   - the frames are taken from the frame source of the `Mapper` one by one, they do not have to be contiguous
+//! - a canary word ([`STACK_CANARY`]) is placed at the limit (the lowest word) of every stack
+//!   - check it on context switch or from panic paths with [`KernelStack::check`] or [`check_all`]
+//! - the stacks are filled with [`STACK_PATTERN`], so the deepest use can be measured ([`KernelStack::high_water_mark`])
+//!   - `Stacks` can be printed as a table of all stacks with their use
+//!
+//! # Example
+//! ```
+//! stack::init(Region::new(0xffff_c000_0000_0000, 0, GB))?;
+//!
+//! let stack = stack::alloc(&mut mapper, DEFAULT_STACK_SIZE)?;
+//! let thread = Thread::new(entry, stack.top());
+//!
+//! //  on context switch
+//! if stack.check().is_err() {
+//!     panic!("stack overflow of thread {}", thread.id());
+//! }
+//!
+//! //  in the page fault handler
+//! if let Some(stack) = stack::guard_of(cr2) {
+//!     panic!("stack overflow, stack top {:#x}", stack.top());
+//! }
+//! ```
+
+
+use core::fmt;
+use core::ptr;
+
+use crate::mem::{Region, KB, PAGE_ALIGN, PAGE_SIZE};
+use crate::mem::paging::{FrameSource, Mapper, PageFlags, PagingError};
+
+
+/// Size of the guard page below every stack
+pub const GUARD_SIZE: usize = PAGE_SIZE;
+
+/// Default size of a kernel stack
+pub const DEFAULT_STACK_SIZE: usize = 64 * KB;
+
+/// Maximal number of stacks of one [`Stacks`] (by default)
+pub const MAX_STACKS: usize = 64;
+
+/// Word placed at the limit of every stack
+pub const STACK_CANARY: u64 = 0xc0de_57ac_c0de_57ac;
+
+/// Word the unused part of every stack is filled with
+pub const STACK_PATTERN: u64 = 0x5a5a_5a5a_5a5a_5a5a;
+
+/// Flags the stacks are mapped with
+const STACK_FLAGS: PageFlags = PageFlags::from_bits(PageFlags::WRITABLE.bits() | PageFlags::NO_EXECUTE.bits());
+
+
+/// Error returned by the stack functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackError {
+    /// The size is zero
+    Invalid,
+    /// The area has no room for the stack and its guard page
+    NoSpace,
+    /// There are too many stacks
+    Full,
+    /// The frame source has no frame for the stack
+    NoFrames,
+    /// The stack could not be mapped or unmapped
+    Paging(PagingError),
+    /// The stack was not allocated by this `Stacks`
+    NotFound,
+    /// The canary of the stack was overwritten
+    Overflow,
+    /// The global stacks are not initialized
+    Uninitialized,
+    /// The global stacks are already initialized
+    AlreadyInitialized,
+}
+
+impl StackError {
+    /// Returns short description of the error
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Invalid => "stack size is zero",
+            Self::NoSpace => "no room for the stack",
+            Self::Full => "too many stacks",
+            Self::NoFrames => "no frame for the stack",
+            Self::Paging(e) => e.as_str(),
+            Self::NotFound => "unknown stack",
+            Self::Overflow => "stack overflow",
+            Self::Uninitialized => "stacks are not initialized",
+            Self::AlreadyInitialized => "stacks are already initialized",
+        }
+    }
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<PagingError> for StackError {
+    fn from(e: PagingError) -> Self {
+        Self::Paging(e)
+    }
+}
+
+
+/// Kernel stack
+/// - the stack grows down from [`top`](KernelStack::top) to [`limit`](KernelStack::limit),
+///   the guard page lies right below the limit
+/// - only describes the stack, the memory is given back by [`Stacks::free`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KernelStack {
+    region: Region,
+}
+
+impl KernelStack {
+
+    /// Prepares stack in `region`
+    /// - fills the stack with `STACK_PATTERN` and places `STACK_CANARY` at the limit
+    /// - the page below `region` should be unmapped (see [`Stacks`])
+    ///
+    /// # Safety
+    /// - `region` must be mapped, writable and not used by anything else (e.g. it is not the current stack)
+    pub unsafe fn new(region: Region) -> Self {
+        let stack = Self { region };
+
+        let words = stack.limit() as *mut u64;
+        for i in 0..stack.size() / size_of::<u64>() {
+            unsafe { ptr::write_volatile(words.add(i), STACK_PATTERN) };
+        }
+        unsafe { ptr::write_volatile(words, STACK_CANARY) };
+
+        stack
+    }
+
+    /// Constructs the stack of region prepared by [`KernelStack::new`] before
+    #[inline]
+    const fn prepared(region: Region) -> Self {
+        Self { region }
+    }
+
+    /// Returns the usable region of the stack (without the guard page)
+    #[inline]
+    pub const fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the initial stack pointer (the end of the stack)
+    #[inline]
+    pub fn top(&self) -> usize {
+        self.region.virt().addr() + self.region.size()
+    }
+
+    /// Returns the lowest address of the stack, the canary is stored here
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.region.virt().addr()
+    }
+
+    /// Returns size of the stack (without the guard page)
+    #[inline]
+    pub const fn size(&self) -> usize {
+        self.region.size()
+    }
+
+    /// Returns the guard page below the stack
+    #[inline]
+    pub fn guard(&self) -> Region {
+        Region::new(self.limit() - GUARD_SIZE, 0, GUARD_SIZE)
+    }
+
+    /// Checks if the address lies in the stack
+    #[inline]
+    pub fn contains(&self, addr: usize) -> bool {
+        self.limit() <= addr && addr < self.top()
+    }
+
+    /// Checks if the address lies in the guard page (an overflow of the stack)
+    #[inline]
+    pub fn in_guard(&self, addr: usize) -> bool {
+        self.limit() - GUARD_SIZE <= addr && addr < self.limit()
+    }
+
+    /// Checks if the canary is intact
+    #[inline]
+    pub fn canary_ok(&self) -> bool {
+        unsafe { ptr::read_volatile(self.limit() as *const u64) == STACK_CANARY }
+    }
+
+    /// Checks the canary
+    /// - returns `Err(Overflow)` if the canary was overwritten
+    #[inline]
+    pub fn check(&self) -> Result<(), StackError> {
+        match self.canary_ok() {
+            true => Ok(()),
+            false => Err(StackError::Overflow),
+        }
+    }
+
+    /// Returns the most bytes the stack has ever used
+    /// - the lowest word that differs from `STACK_PATTERN` is searched for, so the result is a lower bound
+    ///   (the stack may hold the pattern by chance)
+    /// - returns the whole size if the canary was overwritten
+    pub fn high_water_mark(&self) -> usize {
+        if !self.canary_ok() {
+            return self.size();
+        }
+
+        let words = self.limit() as *const u64;
+        let count = self.size() / size_of::<u64>();
+        let unused = (1..count)
+            .find(|&i| unsafe { ptr::read_volatile(words.add(i)) } != STACK_PATTERN)
+            .unwrap_or(count);
+
+        (count - unused) * size_of::<u64>()
+    }
+
+}
+
+impl fmt::Display for KernelStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#018x}  {:>6} KiB  {:>6} KiB  {}", self.top(), self.size() / KB,
+            self.high_water_mark().div_ceil(KB), if self.canary_ok() { "ok" } else { "OVERFLOW" })
+    }
+}
+
+
+/// Kernel stacks in a virtual area
+/// - keeps at most `N` stacks, sorted by address
+/// - the stacks are mapped writable and non-executable by the `Mapper` passed to [`Stacks::alloc`],
+///   it must be the active one (the stack is filled right away)
+pub struct Stacks<const N: usize = MAX_STACKS> {
+    area: Region,
+    stacks: [Region; N],
+    len: usize,
+}
+
+impl<const N: usize> Stacks<N> {
+
+    /// Constructs empty stacks in the virtual `area`
+    pub const fn new(area: Region) -> Self {
+        Self { area, stacks: [Region::empty(); N], len: 0 }
+    }
+
+    /// Returns the virtual area of the stacks
+    #[inline]
+    pub const fn area(&self) -> Region {
+        self.area
+    }
+
+    /// Returns number of the stacks
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if there are no stacks
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Allocates stack of `size` bytes (rounded up to whole pages) with the guard page below it
+    /// - returns `Err(Invalid)` if `size` is zero
+    /// - returns `Err(Full)` if there are `N` stacks, `Err(NoSpace)` if the area has no room for the stack
+    /// - returns `Err(NoFrames)` or `Err(Paging)` if the stack could not be mapped, nothing is mapped then
+    pub fn alloc<S: FrameSource>(&mut self, mapper: &mut Mapper<S>, size: usize) -> Result<KernelStack, StackError> {
+        if size == 0 {
+            return Err(StackError::Invalid);
+        }
+        if self.len == N {
+            return Err(StackError::Full);
+        }
+
+        let size = size.next_multiple_of(PAGE_SIZE);
+        let (index, guard) = self.find_gap(GUARD_SIZE + size).ok_or(StackError::NoSpace)?;
+        let region = Region::new(guard + GUARD_SIZE, 0, size);
+
+        for offset in (0..size).step_by(PAGE_SIZE) {
+            let page = region.virt().addr() + offset;
+            let mapped = match mapper.source().alloc_table() {
+                Some(phys) => mapper.map(Region::<PAGE_ALIGN>::new(page, phys, PAGE_SIZE), STACK_FLAGS)
+                    .map_err(|e| { mapper.source().free_table(phys); StackError::from(e) }),
+                None => Err(StackError::NoFrames),
+            };
+
+            if let Err(e) = mapped {
+                Self::unmap(mapper, Region::new(region.virt().addr(), 0, offset));
+                return Err(e);
+            }
+        }
+
+        self.stacks.copy_within(index..self.len, index + 1);
+        self.stacks[index] = region;
+        self.len += 1;
+
+        Ok(unsafe { KernelStack::new(region) })
+    }
+
+    /// Unmaps the stack and gives its frames back to the frame source of `mapper`
+    /// - returns `Err(NotFound)` if the stack was not allocated by these stacks
+    ///
+    /// # Safety
+    /// - the stack must not be used anymore
+    pub unsafe fn free<S: FrameSource>(&mut self, mapper: &mut Mapper<S>, stack: KernelStack) -> Result<(), StackError> {
+        let index = self.stacks[..self.len].iter().position(|region| *region == stack.region())
+            .ok_or(StackError::NotFound)?;
+
+        Self::unmap(mapper, stack.region());
+        self.stacks.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        Ok(())
+    }
+
+    /// Returns index where the new stack is inserted and start of its guard page
+    /// - the gap must have `size` bytes
+    fn find_gap(&self, size: usize) -> Option<(usize, usize)> {
+        let mut start = self.area.virt().addr();
+        let end = start.checked_add(self.area.size())?;
+
+        for (i, stack) in self.iter().enumerate() {
+            if start.checked_add(size)? <= stack.guard().virt().addr() {
+                return Some((i, start));
+            }
+            start = stack.top();
+        }
+
+        (start.checked_add(size)? <= end).then_some((self.len, start))
+    }
+
+    /// Unmaps the pages of `region` and gives their frames back
+    fn unmap<S: FrameSource>(mapper: &mut Mapper<S>, region: Region) {
+        for page in (region.virt().addr()..region.virt().addr() + region.size()).step_by(PAGE_SIZE) {
+            if let Some(phys) = mapper.translate_addr(page) {
+                let _ = mapper.unmap(Region::<PAGE_ALIGN>::new(page, 0, PAGE_SIZE));
+                mapper.source().free_table(phys);
+            }
+        }
+    }
+
+    /// Returns iterator over the stacks
+    pub fn iter(&self) -> impl Iterator<Item = KernelStack> + '_ {
+        self.stacks[..self.len].iter().map(|&region| KernelStack::prepared(region))
+    }
+
+    /// Returns the stack that contains the address
+    pub fn find(&self, addr: usize) -> Option<KernelStack> {
+        self.iter().find(|stack| stack.contains(addr))
+    }
+
+    /// Returns the stack whose guard page contains the address
+    /// - use it in the page fault handler to recognize stack overflows
+    pub fn guard_of(&self, addr: usize) -> Option<KernelStack> {
+        self.iter().find(|stack| stack.in_guard(addr))
+    }
+
+    /// Checks canaries of all stacks
+    /// - returns the first stack whose canary was overwritten
+    pub fn check_all(&self) -> Result<(), KernelStack> {
+        match self.iter().find(|stack| !stack.canary_ok()) {
+            Some(stack) => Err(stack),
+            None => Ok(()),
+        }
+    }
+
+}
+
+impl<const N: usize> fmt::Display for Stacks<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<18}  {:>10}  {:>10}  canary", "top", "size", "used")?;
+        for stack in self.iter() {
+            writeln!(f, "{}", stack)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(feature = "spin")]
+pub use global::*;
+
+/// The global stacks
+#[cfg(feature = "spin")]
+mod global {
+    use super::*;
+    use crate::sync::IrqMutex;
+
+    static STACKS: IrqMutex<Option<Stacks>> = IrqMutex::new(None);
+
+    /// Initializes the global stacks in the virtual `area`
+    /// - the area must not be used for anything else
+    /// - returns `Err(AlreadyInitialized)` if the stacks are already initialized
+    pub fn init(area: Region) -> Result<(), StackError> {
+        let mut stacks = STACKS.lock();
+
+        if stacks.is_some() {
+            return Err(StackError::AlreadyInitialized);
+        }
+
+        *stacks = Some(Stacks::new(area));
+        Ok(())
+    }
+
+    /// Runs `f` with the global stacks
+    /// - returns `Err(Uninitialized)` if the stacks are not initialized
+    /// - interrupts are disabled while `f` runs
+    pub fn with<R>(f: impl FnOnce(&mut Stacks) -> R) -> Result<R, StackError> {
+        STACKS.lock().as_mut().map(f).ok_or(StackError::Uninitialized)
+    }
+
+    /// Allocates global stack
+    /// - see [`Stacks::alloc`]
+    #[inline]
+    pub fn alloc<S: FrameSource>(mapper: &mut Mapper<S>, size: usize) -> Result<KernelStack, StackError> {
+        with(|stacks| stacks.alloc(mapper, size))?
+    }
+
+    /// Frees global stack
+    /// - see [`Stacks::free`]
+    ///
+    /// # Safety
+    /// - the stack must not be used anymore
+    #[inline]
+    pub unsafe fn free<S: FrameSource>(mapper: &mut Mapper<S>, stack: KernelStack) -> Result<(), StackError> {
+        with(|stacks| unsafe { stacks.free(mapper, stack) })?
+    }
+
+    /// Returns the global stack whose guard page contains the address
+    /// - returns `None` if the stacks are locked, so it can be called from the page fault handler
+    pub fn guard_of(addr: usize) -> Option<KernelStack> {
+        STACKS.try_lock()?.as_ref()?.guard_of(addr)
+    }
+
+    /// Checks canaries of all global stacks
+    /// - returns the first stack whose canary was overwritten
+    /// - returns `Ok` if the stacks are locked or not initialized, so it can be called from panic paths
+    pub fn check_all() -> Result<(), KernelStack> {
+        match STACKS.try_lock() {
+            Some(stacks) => stacks.as_ref().map_or(Ok(()), Stacks::check_all),
+            None => Ok(()),
+        }
+    }
+}
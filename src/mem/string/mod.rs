@@ -12,6 +12,7 @@ pub use pattern::Pattern;
 pub use searcher::{Searcher, ReverseSearcher, SearchStep};
 
 use crate::mem::DynamicBuffer;
+use crate::mem::alloc::{AllocError, AllocRef, Global, TryReserveError};
 use crate::Cow;
 
 #[cfg(all(feature="allocator", feature="spin", feature="spin", feature="string"))]
@@ -39,32 +40,36 @@ const ALIGN: usize = 4;
 /// # Memory layout
 /// - The `ministd::String` has the same memory layout as `ministd::DynamicBuffer`
 /// 
-/// # Generic parameter
+/// # Generic parameters
 /// `STEP` tells the structure how many characters has to be preallocated
 /// - Has to be either `0` (for geometrical growth) or multiple of 4
+/// 
+/// `A` is the allocator (see [`AllocRef`])
+/// - uses the global `ALLOCATOR` by default
 #[repr(transparent)]
-pub struct String<const STEP: usize = 0> {
-    data: DynamicBuffer<u8, STEP, ALIGN>,
+pub struct String<const STEP: usize = 0, A: AllocRef = Global> {
+    data: DynamicBuffer<u8, STEP, ALIGN, A>,
 }
 
-impl<const STEP: usize> String<STEP> {
+impl<const STEP: usize, A: AllocRef> String<STEP, A> {
 
     const VALID: bool = STEP == 0 || STEP.is_multiple_of(4);
 
     /// Describes memory layout for some capacity
     pub const fn layout_for(capacity: usize) -> Layout {
-        DynamicBuffer::<u8, STEP, ALIGN>::layout_for(capacity)
+        DynamicBuffer::<u8, STEP, ALIGN, A>::layout_for(capacity)
     }
 
     /// Describes memory layout for some capacity without aligning to STEP
     pub const fn layout_for_exact(capacity: usize) -> Layout {
-        DynamicBuffer::<u8, STEP, ALIGN>::layout_for_exact(capacity)
+        DynamicBuffer::<u8, STEP, ALIGN, A>::layout_for_exact(capacity)
     }
 
     /// Expands the `capacity` of the vector by `STEP`
     /// - this function always reallocates memory
     /// - **panics** if allocation fails
     #[inline(always)]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn expand(&mut self) {
         self.data.expand();
     }
@@ -73,45 +78,81 @@ impl<const STEP: usize> String<STEP> {
     /// - this function always reallocates memory
     /// - returns `Err` if allocation fails
     #[inline(always)]
-    pub fn try_expand(&mut self) -> Result<(), ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_expand(&mut self) -> Result<(), TryReserveError> {
         self.data.try_expand()
     }
 
 
-    /// Creates a new empty `String`
+    /// Creates a new empty `String` in `alloc`
     /// - no data is allocated
-    pub const fn new() -> Self {
+    pub const fn new_in(alloc: A) -> Self {
         if Self::VALID {
-            Self { data: DynamicBuffer::empty() }
+            Self { data: DynamicBuffer::empty_in(alloc) }
         } else {
             panic!("STEP has to be either `0` or multiple of 4");
         }
     }
 
 
-    /// Creates new `String` with at least the specified capacity
+    /// Creates new `String` with at least the specified capacity allocated in `alloc`
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
         if Self::VALID {
-            Self { data: DynamicBuffer::with_capacity(capacity) }
+            Self { data: DynamicBuffer::with_capacity_in(capacity, alloc) }
         } else {
             panic!("STEP has to be either `0` or multiple of 4");
         }
     }
 
-    /// Tries to create new `String` with at least the specified capacity
+    /// Tries to create new `String` with at least the specified capacity allocated in `alloc`
     /// - returns `Err` if allocation fails
     #[inline]
-    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
         if Self::VALID {
             Ok(Self {
-                data: DynamicBuffer::try_with_capacity(capacity)?
+                data: DynamicBuffer::try_with_capacity_in(capacity, alloc)?
             })
         } else {
             panic!("STEP has to be either `0` or multiple of 4");
         }
     }
 
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+}
+
+
+impl<const STEP: usize> String<STEP> {
+
+    /// Creates a new empty `String`
+    /// - no data is allocated
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+
+    /// Creates new `String` with at least the specified capacity
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    /// Tries to create new `String` with at least the specified capacity
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
     /// Converts a vector of bytes to a String
     /// - returns `Err(None)` on allocation failure
     #[cfg(all(feature="allocator", feature="vector"))]
@@ -160,6 +201,11 @@ impl<const STEP: usize> String<STEP> {
 
     }
 
+}
+
+
+impl<const STEP: usize, A: AllocRef> String<STEP, A> {
+
 
 
 
@@ -191,7 +237,7 @@ impl<const STEP: usize> String<STEP> {
 
     /// Tries to append a given string slice onto the end of this `String`
     /// - returns `Err` if allocation fails
-    pub fn try_push_str(&mut self, string: &str) -> Result<(), ()> {
+    pub fn try_push_str(&mut self, string: &str) -> Result<(), TryReserveError> {
 
         self.try_reserve(string.len())?;
 
@@ -257,7 +303,7 @@ impl<const STEP: usize> String<STEP> {
 
     /// Tries to push the given character to the end of the `String`
     /// - returns `Err` if allocation fails
-    pub fn try_push(&mut self, c: u8) -> Result<(), ()> {
+    pub fn try_push(&mut self, c: u8) -> Result<(), TryReserveError> {
         if self.len() == self.capacity() {
             self.try_expand()?;
         }
@@ -304,6 +350,7 @@ impl<const STEP: usize> String<STEP> {
     /// - `capacity` will be greater than or equal to `self.len() + add`
     ///   - `capacity` is aligned to `STEP`
     #[inline(always)]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn reserve(&mut self, add: usize) {
         self.data.resize(self.len() + add);
     }
@@ -312,8 +359,10 @@ impl<const STEP: usize> String<STEP> {
     /// - returns `Err` if allocation fails
     /// - capacity will be greater than or equal to `self.len() + add.len()`
     #[inline(always)]
-    pub fn try_reserve(&mut self, add: usize) -> Result<(), ()> {
-        self.data.try_resize(self.len() + add)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve(&mut self, add: usize) -> Result<(), TryReserveError> {
+        let min = self.len().checked_add(add).ok_or(TryReserveError::capacity_overflow())?;
+        self.data.try_resize(min)
     }
 
     /// Reserves capacity for at least `add` more characters
@@ -321,6 +370,7 @@ impl<const STEP: usize> String<STEP> {
     /// - `capcity` will be greater than or equal to `self.len() + add`
     ///   - `capacity` is not aligned
     #[inline(always)]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn reserve_exact(&mut self, add: usize) {
         self.data.resize_exact(self.len() + add);
     }
@@ -329,8 +379,10 @@ impl<const STEP: usize> String<STEP> {
     /// - **panics** if allocation fails
     /// - `capacity` will be greater than or equal to `self.len() + add`
     ///   - `capacity` is not aligned
-    pub fn try_reserve_exact(&mut self, add: usize) -> Result<(), ()> {
-        self.data.try_resize_exact(self.len() + add)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve_exact(&mut self, add: usize) -> Result<(), TryReserveError> {
+        let min = self.len().checked_add(add).ok_or(TryReserveError::capacity_overflow())?;
+        self.data.try_resize_exact(min)
     }
 
     /// Shortens this `String` to the specified length.
@@ -351,12 +403,14 @@ impl<const STEP: usize> String<STEP> {
 
     /// Shrinks the `capacity` of this `String` to match its length
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn shrink_to_fit(&mut self) {
         self.data.resize(self.len());
     }
 
     /// Shrinks the `capacity` of this `String` to the specified value
     /// The `capacity` will remain at least as large as both the length and the supplied value
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn shrink_to(&mut self, len: usize) {
         self.data.resize(core::cmp::max(self.len(), len));
     }
@@ -449,7 +503,7 @@ impl<const STEP: usize> String<STEP> {
     /// - returns `Err` if allocation fails
     /// - this is `O(n)` operation
     /// - pushes the character if `index >= self.len()`
-    pub fn try_insert(&mut self, index: usize, c: u8) -> Result<(), ()> {
+    pub fn try_insert(&mut self, index: usize, c: u8) -> Result<(), TryReserveError> {
 
         let len = self.len();
 
@@ -501,15 +555,15 @@ impl<const STEP: usize> String<STEP> {
 
     /// Returns a mutable reference to the contents of the string.
     /// - **warning** this function is not tested enough yet and may result in undefined behaviour
-    pub unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8, ALIGN> {
-        unsafe { ((self as *mut Self) as *mut Vec<u8, ALIGN>).as_mut().unwrap_unchecked() }
+    pub unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8, ALIGN, 0, A> {
+        unsafe { ((self as *mut Self) as *mut Vec<u8, ALIGN, 0, A>).as_mut().unwrap_unchecked() }
     }
 
     /// Tries to insert string at the `index` position
     /// - returns `Err` if allocation fails
     /// - this is `O(n)` operation
     /// - pushes the character if `index >= self.len()`
-    pub fn try_insert_str(&mut self, index: usize, string: &str) -> Result<(), ()> {
+    pub fn try_insert_str(&mut self, index: usize, string: &str) -> Result<(), TryReserveError> {
 
         let len = self.len();
 
@@ -540,7 +594,8 @@ impl<const STEP: usize> String<STEP> {
     /// Note that the `capacity` of `self` does not change
     /// 
     /// **panics** if `at` is out of bounds or allocation fails
-    pub fn split_off(&mut self, at: usize) -> String<STEP> {
+    pub fn split_off(&mut self, at: usize) -> String<STEP, A>
+    where A: Clone {
         if at >= self.len() {
             #[cfg(all(feature="allocator", feature="spin", feature="string"))]
             panic_fmt!("index {at} is out of bounds 0..{}", self.len());
@@ -551,7 +606,7 @@ impl<const STEP: usize> String<STEP> {
 
         let len = self.len() - at;
 
-        let mut new: String<STEP> = String::with_capacity(len);
+        let mut new = String::with_capacity_in(len, self.allocator().clone());
 
         unsafe {
             new.set_len(len);
@@ -574,7 +629,8 @@ impl<const STEP: usize> String<STEP> {
     /// 
     /// **panics** if `at` is out of bounds
     /// - returns `Err` if allocation fails
-    pub fn try_split_off(&mut self, at: usize) -> Result<String<STEP>, ()> {
+    pub fn try_split_off(&mut self, at: usize) -> Result<String<STEP, A>, AllocError>
+    where A: Clone {
         if at >= self.len() {
             #[cfg(all(feature="allocator", feature="spin", feature="string"))]
             panic_fmt!("index {at} is out of bounds 0..{}", self.len());
@@ -584,7 +640,7 @@ impl<const STEP: usize> String<STEP> {
 
         let len = self.len() - at;
 
-        let mut new: String<STEP> = String::try_with_capacity(len)?;
+        let mut new = String::try_with_capacity_in(len, self.allocator().clone())?;
 
         unsafe {
             new.set_len(len);
@@ -697,7 +753,7 @@ impl<const STEP: usize> String<STEP> {
 
 
 
-impl<const STEP: usize> String<STEP> {
+impl<const STEP: usize, A: AllocRef> String<STEP, A> {
 
     /// Returns the number of ASCII characters (bytes) of the string
     pub const fn len(&self) -> usize { self.data.size as usize }
@@ -856,6 +912,12 @@ impl<const STEP: usize> String<STEP> {
         }
     }
 
+}
+
+
+//  raw parts are always allocated with the global allocator
+impl<const STEP: usize> String<STEP> {
+
     /// Creates a new String from a pointer, a length and a capacity
     /// 
     /// Safety:
@@ -874,6 +936,11 @@ impl<const STEP: usize> String<STEP> {
         unsafe { Vec::from_parts(data, size, capacity) }
     }
 
+}
+
+
+impl<const STEP: usize, A: AllocRef> String<STEP, A> {
+
     /// Checks `RangeBounds` for this vector
     #[inline]
     fn handle_bounds<R>(&self, range: &R) -> (usize, usize)
@@ -904,14 +971,14 @@ impl<const STEP: usize> String<STEP> {
 }
 
 
-impl<const STEP: usize> Display for String<STEP> {
+impl<const STEP: usize, A: AllocRef> Display for String<STEP, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
-impl<const STEP: usize> Debug for String<STEP> {
+impl<const STEP: usize, A: AllocRef> Debug for String<STEP, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "size: {}, capacity: {}, conatent: {self}", self.len(), self.capacity())
@@ -919,7 +986,7 @@ impl<const STEP: usize> Debug for String<STEP> {
 }
 
 
-impl<const STEP: usize> PartialEq<str> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> PartialEq<str> for String<STEP, A> {
 
     #[inline]
     fn eq(&self, other: &str) -> bool {
@@ -940,7 +1007,7 @@ impl<const STEP: usize> PartialEq<str> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> PartialEq<&str> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> PartialEq<&str> for String<STEP, A> {
 
     #[inline]
     fn eq(&self, other: &&str) -> bool {
@@ -961,7 +1028,7 @@ impl<const STEP: usize> PartialEq<&str> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> PartialOrd<&str> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> PartialOrd<&str> for String<STEP, A> {
     fn ge(&self, other: &&str) -> bool {
         let s = if let Some(s) = self.as_str_checked() {
             s
@@ -1019,7 +1086,7 @@ impl<const STEP: usize> PartialOrd<&str> for String<STEP> {
 }
 
 
-impl<const STEP: usize> PartialEq<String> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> PartialEq<String> for String<STEP, A> {
 
     fn eq(&self, other: &String) -> bool {
         let s1 = if let Some(s) = self.as_str_checked() {
@@ -1062,7 +1129,8 @@ impl<const STEP: usize> PartialEq<String> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> Clone for String<STEP> {
+impl<const STEP: usize, A: AllocRef + Clone> Clone for String<STEP, A> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn clone(&self) -> Self {
 
         let data = self.data.clone();
@@ -1077,6 +1145,7 @@ impl<const STEP: usize> Clone for String<STEP> {
 }
 
 impl<const STEP: usize> From<&str> for String<STEP> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn from(value: &str) -> Self {
         
         let mut data = DynamicBuffer::with_capacity(value.len());
@@ -1093,6 +1162,7 @@ impl<const STEP: usize> From<&str> for String<STEP> {
 }
 
 impl<const STEP: usize> From<&[u8]> for String<STEP> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn from(value: &[u8]) -> Self {
         let mut data = DynamicBuffer::with_capacity(value.len());
         unsafe {
@@ -1105,7 +1175,7 @@ impl<const STEP: usize> From<&[u8]> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> Deref for String<STEP> {
+impl<const STEP: usize, A: AllocRef> Deref for String<STEP, A> {
     type Target = str;
     fn deref(&self) -> &Self::Target {
         if let Some(s) = self.as_str_checked() {
@@ -1116,7 +1186,7 @@ impl<const STEP: usize> Deref for String<STEP> {
     }
 }
 
-impl<const STEP: usize> DerefMut for String<STEP> {
+impl<const STEP: usize, A: AllocRef> DerefMut for String<STEP, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         if let Some(s) = self.as_mut_str_checked() {
             s
@@ -1126,7 +1196,7 @@ impl<const STEP: usize> DerefMut for String<STEP> {
     }
 }
 
-impl<const STEP: usize> Index<usize> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> Index<usize> for String<STEP, A> {
     type Output = u8;
     fn index(&self, index: usize) -> &Self::Output {
         if self.len() > 0 && index < self.len(){
@@ -1143,7 +1213,7 @@ impl<const STEP: usize> Index<usize> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> IndexMut<usize> for String<STEP> {
+impl<const STEP: usize, A: AllocRef> IndexMut<usize> for String<STEP, A> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         if self.len() > 0 && index < self.len(){
             unsafe {
@@ -1159,15 +1229,15 @@ impl<const STEP: usize> IndexMut<usize> for String<STEP> {
     }
 }
 
-impl<const STEP: usize> Default for String<STEP> {
+impl<const STEP: usize, A: AllocRef + Default> Default for String<STEP, A> {
     #[inline(always)]
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
 
-impl<const STEP: usize> Write for String<STEP> {
+impl<const STEP: usize, A: AllocRef> Write for String<STEP, A> {
     fn write_char(&mut self, c: char) -> core::fmt::Result {
         self.try_push(c as u8).map_err(|_| core::fmt::Error)
     }
@@ -1177,13 +1247,13 @@ impl<const STEP: usize> Write for String<STEP> {
 }
 
 
-impl<'l, const STEP: usize> PartialEq<String<STEP>> for &'l str {
+impl<'l, const STEP: usize, A: AllocRef> PartialEq<String<STEP, A>> for &'l str {
     #[inline(always)]
-    fn eq(&self, other: &String<STEP>) -> bool {
+    fn eq(&self, other: &String<STEP, A>) -> bool {
         other == self
     }
     #[inline(always)]
-    fn ne(&self, other: &String<STEP>) -> bool {
+    fn ne(&self, other: &String<STEP, A>) -> bool {
         other != self
     }
 }
@@ -0,0 +1,149 @@
+//	mem/vec/drain.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Draining iterator of `Vec`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::ptr::{self, drop_in_place, slice_from_raw_parts_mut};
+use core::slice::from_raw_parts;
+
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::Vec;
+
+
+/// A draining iterator of a vector
+/// - created by [`Vec::drain`]
+/// - the drained range is removed even if it is not iterated whole, the elements left are dropped
+/// - the elements after the range (the tail) are moved back when the iterator is dropped
+///   - if the iterator is leaked (e.g. `mem::forget`), the vector is left truncated at the start of the range
+pub struct Drain<'a, T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    //  `vec.len()` is the start of the range while draining
+    pub(super) vec: &'a mut Vec<T, STEP, ALIGN, A>,
+    //  elements that were not yielded yet
+    pub(super) next: usize,
+    pub(super) end: usize,
+    //  the tail is moved back to `vec.len()` on drop
+    pub(super) tail: usize,
+    pub(super) tail_len: usize,
+}
+
+impl<'a, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drain<'a, T, STEP, ALIGN, A> {
+
+    /// Constructs the iterator over `start..end`
+    /// - the range must be in bounds of the vector
+    pub(super) fn new(vec: &'a mut Vec<T, STEP, ALIGN, A>, start: usize, end: usize) -> Self {
+        let len = vec.len();
+        unsafe { vec.set_len(start) };
+
+        Self { vec, next: start, end, tail: end, tail_len: len - end }
+    }
+
+    /// Returns the elements that were not yielded yet as slice
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { from_raw_parts(self.vec.elements().add(self.next), self.end - self.next) }
+    }
+
+    /// Returns reference to the allocator of the vector
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    /// Keeps the elements that were not yielded yet in the vector
+    /// - the vector contains the elements before the range, the elements left and the tail
+    pub fn keep_rest(mut self) {
+        let len = self.vec.len();
+        let left = self.end - self.next;
+
+        unsafe {
+            let ptr = self.vec.elements();
+            if self.next != len {
+                ptr::copy(ptr.add(self.next), ptr.add(len), left);
+            }
+            self.vec.set_len(len + left);
+        }
+        self.next = self.end;
+    }
+
+    /// Moves the tail back after the elements of the vector
+    pub(super) fn move_tail(&mut self) {
+        let len = self.vec.len();
+
+        unsafe {
+            if self.tail != len {
+                let ptr = self.vec.elements();
+                ptr::copy(ptr.add(self.tail), ptr.add(len), self.tail_len);
+            }
+            self.vec.set_len(len + self.tail_len);
+        }
+        self.tail = len;
+        self.tail_len = 0;
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for Drain<'_, T, STEP, ALIGN, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.next += 1;
+        Some(unsafe { self.vec.elements().add(self.next - 1).read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DoubleEndedIterator for Drain<'_, T, STEP, ALIGN, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(unsafe { self.vec.elements().add(self.end).read() })
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> ExactSizeIterator for Drain<'_, T, STEP, ALIGN, A> {}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for Drain<'_, T, STEP, ALIGN, A> {}
+
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for Drain<'_, T, STEP, ALIGN, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for Drain<'_, T, STEP, ALIGN, A> {
+    fn drop(&mut self) {
+        //  moves the tail back even if some element panics while dropping
+        struct MoveTail<'r, 'a, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef>(&'r mut Drain<'a, T, STEP, ALIGN, A>);
+
+        impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for MoveTail<'_, '_, T, STEP, ALIGN, A> {
+            fn drop(&mut self) {
+                self.0.move_tail();
+            }
+        }
+
+        let guard = MoveTail(self);
+        let left = slice_from_raw_parts_mut(unsafe { guard.0.vec.elements().add(guard.0.next) }, guard.0.end - guard.0.next);
+        guard.0.next = guard.0.end;
+
+        unsafe { drop_in_place(left) };
+    }
+}
@@ -0,0 +1,107 @@
+//	mem/vec/extract_if.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Filtering and draining iterator of `Vec`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::ptr;
+
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::Vec;
+
+
+/// An iterator that removes the elements matching a predicate from a vector
+/// - created by [`Vec::extract_if`]
+/// - the elements that were not visited stay in the vector if the iterator is dropped early
+/// - the vector is left consistent if the predicate panics, the element it was called for is kept
+pub struct ExtractIf<'a, T: Sized, F, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global>
+where F: FnMut(&mut T) -> bool {
+    //  `vec.len()` is `0` while iterating
+    vec: &'a mut Vec<T, STEP, ALIGN, A>,
+    //  the next element to visit
+    idx: usize,
+    //  end of the visited range
+    end: usize,
+    //  number of the extracted elements, the kept elements are moved back by `del`
+    del: usize,
+    old_len: usize,
+    pred: F,
+}
+
+impl<'a, T: Sized, F, const STEP: usize, const ALIGN: usize, A: AllocRef> ExtractIf<'a, T, F, STEP, ALIGN, A>
+where F: FnMut(&mut T) -> bool {
+
+    /// Constructs the iterator over `start..end`
+    /// - the range must be in bounds of the vector
+    pub(super) fn new(vec: &'a mut Vec<T, STEP, ALIGN, A>, start: usize, end: usize, pred: F) -> Self {
+        let old_len = vec.len();
+        unsafe { vec.set_len(0) };
+
+        Self { vec, idx: start, end, del: 0, old_len, pred }
+    }
+
+    /// Returns reference to the allocator of the vector
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+}
+
+impl<T: Sized, F, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for ExtractIf<'_, T, F, STEP, ALIGN, A>
+where F: FnMut(&mut T) -> bool {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.idx < self.end {
+            let ptr = self.vec.elements();
+            let current = unsafe { &mut *ptr.add(self.idx) };
+
+            let extract = (self.pred)(current);
+            self.idx += 1;
+
+            if extract {
+                self.del += 1;
+                return Some(unsafe { ptr.add(self.idx - 1).read() });
+            } else if self.del > 0 {
+                unsafe { ptr::copy_nonoverlapping(ptr.add(self.idx - 1), ptr.add(self.idx - 1 - self.del), 1) };
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+
+impl<T: Sized, F, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for ExtractIf<'_, T, F, STEP, ALIGN, A>
+where F: FnMut(&mut T) -> bool {}
+
+impl<T: Sized + Debug, F, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for ExtractIf<'_, T, F, STEP, ALIGN, A>
+where F: FnMut(&mut T) -> bool {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let left = unsafe { core::slice::from_raw_parts(self.vec.elements().add(self.idx), self.end - self.idx) };
+        f.debug_tuple("ExtractIf").field(&left).finish()
+    }
+}
+
+impl<T: Sized, F, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for ExtractIf<'_, T, F, STEP, ALIGN, A>
+where F: FnMut(&mut T) -> bool {
+    fn drop(&mut self) {
+        //  the elements that were not visited are moved back over the extracted ones
+        unsafe {
+            if self.del > 0 && self.idx < self.old_len {
+                let ptr = self.vec.elements();
+                ptr::copy(ptr.add(self.idx), ptr.add(self.idx - self.del), self.old_len - self.idx);
+            }
+            self.vec.set_len(self.old_len - self.del);
+        }
+    }
+}
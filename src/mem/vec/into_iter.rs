@@ -0,0 +1,151 @@
+//	mem/vec/into_iter.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Owning iterator of `Vec`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::mem::ManuallyDrop;
+use core::ptr::{self, drop_in_place, slice_from_raw_parts_mut, NonNull};
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+use crate::mem::DynamicBuffer;
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::Vec;
+
+
+/// An iterator that moves out of a vector
+/// - created by `Vec::into_iter` (provided by the `IntoIterator` trait)
+/// - the elements that are not iterated are dropped together with the iterator, then the buffer is freed
+pub struct IntoIter<T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    //  `buf.size` is not used, the elements left are `start..end`
+    buf: DynamicBuffer<T, STEP, ALIGN, A>,
+    start: usize,
+    end: usize,
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIter<T, STEP, ALIGN, A> {
+
+    /// Returns pointer to the first element of the buffer
+    /// - the pointer of an empty buffer is only aligned to `u8`
+    #[inline(always)]
+    fn ptr(&self) -> *mut T {
+        match self.buf.has_data() {
+            true => self.buf.as_ptr(),
+            false => NonNull::dangling().as_ptr(),
+        }
+    }
+
+    /// Returns the remaining elements as slice
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { from_raw_parts(self.ptr().add(self.start), self.end - self.start) }
+    }
+
+    /// Returns the remaining elements as mutable slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { from_raw_parts_mut(self.ptr().add(self.start), self.end - self.start) }
+    }
+
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for IntoIter<T, STEP, ALIGN, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.start += 1;
+        Some(unsafe { self.ptr().add(self.start - 1).read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DoubleEndedIterator for IntoIter<T, STEP, ALIGN, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(unsafe { self.ptr().add(self.end).read() })
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> ExactSizeIterator for IntoIter<T, STEP, ALIGN, A> {}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for IntoIter<T, STEP, ALIGN, A> {}
+
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for IntoIter<T, STEP, ALIGN, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for IntoIter<T, STEP, ALIGN, A> {
+    fn drop(&mut self) {
+        //  the buffer is freed after this, even if some element panics while dropping
+        let remaining = slice_from_raw_parts_mut(unsafe { self.ptr().add(self.start) }, self.end - self.start);
+        self.start = self.end;
+        unsafe { drop_in_place(remaining) };
+    }
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for Vec<T, STEP, ALIGN, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, STEP, ALIGN, A>;
+
+    /// Creates an iterator that moves the elements out of the vector
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        let this = ManuallyDrop::new(self);
+        let end = this.len();
+
+        IntoIter { buf: unsafe { ptr::read(&this.data) }, start: 0, end }
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for &'l Vec<T, STEP, ALIGN, A> {
+    type Item = &'l T;
+    type IntoIter = core::slice::Iter<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().unwrap_or(&[]).iter()
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for &'l mut Vec<T, STEP, ALIGN, A> {
+    type Item = &'l mut T;
+    type IntoIter = core::slice::IterMut<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_mut_slice().unwrap_or(&mut []).iter_mut()
+    }
+}
@@ -4,6 +4,16 @@
 
 //! Even though `ministd::Vec` is not an exact copy of `std::Vec`, you will like it: it allows you to tweak how data is stored in memory!
 
+mod into_iter;
+mod drain;
+mod splice;
+mod extract_if;
+
+pub use into_iter::IntoIter;
+pub use drain::Drain;
+pub use splice::Splice;
+pub use extract_if::ExtractIf;
+
 use core::marker::PhantomData;
 
 use core::alloc::Layout;
@@ -13,12 +23,13 @@ use core::fmt::Debug;
 use core::hash::Hash;
 use core::hint::unreachable_unchecked;
 use core::mem::{ManuallyDrop, MaybeUninit};
-use core::ptr::{copy_nonoverlapping, drop_in_place, NonNull};
+use core::ptr::{copy_nonoverlapping, drop_in_place, slice_from_raw_parts_mut, NonNull};
 use core::slice::{self, from_raw_parts, from_raw_parts_mut};
 use core::ops::{Bound::*, Deref, DerefMut, Index, IndexMut, Range, RangeBounds};
 use core::cmp::Ordering::*;
 
-use crate::mem::DynamicBuffer;
+use crate::mem::{sort, DynamicBuffer};
+use crate::mem::alloc::{AllocError, AllocRef, Global, TryReserveError};
 use crate::{println, Cow, ToOwned, TryClone};
 
 #[cfg(all(feature="allocator", feature="spin", feature="box"))]
@@ -34,6 +45,7 @@ use crate::panic_fmt;
 /// - This implementation will also allow you to **tweak memory management** using generic parameters
 /// - This vector is not an exact representation of the `std::Vec`, all important functions are preserved, some functions are added
 ///   - To access the `chunks`, `windows` and other functions, use the `as_slice` function as follows: `self.as_slice().chunks()`
+///   - Sorting, deduplication and binary search are provided directly (`sort`, `try_sort`, `dedup`, `binary_search`, `insert_sorted`, ...)
 /// 
 /// ### Generic parameters
 /// 1. `T`: datatype of each element
@@ -41,13 +53,16 @@ use crate::panic_fmt;
 ///     - geometrical growth is used by default
 /// 3. `ALIGN` - defines custom alignment of the data
 ///     - set to 0 to use `align_of::<T>()`
+/// 4. `A` - the allocator (see [`AllocRef`])
+///     - uses the global `ALLOCATOR` by default
 #[repr(transparent)]
-pub struct Vec<T: Sized, const STEP: usize = 0, const ALIGN: usize = 0> {
-    data: DynamicBuffer<T, STEP, ALIGN>,
+pub struct Vec<T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    data: DynamicBuffer<T, STEP, ALIGN, A>,
 }
 
 impl<T: Sized> Vec<T> {
     /// Constructs new `Vec<T>` with `n` elements
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn from_elem<const S: usize>(value: T, n: usize) -> Vec<T, S>
         where T: Clone {
         
@@ -110,15 +125,46 @@ impl<T: Sized> Vec<T> {
 
 impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
+    /// Constructs new empty `Vec<T>`
+    /// - does not allocate any memory
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+
+    /// Constructs new empty `Vec` with at least the specified capacity allocated
+    /// - the vector will be able to hold at least `capacity` elements without reallocating
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+
+    /// Tries to construct new empty `Vec<T>` with at least the specified capacity allocated
+    /// - the vector will be able to hold at least `capacity` elements without reallocating
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Vec<T, STEP, ALIGN, A> {
+
     /// Describes memory layout for `Vec<T>` with certain `capacity`
     /// - is aligned to `STEP`
     pub const fn layout_for(capacity: usize) -> Layout {
-        DynamicBuffer::<T, STEP, ALIGN>::layout_for(capacity)
+        DynamicBuffer::<T, STEP, ALIGN, A>::layout_for(capacity)
     }
 
     /// Describes memory layout for some capacity without aligning to `STEP``
     pub const fn layout_for_exact(capacity: usize) -> Layout {
-        DynamicBuffer::<T, STEP, ALIGN>::layout_for_exact(capacity)
+        DynamicBuffer::<T, STEP, ALIGN, A>::layout_for_exact(capacity)
     }
 
 
@@ -126,6 +172,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - this function always reallocates memory
     /// - **panics** if allocation fails
     #[inline(always)]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn expand(&mut self) {
         self.data.expand();
     }
@@ -134,40 +181,49 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - this function always reallocated memory
     /// - returns `Err` if allocation fails
     #[inline(always)]
-    pub fn try_expand(&mut self) -> Result<(), ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_expand(&mut self) -> Result<(), TryReserveError> {
         self.data.try_expand()
     }
 
-    /// Constructs new empty `Vec<T>`
+    /// Constructs new empty `Vec<T>` in `alloc`
     /// - does not allocate any memory
-    pub const fn new() -> Self {
+    pub const fn new_in(alloc: A) -> Self {
         Self {
-            data: DynamicBuffer::empty(),
+            data: DynamicBuffer::empty_in(alloc),
         }
     }
 
 
-    /// Constructs new empty `Vec` with at least the specified capacity allocated
+    /// Constructs new empty `Vec` with at least the specified capacity allocated in `alloc`
     /// - the vector will be able to hold at least `capacity` elements without reallocating
     /// - **panics** if allocation fails
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
         Self {
-            data: DynamicBuffer::with_capacity(capacity),
+            data: DynamicBuffer::with_capacity_in(capacity, alloc),
         }
     }
 
 
-    /// Tries to construct new empty `Vec<T>` with at least the specified capacity allocated
+    /// Tries to construct new empty `Vec<T>` with at least the specified capacity allocated in `alloc`
     /// - the vector will be able to hold at least `capacity` elements without reallocating
     /// - returns `Err` if allocation fails
     #[inline]
-    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
         Ok(Self {
-            data: DynamicBuffer::try_with_capacity(capacity)?,
+            data: DynamicBuffer::try_with_capacity_in(capacity, alloc)?,
         })
     }
 
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
     /// Resizes the vector to certain size
     /// - **panics** if reallocation fails
     /// 
@@ -246,7 +302,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// If `new_len` is greater than `len`, the `Vec` is extended by the difference, with each additional slot filled with the result of calling the closure `f`. The return values from `f` will end up in the `Vec` in the order they have been generated
     /// 
     /// If `new_len` is less than `len`, the `Vec` is simply truncated
-    pub fn try_resize_with<F>(&mut self, new_len: usize, mut f: F) -> Result<(), ()>
+    pub fn try_resize_with<F>(&mut self, new_len: usize, mut f: F) -> Result<(), TryReserveError>
         where F: FnMut() -> T {
         
         match self.len().cmp(&new_len) {
@@ -282,7 +338,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - returns `Err` if allocation fails
     /// 
     /// If `new_len` is greater than `len`, the `Vec` is extended by the difference, with each additional slot filled with `value`. If `new_len` is less than `len`, the `Vec` is simply truncated
-    pub fn try_resize(&mut self, size: usize, value: T) -> Result<(), ()>
+    pub fn try_resize(&mut self, size: usize, value: T) -> Result<(), TryReserveError>
     where T: Clone {
 
         match self.len().cmp(&size) {
@@ -334,7 +390,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Tries to clone and append all elements in a slice
     /// - return `Err` if allocation fails
-    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), ()>
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), TryReserveError>
     where T: Clone {
 
         self.try_reserve(other.len())?;
@@ -390,7 +446,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - `src` must be a range that can form a valid subslice of the `Vec`
     /// - **panics** if range is out of bounds
     /// - returns `Err` if allocation fails
-    pub fn try_extend_from_within<R>(&mut self, src: R) -> Result<(), ()>
+    pub fn try_extend_from_within<R>(&mut self, src: R) -> Result<(), TryReserveError>
         where T: Clone, R: RangeBounds<usize> {
         
         let (start, end) = self.handle_bounds(&src);
@@ -429,6 +485,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - **panics** if allocation fails
     /// - `capacity` will be greater than or equal to `self.len() + additional` 
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn reserve(&mut self, additional: usize) {
         let min = self.len() + additional;
         if self.capacity() < min {
@@ -440,8 +497,9 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - returns `Err` if allocation fails
     /// - `capacity` will be greater than or equal to `self.len() + additional` 
     #[inline(always)]
-    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ()> {
-        let min = self.len() + additional;
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let min = self.len().checked_add(additional).ok_or(TryReserveError::capacity_overflow())?;
         if self.capacity() < min {
             self.data.try_resize(min)
         } else {
@@ -454,6 +512,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - **panics** if allocation fails
     /// - `capacity` will be greater than or equal to `self.len() + additional` 
     #[inline(always)]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn reserve_exact(&mut self, additional: usize) {
         let min = self.len() + additional;
         if self.capacity() < min {
@@ -467,8 +526,9 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// - returns `Err` if allocation fails
     /// - `capacity` will be greater than or equal to `self.len() + additional` 
     #[inline]
-    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), ()> {
-        let min = self.len() + additional;
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let min = self.len().checked_add(additional).ok_or(TryReserveError::capacity_overflow())?;
         if self.capacity() < min {
             self.data.try_resize_exact(min)
         } else {
@@ -478,6 +538,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Appends one element at the end of the vector
     /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn push(&mut self, val: T) {
         if self.len() == self.capacity() {
             self.data.expand();
@@ -492,6 +553,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// Tries to append one element at the end of the vector
     /// - returns `Err` if allocation fails
     ///     - in this case returns the ownership of `val`
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn try_push(&mut self, val: T) -> Result<(), T> {
         if self.len() == self.capacity() {
             if let Err(_) = self.data.try_expand() {
@@ -581,6 +643,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// Shrinks the capacity of the vector as much as possible
     /// - **panics** if allocation fails
     #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn shrink_to_fit(&mut self) {
         self.data.resize_exact(self.len());
     }
@@ -588,12 +651,14 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// Shrinks the capacity of the vector as much as possible
     /// - returns `Err` if allocation fails
     #[inline]
-    pub fn try_shrink_to_fit(&mut self) -> Result<(), ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), TryReserveError> {
         self.data.try_resize_exact(self.len())
     }
 
     /// Shrinks the vector to some size while dropping all elements that will not be preserved
     /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn shrink_to(&mut self, size: usize) {
         if size == 0 { return; }
         let wanted = core::cmp::max(size, self.len());
@@ -604,7 +669,8 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Tries to shrink the vector to some size while dropping all elements that will not be preserved
     /// - returns `Err` if allocation fails
-    pub fn try_shrink_to(&mut self, size: usize) -> Result<(), ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_shrink_to(&mut self, size: usize) -> Result<(), TryReserveError> {
         if self.capacity() > size {
             if size < self.len() {
                 unsafe {
@@ -625,13 +691,13 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// If `len` is greater or equal to the vector’s current length, this has no effect
     pub fn truncate(&mut self, len: usize) {
         if len < self.len() {
-            unsafe {
-                if core::mem::needs_drop::<T>() {
-                    let slice = from_raw_parts_mut(self.as_mut_ptr().add(len), self.len() - len).as_mut_ptr();
-                    drop_in_place(slice);
-                }
-            }
+            //  the length is set first, so the vector stays valid if some element panics while dropping
+            let tail = slice_from_raw_parts_mut(unsafe { self.as_mut_ptr().add(len) }, self.len() - len);
             self.data.size = len as u32;
+
+            if core::mem::needs_drop::<T>() {
+                unsafe { drop_in_place(tail) };
+            }
         }
     }
 
@@ -821,45 +887,302 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
     /// 
     /// In other words, remove all elements `e` for which `f(&e)` returns `false`. This method operates in place, visiting each element exactly once in the original order, and preserves the order of the retained elements
     /// - this is an `O(n)` operation
-    pub fn retain<F: Fn(&T) -> bool>(&mut self, f: F) {
-        let mut ptr = self.data.data();
-        let mut i = 0;
+    /// - does not allocate, so it cannot fail
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|e| f(e));
+    }
 
-        loop {
+    /// Retains only the elements for which `f` returns `true`, `f` may modify the elements
+    /// - see [`Vec::retain`]
+    /// - if `f` panics, the elements it returned `false` for are already dropped, the other elements are kept in their order
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        //  the iterator moves the kept elements back over the removed ones, also when it is dropped by a panic of `f`
+        self.extract_if(.., |e| !f(e)).for_each(drop);
+    }
 
-            if !f(unsafe { ptr.as_ref() }) {
-                self.remove_drop(i)
-            }
 
-            if i >= self.len() {
-                break;
-            }
+    /// Removes the elements in `range` and returns them as iterator
+    /// - the range is removed even if the iterator is not iterated whole, the elements left are dropped
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, STEP, ALIGN, A>
+    where R: RangeBounds<usize> {
+        let (start, end) = self.checked_range(&range);
+        Drain::new(self, start, end)
+    }
+
+    /// Replaces the elements in `range` with the elements of `replace_with`, returns the removed elements as iterator
+    /// - the replacement is inserted when the iterator is dropped, `replace_with` does not have to be as long as the range
+    /// - **panics** if the range is out of bounds, its start is greater than its end or allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, STEP, ALIGN, A>
+    where R: RangeBounds<usize>, I: IntoIterator<Item = T> {
+        let (start, end) = self.checked_range(&range);
+        Splice::new(Drain::new(self, start, end), replace_with.into_iter())
+    }
+
+    /// Returns iterator that removes and yields the elements in `range` for which `filter` returns `true`
+    /// - the other elements are kept in their order
+    /// - the elements that were not visited stay in the vector if the iterator is dropped early
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> ExtractIf<'_, T, F, STEP, ALIGN, A>
+    where F: FnMut(&mut T) -> bool, R: RangeBounds<usize> {
+        let (start, end) = self.checked_range(&range);
+        ExtractIf::new(self, start, end, filter)
+    }
+
+    /// Splits the vector in two at `at`, returns the elements `at..len` in new vector
+    /// - the new vector uses clone of the allocator, `self` keeps its capacity
+    /// - **panics** if `at > self.len()` or allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn split_off(&mut self, at: usize) -> Self
+    where A: Clone {
+        match self.try_split_off(at) {
+            Ok(other) => other,
+            Err(e) => panic!("failed to allocate memory: {}", e),
+        }
+    }
+
+    /// Tries to split the vector in two at `at`, returns the elements `at..len` in new vector
+    /// - the new vector uses clone of the allocator, `self` keeps its capacity
+    /// - returns `Err` if allocation fails, `self` is not changed then
+    /// - **panics** if `at > self.len()`
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_split_off(&mut self, at: usize) -> Result<Self, TryReserveError>
+    where A: Clone {
+        if at > self.len() {
+            #[cfg(all(feature="allocator", feature="spin", feature="string"))]
+            panic_fmt!("index {at} out of bounds 0..{}", self.len());
+            #[cfg(not(all(feature="allocator", feature="spin", feature="string")))]
+            panic!("index is out of bounds");
+        }
+
+        let len = self.len() - at;
+        let mut other = Self::try_with_capacity_in(len, self.allocator().clone())?;
 
-            unsafe { ptr = ptr.add(1) };
-            i += 1;
+        unsafe {
+            copy_nonoverlapping(self.elements().add(at), other.elements(), len);
+            other.set_len(len);
+            self.set_len(at);
         }
 
+        Ok(other)
     }
 
-    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
-        let mut ptr = self.data.data();
-        let mut i = 0;
+    /// Sorts the vector with stable merge sort
+    /// - this is an `O(n * log(n))` operation
+    /// - allocates scratch space for `len / 2` elements from `ALLOCATOR`, vectors up to 20 elements are sorted without allocating
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort(&mut self)
+    where T: Ord {
+        self.sort_by(T::cmp);
+    }
 
-        loop {
+    /// Tries to sort the vector with stable merge sort
+    /// - see [`Vec::sort`]
+    /// - returns `Err` if allocation fails, the vector is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort(&mut self) -> Result<(), TryReserveError>
+    where T: Ord {
+        self.try_sort_by(T::cmp)
+    }
 
-            if !f(unsafe { ptr.as_mut() }) {
-                self.remove_drop(i)
-            }
+    /// Sorts the vector with stable merge sort using `compare`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where F: FnMut(&T, &T) -> core::cmp::Ordering {
+        if let Err(e) = self.try_sort_by(compare) {
+            panic!("failed to allocate memory: {}", e);
+        }
+    }
 
-            if i >= self.len() {
-                break;
-            }
+    /// Tries to sort the vector with stable merge sort using `compare`
+    /// - returns `Err` if allocation fails, the vector is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort_by<F>(&mut self, mut compare: F) -> Result<(), TryReserveError>
+    where F: FnMut(&T, &T) -> core::cmp::Ordering {
+        sort::merge_sort(self.elements_mut(), &mut |a, b| compare(a, b) == Less)
+    }
+
+    /// Sorts the vector with stable merge sort by the keys returned by `f`
+    /// - `f` is called `O(n * log(n))` times
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn sort_by_key<K: Ord, F>(&mut self, mut f: F)
+    where F: FnMut(&T) -> K {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Tries to sort the vector with stable merge sort by the keys returned by `f`
+    /// - returns `Err` if allocation fails, the vector is not changed then
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_sort_by_key<K: Ord, F>(&mut self, mut f: F) -> Result<(), TryReserveError>
+    where F: FnMut(&T) -> K {
+        self.try_sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Sorts the vector in place, the order of equal elements may change
+    /// - uses the unstable sort of `core`, does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable(&mut self)
+    where T: Ord {
+        self.elements_mut().sort_unstable();
+    }
 
-            unsafe { ptr = ptr.add(1) };
-            i += 1;
+    /// Sorts the vector in place using `compare`, the order of equal elements may change
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where F: FnMut(&T, &T) -> core::cmp::Ordering {
+        self.elements_mut().sort_unstable_by(compare);
+    }
+
+    /// Sorts the vector in place by the keys returned by `f`, the order of equal elements may change
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn sort_unstable_by_key<K: Ord, F>(&mut self, f: F)
+    where F: FnMut(&T) -> K {
+        self.elements_mut().sort_unstable_by_key(f);
+    }
+
+    /// Removes consecutive repeated elements, keeps the first one of each run
+    /// - removes all duplicates if the vector is sorted
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn dedup(&mut self)
+    where T: PartialEq {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`
+    /// - `same_bucket(a, b)` is called with the element `a` and the last kept element `b` before it
+    /// - if `same_bucket` panics, all elements are kept (some of them may be moved to the end)
+    /// - does not allocate, so it cannot fail
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where F: FnMut(&mut T, &mut T) -> bool {
+        let kept = sort::partition_dedup(self.elements_mut(), same_bucket);
+        self.truncate(kept);
+    }
+
+    /// Removes consecutive elements that have the same key
+    /// - does not allocate, so it cannot fail
+    #[inline]
+    pub fn dedup_by_key<K: PartialEq, F>(&mut self, mut key: F)
+    where F: FnMut(&mut T) -> K {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Binary searches the sorted vector for `x`
+    /// - returns `Ok` with index of a matching element or `Err` with index where `x` could be inserted
+    /// - unlike the slice method, works on vectors that have no data allocated
+    #[inline]
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize>
+    where T: Ord {
+        self.binary_search_by(|e| e.cmp(x))
+    }
+
+    /// Binary searches the sorted vector with `f`
+    /// - `f` returns ordering of the element compared to the target
+    #[inline]
+    pub fn binary_search_by<F>(&self, f: F) -> Result<usize, usize>
+    where F: FnMut(&T) -> core::cmp::Ordering {
+        self.elements_ref().binary_search_by(f)
+    }
+
+    /// Binary searches the vector sorted by the keys returned by `f` for `key`
+    #[inline]
+    pub fn binary_search_by_key<K: Ord, F>(&self, key: &K, f: F) -> Result<usize, usize>
+    where F: FnMut(&T) -> K {
+        self.elements_ref().binary_search_by_key(key, f)
+    }
+
+    /// Inserts `val` into the sorted vector, keeping it sorted
+    /// - `val` goes after the elements equal to it, returns its index
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn insert_sorted(&mut self, val: T) -> usize
+    where T: Ord {
+        self.insert_sorted_by(val, T::cmp)
+    }
+
+    /// Tries to insert `val` into the sorted vector, keeping it sorted
+    /// - returns index of `val` or `Err(val)` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_insert_sorted(&mut self, val: T) -> Result<usize, T>
+    where T: Ord {
+        self.try_insert_sorted_by(val, T::cmp)
+    }
+
+    /// Inserts `val` into the vector sorted by `compare`, keeping it sorted
+    /// - `val` goes after the elements equal to it, returns its index
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn insert_sorted_by<F>(&mut self, val: T, compare: F) -> usize
+    where F: FnMut(&T, &T) -> core::cmp::Ordering {
+        let index = sort::upper_bound(self.elements_ref(), &val, compare);
+        self.insert(index, val);
+        index
+    }
+
+    /// Tries to insert `val` into the vector sorted by `compare`, keeping it sorted
+    /// - returns index of `val` or `Err(val)` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_insert_sorted_by<F>(&mut self, val: T, compare: F) -> Result<usize, T>
+    where F: FnMut(&T, &T) -> core::cmp::Ordering {
+        let index = sort::upper_bound(self.elements_ref(), &val, compare);
+        self.try_insert(index, val).map(|_| index)
+    }
+
+    /// Returns pointer to the elements
+    /// - unlike `as_ptr`, the pointer is aligned for `T` even if no data is allocated
+    #[inline(always)]
+    fn elements(&self) -> *mut T {
+        match self.has_data() {
+            true => self.data.as_ptr(),
+            false => NonNull::dangling().as_ptr(),
+        }
+    }
+
+    /// Returns the elements as slice, even if no data is allocated
+    #[inline(always)]
+    fn elements_ref(&self) -> &[T] {
+        unsafe { from_raw_parts(self.elements(), self.len()) }
+    }
+
+    /// Returns the elements as mutable slice, even if no data is allocated
+    #[inline(always)]
+    fn elements_mut(&mut self) -> &mut [T] {
+        unsafe { from_raw_parts_mut(self.elements(), self.len()) }
+    }
+
+    /// Returns bounds of `range`
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    #[inline]
+    fn checked_range<R>(&self, range: &R) -> (usize, usize)
+    where R: RangeBounds<usize> {
+        let (start, end) = self.handle_bounds(range);
+
+        if start > end || end > self.len() {
+            #[cfg(all(feature="allocator", feature="spin", feature="string"))]
+            panic_fmt!("range {start}..{end} out of bounds 0..{}", self.len());
+            #[cfg(not(all(feature="allocator", feature="spin", feature="string")))]
+            panic!("range is out of bounds");
         }
 
+        (start, end)
     }
+}
+
+
+//  constructors that allocate with the global allocator
+impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Constructs new `Vec<T>` from slice of `T`
     /// - **panics** if allocation fails
@@ -883,8 +1206,9 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Tries to construct `Vec<T>` from slice of `T`
     /// - returns `Err` if allocation fails of `slice[i].try_clone()` returns `Err`
-    pub fn try_from_slice(slice: &[T]) -> Result<Self, ()>
-        where T: Sized + TryClone {
+    ///   - allocation failure is converted into `T::Error`
+    pub fn try_from_slice(slice: &[T]) -> Result<Self, T::Error>
+        where T: Sized + TryClone, T::Error: From<AllocError> {
         
         let mut db = DynamicBuffer::<T, STEP, ALIGN>::try_with_capacity(slice.len())?;
         db.size = slice.len() as u32;
@@ -895,10 +1219,10 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
             for (i, item) in slice.iter().enumerate() {
                 let e = match item.try_clone() {
                     Ok(e) => e,
-                    Err(_) => {
+                    Err(e) => {
                         let slice = from_raw_parts_mut(db.data().as_ptr(), i).as_mut_ptr();
                         drop_in_place(slice);
-                        return Err(());
+                        return Err(e);
                     }
                 };
                 this.write(e);
@@ -934,6 +1258,11 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     }
 
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Vec<T, STEP, ALIGN, A> {
+
     /// Drops the last element of the vector
     /// - **no-op** if `self.is_empty()`
     /// - does not affect `capacity`
@@ -1065,7 +1394,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
     /// Append all elements from `other` to `self`, leaving `other` empty
     /// - returns `Err` if allocation fails
-    pub fn try_append(&mut self, other: &mut Vec<T>) -> Result<(), ()> {
+    pub fn try_append(&mut self, other: &mut Vec<T>) -> Result<(), TryReserveError> {
         if other.is_empty() {
             return Ok(());
         }
@@ -1334,7 +1663,7 @@ impl<T, const STEP: usize, const N: usize, const ALIGN: usize> Vec<[T; N], STEP,
 }
 
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Vec<T, STEP, ALIGN, A> {
 
     //  Deref<[T]>
 
@@ -1792,6 +2121,12 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
         unsafe { self.as_mut_slice_unchecked() }.iter_mut()
     }
 
+}
+
+
+//  raw parts are always allocated with the global allocator
+impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
+
     /// Creates a `Vec<T>` directly from a pointer, a length and a capacity
     /// 
     /// This is **highly unsafe**, due to the number of invariants that aren’t checked:
@@ -1858,6 +2193,11 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
         })
     }
 
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Vec<T, STEP, ALIGN, A> {
+
     /// Decomposes a `Vec<T>` into its raw components: `(pointer, length, capacity)`
     #[inline]
     pub unsafe fn into_raw_parts(self) -> (*mut T, usize, usize) {
@@ -1872,19 +2212,21 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
         (m.data.data(), m.len(), m.capacity())
     }
 
-    
-    /*pub(crate) unsafe fn into_dynamic_buffer(self) -> DynamicBuffer<T, STEP, ALIGN> {
-        unsafe {
-            let (ptr, size, cap) = self.into_parts();
-            DynamicBuffer::from_raw(ptr, cap as u32, size as u32)
-        }
 
+    /// Takes the buffer of the vector, `size` of the buffer is the length of the vector
+    /// - the elements are not dropped, they are owned by the caller now
+    #[inline]
+    pub(crate) fn into_dynamic_buffer(self) -> DynamicBuffer<T, STEP, ALIGN, A> {
+        let m = ManuallyDrop::new(self);
+        unsafe { core::ptr::read(&m.data) }
     }
 
-
-    pub(crate) const unsafe fn from_dynamic_buffer(db: DynamicBuffer<T, STEP, ALIGN>) -> Self {
+    /// Constructs the vector from a buffer
+    /// - safety: the first `db.size` elements of the buffer must be initialized
+    #[inline]
+    pub(crate) const unsafe fn from_dynamic_buffer(db: DynamicBuffer<T, STEP, ALIGN, A>) -> Self {
         Self { data: db }
-    }*/
+    }
 
 
 
@@ -1892,47 +2234,47 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Vec<T, STEP, ALIGN> {
 
 
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> AsRef<[T]> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> AsRef<[T]> for Vec<T, STEP, ALIGN, A> {
     /// **panics** if has no data
     fn as_ref(&self) -> &[T] {
         self.as_slice().expect("Vec has no data")
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> AsMut<[T]> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> AsMut<[T]> for Vec<T, STEP, ALIGN, A> {
     /// **panics** if has no data
     fn as_mut(&mut self) -> &mut [T] {
         self.as_mut_slice().expect("Vec has no data")
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> AsRef<Vec<T, STEP, ALIGN>> for Vec<T, STEP, ALIGN> {
-    fn as_ref(&self) -> &Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> AsRef<Vec<T, STEP, ALIGN, A>> for Vec<T, STEP, ALIGN, A> {
+    fn as_ref(&self) -> &Vec<T, STEP, ALIGN, A> {
         &self
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> AsMut<Vec<T, STEP, ALIGN>> for Vec<T, STEP, ALIGN> {
-    fn as_mut(&mut self) -> &mut Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> AsMut<Vec<T, STEP, ALIGN, A>> for Vec<T, STEP, ALIGN, A> {
+    fn as_mut(&mut self) -> &mut Vec<T, STEP, ALIGN, A> {
         self
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Borrow<[T]> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Borrow<[T]> for Vec<T, STEP, ALIGN, A> {
     /// **panics** if has no data
     fn borrow(&self) -> &[T] {
         self.as_slice().expect("Vec has no data")
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> BorrowMut<[T]> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> BorrowMut<[T]> for Vec<T, STEP, ALIGN, A> {
     /// **panics** if has no data
     fn borrow_mut(&mut self) -> &mut [T] {
         self.as_mut_slice().expect("Vec has no data")
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Drop for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for Vec<T, STEP, ALIGN, A> {
     fn drop(&mut self) {
         if self.capacity() > 0 {
             unsafe {
@@ -1942,7 +2284,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Drop for Vec<T, STEP, ALIG
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Index<usize> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Index<usize> for Vec<T, STEP, ALIGN, A> {
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
         if index < self.len() {
@@ -1962,7 +2304,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Index<usize> for Vec<T, ST
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> IndexMut<usize> for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IndexMut<usize> for Vec<T, STEP, ALIGN, A> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         if index < self.len() {
             unsafe {
@@ -1981,7 +2323,8 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> IndexMut<usize> for Vec<T,
     }
 }
 
-impl<T: Sized + Clone, const STEP: usize, const ALIGN: usize> Clone for Vec<T, STEP, ALIGN> {
+impl<T: Sized + Clone, const STEP: usize, const ALIGN: usize, A: AllocRef + Clone> Clone for Vec<T, STEP, ALIGN, A> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn clone(&self) -> Self {
 
         let db = self.data.clone();
@@ -2000,11 +2343,12 @@ impl<T: Sized + Clone, const STEP: usize, const ALIGN: usize> Clone for Vec<T, S
     }
 }
 
-impl<T: Sized + TryClone, const STEP: usize, const ALIGN: usize> TryClone for Vec<T, STEP, ALIGN> {
-    type Error = ();
+impl<T: Sized + TryClone, const STEP: usize, const ALIGN: usize, A: AllocRef + Clone> TryClone for Vec<T, STEP, ALIGN, A>
+where T::Error: From<AllocError> {
+    type Error = T::Error;
 
     fn try_clone(&self) -> Result<Self, Self::Error>
-    where Self: Sized, Self::Error: Default {
+    where Self: Sized {
         
         let db = self.data.try_clone()?;
 
@@ -2015,9 +2359,9 @@ impl<T: Sized + TryClone, const STEP: usize, const ALIGN: usize> TryClone for Ve
         for (i, item) in new_slice.iter_mut().enumerate() {
             *item = match old_slice[i].try_clone() {
                 Ok(i) => i,
-                Err(_) => {
+                Err(e) => {
                     unsafe { drop_in_place(new_slice[0..i].as_mut_ptr()) }
-                    return Err(());
+                    return Err(e);
                 },
             };
         }
@@ -2029,7 +2373,7 @@ impl<T: Sized + TryClone, const STEP: usize, const ALIGN: usize> TryClone for Ve
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Deref for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Deref for Vec<T, STEP, ALIGN, A> {
     type Target = [T];
     #[inline]
     /// Does not check for null at all
@@ -2038,7 +2382,7 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> Deref for Vec<T, STEP, ALI
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> DerefMut for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DerefMut for Vec<T, STEP, ALIGN, A> {
     #[inline]
     /// Does not check for null at all
     fn deref_mut(&mut self) -> &mut Self::Target {
@@ -2046,16 +2390,16 @@ impl<T: Sized, const STEP: usize, const ALIGN: usize> DerefMut for Vec<T, STEP,
     }
 }
 
-impl<T: Sized, const STEP: usize, const ALIGN: usize> Default for Vec<T, STEP, ALIGN> {
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef + Default> Default for Vec<T, STEP, ALIGN, A> {
     #[inline(always)]
-    /// Equivalent of `Vec::new()`
+    /// Equivalent of `Vec::new_in(A::default())`
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
 
-impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize> Debug for Vec<T, STEP, ALIGN> {
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for Vec<T, STEP, ALIGN, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 
         if f.alternate() {
@@ -2068,11 +2412,11 @@ impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize> Debug for Vec<T, S
 
 
 
-impl<T, U, const SSTEP: usize, const OSTEP: usize, const SALIGN: usize, const OALIGN: usize>
-    PartialEq<Vec::<U, OSTEP, OALIGN>> for Vec<T, SSTEP, SALIGN>
+impl<T, U, const SSTEP: usize, const OSTEP: usize, const SALIGN: usize, const OALIGN: usize, SA: AllocRef, OA: AllocRef>
+    PartialEq<Vec::<U, OSTEP, OALIGN, OA>> for Vec<T, SSTEP, SALIGN, SA>
     where T: Sized + PartialEq<U> {
 
-    fn eq(&self, other: &Vec::<U, OSTEP, OALIGN>) -> bool {
+    fn eq(&self, other: &Vec::<U, OSTEP, OALIGN, OA>) -> bool {
 
         match self.is_empty() as usize | ((other.is_empty() as usize) << 1) {
             0b00 => {   //  both have any data
@@ -2093,7 +2437,7 @@ impl<T, U, const SSTEP: usize, const OSTEP: usize, const SALIGN: usize, const OA
         }
     }
 
-    fn ne(&self, other: &Vec::<U, OSTEP, OALIGN>) -> bool {
+    fn ne(&self, other: &Vec::<U, OSTEP, OALIGN, OA>) -> bool {
         
         match self.is_empty() as usize | ((other.is_empty() as usize) << 1) {
             0b00 => {   //  both have any data
@@ -2117,7 +2461,7 @@ impl<T, U, const SSTEP: usize, const OSTEP: usize, const SALIGN: usize, const OA
 
 }
 
-impl<T, U, const STEP: usize, const ALIGN: usize> PartialEq<[U]> for Vec<T, STEP, ALIGN>
+impl<T, U, const STEP: usize, const ALIGN: usize, A: AllocRef> PartialEq<[U]> for Vec<T, STEP, ALIGN, A>
     where T: Sized + PartialEq<U>, U: Sized {
 
     fn eq(&self, other: &[U]) -> bool {
@@ -2145,7 +2489,7 @@ impl<T, U, const STEP: usize, const ALIGN: usize> PartialEq<[U]> for Vec<T, STEP
     }
 }
 
-impl<T, U, const STEP: usize, const N: usize, const ALIGN: usize> PartialEq<[U; N]> for Vec<T, STEP, ALIGN>
+impl<T, U, const STEP: usize, const N: usize, const ALIGN: usize, A: AllocRef> PartialEq<[U; N]> for Vec<T, STEP, ALIGN, A>
     where T: Sized + PartialEq<U>, U: Sized {
 
     fn eq(&self, other: &[U; N]) -> bool {
@@ -2265,7 +2609,7 @@ impl<const STEP: usize, const ALIGN: usize> From<&CStr> for Vec<u8, STEP, ALIGN>
     }
 }
 
-impl<T, const STEP: usize, const ALIGN: usize> Hash for Vec<T, STEP, ALIGN>
+impl<T, const STEP: usize, const ALIGN: usize, A: AllocRef> Hash for Vec<T, STEP, ALIGN, A>
     where T: Sized + Hash {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         Hash::hash_slice(self.as_slice().expect("vector is empty"), state);
@@ -2300,6 +2644,7 @@ impl<'a, T: Clone> From<Vec<T>> for Cow<'a, [T]> {
 
 
 impl<'l, T: Sized + Clone, const STEP: usize, const ALIGN: usize> FromIterator<&'l T> for Vec<T, STEP, ALIGN> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn from_iter<I: IntoIterator<Item = &'l T>>(iter: I) -> Self {
         let iter = iter.into_iter();
 
@@ -2329,6 +2674,7 @@ impl<'l, T: Sized + Clone, const STEP: usize, const ALIGN: usize> FromIterator<&
 
 impl<T: Sized, const STEP: usize, const ALIGN: usize> FromIterator<T> for Vec<T, STEP, ALIGN> {
     
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let iter = iter.into_iter();
 
@@ -0,0 +1,128 @@
+//	mem/vec/splice.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Splicing iterator of `Vec`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::ptr;
+
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::{Drain, Vec};
+
+
+/// A splicing iterator of a vector
+/// - created by [`Vec::splice`]
+/// - yields the removed elements like [`Drain`], the replacement is inserted when the iterator is dropped
+/// - **panics** on drop if allocation fails
+pub struct Splice<'a, I: Iterator, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    drain: Drain<'a, I::Item, STEP, ALIGN, A>,
+    replace_with: I,
+}
+
+impl<'a, I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> Splice<'a, I, STEP, ALIGN, A> {
+
+    /// Constructs the iterator
+    pub(super) fn new(drain: Drain<'a, I::Item, STEP, ALIGN, A>, replace_with: I) -> Self {
+        Self { drain, replace_with }
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drain<'_, T, STEP, ALIGN, A> {
+
+    /// Fills the gap between the vector and the tail from `iter`
+    /// - returns `false` if `iter` ran out
+    fn fill(&mut self, iter: &mut impl Iterator<Item = T>) -> bool {
+        while self.vec.len() < self.tail {
+            match iter.next() {
+                Some(item) => unsafe {
+                    let len = self.vec.len();
+                    self.vec.elements().add(len).write(item);
+                    self.vec.set_len(len + 1);
+                },
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Moves the tail `by` elements further, reallocates if needed
+    fn shift_tail(&mut self, by: usize) {
+        let needed = self.tail + self.tail_len + by;
+        if needed > self.vec.capacity() {
+            //  the whole buffer is moved, not only `len` elements
+            self.vec.data.resize(needed);
+        }
+
+        unsafe {
+            let ptr = self.vec.elements();
+            ptr::copy(ptr.add(self.tail), ptr.add(self.tail + by), self.tail_len);
+        }
+        self.tail += by;
+    }
+
+}
+
+impl<I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for Splice<'_, I, STEP, ALIGN, A> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> DoubleEndedIterator for Splice<'_, I, STEP, ALIGN, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.drain.next_back()
+    }
+}
+
+impl<I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> ExactSizeIterator for Splice<'_, I, STEP, ALIGN, A> {}
+
+impl<I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for Splice<'_, I, STEP, ALIGN, A> {}
+
+impl<I: Iterator + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for Splice<'_, I, STEP, ALIGN, A>
+where I::Item: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Splice").field("drain", &self.drain).field("replace_with", &self.replace_with).finish()
+    }
+}
+
+impl<I: Iterator, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for Splice<'_, I, STEP, ALIGN, A> {
+    fn drop(&mut self) {
+        //  if anything below panics, the drop of `drain` moves the tail back after the inserted elements
+        self.drain.by_ref().for_each(drop);
+
+        //  the gap left by the drained range
+        if !self.drain.fill(&mut self.replace_with) {
+            return;
+        }
+
+        //  the lower bound of the size hint is trusted to make room, the rest is collected
+        let (lower, _) = self.replace_with.size_hint();
+        if lower > 0 {
+            self.drain.shift_tail(lower);
+            if !self.drain.fill(&mut self.replace_with) {
+                return;
+            }
+        }
+
+        let rest: Vec<I::Item> = self.replace_with.by_ref().collect();
+        let mut rest = rest.into_iter();
+        if rest.len() > 0 {
+            self.drain.shift_tail(rest.len());
+            self.drain.fill(&mut rest);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//	mem/vec_deque/drain.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Draining iterator of `VecDeque`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::VecDeque;
+
+
+/// A draining iterator of a queue
+/// - created by [`VecDeque::drain`]
+/// - the drained range is removed even if it is not iterated whole, the elements left are dropped
+/// - the gap is closed when the iterator is dropped, by moving the shorter of the parts around it
+///   - if the iterator is leaked (e.g. `mem::forget`), the queue is left truncated at the start of the range
+pub struct Drain<'a, T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    //  `deque.len()` is the start of the range while draining, the indexes are logical
+    deque: &'a mut VecDeque<T, STEP, ALIGN, A>,
+    start: usize,
+    //  elements that were not yielded yet
+    next: usize,
+    end: usize,
+    //  the elements `tail..old_len` follow the range
+    tail: usize,
+    old_len: usize,
+}
+
+impl<'a, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drain<'a, T, STEP, ALIGN, A> {
+
+    /// Constructs the iterator over `start..end`
+    /// - the range must be in bounds of the queue
+    pub(super) fn new(deque: &'a mut VecDeque<T, STEP, ALIGN, A>, start: usize, end: usize) -> Self {
+        let old_len = deque.len();
+        unsafe { deque.set_len(start) };
+
+        Self { deque, start, next: start, end, tail: end, old_len }
+    }
+
+    /// Returns reference to the allocator of the queue
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.deque.allocator()
+    }
+
+    /// Closes the gap left by the drained range
+    fn join(&mut self) {
+        let front_len = self.start;
+        let back_len = self.old_len - self.tail;
+        let gap = self.tail - self.start;
+
+        unsafe {
+            if front_len < back_len {
+                self.deque.copy_logical(0, gap, front_len);
+                self.deque.head = self.deque.phys(gap);
+            } else {
+                self.deque.copy_logical(self.tail, self.start, back_len);
+            }
+            self.deque.set_len(front_len + back_len);
+        }
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for Drain<'_, T, STEP, ALIGN, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.next += 1;
+        Some(unsafe { self.deque.ptr().add(self.deque.phys(self.next - 1)).read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DoubleEndedIterator for Drain<'_, T, STEP, ALIGN, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(unsafe { self.deque.ptr().add(self.deque.phys(self.end)).read() })
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> ExactSizeIterator for Drain<'_, T, STEP, ALIGN, A> {}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for Drain<'_, T, STEP, ALIGN, A> {}
+
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for Drain<'_, T, STEP, ALIGN, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let left = (self.next..self.end).map(|i| unsafe { &*self.deque.ptr().add(self.deque.phys(i)) });
+        f.debug_list().entries(left).finish()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for Drain<'_, T, STEP, ALIGN, A> {
+    fn drop(&mut self) {
+        //  closes the gap even if some element panics while dropping
+        struct Join<'r, 'a, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef>(&'r mut Drain<'a, T, STEP, ALIGN, A>);
+
+        impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for Join<'_, '_, T, STEP, ALIGN, A> {
+            fn drop(&mut self) {
+                self.0.join();
+            }
+        }
+
+        let guard = Join(self);
+        for item in guard.0.by_ref() {
+            drop(item);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//	mem/vec_deque/iter.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Iterators of `VecDeque`
+
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::mem::alloc::{AllocRef, Global};
+
+use super::VecDeque;
+
+
+/// An iterator over the elements of a queue
+/// - created by [`VecDeque::iter`] and [`VecDeque::range`]
+pub struct Iter<'a, T> {
+    //  the front part, then the wrapped part
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(super) fn new(front: &'a [T], back: &'a [T]) -> Self {
+        Self { front: front.iter(), back: back.iter() }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => self.back.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => self.front.next_back(),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self { front: self.front.clone(), back: self.back.clone() }
+    }
+}
+
+impl<T: Debug> Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Iter").field(&self.front.as_slice()).field(&self.back.as_slice()).finish()
+    }
+}
+
+
+/// An iterator over the elements of a queue that returns mutable references
+/// - created by [`VecDeque::iter_mut`] and [`VecDeque::range_mut`]
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub(super) fn new(front: &'a mut [T], back: &'a mut [T]) -> Self {
+        Self { front: front.iter_mut(), back: back.iter_mut() }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut T> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => self.back.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => self.front.next_back(),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<T: Debug> Debug for IterMut<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("IterMut").field(&self.front.as_slice()).field(&self.back.as_slice()).finish()
+    }
+}
+
+
+/// An iterator that moves out of a queue
+/// - created by `VecDeque::into_iter` (provided by the `IntoIterator` trait)
+/// - the elements that are not iterated are dropped together with the iterator
+pub struct IntoIter<T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    inner: VecDeque<T, STEP, ALIGN, A>,
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIter<T, STEP, ALIGN, A> {
+
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        self.inner.allocator()
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Iterator for IntoIter<T, STEP, ALIGN, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.inner.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.inner.len();
+        (len, Some(len))
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> DoubleEndedIterator for IntoIter<T, STEP, ALIGN, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.pop_back()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> ExactSizeIterator for IntoIter<T, STEP, ALIGN, A> {}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> FusedIterator for IntoIter<T, STEP, ALIGN, A> {}
+
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for IntoIter<T, STEP, ALIGN, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.inner).finish()
+    }
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for VecDeque<T, STEP, ALIGN, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, STEP, ALIGN, A>;
+
+    /// Creates an iterator that moves the elements out of the queue, front to back
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { inner: self }
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for &'l VecDeque<T, STEP, ALIGN, A> {
+    type Item = &'l T;
+    type IntoIter = Iter<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IntoIterator for &'l mut VecDeque<T, STEP, ALIGN, A> {
+    type Item = &'l mut T;
+    type IntoIter = IterMut<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
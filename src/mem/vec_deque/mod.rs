@@ -0,0 +1,736 @@
+//	mem/vec_deque/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//! Double-ended queue implemented with a growable ring buffer
+//! - meant for run queues, input buffers and deferred work lists, where elements are pushed at one end and popped at the other
+
+
+mod iter;
+mod drain;
+
+pub use iter::{Iter, IterMut, IntoIter};
+pub use drain::Drain;
+
+use core::fmt::Debug;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Bound::*, Index, IndexMut, Range, RangeBounds};
+use core::ptr::{self, copy_nonoverlapping, slice_from_raw_parts_mut, NonNull};
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+use crate::mem::DynamicBuffer;
+use crate::mem::alloc::{AllocError, AllocRef, Global, TryReserveError};
+use crate::mem::vec::Vec;
+
+#[cfg(all(feature="allocator", feature="spin", feature="string"))]
+use crate::panic_fmt;
+
+
+/// A double-ended queue implemented with a growable ring buffer, written as `VecDeque<T>`
+/// - the elements start at `head` and wrap around the end of the buffer, use `as_slices` or `make_contiguous` to get them as slices
+/// - uses [`DynamicBuffer`] and its growth policy, just like `Vec`
+///
+/// ### Generic parameters
+/// 1. `T`: datatype of each element
+/// 2. `STEP`: indicates how much will the queue grow
+///     - geometrical growth is used by default
+/// 3. `ALIGN` - defines custom alignment of the data
+///     - set to 0 to use `align_of::<T>()`
+/// 4. `A` - the allocator (see [`AllocRef`])
+///     - uses the global `ALLOCATOR` by default
+pub struct VecDeque<T: Sized, const STEP: usize = 0, const ALIGN: usize = 0, A: AllocRef = Global> {
+    //  `buf.size` is the number of elements
+    buf: DynamicBuffer<T, STEP, ALIGN, A>,
+    //  physical index of the first element
+    head: usize,
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize> VecDeque<T, STEP, ALIGN> {
+
+    /// Constructs new empty `VecDeque<T>`
+    /// - does not allocate any memory
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Constructs new empty `VecDeque` able to hold at least `capacity` elements without reallocating
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    /// Tries to construct new empty `VecDeque` able to hold at least `capacity` elements without reallocating
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, AllocError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> VecDeque<T, STEP, ALIGN, A> {
+
+    /// Constructs new empty `VecDeque` in `alloc`
+    /// - does not allocate any memory
+    pub const fn new_in(alloc: A) -> Self {
+        Self { buf: DynamicBuffer::empty_in(alloc), head: 0 }
+    }
+
+    /// Constructs new empty `VecDeque` in `alloc` able to hold at least `capacity` elements without reallocating
+    /// - **panics** if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        match Self::try_with_capacity_in(capacity, alloc) {
+            Ok(deque) => deque,
+            Err(e) => panic!("failed to allocate memory: {}", e),
+        }
+    }
+
+    /// Tries to construct new empty `VecDeque` in `alloc` able to hold at least `capacity` elements without reallocating
+    /// - returns `Err` if allocation fails
+    #[inline]
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, AllocError> {
+        Ok(Self { buf: DynamicBuffer::try_with_capacity_in(capacity, alloc)?, head: 0 })
+    }
+
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    /// Returns number of elements in the queue
+    #[inline]
+    pub const fn len(&self) -> usize { self.buf.size as usize }
+
+    /// Returns number of elements the queue can hold without reallocating
+    #[inline]
+    pub const fn capacity(&self) -> usize { self.buf.capacity() }
+
+    /// Indicates if the queue has no elements
+    #[inline]
+    pub const fn is_empty(&self) -> bool { self.buf.size == 0 }
+
+
+    /// Reserves capacity for at least `additional` more elements
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("failed to allocate memory: {}", e);
+        }
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements
+    /// - returns `Err` if allocation fails, the queue is not changed then
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self.len().checked_add(additional).ok_or(AllocError::capacity_overflow())?;
+        if needed <= self.capacity() {
+            return Ok(());
+        }
+
+        let old = self.capacity();
+        self.buf.try_resize(needed)?;
+        unsafe { self.handle_capacity_increase(old) };
+        Ok(())
+    }
+
+    /// Appends `val` to the back of the queue
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn push_back(&mut self, val: T) {
+        if self.is_full() {
+            self.grow();
+        }
+        unsafe { self.push_back_unchecked(val) };
+    }
+
+    /// Tries to append `val` to the back of the queue
+    /// - returns `Err(val)` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_push_back(&mut self, val: T) -> Result<(), T> {
+        if self.is_full() && self.try_grow().is_err() {
+            return Err(val);
+        }
+        unsafe { self.push_back_unchecked(val) };
+        Ok(())
+    }
+
+    /// Prepends `val` to the front of the queue
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn push_front(&mut self, val: T) {
+        if self.is_full() {
+            self.grow();
+        }
+        unsafe { self.push_front_unchecked(val) };
+    }
+
+    /// Tries to prepend `val` to the front of the queue
+    /// - returns `Err(val)` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_push_front(&mut self, val: T) -> Result<(), T> {
+        if self.is_full() && self.try_grow().is_err() {
+            return Err(val);
+        }
+        unsafe { self.push_front_unchecked(val) };
+        Ok(())
+    }
+
+    /// Removes the last element and returns it, or `None` if the queue is empty
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let len = self.len() - 1;
+        unsafe {
+            self.set_len(len);
+            Some(self.ptr().add(self.phys(len)).read())
+        }
+    }
+
+    /// Removes the first element and returns it, or `None` if the queue is empty
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let head = self.head;
+        unsafe {
+            self.head = self.phys(1);
+            self.set_len(self.len() - 1);
+            Some(self.ptr().add(head).read())
+        }
+    }
+
+    /// Returns reference to the element at `index`, `0` is the front
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len() {
+            Some(unsafe { &*self.ptr().add(self.phys(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns mutable reference to the element at `index`, `0` is the front
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len() {
+            Some(unsafe { &mut *self.ptr().add(self.phys(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns reference to the first element
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns mutable reference to the first element
+    #[inline]
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns reference to the last element
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len().wrapping_sub(1))
+    }
+
+    /// Returns mutable reference to the last element
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len().wrapping_sub(1))
+    }
+
+    /// Swaps elements at `a` and `b`
+    /// - **panics** if any of the indexes is out of bounds
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.check_index(a);
+        self.check_index(b);
+
+        unsafe {
+            let ptr = self.ptr();
+            ptr::swap(ptr.add(self.phys(a)), ptr.add(self.phys(b)));
+        }
+    }
+
+    /// Checks if the queue contains `x`
+    pub fn contains(&self, x: &T) -> bool
+    where T: PartialEq {
+        let (a, b) = self.as_slices();
+        a.contains(x) || b.contains(x)
+    }
+
+    /// Shortens the queue, keeping the first `len` elements and dropping the rest
+    /// - **no-op** if `len >= self.len()`
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+
+        let (a, b) = self.slice_ranges(len, self.len());
+        let ptr = self.ptr();
+        unsafe {
+            //  the length is set first, so the queue stays valid if some element panics while dropping
+            self.set_len(len);
+            ptr::drop_in_place(slice_from_raw_parts_mut(ptr.add(a.start), a.len()));
+            ptr::drop_in_place(slice_from_raw_parts_mut(ptr.add(b.start), b.len()));
+        }
+    }
+
+    /// Removes and drops all elements
+    /// - keeps the allocated memory
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+
+
+    /// Returns the elements as two slices, the front part and the wrapped part
+    /// - the second slice is empty if the elements are contiguous
+    #[inline]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (a, b) = self.slice_ranges(0, self.len());
+        let ptr = self.ptr();
+        unsafe { (from_raw_parts(ptr.add(a.start), a.len()), from_raw_parts(ptr.add(b.start), b.len())) }
+    }
+
+    /// Returns the elements as two mutable slices, the front part and the wrapped part
+    /// - the second slice is empty if the elements are contiguous
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (a, b) = self.slice_ranges(0, self.len());
+        let ptr = self.ptr();
+        unsafe { (from_raw_parts_mut(ptr.add(a.start), a.len()), from_raw_parts_mut(ptr.add(b.start), b.len())) }
+    }
+
+    /// Rearranges the buffer so the elements are contiguous, returns them as slice
+    /// - does not allocate, this is `O(capacity)` operation if the elements wrap around
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len() > self.capacity() {
+            let buf = unsafe { from_raw_parts_mut(self.ptr() as *mut MaybeUninit<T>, self.capacity()) };
+            buf.rotate_left(self.head);
+            self.head = 0;
+        }
+
+        unsafe { from_raw_parts_mut(self.ptr().add(self.head), self.len()) }
+    }
+
+    /// Rotates the queue `n` places to the left, the element at `n` becomes the first one
+    /// - moves `min(n, len - n)` elements, does not allocate
+    /// - **panics** if `n > self.len()`
+    pub fn rotate_left(&mut self, n: usize) {
+        self.check_rotation(n);
+
+        let k = self.len() - n;
+        if n <= k {
+            unsafe { self.rotate_left_inner(n) };
+        } else {
+            unsafe { self.rotate_right_inner(k) };
+        }
+    }
+
+    /// Rotates the queue `n` places to the right, the element at `len - n` becomes the first one
+    /// - moves `min(n, len - n)` elements, does not allocate
+    /// - **panics** if `n > self.len()`
+    pub fn rotate_right(&mut self, n: usize) {
+        self.check_rotation(n);
+
+        let k = self.len() - n;
+        if n <= k {
+            unsafe { self.rotate_right_inner(n) };
+        } else {
+            unsafe { self.rotate_left_inner(k) };
+        }
+    }
+
+
+    /// Returns front-to-back iterator
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        Iter::new(a, b)
+    }
+
+    /// Returns front-to-back iterator that returns mutable references
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (a, b) = self.as_mut_slices();
+        IterMut::new(a, b)
+    }
+
+    /// Returns iterator over the elements in `range`
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    pub fn range<R>(&self, range: R) -> Iter<'_, T>
+    where R: RangeBounds<usize> {
+        let (start, end) = self.checked_range(&range);
+        let (a, b) = self.slice_ranges(start, end);
+        let ptr = self.ptr();
+
+        unsafe { Iter::new(from_raw_parts(ptr.add(a.start), a.len()), from_raw_parts(ptr.add(b.start), b.len())) }
+    }
+
+    /// Returns iterator over the elements in `range` that returns mutable references
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    pub fn range_mut<R>(&mut self, range: R) -> IterMut<'_, T>
+    where R: RangeBounds<usize> {
+        let (start, end) = self.checked_range(&range);
+        let (a, b) = self.slice_ranges(start, end);
+        let ptr = self.ptr();
+
+        unsafe { IterMut::new(from_raw_parts_mut(ptr.add(a.start), a.len()), from_raw_parts_mut(ptr.add(b.start), b.len())) }
+    }
+
+    /// Removes the elements in `range` and returns them as iterator
+    /// - the range is removed even if the iterator is not iterated whole, the elements left are dropped
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, STEP, ALIGN, A>
+    where R: RangeBounds<usize> {
+        let (start, end) = self.checked_range(&range);
+        Drain::new(self, start, end)
+    }
+
+
+    /// Returns pointer to the buffer
+    /// - unlike `DynamicBuffer::as_ptr`, the pointer is aligned for `T` even if no data is allocated
+    #[inline(always)]
+    fn ptr(&self) -> *mut T {
+        match self.buf.has_data() {
+            true => self.buf.as_ptr(),
+            false => NonNull::dangling().as_ptr(),
+        }
+    }
+
+    /// Returns physical index of the element at `index`
+    /// - `index` must be less than the capacity
+    #[inline(always)]
+    const fn phys(&self, index: usize) -> usize {
+        let i = self.head + index;
+        if i >= self.capacity() { i - self.capacity() } else { i }
+    }
+
+    /// Returns physical index of the slot before the first element
+    #[inline(always)]
+    const fn before_head(&self) -> usize {
+        if self.head == 0 { self.capacity() - 1 } else { self.head - 1 }
+    }
+
+    #[inline(always)]
+    const fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    #[inline(always)]
+    unsafe fn set_len(&mut self, len: usize) {
+        self.buf.size = len as u32;
+    }
+
+    /// Returns physical ranges of the elements `start..end`
+    fn slice_ranges(&self, start: usize, end: usize) -> (Range<usize>, Range<usize>) {
+        if start == end {
+            return (0..0, 0..0);
+        }
+
+        let first = self.phys(start);
+        let len = end - start;
+        let to_end = self.capacity() - first;
+
+        if len <= to_end {
+            (first..first + len, 0..0)
+        } else {
+            (first..self.capacity(), 0..len - to_end)
+        }
+    }
+
+    /// Copies `count` elements from logical index `src` to `dst`, the ranges may overlap
+    /// - safety: both ranges must be in bounds of the buffer
+    unsafe fn copy_logical(&mut self, src: usize, dst: usize, count: usize) {
+        let ptr = self.ptr();
+        let copy = |i: usize| unsafe { copy_nonoverlapping(ptr.add(self.phys(src + i)), ptr.add(self.phys(dst + i)), 1) };
+
+        if dst < src {
+            (0..count).for_each(copy);
+        } else {
+            (0..count).rev().for_each(copy);
+        }
+    }
+
+    /// Moves the wrapped elements after the buffer grew from `old` elements
+    /// - the allocator copies the whole old buffer, the part that wrapped around has to follow the front part again
+    unsafe fn handle_capacity_increase(&mut self, old: usize) {
+        let len = self.len();
+        if self.head + len <= old {
+            return;
+        }
+
+        let front_len = old - self.head;
+        let back_len = len - front_len;
+        let ptr = self.ptr();
+
+        unsafe {
+            if back_len < front_len && back_len <= self.capacity() - old {
+                //  the wrapped part goes right after the old end
+                copy_nonoverlapping(ptr, ptr.add(old), back_len);
+            } else {
+                //  the front part goes to the new end
+                let head = self.capacity() - front_len;
+                ptr::copy(ptr.add(self.head), ptr.add(head), front_len);
+                self.head = head;
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn grow(&mut self) {
+        if let Err(e) = self.try_grow() {
+            panic!("failed to allocate memory: {}", e);
+        }
+    }
+
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn try_grow(&mut self) -> Result<(), TryReserveError> {
+        let old = self.capacity();
+        self.buf.try_expand()?;
+        unsafe { self.handle_capacity_increase(old) };
+        Ok(())
+    }
+
+    /// safety: the queue must not be full
+    #[inline(always)]
+    unsafe fn push_back_unchecked(&mut self, val: T) {
+        let len = self.len();
+        unsafe {
+            self.ptr().add(self.phys(len)).write(val);
+            self.set_len(len + 1);
+        }
+    }
+
+    /// safety: the queue must not be full
+    #[inline(always)]
+    unsafe fn push_front_unchecked(&mut self, val: T) {
+        self.head = self.before_head();
+        unsafe {
+            self.ptr().add(self.head).write(val);
+            self.set_len(self.len() + 1);
+        }
+    }
+
+    /// Moves `n` elements from the front to the back
+    /// - safety: `n` must not be greater than the length
+    unsafe fn rotate_left_inner(&mut self, n: usize) {
+        if self.is_full() {
+            self.head = self.phys(n);
+            return;
+        }
+
+        let ptr = self.ptr();
+        for _ in 0..n {
+            unsafe { copy_nonoverlapping(ptr.add(self.head), ptr.add(self.phys(self.len())), 1) };
+            self.head = self.phys(1);
+        }
+    }
+
+    /// Moves `n` elements from the back to the front
+    /// - safety: `n` must not be greater than the length
+    unsafe fn rotate_right_inner(&mut self, n: usize) {
+        if self.is_full() {
+            self.head = self.phys(self.len() - n);
+            return;
+        }
+
+        let ptr = self.ptr();
+        for _ in 0..n {
+            let last = self.phys(self.len() - 1);
+            self.head = self.before_head();
+            unsafe { copy_nonoverlapping(ptr.add(last), ptr.add(self.head), 1) };
+        }
+    }
+
+    /// **panics** if `index` is out of bounds
+    #[inline]
+    fn check_index(&self, index: usize) {
+        if index >= self.len() {
+            #[cfg(all(feature="allocator", feature="spin", feature="string"))]
+            panic_fmt!("index {index} out of bounds 0..{}", self.len());
+            #[cfg(not(all(feature="allocator", feature="spin", feature="string")))]
+            panic!("index is out of bounds");
+        }
+    }
+
+    /// **panics** if the queue cannot be rotated by `n`
+    #[inline]
+    fn check_rotation(&self, n: usize) {
+        if n > self.len() {
+            #[cfg(all(feature="allocator", feature="spin", feature="string"))]
+            panic_fmt!("cannot rotate {} elements by {n}", self.len());
+            #[cfg(not(all(feature="allocator", feature="spin", feature="string")))]
+            panic!("rotation is out of bounds");
+        }
+    }
+
+    /// Returns bounds of `range`
+    /// - **panics** if the range is out of bounds or its start is greater than its end
+    fn checked_range<R>(&self, range: &R) -> (usize, usize)
+    where R: RangeBounds<usize> {
+        let start = match range.start_bound() {
+            Excluded(&val) => val + 1,
+            Included(&val) => val,
+            Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Included(&val) => val + 1,
+            Excluded(&val) => val,
+            Unbounded => self.len(),
+        };
+
+        if start > end || end > self.len() {
+            #[cfg(all(feature="allocator", feature="spin", feature="string"))]
+            panic_fmt!("range {start}..{end} out of bounds 0..{}", self.len());
+            #[cfg(not(all(feature="allocator", feature="spin", feature="string")))]
+            panic!("range is out of bounds");
+        }
+
+        (start, end)
+    }
+}
+
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Drop for VecDeque<T, STEP, ALIGN, A> {
+    fn drop(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Index<usize> for VecDeque<T, STEP, ALIGN, A> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.check_index(index);
+        unsafe { &*self.ptr().add(self.phys(index)) }
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> IndexMut<usize> for VecDeque<T, STEP, ALIGN, A> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.check_index(index);
+        unsafe { &mut *self.ptr().add(self.phys(index)) }
+    }
+}
+
+impl<T: Sized + Clone, const STEP: usize, const ALIGN: usize, A: AllocRef + Clone> Clone for VecDeque<T, STEP, ALIGN, A> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn clone(&self) -> Self {
+        let mut deque = Self::with_capacity_in(self.len(), self.allocator().clone());
+        for item in self.iter() {
+            unsafe { deque.push_back_unchecked(item.clone()) };
+        }
+        deque
+    }
+}
+
+impl<T: Sized + Debug, const STEP: usize, const ALIGN: usize, A: AllocRef> Debug for VecDeque<T, STEP, ALIGN, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef + Default> Default for VecDeque<T, STEP, ALIGN, A> {
+    /// Equivalent of `VecDeque::new_in(A::default())`
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<T, U, const SSTEP: usize, const OSTEP: usize, const SALIGN: usize, const OALIGN: usize, SA: AllocRef, OA: AllocRef>
+    PartialEq<VecDeque<U, OSTEP, OALIGN, OA>> for VecDeque<T, SSTEP, SALIGN, SA>
+    where T: PartialEq<U> {
+    fn eq(&self, other: &VecDeque<U, OSTEP, OALIGN, OA>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T, U, const STEP: usize, const N: usize, const ALIGN: usize, A: AllocRef> PartialEq<[U; N]> for VecDeque<T, STEP, ALIGN, A>
+    where T: PartialEq<U> {
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.len() == N && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T: Sized + Eq, const STEP: usize, const ALIGN: usize, A: AllocRef> Eq for VecDeque<T, STEP, ALIGN, A> {}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> Extend<T> for VecDeque<T, STEP, ALIGN, A> {
+    /// Appends the elements to the back of the queue
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize> FromIterator<T> for VecDeque<T, STEP, ALIGN> {
+    /// **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T: Sized, const STEP: usize, const N: usize, const ALIGN: usize> From<[T; N]> for VecDeque<T, STEP, ALIGN> {
+    /// **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    fn from(value: [T; N]) -> Self {
+        let mut deque = Self::with_capacity(N);
+        for item in value {
+            unsafe { deque.push_back_unchecked(item) };
+        }
+        deque
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> From<Vec<T, STEP, ALIGN, A>> for VecDeque<T, STEP, ALIGN, A> {
+    /// Turns the vector into a queue, does not allocate nor move the elements
+    #[inline]
+    fn from(value: Vec<T, STEP, ALIGN, A>) -> Self {
+        Self { buf: value.into_dynamic_buffer(), head: 0 }
+    }
+}
+
+impl<T: Sized, const STEP: usize, const ALIGN: usize, A: AllocRef> From<VecDeque<T, STEP, ALIGN, A>> for Vec<T, STEP, ALIGN, A> {
+    /// Turns the queue into a vector, does not allocate
+    /// - the elements are moved to the start of the buffer if needed
+    fn from(mut value: VecDeque<T, STEP, ALIGN, A>) -> Self {
+        value.make_contiguous();
+
+        let value = ManuallyDrop::new(value);
+        unsafe {
+            if value.head != 0 {
+                let ptr = value.ptr();
+                ptr::copy(ptr.add(value.head), ptr, value.len());
+            }
+            Vec::from_dynamic_buffer(ptr::read(&value.buf))
+        }
+    }
+}
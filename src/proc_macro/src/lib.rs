@@ -136,6 +136,9 @@ pub fn entry(attr: TokenStream, input: TokenStream) -> TokenStream {
 }*/
 
 
+/// Defines the last-resort out-of-memory handler
+/// - the handler is called only with the `oom-handler` feature of `ministd`
+/// - it runs after all reclaim handlers registered in `ministd::alloc::oom` failed
 #[proc_macro_attribute]
 pub fn oom(attr: TokenStream, input: TokenStream) -> TokenStream {
 
@@ -5,7 +5,8 @@
 //! Provides the default text renderer for the `ministd` library through external crate (`lib/renderer`)
 
 
-use spin::{Mutex, MutexGuard};
+use spin::Mutex;
+use crate::sync::IrqMutexGuard;
 use super::Color;
 use super::font;
 extern crate renderer;
@@ -283,5 +284,5 @@ impl Renderer {
 /// - classic [`core::fmt::Display`] should be prefered
 pub trait Render {
     fn render(&self);
-    fn render_locked<'l>(&self, guard: &'l mut MutexGuard<Renderer>);
+    fn render_locked<'l>(&self, guard: &'l mut IrqMutexGuard<Renderer>);
 }
\ No newline at end of file
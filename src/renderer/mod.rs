@@ -1,7 +1,7 @@
 pub mod font;
 
 #[cfg(feature="spin")]
-use crate::Mutex;
+use crate::sync::IrqMutex;
 extern crate renderer;
 pub use renderer::{MinistdRenderer, RendererStatus, Color};
 
@@ -14,5 +14,7 @@ pub use default_renderer::Renderer;
 #[cfg(not(feature = "default-renderer"))]
 pub use renderer::Renderer;
 
+/// The renderer used by the `print!` family of macros
+/// - interrupts are disabled while the lock is held, so interrupt handlers can print
 #[cfg(feature="spin")]
-pub static RENDERER: Mutex<Renderer> = Mutex::new(Renderer::new());
\ No newline at end of file
+pub static RENDERER: IrqMutex<Renderer> = IrqMutex::new(Renderer::new());
\ No newline at end of file
@@ -11,9 +11,9 @@ use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::Deref;
 use core::panic::UnwindSafe;
 use core::pin::Pin;
-use core::ptr::{drop_in_place, NonNull};
+use core::ptr::{self, drop_in_place, NonNull};
 use core::sync::atomic::{AtomicUsize, Ordering};
-use crate::alloc::{ALLOCATOR, GlobalAlloc};
+use crate::mem::alloc::{AllocError, AllocRef, Global};
 
 
 /// A thread-safe reference-counting pointer. ‘Arc’ stands for ‘Atomically Reference Counted’.
@@ -22,214 +22,271 @@ use crate::alloc::{ALLOCATOR, GlobalAlloc};
 /// - both `Arc` and `Weak` does keep the allocation
 ///   - if `strong` counter is zero, the value is dropped
 ///   - if both `strong` and `weak` counters are zero, the value is deallocated
-pub struct Arc<T: Sized> {
+/// - the allocation is made by the allocator `A` (see [`AllocRef`])
+pub struct Arc<T: Sized, A: AllocRef = Global> {
     ptr: NonNull<ArcInner<T>>,
+    alloc: A,
 }
 
 impl<T: Sized> Arc<T> {
 
     /// Constructs and allocates new `Arc`
     /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new(data: T) -> Self {
-
-        let ptr =  unsafe {
-            ALLOCATOR.allocate(ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(0),
-                data,
-            })
-        };
-
-        let ptr = match ptr {
-            Ok(p) => p,
-            Err(_) => panic!("Arc: allocation failed"),
-        };
-
-        Self { ptr }
+        Self::new_in(data, Global)
     }
 
     /// Tries to contruct and allocate new `Arc`
     /// - return `Err` if allocation fails
-    pub fn try_new(data: T) -> Result<Self, ()> {
-
-        Ok(Self {
-            ptr: unsafe {
-                ALLOCATOR.allocate(ArcInner {
-                    strong: AtomicUsize::new(1),
-                    weak: AtomicUsize::new(0),
-                    data
-                })
-            }.map_err(|inner| drop(inner) )?
-        })
-
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new(data: T) -> Result<Self, AllocError> {
+        Self::try_new_in(data, Global)
     }
 
     /// Constructs and allocates new `Arc` with uninitialized content
     /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
     pub fn new_uninit() -> Arc<MaybeUninit<T>> {
-        let a = unsafe {
-            ALLOCATOR.allocate(ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(0),
-                data: MaybeUninit::uninit()
-            })
-        };
+        Self::new_uninit_in(Global)
+    }
 
-        let ptr = match a {
-            Ok(p) => p,
-            Err(_) => panic!("Arc: allocation failed"),
-        };
+    /// Tries to construct and allocate new `Arc` with uninitialize content
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit() -> Result<Arc<MaybeUninit<T>>, AllocError> {
+        Self::try_new_uninit_in(Global)
+    }
 
-        Arc { ptr }
+    /// Constructs and allocates new `Arc` with zeroed content
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed() -> Arc<MaybeUninit<T>> {
+        Self::new_zeroed_in(Global)
     }
 
-    /// Tries to construct and allocate new `Arc` with uninitialize content
+
+    /// Tries to construct and allocate new `Arc` with zeroed content
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed() -> Result<Arc<MaybeUninit<T>>, AllocError> {
+        Self::try_new_zeroed_in(Global)
+    }
+
+    /// Constructs a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
     /// - **panics** if allocation fails
-    pub fn try_new_uninit() -> Result<Arc<MaybeUninit<T>>, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_cyclic<F>(data_fn: F) -> Self
+    where F: FnOnce(&Weak<T>) -> T {
+        Self::new_cyclic_in(data_fn, Global)
+    }
 
-        Ok(Arc {
-            ptr: unsafe {
-                ALLOCATOR.allocate(ArcInner {
-                    strong: AtomicUsize::new(1),
-                    weak: AtomicUsize::new(0),
-                    data: MaybeUninit::uninit()
-                })
-            }.map_err(|inner| drop(inner) )?
-        })
+    /// Tries to construct a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_cyclic<F>(data_fn: F) -> Result<Self, AllocError>
+    where F: FnOnce(&Weak<T>) -> T {
+        Self::try_new_cyclic_in(data_fn, Global)
+    }
 
+    /// Tries to construct a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
+    /// - closure is allowed to return an error, which is passed to the caller
+    /// - returns `Err` if allocation fails (the `AllocError` is converted into `E`)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_cyclic_result<F, E>(data_fn: F) -> Result<Self, E>
+    where F: FnOnce(&Weak<T>) -> Result<T, E>, E: From<AllocError> {
+        Self::try_new_cyclic_result_in(data_fn, Global)
     }
 
-    /// Constructs and allocates new `Arc` with zeroed content
-    pub fn new_zeroed() -> Arc<MaybeUninit<T>> {
-        let p = unsafe {
-            ALLOCATOR.alloc_zeroed(ArcInner::<T>::layout())
-            as *mut ArcInner<MaybeUninit<T>>
-        };
 
-        match NonNull::new(p) {
-            Some(mut ptr) => {
-                unsafe {
-                    ptr.as_mut().strong = AtomicUsize::new(1);
-                    ptr.as_mut().weak = AtomicUsize::new(0);
-                }
-                Arc { ptr }
-            },
-            None => panic!("Arc: allocation failed"),
+    pub fn pin(data: T) -> Pin<Arc<T>> {
+        unsafe { Pin::new_unchecked(Arc::new(data)) }
+    }
+
+
+    /// Constructs an `Arc<T>` from a raw pointer.
+    /// - the raw pointer must have been previously returned by a call to `Arc<T>::into_raw`
+    pub const unsafe fn from_raw(ptr: NonNull<T>) -> Self {
+        Self {
+            ptr: unsafe { ptr.cast::<usize>().offset(-2) }.cast(),
+            alloc: Global,
         }
     }
 
 
-    /// Tries to construct and allocate new `Arc` with zeroed content
-    pub fn try_new_zeroed() -> Result<Arc<MaybeUninit<T>>, ()> {
+    /// Consumes the `Arc`, returning the wrapped pointer
+    /// - to avoid memory leak, the pointer must be converted back to an `Arc` using `Arc::from_raw`
+    pub fn into_raw(self) -> NonNull<T> {
+        let this = ManuallyDrop::new(self);
+        unsafe {
+            NonNull::new_unchecked(this.inner().data_as_mut_ptr())
+        }
+    }
 
-        Ok(Arc {
-            ptr: unsafe {
+    /// Increments the `strong` reference count on the `Arc<T>` associated with the provided pointer by one
+    /// - the pointer must have been obtained through `Arc::into_raw`
+    #[inline]
+    pub unsafe fn increment_strong_count(ptr: NonNull<T>) {
+        unsafe {
+            let inner = (ptr.cast::<usize>().offset(-2)).cast::<ArcInner<T>>();
+            inner.as_ref().strong.fetch_add(1, Ordering::Acquire);
+        }
+    }
 
-                let mut ptr = NonNull::new(ALLOCATOR.alloc_zeroed(ArcInner::<T>::layout())
-                    as *mut ArcInner<MaybeUninit<T>>).ok_or(())?;
+    /// Decrements the `strong` reference count on the `Arc<T>` associated with the provided pointer by one
+    /// - the pointer must have been obtained through `Arc::into_raw`
+    #[inline]
+    pub unsafe fn decrement_strong_count(ptr: NonNull<T>) {
+        unsafe {
+            let inner = (ptr.cast::<usize>().offset(-2)).cast::<ArcInner<T>>();
+            inner.as_ref().strong.fetch_sub(1, Ordering::Acquire);
+        }
+    }
 
-                ptr.as_mut().strong = AtomicUsize::new(1);
-                ptr.as_mut().weak = AtomicUsize::new(0);
+}
 
-                ptr
-                
-            }
-        })
+impl<T: Sized, A: AllocRef> Arc<T, A> {
 
+    /// Constructs and allocates new `Arc` in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_in(data: T, alloc: A) -> Self {
+        match Self::try_new_in(data, alloc) {
+            Ok(arc) => arc,
+            Err(e) => panic!("Arc: {}", e),
+        }
     }
 
-    /// Constructs a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
-    /// - **panics** if allocation fails
-    pub fn new_cyclic<F>(data_fn: F) -> Self
-    where F: FnOnce(&Weak<T>) -> T {
+    /// Tries to contruct and allocate new `Arc` in `alloc`
+    /// - return `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_in(data: T, alloc: A) -> Result<Self, AllocError> {
 
-        let mut ptr = unsafe {
-            ALLOCATOR.allocate::<ArcInner<MaybeUninit<T>>>(ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(0),
-                data: MaybeUninit::uninit(),
-            })
-        }.map_err(|inner| drop(inner) ).expect("Arc: allocation failed");
+        let ptr = alloc.allocate(ArcInner::<T>::layout())?.cast::<ArcInner<T>>();
 
         unsafe {
-            let weak = Weak::new_from_inner(ptr.cast::<ArcInner<T>>().as_ref());
-
-            ptr.as_mut().data.write(data_fn(&weak));
+            ptr.write(ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(0),
+                data
+            });
         }
 
-        Self { ptr: ptr.cast::<ArcInner<T>>() }
+        Ok(Self { ptr, alloc })
 
     }
 
-    /// Tries to construct a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
+    /// Constructs and allocates new `Arc` with uninitialized content in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_uninit_in(alloc: A) -> Arc<MaybeUninit<T>, A> {
+        match Self::try_new_uninit_in(alloc) {
+            Ok(arc) => arc,
+            Err(e) => panic!("Arc: {}", e),
+        }
+    }
+
+    /// Tries to construct and allocate new `Arc` with uninitialize content in `alloc`
     /// - returns `Err` if allocation fails
-    pub fn try_new_cyclic<F>(data_fn: F) -> Result<Self, ()>
-    where F: FnOnce(&Weak<T>) -> T {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_uninit_in(alloc: A) -> Result<Arc<MaybeUninit<T>, A>, AllocError> {
+
+        let ptr = alloc.allocate(ArcInner::<T>::layout())?.cast::<ArcInner<MaybeUninit<T>>>();
 
-        let mut ptr = unsafe {
-            ALLOCATOR.allocate::<ArcInner<MaybeUninit<T>>>(ArcInner {
+        unsafe {
+            ptr.write(ArcInner {
                 strong: AtomicUsize::new(1),
                 weak: AtomicUsize::new(0),
-                data: MaybeUninit::uninit(),
-            })
-        }.map_err(|inner| drop(inner) )?;
+                data: MaybeUninit::uninit()
+            });
+        }
 
-        unsafe {
-            let weak = Weak::new_from_inner(ptr.cast::<ArcInner<T>>().as_ref());
+        Ok(Arc { ptr, alloc })
+
+    }
+
+    /// Constructs and allocates new `Arc` with zeroed content in `alloc`
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_zeroed_in(alloc: A) -> Arc<MaybeUninit<T>, A> {
+        match Self::try_new_zeroed_in(alloc) {
+            Ok(arc) => arc,
+            Err(e) => panic!("Arc: {}", e),
+        }
+    }
+
+    /// Tries to construct and allocate new `Arc` with zeroed content in `alloc`
+    /// - returns `Err` if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_zeroed_in(alloc: A) -> Result<Arc<MaybeUninit<T>, A>, AllocError> {
 
-            ptr.as_mut().data.write(data_fn(&weak));
+        let mut ptr = alloc.allocate_zeroed(ArcInner::<T>::layout())?.cast::<ArcInner<MaybeUninit<T>>>();
+
+        unsafe {
+            ptr.as_mut().strong = AtomicUsize::new(1);
+            ptr.as_mut().weak = AtomicUsize::new(0);
         }
 
-        Ok(Self { ptr: ptr.cast::<ArcInner<T>>() })
+        Ok(Arc { ptr, alloc })
 
     }
 
-    /// Tries to construct a new `Arc<T>` while giving you a `Weak<T>` to the allocation, to allow you to construct a `T` which holds a weak pointer to itself
-    /// - closure is allowed to return an error
+    /// Constructs a new `Arc<T>` in `alloc` while giving you a `Weak<T>` to the allocation
+    /// - **panics** if allocation fails
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn new_cyclic_in<F>(data_fn: F, alloc: A) -> Self
+    where F: FnOnce(&Weak<T, A>) -> T, A: Clone {
+        match Self::try_new_cyclic_in(data_fn, alloc) {
+            Ok(arc) => arc,
+            Err(e) => panic!("Arc: {}", e),
+        }
+    }
+
+    /// Tries to construct a new `Arc<T>` in `alloc` while giving you a `Weak<T>` to the allocation
     /// - returns `Err` if allocation fails
-    pub fn try_new_cyclic_result<F>(data_fn: F) -> Result<Self, ()>
-    where F: FnOnce(&Weak<T>) -> Result<T, ()> {
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_cyclic_in<F>(data_fn: F, alloc: A) -> Result<Self, AllocError>
+    where F: FnOnce(&Weak<T, A>) -> T, A: Clone {
+        Self::try_new_cyclic_result_in(|weak| Ok::<T, AllocError>(data_fn(weak)), alloc)
+    }
 
-        let mut ptr = unsafe {
-            ALLOCATOR.allocate::<ArcInner<MaybeUninit<T>>>(ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(0),
-                data: MaybeUninit::uninit(),
-            })
-        }.map_err(|inner| drop(inner) )?;
+    /// Tries to construct a new `Arc<T>` in `alloc` while giving you a `Weak<T>` to the allocation
+    /// - closure is allowed to return an error, which is passed to the caller
+    /// - returns `Err` if allocation fails (the `AllocError` is converted into `E`)
+    #[cfg_attr(feature = "alloc-tracking", track_caller)]
+    pub fn try_new_cyclic_result_in<F, E>(data_fn: F, alloc: A) -> Result<Self, E>
+    where F: FnOnce(&Weak<T, A>) -> Result<T, E>, E: From<AllocError>, A: Clone {
+
+        let uninit = Self::try_new_uninit_in(alloc)?;
+        let mut this = ManuallyDrop::new(uninit);
 
         unsafe {
-            let weak = Weak::new_from_inner(ptr.cast::<ArcInner<T>>().as_ref());
+            let weak = Weak::new_from_inner(this.ptr.cast::<ArcInner<T>>().as_ref(), this.alloc.clone());
 
             let val = match data_fn(&weak) {
                 Ok(v) => v,
-                Err(_) => {
-                    ALLOCATOR.dealloc(ptr.as_ptr() as *mut u8, ArcInner::<T>::layout());
-                    return Err(());
+                Err(e) => {
+                    let alloc = ptr::read(&this.alloc);
+                    alloc.deallocate(this.ptr.cast(), ArcInner::<T>::layout());
+                    return Err(e);
                 }
             };
 
-            ptr.as_mut().data.write(val);
-        }
-
-        Ok(Self { ptr: ptr.cast::<ArcInner<T>>() })
-
-    }
+            this.inner_mut().data.write(val);
 
+            Ok(ManuallyDrop::into_inner(this).assume_init())
+        }
 
-    pub fn pin(data: T) -> Pin<Arc<T>> {
-        unsafe { Pin::new_unchecked(Arc::new(data)) }
     }
 
 
     /// Constructs new `Arc` from an inner value
     /// - increases `strong` counter
-    pub(crate) fn from_inner<'l>(inner: &'l ArcInner<T>) -> Self
+    pub(crate) fn from_inner<'l>(inner: &'l ArcInner<T>, alloc: A) -> Self
     where Self: 'l {
         
         inner.strong.fetch_add(1, Ordering::Acquire);
         Self {
             ptr: NonNull::from(inner),
+            alloc,
         }
     }
 
@@ -244,45 +301,6 @@ impl<T: Sized> Arc<T> {
     }
 
 
-    /// Constructs an `Arc<T>` from a raw pointer.
-    /// - the raw pointer must have been previously returned by a call to `Arc<T>::into_raw`
-    pub const unsafe fn from_raw(ptr: NonNull<T>) -> Self {
-        Self {
-            ptr: unsafe { ptr.cast::<usize>().offset(-2) }.cast()
-        }
-    }
-
-
-    /// Consumes the `Arc`, returning the wrapped pointer
-    /// - to avoid memory leak, the pointer must be converted back to an `Arc` using `Arc::from_raw`
-    pub fn into_raw(self) -> NonNull<T> {
-        let this = ManuallyDrop::new(self);
-        unsafe {
-            NonNull::new_unchecked(this.inner().data_as_mut_ptr())
-        }
-    }
-
-    /// Increments the `strong` reference count on the `Arc<T>` associated with the provided pointer by one
-    /// - the pointer must have been obtained through `Arc::into_raw`
-    #[inline]
-    pub unsafe fn increment_strong_count(ptr: NonNull<T>) {
-        unsafe {
-            let inner = (ptr.cast::<usize>().offset(-2)).cast::<ArcInner<T>>();
-            inner.as_ref().strong.fetch_add(1, Ordering::Acquire);
-        }
-    }
-
-    /// Decrements the `strong` reference count on the `Arc<T>` associated with the provided pointer by one
-    /// - the pointer must have been obtained through `Arc::into_raw`
-    #[inline]
-    pub unsafe fn decrement_strong_count(ptr: NonNull<T>) {
-        unsafe {
-            let inner = (ptr.cast::<usize>().offset(-2)).cast::<ArcInner<T>>();
-            inner.as_ref().strong.fetch_sub(1, Ordering::Acquire);
-        }
-    }
-
-
 
     /// Returns the `Weak` reference count
     #[inline]
@@ -298,26 +316,34 @@ impl<T: Sized> Arc<T> {
 
     /// Returns whether these two pointers has the same allocation
     #[inline]
-    pub fn ptr_eq(&self, other: &Arc<T>) -> bool {
+    pub fn ptr_eq(&self, other: &Arc<T, A>) -> bool {
         self.ptr == other.ptr
     }
 
     /// Creates new `Weak` pointer to this allocation
     #[inline]
-    pub fn downgrade(&self) -> Weak<T> {
-        Weak::new_from_inner(self.inner())
+    pub fn downgrade(&self) -> Weak<T, A>
+    where A: Clone {
+        Weak::new_from_inner(self.inner(), self.alloc.clone())
+    }
+
+    /// Returns reference to the allocator
+    #[inline]
+    pub const fn allocator(&self) -> &A {
+        &self.alloc
     }
     
     
 }
 
-impl<T: Sized> Arc<MaybeUninit<T>> {
+impl<T: Sized, A: AllocRef> Arc<MaybeUninit<T>, A> {
 
     /// Converts to `Arc<T>`
-    pub unsafe fn assume_init(self) -> Arc<T> {
+    pub unsafe fn assume_init(self) -> Arc<T, A> {
         let this = ManuallyDrop::new(self);
         Arc {
             ptr: this.ptr.cast(),
+            alloc: unsafe { ptr::read(&this.alloc) },
         }
     }
 
@@ -326,21 +352,21 @@ impl<T: Sized> Arc<MaybeUninit<T>> {
 
 
 
-impl<T: Sized> AsRef<T> for Arc<T> {
+impl<T: Sized, A: AllocRef> AsRef<T> for Arc<T, A> {
     #[inline(always)]
     fn as_ref(&self) -> &T {
         self.inner().get_ref()
     }
 }
 
-impl<T: Sized> Borrow<T> for Arc<T> {
+impl<T: Sized, A: AllocRef> Borrow<T> for Arc<T, A> {
     #[inline(always)]
     fn borrow(&self) -> &T {
         self.inner().get_ref()
     }
 }
 
-impl<T: Sized> Clone for Arc<T> {
+impl<T: Sized, A: AllocRef + Clone> Clone for Arc<T, A> {
     /// Makes a clone of the Arc pointer.
     /// - this creates another pointer to the same allocation, increasing the strong reference count.
     #[inline]
@@ -348,25 +374,26 @@ impl<T: Sized> Clone for Arc<T> {
         self.inner().strong.fetch_add(1, Ordering::Acquire);
         Self {
             ptr: self.ptr,
+            alloc: self.alloc.clone(),
         }
     }
 }
 
-impl<T: Sized + Debug> Debug for Arc<T> {
+impl<T: Sized + Debug, A: AllocRef> Debug for Arc<T, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}", self.inner().data)
     }
 }
 
-impl<T: Sized + Default> Default for Arc<T> {
+impl<T: Sized + Default, A: AllocRef + Default> Default for Arc<T, A> {
     #[inline]
     fn default() -> Self {
-        Arc::new(T::default())
+        Arc::new_in(T::default(), A::default())
     }
 }
 
-impl<T: Sized> Deref for Arc<T> {
+impl<T: Sized, A: AllocRef> Deref for Arc<T, A> {
     type Target = T;
 
     #[inline]
@@ -375,14 +402,14 @@ impl<T: Sized> Deref for Arc<T> {
     }
 }
 
-impl<T: Sized + Display> Display for Arc<T> {
+impl<T: Sized + Display, A: AllocRef> Display for Arc<T, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.inner().data)
     }
 }
 
-impl<T: Sized> Drop for Arc<T> {
+impl<T: Sized, A: AllocRef> Drop for Arc<T, A> {
     fn drop(&mut self) {
         
         let inner = self.inner();
@@ -397,7 +424,7 @@ impl<T: Sized> Drop for Arc<T> {
             if weak == 1 {
                 //  no other weak references => deallocate
                 unsafe {
-                    ALLOCATOR.dealloc(self.ptr.as_ptr() as *mut u8, ArcInner::<T>::layout());
+                    self.alloc.deallocate(self.ptr.cast(), ArcInner::<T>::layout());
                 }
             }
 
@@ -418,25 +445,25 @@ impl<T: Sized + Clone> From<&T> for Arc<T> {
     }
 }
 
-impl<T: Sized + Hash> Hash for Arc<T> {
+impl<T: Sized + Hash, A: AllocRef> Hash for Arc<T, A> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.inner().data.hash(state);
     }
 }
 
-impl<T: Sized + Ord> Ord for Arc<T> {
+impl<T: Sized + Ord, A: AllocRef> Ord for Arc<T, A> {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.inner().data.cmp(other)
     }
 }
 
-impl<T: Sized + PartialOrd> PartialOrd for Arc<T> {
+impl<T: Sized + PartialOrd, A: AllocRef> PartialOrd for Arc<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         self.inner().data.partial_cmp(other)
     }
 }
 
-impl<T: Sized + PartialEq> PartialEq for Arc<T> {
+impl<T: Sized + PartialEq, A: AllocRef> PartialEq for Arc<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.inner().data.eq(other)
     }
@@ -445,11 +472,18 @@ impl<T: Sized + PartialEq> PartialEq for Arc<T> {
     }
 }
 
-impl<T: Sized + Eq> Eq for Arc<T> {}
+impl<T: Sized + Eq, A: AllocRef> Eq for Arc<T, A> {}
+
+impl<T: Sized, A: AllocRef> Unpin for Arc<T, A> {}
+
+impl<T: Sized, A: AllocRef> UnwindSafe for Arc<T, A> {}
 
-impl<T: Sized> Unpin for Arc<T> {}
+//  the counters are atomic, so the pointers can be shared like the `ArcInner` itself
+unsafe impl<T: Sized + Sync + Send, A: AllocRef + Send> Send for Arc<T, A> {}
+unsafe impl<T: Sized + Sync + Send, A: AllocRef + Sync> Sync for Arc<T, A> {}
 
-impl<T: Sized> UnwindSafe for Arc<T> {}
+unsafe impl<T: Sized + Sync + Send, A: AllocRef + Send> Send for Weak<T, A> {}
+unsafe impl<T: Sized + Sync + Send, A: AllocRef + Sync> Sync for Weak<T, A> {}
 
 
 
@@ -468,9 +502,9 @@ impl<T: Sized> UnwindSafe for Arc<T> {}
 ///   - however, `Weak` does not promise the value is initialized
 ///
 /// The allocation is accessed by calling `upgrade` on the `Weak` pointer, which returns an `Option<Arc<T>>`
-#[repr(transparent)]
-pub struct Weak<T: Sized> {
+pub struct Weak<T: Sized, A: AllocRef = Global> {
     ptr: NonNull<ArcInner<T>>,
+    alloc: A,
 }
 
 pub const fn dangling<T>() -> NonNull<T> {
@@ -487,9 +521,7 @@ impl<T: Sized> Weak<T> {
     /// Creates new `Weak` with no data
     /// - calling `upgrade()` on this will always return `None`
     pub const fn new() -> Self {
-        Self {
-            ptr: dangling(),
-        }
+        Self::new_in(Global)
     }
 
     /// Converts a raw pointer previously created by `into_raw` back into `Weak<T>`.
@@ -502,6 +534,7 @@ impl<T: Sized> Weak<T> {
 
             Self {
                 ptr: NonNull::new_unchecked(ptr),
+                alloc: Global,
             }
         }
     }
@@ -515,6 +548,19 @@ impl<T: Sized> Weak<T> {
         this.inner().data_as_ptr()
     }
 
+}
+
+impl<T: Sized, A: AllocRef> Weak<T, A> {
+
+    /// Creates new `Weak` with no data in `alloc`
+    /// - calling `upgrade()` on this will always return `None`
+    pub const fn new_in(alloc: A) -> Self {
+        Self {
+            ptr: dangling(),
+            alloc,
+        }
+    }
+
     /// Returns pointer to the allocated data
     #[inline]
     pub const fn as_ptr(&self) -> *const T {
@@ -522,11 +568,12 @@ impl<T: Sized> Weak<T> {
     }
 
     /// Attempts to upgrade `Weak` pointer to `Arc`
-    pub fn upgrade(&self) -> Option<Arc<T>> {
+    pub fn upgrade(&self) -> Option<Arc<T, A>>
+    where A: Clone {
         if is_dangling(self.ptr) {
             None
         } else {
-            Some(Arc::from_inner(self.inner()))
+            Some(Arc::from_inner(self.inner(), self.alloc.clone()))
         }
     }
 
@@ -543,14 +590,14 @@ impl<T: Sized> Weak<T> {
     }
 
     /// Returns whether these two pointers has the same allocation
-    pub fn ptr_eq(&self, other: &Weak<T>) -> bool {
+    pub fn ptr_eq(&self, other: &Weak<T, A>) -> bool {
         self.ptr == other.ptr
     }
 
 
     /// Adds one `Weak` pointer to allocation of the arc
-    pub(crate) fn new_from<'l>(arc: &'l Arc<T>) -> Self
-    where Self: 'l {
+    pub(crate) fn new_from<'l>(arc: &'l Arc<T, A>) -> Self
+    where Self: 'l, A: Clone {
 
         let inner = arc.inner();
 
@@ -558,16 +605,18 @@ impl<T: Sized> Weak<T> {
 
         Self {
             ptr: arc.ptr,
+            alloc: arc.alloc.clone(),
         }
     }
 
     /// Constructs new `Weak` and makes it point to the allocation
-    pub(crate) fn new_from_inner<'l>(inner: &'l ArcInner<T>) -> Self
+    pub(crate) fn new_from_inner<'l>(inner: &'l ArcInner<T>, alloc: A) -> Self
     where Self: 'l {
         inner.weak.fetch_add(1, Ordering::Acquire);
 
         Self {
             ptr: NonNull::from(inner),
+            alloc,
         }
     }
 
@@ -577,31 +626,31 @@ impl<T: Sized> Weak<T> {
 
 }
 
-impl<T: Sized> Clone for Weak<T> {
+impl<T: Sized, A: AllocRef + Clone> Clone for Weak<T, A> {
     fn clone(&self) -> Self {
         let inner = self.inner();
         inner.weak.fetch_add(1, Ordering::Acquire);
-        Self { ptr: NonNull::from(inner) }
+        Self { ptr: NonNull::from(inner), alloc: self.alloc.clone() }
     }
 }
 
-impl<T: Sized + Debug> Debug for Weak<T> {
+impl<T: Sized + Debug, A: AllocRef> Debug for Weak<T, A> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}", self.inner().data)
     }
 }
 
-impl<T: Sized> Default for Weak<T> {
+impl<T: Sized, A: AllocRef + Default> Default for Weak<T, A> {
     /// Constructs new `Weak<T>` without allocating any memory
     /// - calling `upgrade` will return None
     #[inline(always)]
     fn default() -> Self {
-        Self { ptr: NonNull::dangling() }
+        Self { ptr: NonNull::dangling(), alloc: A::default() }
     }
 }
 
-impl<T: Sized> Drop for Weak<T> {
+impl<T: Sized, A: AllocRef> Drop for Weak<T, A> {
     fn drop(&mut self) {
         
         let inner = self.inner();
@@ -613,14 +662,14 @@ impl<T: Sized> Drop for Weak<T> {
             //  this is the last weak reference + no holding references
             //      => deallocate
             unsafe {
-                ALLOCATOR.dealloc(self.inner().as_mut_ptr() as *mut u8, ArcInner::<T>::layout());
+                self.alloc.deallocate(self.ptr.cast(), ArcInner::<T>::layout());
             }
         }
 
     }
 }
 
-impl<T: Sized> Deref for Weak<T> {
+impl<T: Sized, A: AllocRef> Deref for Weak<T, A> {
     type Target = T;
     #[inline]
     fn deref(&self) -> &Self::Target {
@@ -629,7 +678,7 @@ impl<T: Sized> Deref for Weak<T> {
 }
 
 
-impl<T: Sized> Borrow<T> for Weak<T> {
+impl<T: Sized, A: AllocRef> Borrow<T> for Weak<T, A> {
     #[inline]
     fn borrow(&self) -> &T {
         &self.inner().data
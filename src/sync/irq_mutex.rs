@@ -0,0 +1,162 @@
+//	sync/irq_mutex.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+
+//! Interrupt-safe spin lock
+//! - locking saves the interrupt flag and disables interrupts (through [`io::int::disable`]), then spins on the lock
+//! - dropping the guard releases the lock and enables interrupts again if they were enabled before
+//! - interrupt handlers can therefore use the same lock without deadlocking the CPU they interrupted
+//!
+//! # Example
+//! ```
+//! static COUNTER: IrqMutex<usize> = IrqMutex::new(0);
+//!
+//! *COUNTER.lock() += 1;   //  interrupts are disabled only while the guard lives
+//! ```
+//!
+//! [`io::int::disable`]: crate::io::int::disable
+
+
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+
+use crate::io::int;
+
+
+/// Spin lock that disables interrupts while it is held
+pub struct IrqMutex<T: ?Sized> {
+    inner: spin::Mutex<T>,
+}
+
+/// Guard of the [`IrqMutex`]
+/// - restores the interrupt flag when dropped
+pub struct IrqMutexGuard<'l, T: ?Sized + 'l> {
+    guard: ManuallyDrop<spin::MutexGuard<'l, T>>,
+    were_enabled: bool,
+}
+
+impl<T> IrqMutex<T> {
+
+    /// Constructs new unlocked `IrqMutex`
+    #[inline(always)]
+    pub const fn new(val: T) -> Self {
+        Self { inner: spin::Mutex::new(val) }
+    }
+
+    /// Consumes the mutex and returns the inner value
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+
+}
+
+impl<T: ?Sized> IrqMutex<T> {
+
+    /// Disables interrupts and locks the mutex
+    /// - spins until the lock is free
+    #[inline]
+    pub fn lock(&self) -> IrqMutexGuard<'_, T> {
+        let were_enabled = int::are_enabled();
+        int::disable();
+
+        IrqMutexGuard {
+            guard: ManuallyDrop::new(self.inner.lock()),
+            were_enabled,
+        }
+    }
+
+    /// Tries to lock the mutex
+    /// - returns `None` (and leaves interrupts untouched) if the mutex is locked
+    #[inline]
+    pub fn try_lock(&self) -> Option<IrqMutexGuard<'_, T>> {
+        let were_enabled = int::are_enabled();
+        int::disable();
+
+        match self.inner.try_lock() {
+            Some(guard) => Some(IrqMutexGuard { guard: ManuallyDrop::new(guard), were_enabled }),
+            None => {
+                if were_enabled {
+                    int::enable();
+                }
+                None
+            }
+        }
+    }
+
+    /// Checks if the mutex is locked
+    /// - the result may be outdated by the time it is used
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool {
+        self.inner.is_locked()
+    }
+
+    /// Returns mutable reference to the inner value
+    /// - no locking is needed, the borrow guarantees exclusive access
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Releases the lock without a guard
+    ///
+    /// # Safety
+    /// - the lock must be held by the current context and its guard must have been forgotten
+    /// - interrupts are not restored
+    #[inline(always)]
+    pub unsafe fn force_unlock(&self) {
+        unsafe { self.inner.force_unlock() }
+    }
+
+}
+
+impl<T: Default> Default for IrqMutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for IrqMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner.try_lock() {
+            Some(guard) => write!(f, "IrqMutex {{ data: {:?} }}", &*guard),
+            None => f.write_str("IrqMutex { <locked> }"),
+        }
+    }
+}
+
+
+impl<T: ?Sized> Deref for IrqMutexGuard<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: ?Sized> DerefMut for IrqMutexGuard<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for IrqMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Drop for IrqMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        //  the lock has to be released before interrupts are enabled again
+        unsafe { ManuallyDrop::drop(&mut self.guard); }
+
+        if self.were_enabled {
+            int::enable();
+        }
+    }
+}
@@ -4,10 +4,14 @@
 
 //! Provides the `Arc` smart pointer (without the `Weak` pointer) and re-exports all usefult strucutres from the `spin` crate
 //! - such as `Once`, `Lazy`, `Mutex` and `RwLock`
+//! - provides the interrupt-safe [`IrqMutex`] used by the heap and the renderer
 
 #[cfg(all(feature="allocator", feature="spin", feature="rc"))]
 mod arc;
 #[cfg(all(feature="allocator", feature="spin", feature="rc"))]
 pub use arc::Arc;
 
+mod irq_mutex;
+pub use irq_mutex::{IrqMutex, IrqMutexGuard};
+
 pub use spin::{Once, Lazy, Mutex, RwLock};
\ No newline at end of file
@@ -0,0 +1,208 @@
+//	tests/alloc.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::{ALLOCATOR, Box, Vec};
+use ministd::alloc::{oom, percpu};
+
+
+/// Serializes the tests, the caches and the registry are global
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn cpu0() -> usize {
+    0
+}
+
+std::thread_local! {
+    static CPU: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Each thread acts as one CPU, the id is set with `CPU`
+fn thread_cpu() -> usize {
+    CPU.with(|id| id.get())
+}
+
+
+#[test]
+fn stats_count_allocations() {
+    let _serial = serial();
+    let before = ALLOCATOR.stats();
+
+    let v: Vec<u64> = Vec::with_capacity(1024);
+    let during = ALLOCATOR.stats();
+    assert!(during.allocations > before.allocations);
+    assert!(during.allocated_bytes >= before.allocated_bytes + 1024 * 8);
+    assert!(during.peak_bytes >= during.allocated_bytes);
+    assert!(during.largest_free_block.is_some());
+
+    drop(v);
+    let after = ALLOCATOR.stats();
+    assert!(after.frees > during.frees);
+    assert_eq!(after.allocated_bytes, before.allocated_bytes);
+
+    let printed = std::format!("{}", after);
+    assert!(printed.starts_with("heap: "));
+}
+
+#[test]
+#[cfg(not(feature = "debug-heap"))]
+fn percpu_cache_keeps_small_objects() {
+    let _serial = serial();
+    let allocated = ALLOCATOR.allocated_bytes();
+
+    percpu::set_cpu_id_provider(cpu0);
+    assert!(percpu::enabled());
+
+    drop(Box::new(7u64));
+    assert!(percpu::cached_bytes() > 0);
+    //  cached objects are still allocated in the heap
+    assert!(ALLOCATOR.allocated_bytes() > allocated);
+
+    percpu::flush_all();
+    assert_eq!(percpu::cached_bytes(), 0);
+
+    percpu::disable();
+    assert!(!percpu::enabled());
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated);
+}
+
+#[test]
+#[cfg(feature = "debug-heap")]
+fn percpu_cache_is_bypassed_by_debug_heap() {
+    let _serial = serial();
+    let allocated = ALLOCATOR.allocated_bytes();
+
+    //  the debug heap has to see every free, so nothing is cached
+    percpu::set_cpu_id_provider(cpu0);
+    drop(Box::new(7u64));
+    assert_eq!(percpu::cached_bytes(), 0);
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated);
+
+    percpu::disable();
+}
+
+#[test]
+fn oom_handlers_run_by_priority() {
+    let _serial = serial();
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn low(_: Layout) -> usize {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        0
+    }
+    fn high(_: Layout) -> usize {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        0
+    }
+
+    let count = oom::count();
+    let low = oom::register("low", -1, low).unwrap();
+    let high = oom::register("high", 10, high).unwrap();
+    assert_eq!(oom::count(), count + 2);
+
+    let order: std::vec::Vec<_> = oom::handlers().map(|h| h.name).filter(|n| *n == "low" || *n == "high").collect();
+    assert_eq!(order, ["high", "low"]);
+
+    let invocations = ALLOCATOR.stats().oom_invocations;
+    let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+    assert!(unsafe { ALLOCATOR.alloc(layout) }.is_null());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    assert_eq!(ALLOCATOR.stats().oom_invocations, invocations + 1);
+
+    assert!(oom::unregister(high));
+    assert!(oom::unregister(low));
+    assert!(!oom::unregister(low));
+    assert_eq!(oom::count(), count);
+}
+
+#[test]
+fn oom_handlers_do_not_reenter() {
+    let _serial = serial();
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn nested(_: Layout) -> usize {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        //  fails as well, but must not run the handlers again
+        let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+        assert!(unsafe { ALLOCATOR.alloc(layout) }.is_null());
+        0
+    }
+
+    percpu::set_cpu_id_provider(cpu0);
+    let id = oom::register("nested", 0, nested).unwrap();
+
+    let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+    assert!(unsafe { ALLOCATOR.alloc(layout) }.is_null());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    oom::unregister(id);
+    percpu::disable();
+}
+
+#[test]
+fn oom_handlers_run_again_after_a_panic() {
+    let _serial = serial();
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn panicking(_: Layout) -> usize {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        panic!("reclaim failed");
+    }
+
+    let id = oom::register("panicking", 0, panicking).unwrap();
+
+    //  the second allocation runs the handler again
+    let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+    for _ in 0..2 {
+        assert!(std::panic::catch_unwind(|| unsafe { ALLOCATOR.alloc(layout) }).is_err());
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    oom::unregister(id);
+}
+
+#[test]
+fn oom_handlers_run_on_other_cpus_while_reclaiming() {
+    let _serial = serial();
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn spawning(_: Layout) -> usize {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        if thread_cpu() == 0 {
+            //  the handlers are running on CPU 0, CPU 1 still runs them for its own failure
+            std::thread::spawn(|| {
+                CPU.with(|id| id.set(1));
+                let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+                assert!(unsafe { ALLOCATOR.alloc(layout) }.is_null());
+            }).join().unwrap();
+        }
+        0
+    }
+
+    percpu::set_cpu_id_provider(thread_cpu);
+    let id = oom::register("spawning", 0, spawning).unwrap();
+
+    let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+    assert!(unsafe { ALLOCATOR.alloc(layout) }.is_null());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    oom::unregister(id);
+    percpu::disable();
+}
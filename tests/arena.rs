@@ -0,0 +1,111 @@
+//	tests/arena.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::Vec;
+use ministd::mem::Arena;
+
+
+#[test]
+fn values_are_allocated_in_order() {
+    let arena = Arena::with_capacity(256);
+
+    let a = arena.alloc(1u32);
+    let b = arena.alloc(2u32);
+    *a += 10;
+
+    assert_eq!((*a, *b), (11, 2));
+    assert_eq!(b as *mut u32 as usize - a as *mut u32 as usize, 4);
+    assert_eq!(arena.used(), 8);
+    assert_eq!(arena.remaining(), 248);
+
+    let slice = arena.alloc_slice(&[1u16, 2, 3]);
+    let squares = arena.alloc_slice_with(4, |i| i * i);
+    let name = arena.alloc_str("RSDT");
+
+    assert_eq!(slice, [1, 2, 3]);
+    assert_eq!(squares, [0, 1, 4, 9]);
+    assert_eq!(name, "RSDT");
+}
+
+#[test]
+fn allocations_are_aligned() {
+    let arena = Arena::with_capacity(256);
+
+    arena.alloc(1u8);
+    let wide = arena.alloc(2u64);
+    assert_eq!(wide as *mut u64 as usize % align_of::<u64>(), 0);
+    //  7 bytes of padding after the `u8`
+    assert_eq!(arena.used(), 16);
+
+    #[repr(align(64))]
+    struct Line(#[allow(dead_code)] u8);
+
+    let line = arena.alloc(Line(0));
+    assert_eq!(line as *mut Line as usize % 64, 0);
+}
+
+#[test]
+fn full_arena_fails_cleanly() {
+    let arena = Arena::with_capacity(16);
+
+    assert!(arena.try_alloc([0u8; 16]).is_ok());
+    assert!(arena.try_alloc(0u8).is_err());
+    assert!(arena.try_alloc_str("x").is_err());
+    assert_eq!(arena.remaining(), 0);
+}
+
+#[test]
+fn rewind_frees_after_checkpoint() {
+    let mut arena = Arena::with_capacity(256);
+
+    arena.alloc(1u64);
+    let mark = arena.checkpoint();
+    let before = arena.used();
+
+    arena.alloc_slice(&[0u64; 8]);
+    assert_eq!(arena.used(), before + 64);
+
+    arena.rewind(mark);
+    assert_eq!(arena.used(), before);
+
+    let kept = arena.scope(|arena| {
+        arena.alloc_str("scratch");
+        arena.used()
+    });
+    assert_eq!(kept, before + 7);
+    assert_eq!(arena.used(), before);
+}
+
+#[test]
+fn reset_frees_everything() {
+    let mut arena = Arena::with_capacity(64);
+
+    arena.alloc([0u8; 40]);
+    arena.reset();
+    assert_eq!(arena.used(), 0);
+    assert_eq!(arena.remaining(), arena.capacity());
+
+    assert!(arena.try_alloc([0u8; 64]).is_ok());
+}
+
+#[test]
+fn last_collection_grows_in_place() {
+    let arena = Arena::with_capacity(1024);
+
+    let mut v: Vec<u32, 0, 0, &Arena> = Vec::new_in(&arena);
+    v.extend_from_slice(&[1, 2, 3]);
+    let ptr = v.as_ptr();
+    for i in 0..32 {
+        v.push(i);
+    }
+
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(v.len(), 35);
+
+    drop(v);
+    assert_eq!(arena.used(), 0);
+}
@@ -0,0 +1,64 @@
+//	tests/array.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::Array;
+
+
+/// Counts its drops
+struct Tracked<'c>(&'c Cell<usize>, u32);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+
+#[test]
+fn sorts_in_place() {
+    let mut a = Array::new_with(|i| ((i * 37) % 100, i), 100);
+    a.sort_by_key(|e| e.0 / 10);
+    assert!(a.iter().zip(a.iter().skip(1)).all(|(x, y)| x.0 / 10 < y.0 / 10 || x.1 < y.1));
+
+    a.try_sort().unwrap();
+    assert!(a.iter().zip(a.iter().skip(1)).all(|(x, y)| x < y));
+
+    a.sort_unstable_by(|x, y| y.cmp(x));
+    assert_eq!(a[0].0, 99);
+}
+
+#[test]
+fn retain_and_dedup_shrink() {
+    let drops = Cell::new(0);
+
+    let mut a = Array::new_with(|i| Tracked(&drops, i as u32), 10);
+    a.retain(|e| e.1 % 2 == 0);
+    assert_eq!(a.len(), 5);
+    assert_eq!(drops.get(), 5);
+    assert_eq!(a.iter().map(|e| e.1).collect::<std::vec::Vec<_>>(), [0, 2, 4, 6, 8]);
+
+    let mut b = Array::from([1u32, 1, 2, 2, 2, 3, 1]);
+    b.dedup();
+    assert_eq!(*b, [1, 2, 3, 1]);
+    b.try_dedup_by_key(|e| *e > 1).unwrap();
+    assert_eq!(*b, [1, 2, 1]);
+
+    b.try_retain_mut(|e| { *e *= 10; false }).unwrap();
+    assert!(b.is_empty());
+}
+
+#[test]
+fn insert_sorted_grows() {
+    let mut a = Array::from([10u32, 20, 30]);
+
+    assert_eq!(a.insert_sorted(25), 2);
+    assert_eq!(a.try_insert_sorted(20), Ok(2));
+    assert_eq!(a.insert_sorted(0), 0);
+    assert_eq!(*a, [0, 10, 20, 20, 25, 30]);
+}
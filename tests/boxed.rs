@@ -0,0 +1,60 @@
+//	tests/boxed.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::Box;
+
+
+struct Tracked<'c>(&'c Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+
+#[test]
+fn new_and_deref() {
+    let mut b = Box::new([1u64, 2, 3, 4]);
+    b[0] = 10;
+
+    assert_eq!(*b, [10, 2, 3, 4]);
+}
+
+#[test]
+fn drops_the_value() {
+    let drops = Cell::new(0);
+
+    let b = Box::new(Tracked(&drops));
+    assert_eq!(drops.get(), 0);
+
+    drop(b);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn slices() {
+    let zeroed = Box::<[u32]>::new_zeroed_slice(64);
+    assert_eq!(zeroed.len(), 64);
+    assert!(zeroed.iter().all(|x| unsafe { x.assume_init() } == 0));
+
+    let mut n = 0;
+    let counted = Box::new_slice_with(5, || { n += 1; n });
+    assert_eq!(*counted, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn clone_is_deep() {
+    let a = Box::new(41);
+    let mut b = a.clone();
+    *b += 1;
+
+    assert_eq!(*a, 41);
+    assert_eq!(*b, 42);
+}
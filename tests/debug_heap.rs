@@ -0,0 +1,51 @@
+//	tests/debug_heap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing,debug-heap`
+
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use allocator::debug::{ALLOC_FILL, FREE_FILL};
+use ministd::ALLOCATOR;
+
+
+#[test]
+fn fresh_and_freed_memory_is_filled() {
+    let layout = Layout::from_size_align(64, 8).unwrap();
+
+    unsafe {
+        let ptr = ALLOCATOR.alloc(layout);
+        assert!(!ptr.is_null());
+        assert!(core::slice::from_raw_parts(ptr, 64).iter().all(|b| *b == ALLOC_FILL));
+
+        ALLOCATOR.dealloc(ptr, layout);
+        //  the block is still owned by the heap, only the user part is filled
+        assert!(core::slice::from_raw_parts(ptr, 64).iter().all(|b| *b == FREE_FILL));
+    }
+}
+
+#[test]
+#[should_panic]
+fn overwritten_red_zone_is_reported() {
+    let layout = Layout::from_size_align(32, 8).unwrap();
+
+    unsafe {
+        let ptr = ALLOCATOR.alloc(layout);
+        ptr.add(32).write(0);
+        ALLOCATOR.dealloc(ptr, layout);
+    }
+}
+
+#[test]
+#[should_panic]
+fn double_free_is_reported() {
+    let layout = Layout::from_size_align(32, 8).unwrap();
+
+    unsafe {
+        let ptr = ALLOCATOR.alloc(layout);
+        ALLOCATOR.dealloc(ptr, layout);
+        ALLOCATOR.dealloc(ptr, layout);
+    }
+}
@@ -0,0 +1,171 @@
+//	tests/dma.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use core::alloc::Layout;
+
+use ministd::mem::{dma, DmaBox, DmaConstraints, DmaVec, Region, KB, MB, PAGE_ALIGN};
+use ministd::mem::dma::{DmaError, MAX_DMA_EXTENTS};
+
+
+/// Serializes the tests, the DMA pool is global
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Virtual address of the low region
+static LOW_VIRT: OnceLock<usize> = OnceLock::new();
+
+/// Physical address of the low region, it lies just below 16 MiB and spans two 64 KiB boundaries
+const LOW_PHYS: usize = 15 * MB + 32 * KB;
+const LOW_SIZE: usize = 128 * KB;
+
+/// Physical address of the high region, above the ISA limit
+const HIGH_PHYS: usize = 16 * MB;
+const HIGH_SIZE: usize = 64 * KB;
+
+/// Leaks host buffer of `size` bytes
+/// - the physical addresses of the regions are made up, only the virtual memory is ever touched
+fn host_buffer(size: usize) -> usize {
+    let ptr = unsafe { std::alloc::alloc(Layout::from_size_align(size, 4 * KB).unwrap()) };
+    assert!(!ptr.is_null());
+    ptr as usize
+}
+
+/// Gives the two regions to the pool (only once) and serializes the tests
+fn setup() -> MutexGuard<'static, ()> {
+    let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+
+    LOW_VIRT.get_or_init(|| unsafe {
+        let low = host_buffer(LOW_SIZE);
+        dma::add_region(Region::<PAGE_ALIGN>::new(low, LOW_PHYS, LOW_SIZE)).unwrap();
+        dma::add_region(Region::<PAGE_ALIGN>::new(host_buffer(HIGH_SIZE), HIGH_PHYS, HIGH_SIZE)).unwrap();
+        low
+    });
+
+    //  every test gives all of its buffers back
+    assert_eq!(dma::free_bytes(), dma::total_bytes());
+    guard
+}
+
+
+#[test]
+fn isa_buffer_is_placed_below_16m_without_crossing_64k() {
+    let _setup = setup();
+
+    //  the start of the low region would cross 15 MiB + 64 KiB
+    let first: DmaVec<u8> = DmaVec::with_capacity(48 * KB, DmaConstraints::ISA);
+    assert_eq!(first.phys_addr(), 15 * MB + 64 * KB);
+
+    //  the rest of the low region crosses a boundary, the high region is above the limit
+    let second = DmaVec::<u8>::try_with_capacity(48 * KB, DmaConstraints::ISA);
+    assert_eq!(second.err(), Some(DmaError::OutOfMemory));
+
+    //  without the constraints the end of the low region fits
+    let third: DmaVec<u8> = DmaVec::with_capacity(48 * KB, DmaConstraints::new());
+    assert_eq!(third.phys_addr(), 15 * MB + 112 * KB);
+
+    let high: DmaVec<u8> = DmaVec::with_capacity(48 * KB, DmaConstraints::new());
+    assert_eq!(high.phys_addr(), HIGH_PHYS);
+}
+
+#[test]
+fn buffer_is_aligned() {
+    let _setup = setup();
+
+    let small = DmaBox::new(1u8, DmaConstraints::new());
+    let aligned = DmaBox::new([0u8; 64], DmaConstraints::new().align(4 * KB));
+
+    assert_eq!(small.phys_addr(), LOW_PHYS);
+    assert_eq!(aligned.phys_addr() % (4 * KB), 0);
+    assert!(aligned.phys_addr() > small.phys_addr());
+    //  the virtual address moves together with the physical one
+    assert_eq!(aligned.as_ptr() as usize - small.as_ptr() as usize, aligned.phys_addr() - small.phys_addr());
+}
+
+#[test]
+fn freed_extents_are_merged() {
+    let _setup = setup();
+    let quarter = LOW_SIZE / 4;
+
+    let mut buffers: std::vec::Vec<Option<DmaVec<u8>>> = (0..4)
+        .map(|_| Some(DmaVec::with_capacity(quarter, DmaConstraints::new())))
+        .collect();
+    assert_eq!(buffers[3].as_ref().unwrap().phys_addr(), LOW_PHYS + 3 * quarter);
+
+    //  merges with the next extent, then with the previous one, then with both
+    for i in [1, 3, 0, 2] {
+        buffers[i] = None;
+    }
+
+    let whole: DmaVec<u8> = DmaVec::with_capacity(LOW_SIZE, DmaConstraints::new());
+    assert_eq!(whole.phys_addr(), LOW_PHYS);
+}
+
+#[test]
+fn too_many_extents_fail_with_fragmented() {
+    let _setup = setup();
+
+    //  every other freed buffer is one extent, the rest of both regions are two more
+    let count = 2 * (MAX_DMA_EXTENTS - 2);
+    let mut buffers: std::vec::Vec<Option<DmaBox<[u8; 16]>>> = (0..count)
+        .map(|_| Some(DmaBox::new([0; 16], DmaConstraints::new())))
+        .collect();
+    for buffer in buffers.iter_mut().step_by(2) {
+        *buffer = None;
+    }
+
+    //  the aligned buffer would split the rest of the low region in the middle
+    let split = DmaBox::try_new([0u8; 32], DmaConstraints::new().align(4 * KB));
+    assert_eq!(split.err(), Some(DmaError::Fragmented));
+
+    drop(buffers);
+}
+
+#[test]
+fn large_request_fails_with_out_of_memory() {
+    let _setup = setup();
+
+    let large = DmaVec::<u8>::try_with_capacity(LOW_SIZE + 1, DmaConstraints::new());
+    assert_eq!(large.err(), Some(DmaError::OutOfMemory));
+}
+
+#[test]
+fn invalid_constraints_are_rejected() {
+    let _setup = setup();
+
+    let odd = DmaBox::try_new(0u8, DmaConstraints::new().align(24));
+    assert_eq!(odd.err(), Some(DmaError::InvalidAlignment));
+
+    let align_over_boundary = DmaBox::try_new(0u8, DmaConstraints::new().align(8 * KB).boundary(4 * KB));
+    assert_eq!(align_over_boundary.err(), Some(DmaError::InvalidAlignment));
+
+    let over_boundary = DmaVec::<u8>::try_with_capacity(128, DmaConstraints::new().boundary(64));
+    assert_eq!(over_boundary.err(), Some(DmaError::TooLarge));
+}
+
+#[test]
+fn overlapping_region_is_rejected() {
+    let _setup = setup();
+
+    //  physical overlap with the low region
+    let phys = Region::<PAGE_ALIGN>::new(host_buffer(4 * KB), LOW_PHYS + 4 * KB, 4 * KB);
+    assert_eq!(unsafe { dma::add_region(phys) }, Err(DmaError::Overlap));
+
+    //  virtual overlap with the low region
+    let virt = Region::<PAGE_ALIGN>::new(LOW_VIRT.get().unwrap() + 4 * KB, 32 * MB, 4 * KB);
+    assert_eq!(unsafe { dma::add_region(virt) }, Err(DmaError::Overlap));
+}
+
+#[test]
+fn region_with_different_page_offsets_is_rejected() {
+    let _setup = setup();
+
+    //  the physical address is page aligned, the virtual one is not
+    let shifted = Region::<8>::new(host_buffer(8 * KB) + 8, 48 * MB, 4 * KB);
+    assert_eq!(unsafe { dma::add_region(shifted) }, Err(DmaError::Misaligned));
+    assert_eq!(dma::total_bytes(), LOW_SIZE + HIGH_SIZE);
+}
@@ -0,0 +1,43 @@
+//	tests/dynamic_buffer.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::alloc::AllocErrorKind;
+use ministd::mem::DynamicBuffer;
+
+
+#[test]
+fn capacity_follows_step() {
+    let mut buf: DynamicBuffer<u32, 8> = DynamicBuffer::with_capacity(3);
+    assert_eq!(buf.capacity(), 8);
+
+    buf.expand();
+    assert_eq!(buf.capacity(), 16);
+
+    buf.resize_exact(5);
+    assert_eq!(buf.capacity(), 5);
+}
+
+#[test]
+fn contents_survive_resize() {
+    let mut buf: DynamicBuffer<u64, 0> = DynamicBuffer::with_capacity(4);
+    for i in 0..4 {
+        unsafe { buf.as_ptr().add(i).write(i as u64 * 3); }
+    }
+
+    buf.resize(1000);
+    assert!(buf.capacity() >= 1000);
+
+    for i in 0..4 {
+        assert_eq!(unsafe { buf.as_ptr().add(i).read() }, i as u64 * 3);
+    }
+}
+
+#[test]
+fn layout_overflow_is_reported() {
+    let err = DynamicBuffer::<u64, 0>::try_layout_for(usize::MAX).unwrap_err();
+    assert_eq!(err.kind(), AllocErrorKind::CapacityOverflow);
+}
@@ -0,0 +1,373 @@
+//	tests/fault.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+
+use ministd::{ALLOCATOR, Array, Box, Rc, String, Vec, VecDeque};
+use ministd::alloc::fault::{self, Fault};
+
+
+/// Serializes the tests, the injected failures hit every thread
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Counts its drops
+#[derive(Clone)]
+struct Tracked<'c>(&'c Cell<usize>, u32);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// Fills the vector up to its capacity, so the next push has to allocate
+fn fill<T: Clone>(v: &mut Vec<T>, val: T) {
+    v.push(val.clone());
+    while v.len() < v.capacity() {
+        v.push(val.clone());
+    }
+}
+
+
+#[test]
+fn nth_fails_only_once() {
+    let _serial = serial();
+    let before = fault::injected();
+
+    let guard = fault::inject(Fault::Nth(2));
+    assert!(Box::try_new(1u64).is_ok());
+    assert!(Box::try_new(2u64).is_err());
+    assert!(Box::try_new(3u64).is_ok());
+    drop(guard);
+
+    assert_eq!(fault::injected(), before + 1);
+}
+
+#[test]
+fn probability_is_reproducible() {
+    let _serial = serial();
+
+    let run = |seed| {
+        let _fault = fault::inject(Fault::Probability { p: 0.5, seed });
+        (0..64).map(|i| Box::try_new(i).is_err()).collect::<std::vec::Vec<bool>>()
+    };
+
+    let first = run(42);
+    assert_eq!(first, run(42));
+    assert!(first.contains(&true) && first.contains(&false));
+
+    {
+        let _fault = fault::inject(Fault::Probability { p: 0.0, seed: 1 });
+        assert!((0..64).all(|i| Box::try_new(i).is_ok()));
+    }
+    {
+        let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 1 });
+        assert!((0..64).all(|i| Box::try_new(i).is_err()));
+    }
+}
+
+#[test]
+fn budget_limits_the_heap() {
+    let _serial = serial();
+
+    let _fault = fault::inject(Fault::Budget(ALLOCATOR.allocated_bytes() + 4096));
+
+    let small = Box::try_new([0u8; 1024]);
+    assert!(small.is_ok());
+    assert!(Box::try_new([0u8; 8192]).is_err());
+    drop(small);
+
+    let mut v: Vec<u8> = Vec::new();
+    let mut pushed = 0;
+    while v.try_push(0).is_ok() {
+        pushed += 1;
+        assert!(pushed <= 4096, "the budget was not enforced");
+    }
+    assert_eq!(v.len(), pushed);
+}
+
+#[test]
+fn disabled_after_guard() {
+    let _serial = serial();
+
+    drop(fault::inject(Fault::Probability { p: 1.0, seed: 7 }));
+    assert!(Box::try_new(0u8).is_ok());
+
+    let _fault = fault::inject(Fault::Nth(1));
+    fault::disable();
+    assert!(Box::try_new(0u8).is_ok());
+}
+
+
+#[test]
+fn vec_try_push_keeps_contents() {
+    let _serial = serial();
+    let drops = Cell::new(0);
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut v: Vec<Tracked> = Vec::new();
+        fill(&mut v, Tracked(&drops, 0));
+        let (len, capacity) = (v.len(), v.capacity());
+        let dropped = drops.get();
+
+        let failed = {
+            let _fault = fault::inject(Fault::Nth(1));
+            v.try_push(Tracked(&drops, 1))
+        };
+
+        //  the value comes back and nothing was dropped
+        let val = failed.err().expect("the push had to fail");
+        assert_eq!(val.1, 1);
+        assert_eq!(drops.get(), dropped);
+        assert_eq!((v.len(), v.capacity()), (len, capacity));
+        assert!(v.iter().all(|t| t.1 == 0));
+        drop(val);
+
+        assert!(v.try_push_mut(Tracked(&drops, 2)).is_ok());
+        assert_eq!(v.len(), len + 1);
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn vec_try_extend_keeps_contents() {
+    let _serial = serial();
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut v: Vec<u32> = Vec::from([1, 2, 3]);
+        fill(&mut v, 7);
+        let copy: std::vec::Vec<u32> = v.iter().copied().collect();
+
+        {
+            let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 3 });
+
+            assert!(v.try_extend_from_within(..).is_err());
+            assert!(v.try_extend_from_slice(&[1, 2, 3]).is_err());
+            assert!(v.try_reserve(1).is_err());
+            assert!(v.try_reserve_exact(1).is_err());
+            assert!(v.try_resize(v.len() + 1, 0).is_err());
+            assert!(v.try_resize_with(v.len() + 1, || 0).is_err());
+            assert!(Vec::<u32>::try_with_capacity(16).is_err());
+        }
+
+        assert_eq!(v, *copy.as_slice());
+
+        v.try_extend_from_within(..2).unwrap();
+        assert_eq!(v.len(), copy.len() + 2);
+        assert_eq!(v[copy.len()], 1);
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn string_try_insert_keeps_contents() {
+    let _serial = serial();
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut s: String = String::from("hello");
+        while s.len() < s.capacity() {
+            s.push(b'!');
+        }
+        let copy = std::string::String::from_utf8(s.as_bytes().to_vec()).unwrap();
+
+        {
+            let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 5 });
+
+            assert!(s.try_insert_str(1, "XYZ").is_err());
+            assert!(s.try_insert(0, b'X').is_err());
+            assert!(s.try_push_str("world").is_err());
+            assert!(s.try_push(b'?').is_err());
+            assert!(s.try_reserve(1).is_err());
+            assert!(s.try_split_off(2).is_err());
+        }
+
+        assert_eq!(s.as_bytes(), copy.as_bytes());
+
+        s.try_insert_str(5, ", world").unwrap();
+        assert!(s.as_bytes().starts_with(b"hello, world"));
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn constructors_report_failure() {
+    let _serial = serial();
+    let baseline = ALLOCATOR.allocated_bytes();
+    let drops = Cell::new(0);
+
+    {
+        let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 9 });
+
+        assert!(Box::try_new(Tracked(&drops, 0)).is_err());
+        assert!(Box::<[u64]>::try_new_zeroed_slice(32).is_err());
+        assert!(Rc::try_new(Tracked(&drops, 0)).is_err());
+        assert!(String::<0>::try_with_capacity(64).is_err());
+    }
+
+    //  the values were given to the failed constructors and dropped there
+    assert_eq!(drops.get(), 2);
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn every_failure_point_of_growth() {
+    let _serial = serial();
+    let drops = Cell::new(0);
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    //  fail each allocation of the loop in turn
+    for n in 1..32 {
+        let mut v: Vec<Tracked> = Vec::new();
+        let mut skipped = None;
+
+        {
+            let _fault = fault::inject(Fault::Nth(n));
+            for i in 0..256 {
+                if let Err(val) = v.try_push(Tracked(&drops, i)) {
+                    assert_eq!(val.1, i);
+                    assert!(skipped.replace(i).is_none(), "only one push may fail");
+                }
+            }
+        }
+
+        //  the vector holds every value but the one that failed
+        let expected = (0..256).filter(|&i| Some(i) != skipped);
+        assert!(v.iter().map(|t| t.1).eq(expected));
+        drop(v);
+    }
+
+    //  every value was dropped exactly once
+    assert_eq!(drops.get(), 31 * 256);
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn random_failures_do_not_leak() {
+    let _serial = serial();
+    let drops = Cell::new(0);
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut v: Vec<Box<Tracked>> = Vec::new();
+        let mut s: String = String::new();
+        let mut created = 0;
+
+        let _fault = fault::inject(Fault::Probability { p: 0.3, seed: 0xdead_beef });
+        for i in 0..2000 {
+            created += 1;
+            if let Ok(b) = Box::try_new(Tracked(&drops, i)) {
+                let _ = v.try_push(b);
+            }
+            let _ = s.try_push_str("ab");
+        }
+        fault::disable();
+
+        assert!(fault::injected() > 0);
+        assert_eq!(s.len() % 2, 0);
+        assert!(v.iter().zip(v.iter().skip(1)).all(|(a, b)| a.1 < b.1));
+
+        drop(v);
+        assert_eq!(drops.get(), created);
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn try_sort_and_retain_keep_contents() {
+    let _serial = serial();
+    let drops = Cell::new(0);
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut v: Vec<u32> = (0..100).rev().collect();
+        let mut a = Array::new_with(|i| Tracked(&drops, i as u32), 8);
+
+        {
+            let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 9 });
+
+            assert!(v.try_sort().is_err());
+            assert!(v.try_sort_by_key(|e| *e).is_err());
+            assert!(a.try_retain(|e| e.1 < 3).is_err());
+            assert_eq!(a.try_insert_sorted_by(Tracked(&drops, 4), |x, y| x.1.cmp(&y.1)).err().map(|t| t.1), Some(4));
+        }
+
+        //  nothing was dropped except the value that came back, nothing was moved
+        assert_eq!(drops.get(), 1);
+        assert!(v.iter().zip(v.iter().skip(1)).all(|(a, b)| a > b));
+        assert_eq!(a.iter().map(|e| e.1).collect::<std::vec::Vec<_>>(), [0, 1, 2, 3, 4, 5, 6, 7]);
+
+        //  the marks are allocated, the new block is not
+        {
+            let _fault = fault::inject(Fault::Nth(2));
+            assert!(a.try_retain(|e| e.1 % 2 == 1).is_err());
+        }
+        {
+            let _fault = fault::inject(Fault::Nth(2));
+            assert!(a.try_dedup_by_key(|e| e.1 / 4).is_err());
+        }
+        assert_eq!(drops.get(), 1);
+        assert_eq!(a.iter().map(|e| e.1).collect::<std::vec::Vec<_>>(), [0, 1, 2, 3, 4, 5, 6, 7]);
+
+        //  sorting short vectors does not allocate
+        let mut short: Vec<u32> = Vec::from([3, 1, 2]);
+        {
+            let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 9 });
+            assert!(short.try_sort().is_ok());
+        }
+        assert_eq!(short, [1, 2, 3]);
+
+        v.try_sort().unwrap();
+        assert_eq!(v[0], 0);
+        a.try_retain(|e| e.1 < 3).unwrap();
+        assert_eq!(drops.get(), 6);
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
+
+#[test]
+fn vec_deque_try_push_keeps_contents() {
+    let _serial = serial();
+    let baseline = ALLOCATOR.allocated_bytes();
+
+    {
+        let mut d: VecDeque<u32> = VecDeque::with_capacity(4);
+        for i in 0..d.capacity() as u32 {
+            d.push_front(i);
+        }
+        let copy: std::vec::Vec<u32> = d.iter().copied().collect();
+
+        {
+            let _fault = fault::inject(Fault::Probability { p: 1.0, seed: 11 });
+
+            assert_eq!(d.try_push_back(7), Err(7));
+            assert_eq!(d.try_push_front(8), Err(8));
+            assert!(d.try_reserve(1).is_err());
+            assert!(VecDeque::<u32>::try_with_capacity(16).is_err());
+        }
+
+        assert!(d.iter().copied().eq(copy.iter().copied()));
+
+        d.try_push_back(7).unwrap();
+        assert_eq!(d.back(), Some(&7));
+        assert_eq!(d.len(), copy.len() + 1);
+    }
+
+    assert_eq!(ALLOCATOR.allocated_bytes(), baseline);
+}
@@ -0,0 +1,88 @@
+//	tests/frame.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::mem::{Region, KB, MB};
+use ministd::mem::frame::{self, Frame, FrameAllocator, FrameError, FRAME_SIZE};
+
+
+/// Two usable regions with a hole between them
+const REGIONS: [Region; 2] = [
+    Region::new(0, 0x1000, 64 * KB),
+    Region::new(0, MB, MB),
+];
+
+
+#[test]
+fn counts_usable_frames() {
+    let mut bitmap = [0; 8];
+    let frames = FrameAllocator::new(&mut bitmap, &REGIONS).unwrap();
+
+    assert_eq!(frames.stats().total_frames, 16 + 256);
+    assert_eq!(frames.stats().free_frames, 16 + 256);
+    assert_eq!(frames.largest_free_run(), 256);
+}
+
+#[test]
+fn bitmap_must_be_large_enough() {
+    let words = frame::bitmap_words(&REGIONS);
+    let mut bitmap = [0; 8];
+
+    assert!(FrameAllocator::new(&mut bitmap[..words - 1], &REGIONS).is_err_and(|e| e == FrameError::BitmapTooSmall));
+}
+
+#[test]
+fn alloc_and_free() {
+    let mut bitmap = [0; 8];
+    let mut frames = FrameAllocator::new(&mut bitmap, &REGIONS).unwrap();
+
+    let first = frames.alloc_frame().unwrap();
+    assert_eq!(first.addr(), 0x1000);
+    assert!(!frames.is_free(first));
+
+    frames.free_frame(first).unwrap();
+    assert_eq!(frames.free_frame(first), Err(FrameError::NotAllocated));
+
+    //  the hole between the regions is never handed out nor freed
+    assert_eq!(frames.free_frame(Frame::containing(512 * KB)), Err(FrameError::NotAllocated));
+    assert_eq!(frames.free_frame(Frame::containing(64 * MB)), Err(FrameError::OutOfRange));
+}
+
+#[test]
+fn contiguous_respects_align() {
+    let mut bitmap = [0; 8];
+    let mut frames = FrameAllocator::new(&mut bitmap, &REGIONS).unwrap();
+
+    let run = frames.alloc_contiguous(16, 64 * KB).unwrap();
+    assert_eq!(run.addr() % (64 * KB), 0);
+    assert_eq!(run.addr(), MB);
+
+    let small = frames.alloc_contiguous(3, 2 * FRAME_SIZE).unwrap();
+    assert_eq!(small.addr(), 0x2000);
+
+    assert_eq!(frames.alloc_contiguous(300, FRAME_SIZE), Err(FrameError::OutOfFrames));
+    assert_eq!(frames.alloc_contiguous(usize::MAX, FRAME_SIZE), Err(FrameError::OutOfFrames));
+    assert_eq!(frames.alloc_contiguous(1, 3 * FRAME_SIZE), Err(FrameError::InvalidAlignment));
+
+    frames.free_contiguous(run, 16).unwrap();
+    assert_eq!(frames.stats().free_frames, 16 + 256 - 3);
+}
+
+#[test]
+fn reserve_takes_frames_out() {
+    let mut bitmap = [0; 8];
+    let mut frames = FrameAllocator::new(&mut bitmap, &REGIONS).unwrap();
+
+    assert_eq!(frames.reserve(Region::<FRAME_SIZE>::new(0, MB, 8 * KB)), 2);
+    assert_eq!(frames.alloc_contiguous(1, MB).err(), Some(FrameError::OutOfFrames));
+
+    let mut count = 0;
+    while frames.alloc_frame().is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 16 + 256 - 2);
+    assert_eq!(frames.stats().min_free_frames, 0);
+}
@@ -0,0 +1,51 @@
+//	tests/heap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::{ALLOCATOR, Vec};
+use ministd::sync::IrqMutex;
+
+
+#[test]
+fn hosted_heap_is_seeded() {
+    let v: Vec<u8> = Vec::with_capacity(4096);
+    assert!(v.capacity() >= 4096);
+
+    assert!(ALLOCATOR.total_bytes() >= ministd::alloc::hosted::HOSTED_HEAP_SIZE / 2);
+    assert!(ALLOCATOR.allocated_bytes() > 0);
+}
+
+#[test]
+fn large_allocation_fails_cleanly() {
+    let layout = Layout::from_size_align(1 << 40, 8).unwrap();
+    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+
+    assert!(ptr.is_null());
+    assert!(ALLOCATOR.stats().failed_allocations > 0);
+}
+
+#[test]
+fn std_and_ministd_allocators_coexist() {
+    let ours: Vec<u32> = Vec::from([1, 2, 3]);
+    let theirs: std::vec::Vec<u32> = ours.iter().copied().collect();
+
+    assert_eq!(ours, *theirs.as_slice());
+}
+
+#[test]
+fn irq_mutex_locks() {
+    static VALUE: IrqMutex<u32> = IrqMutex::new(0);
+
+    *VALUE.lock() += 1;
+    {
+        let guard = VALUE.lock();
+        assert!(VALUE.try_lock().is_none());
+        assert_eq!(*guard, 1);
+    }
+    assert!(VALUE.try_lock().is_some());
+}
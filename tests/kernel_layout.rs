@@ -0,0 +1,116 @@
+//	tests/kernel_layout.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::init::{self, MapConfig, MemoryKind};
+use ministd::mem::{Region, KB, MB};
+use ministd::mem::kernel::{self, Layout, Section, LINKER_SCRIPT};
+use ministd::mem::paging::{FrameSource, Mapper, PageFlags, PageTable};
+
+
+const BASE: usize = 0xffff_ffff_8000_0000;
+
+/// Layout of a small kernel, as the template linker script places it
+fn layout() -> Layout {
+    Layout {
+        kernel: Region::new(BASE, 0, 64 * KB),
+        text: Region::new(BASE, 0, 32 * KB),
+        rodata: Region::new(BASE + 32 * KB, 0, 8 * KB),
+        data: Region::new(BASE + 40 * KB, 0, 4 * KB),
+        dynamic: Region::empty(),
+        bss: Region::new(BASE + 44 * KB, 0, 20 * KB),
+    }
+}
+
+
+/// Fake physical address of the first table
+const PHYS: usize = 0x100_0000;
+
+/// Tables in normal memory, handed out as fake physical frames
+struct Tables {
+    memory: std::boxed::Box<[PageTable]>,
+    next: usize,
+}
+
+impl FrameSource for Tables {
+    fn alloc_table(&mut self) -> Option<usize> {
+        self.next += 1;
+        (self.next < self.memory.len()).then(|| PHYS + self.next * 4 * KB)
+    }
+
+    fn free_table(&mut self, _: usize) {}
+}
+
+
+#[test]
+fn template_defines_symbols() {
+    for symbol in ["KERNEL", "TEXT", "RODATA", "DATA", "DYNAMIC", "BSS"] {
+        assert!(LINKER_SCRIPT.contains(&std::format!("__{}_START = .;", symbol)));
+        assert!(LINKER_SCRIPT.contains(&std::format!("__{}_END = .;", symbol)));
+    }
+}
+
+#[test]
+fn sections_and_phys() {
+    let mut layout = layout();
+
+    assert_eq!(layout.section_of(BASE + 100), Some(Section::Text));
+    assert_eq!(layout.section_of(BASE + 33 * KB), Some(Section::Rodata));
+    assert_eq!(layout.section_of(BASE + 63 * KB), Some(Section::Bss));
+    assert_eq!(layout.section_of(BASE + 64 * KB), None);
+    assert_eq!(layout.sections().count(), 4);
+
+    assert_eq!(layout.phys_range(), None);
+    layout.set_phys(2 * MB);
+    assert_eq!(layout.phys_range(), Some((2 * MB, 64 * KB)));
+    assert_eq!(layout.bss.phys(), 2 * MB + 44 * KB);
+    assert_eq!(layout.dynamic.phys(), 0);
+
+    let table = std::format!("{}", layout);
+    assert!(table.contains(".bss") && !table.contains(".dynamic"));
+}
+
+#[test]
+fn protects_sections() {
+    let tables = Tables { memory: (0..8).map(|_| PageTable::new()).collect(), next: 0 };
+    let offset = (tables.memory.as_ptr() as usize).wrapping_sub(PHYS);
+    let mut mapper = unsafe { Mapper::new(PHYS, offset, tables) };
+
+    let mut layout = layout();
+    layout.set_phys(2 * MB);
+    mapper.map(layout.kernel, PageFlags::WRITABLE).unwrap();
+
+    layout.protect(&mut mapper, PageFlags::GLOBAL).unwrap();
+
+    let flags = |virt: usize| mapper.translate(virt).unwrap().flags;
+    assert!(!flags(BASE).contains(PageFlags::WRITABLE));
+    assert!(!flags(BASE + 31 * KB).contains(PageFlags::NO_EXECUTE));
+    assert!(flags(BASE + 32 * KB).contains(PageFlags::NO_EXECUTE));
+    assert!(!flags(BASE + 32 * KB).contains(PageFlags::WRITABLE));
+    assert!(flags(BASE + 60 * KB).contains(PageFlags::WRITABLE | PageFlags::NO_EXECUTE | PageFlags::GLOBAL));
+    assert_eq!(mapper.translate_addr(BASE + 60 * KB), Some(2 * MB + 60 * KB));
+}
+
+#[test]
+fn heap_avoids_kernel_image() {
+    //  fake physical memory at 256 MiB, backed by a buffer of the test process
+    const PHYS: usize = 256 * MB;
+    let memory = std::vec![0u64; 12 * MB / 8].leak();
+    let offset = (memory.as_mut_ptr() as usize).next_multiple_of(4 * KB) - PHYS;
+
+    {
+        let mut layout = kernel::LAYOUT.write();
+        *layout = self::layout();
+        layout.set_phys(PHYS + 4 * MB);
+    }
+
+    let config = MapConfig::new(offset).heap_size(MB);
+    let map = unsafe { init::memory_map([(PHYS, 8 * MB, MemoryKind::Usable)], &config) }.unwrap();
+
+    let kernel = map.iter().find(|e| e.kind == MemoryKind::Kernel).unwrap();
+    assert_eq!((kernel.base, kernel.size), (PHYS + 4 * MB, 64 * KB));
+    assert!(map.iter().filter(|e| e.kind == MemoryKind::Heap).all(|e| e.end() <= kernel.base || e.base >= kernel.end()));
+}
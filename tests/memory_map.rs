@@ -0,0 +1,138 @@
+//	tests/memory_map.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::ALLOCATOR;
+use ministd::init::{self, MapConfig, MapEntry, MapError, MemoryKind, MemoryMap};
+use ministd::init::memmap::MAX_MAP_ENTRIES;
+use ministd::mem::{frame, KB, MB};
+
+
+/// Typical BIOS machine as reported by Limine
+const LIMINE: [(usize, usize, u64); 7] = [
+    (0, 0x9fc00, 0),                //  usable, partial last page
+    (0x9fc00, 0x400, 1),            //  EBDA
+    (0xf0000, 0x10000, 1),          //  BIOS
+    (0x10_0000, 31 * MB, 0),        //  usable
+    (0x200_0000, 2 * MB, 6),        //  kernel and modules
+    (0x220_0000, 94 * MB, 0),       //  usable
+    (0x7fe_0000, 128 * KB, 2),      //  ACPI reclaimable, overlaps the usable entry
+];
+
+fn limine_map() -> MemoryMap {
+    MemoryMap::from_entries(LIMINE.iter().map(|&(base, size, kind)| (base, size, MemoryKind::from_limine(kind)))).unwrap()
+}
+
+
+#[test]
+fn classifies_boot_protocols() {
+    assert_eq!(MemoryKind::from_limine(0), MemoryKind::Usable);
+    assert_eq!(MemoryKind::from_limine(5), MemoryKind::Reclaimable);
+    assert_eq!(MemoryKind::from_limine(7), MemoryKind::Reserved);
+    assert_eq!(MemoryKind::from_multiboot2(1), MemoryKind::Usable);
+    assert_eq!(MemoryKind::from_multiboot2(3), MemoryKind::Acpi);
+    assert_eq!(MemoryKind::from_multiboot2(2), MemoryKind::Reserved);
+    assert_eq!(MemoryKind::from_uefi(7), MemoryKind::Usable);
+    assert_eq!(MemoryKind::from_uefi(3), MemoryKind::Reclaimable);
+    assert_eq!(MemoryKind::from_uefi(10), MemoryKind::AcpiNvs);
+    assert_eq!(MemoryKind::from_uefi(11), MemoryKind::Reserved);
+}
+
+#[test]
+fn rounds_and_merges() {
+    let map = limine_map();
+
+    assert_eq!(map.entries()[..2], [
+        MapEntry { base: 0, size: 0x9f000, kind: MemoryKind::Usable },
+        MapEntry { base: 0x9f000, size: 0x1000, kind: MemoryKind::Reserved },
+    ]);
+
+    //  the usable entries around the kernel are not merged, the touching ones are
+    let usable: std::vec::Vec<_> = map.iter().filter(|e| e.kind == MemoryKind::Usable).map(|e| (e.base, e.end())).collect();
+    assert_eq!(usable, [(0, 0x9f000), (0x10_0000, 0x200_0000), (0x220_0000, 0x7fe_0000)]);
+    assert_eq!(map.total(MemoryKind::Kernel), 2 * MB);
+    assert_eq!(map.largest(MemoryKind::Usable).unwrap().size, 94 * MB - 128 * KB);
+}
+
+#[test]
+fn restrictive_kind_wins() {
+    let mut map = MemoryMap::from_entries([
+        (0, 16 * MB, MemoryKind::Usable),
+        (8 * MB, 16 * MB, MemoryKind::Usable),     //  overlapping duplicate
+        (4 * MB, MB, MemoryKind::Bad),
+    ]).unwrap();
+
+    map.reserve(12 * MB, 4 * KB).unwrap();
+    map.add(4 * MB, 2 * MB, MemoryKind::Kernel).unwrap();
+
+    let kinds: std::vec::Vec<_> = map.iter().map(|e| (e.base, e.size, e.kind)).collect();
+    assert_eq!(kinds, [
+        (0, 4 * MB, MemoryKind::Usable),
+        (4 * MB, MB, MemoryKind::Bad),
+        (5 * MB, MB, MemoryKind::Kernel),
+        (6 * MB, 6 * MB, MemoryKind::Usable),
+        (12 * MB, 4 * KB, MemoryKind::Reserved),
+        (12 * MB + 4 * KB, 12 * MB - 4 * KB, MemoryKind::Usable),
+    ]);
+}
+
+#[test]
+fn take_uses_best_fit() {
+    let mut map = limine_map();
+
+    let taken = map.take(100 * KB, MemoryKind::Heap).unwrap();
+    assert_eq!(taken, 0x9f000 - 100 * KB);
+    assert_eq!(map.total(MemoryKind::Heap), 100 * KB);
+
+    assert_eq!(map.take(200 * MB, MemoryKind::Heap), Err(MapError::NoMemory));
+}
+
+#[test]
+fn full_map_is_left_unchanged() {
+    let mut map = MemoryMap::from_entries((0..MAX_MAP_ENTRIES).map(|i| (i * 2 * MB, MB, MemoryKind::Usable))).unwrap();
+    assert_eq!(map.entries().len(), MAX_MAP_ENTRIES);
+
+    assert_eq!(map.reserve(MB / 2, 4 * KB), Err(MapError::Full));
+    assert_eq!(map.entries().len(), MAX_MAP_ENTRIES);
+    assert_eq!(map.total(MemoryKind::Usable), MAX_MAP_ENTRIES * MB);
+}
+
+#[test]
+fn prints_table() {
+    let table = std::format!("{}", limine_map());
+
+    assert!(table.lines().next().unwrap().contains("base"));
+    assert!(table.contains("0x0000000000100000  0x0000000002000000     31744 KiB  usable"));
+    assert!(table.contains("kernel"));
+    assert!(table.contains("ACPI tables"));
+}
+
+#[test]
+fn feeds_heap_and_frames() {
+    //  fake physical memory at 256 MiB, backed by a buffer of the test process
+    const PHYS: usize = 256 * MB;
+    let memory = std::vec![0u64; 24 * MB / 8].leak();
+    let offset = (memory.as_mut_ptr() as usize).next_multiple_of(4 * KB) - PHYS;
+
+    let before = ALLOCATOR.total_bytes();
+    let config = MapConfig::new(offset)
+        .heap_size(4 * MB)
+        .kernel(PHYS, MB)
+        .reserve(PHYS + 2 * MB, MB);
+
+    let map = unsafe { init::memory_map([(PHYS, 20 * MB, MemoryKind::Usable)], &config) }.unwrap();
+
+    assert_eq!(map.total(MemoryKind::Heap), 4 * MB);
+    assert_eq!(ALLOCATOR.total_bytes(), before + 4 * MB);
+    assert_eq!(map.total(MemoryKind::FrameBitmap), 4 * KB);
+
+    let stats = frame::stats().unwrap();
+    assert_eq!(stats.total_bytes(), map.total(MemoryKind::Usable));
+    assert_eq!(stats.total_bytes(), 20 * MB - 2 * MB - 4 * MB - 4 * KB);
+
+    let frame = frame::alloc_frame().unwrap();
+    assert!(map.iter().any(|e| e.kind == MemoryKind::Usable && e.base <= frame.addr() && frame.addr() < e.end()));
+}
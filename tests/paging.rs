@@ -0,0 +1,202 @@
+//	tests/paging.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ministd::mem::{Region, KB, MB, GB};
+use ministd::mem::paging::{
+    CacheMode, FrameSource, Mapper, PageFlags, PageSize, PageTable, PagingError, set_shootdown_handler,
+};
+
+
+/// Fake physical address of the first table
+const PHYS: usize = 0x100_0000;
+
+/// Tables in normal memory, handed out as fake physical frames
+struct Tables {
+    memory: std::boxed::Box<[PageTable]>,
+    free: std::vec::Vec<usize>,
+}
+
+impl Tables {
+    fn new(count: usize) -> Self {
+        let memory = (0..count).map(|_| {
+            let mut table = PageTable::new();
+            table.entries.fill(ministd::mem::paging::PageTableEntry::new(0xdead_0000, PageFlags::PRESENT));   //  garbage
+            table
+        }).collect();
+        //  the first table is the root
+        Self { memory, free: (1..count).rev().map(|i| PHYS + i * 4 * KB).collect() }
+    }
+
+    fn offset(&self) -> usize {
+        (self.memory.as_ptr() as usize).wrapping_sub(PHYS)
+    }
+
+    fn used(&self) -> usize {
+        self.memory.len() - 1 - self.free.len()
+    }
+}
+
+impl FrameSource for Tables {
+    fn alloc_table(&mut self) -> Option<usize> {
+        self.free.pop()
+    }
+
+    fn free_table(&mut self, phys: usize) {
+        assert!(!self.free.contains(&phys), "table freed twice");
+        self.free.push(phys);
+    }
+}
+
+fn mapper(count: usize) -> Mapper<Tables> {
+    let mut tables = Tables::new(count);
+    tables.memory[0].zero();
+    let offset = tables.offset();
+    unsafe { Mapper::new(PHYS, offset, tables) }
+}
+
+const KERNEL: usize = 0xffff_8000_0000_0000;
+
+
+#[test]
+fn map_and_translate_4k() {
+    let mut mapper = mapper(8);
+    let flags = PageFlags::WRITABLE | PageFlags::NO_EXECUTE;
+
+    mapper.map(Region::<4096>::new(KERNEL + 4 * KB, 0x5000, 8 * KB), flags).unwrap();
+
+    let t = mapper.translate(KERNEL + 4 * KB + 0x123).unwrap();
+    assert_eq!(t.phys, 0x5123);
+    assert_eq!(t.size, PageSize::Size4K);
+    assert!(t.flags.contains(PageFlags::PRESENT | PageFlags::WRITABLE | PageFlags::NO_EXECUTE));
+    assert_eq!(mapper.translate_addr(KERNEL + 8 * KB), Some(0x6000));
+    assert_eq!(mapper.translate_addr(KERNEL), None);
+    assert_eq!(mapper.translate_addr(KERNEL + 12 * KB), None);
+
+    //  PDPT, PD and PT
+    assert_eq!(mapper.source().used(), 3);
+}
+
+#[test]
+fn picks_largest_pages() {
+    let mut mapper = mapper(8);
+    mapper.set_max_page_size(PageSize::Size1G);
+
+    //  4 KiB up to the 2 MiB boundary, 2 MiB pages, one 1 GiB page, 4 KiB at the end
+    let virt = GB - 8 * KB;
+    mapper.map(Region::<4096>::new(virt, virt, 8 * KB + GB + 4 * KB), PageFlags::WRITABLE).unwrap();
+
+    assert_eq!(mapper.translate(virt).unwrap().size, PageSize::Size4K);
+    assert_eq!(mapper.translate(GB + 123).unwrap(), ministd::mem::paging::Translation {
+        phys: GB + 123,
+        flags: PageFlags::PRESENT | PageFlags::WRITABLE | PageFlags::HUGE,
+        size: PageSize::Size1G,
+    });
+    assert_eq!(mapper.translate(2 * GB).unwrap().size, PageSize::Size4K);
+
+    //  the default stops at 2 MiB
+    let mut small = self::mapper(8);
+    small.map(Region::<4096>::new(0, 0, 4 * MB), PageFlags::EMPTY).unwrap();
+    assert_eq!(small.translate(3 * MB).unwrap().size, PageSize::Size2M);
+}
+
+#[test]
+fn map_with_checks_alignment() {
+    let mut mapper = mapper(8);
+
+    assert_eq!(mapper.map_with(Region::<4096>::new(MB, 0, 2 * MB), PageFlags::EMPTY, PageSize::Size2M), Err(PagingError::NotAligned));
+    assert_eq!(mapper.map(unsafe { Region::<1>::new_unchecked(0x1800, 0, 4 * KB) }, PageFlags::EMPTY), Err(PagingError::NotAligned));
+    assert_eq!(mapper.map(Region::<4096>::new(0x0000_8000_0000_0000, 0, 4 * KB), PageFlags::EMPTY), Err(PagingError::NonCanonical));
+
+    mapper.map_with(Region::<4096>::new(2 * MB, 4 * MB, 4 * MB), PageFlags::EMPTY, PageSize::Size2M).unwrap();
+    assert_eq!(mapper.translate_addr(5 * MB), Some(7 * MB));
+}
+
+#[test]
+fn failed_map_changes_nothing() {
+    let mut mapper = mapper(8);
+    mapper.map(Region::<4096>::new(KERNEL + 16 * KB, 0, 4 * KB), PageFlags::EMPTY).unwrap();
+    let used = mapper.source().used();
+
+    //  overlaps the page mapped above
+    assert_eq!(mapper.map(Region::<4096>::new(KERNEL, 0x10_0000, 32 * KB), PageFlags::EMPTY), Err(PagingError::AlreadyMapped));
+    assert_eq!(mapper.translate_addr(KERNEL), None);
+    assert_eq!(mapper.translate_addr(KERNEL + 16 * KB), Some(0));
+    assert_eq!(mapper.source().used(), used);
+
+    //  runs out of tables in the middle
+    let mut tiny = self::mapper(4);
+    assert_eq!(tiny.map(Region::<4096>::new(0, 0, 4 * MB), PageFlags::EMPTY.cache(CacheMode::Uncached)).ok(), Some(()));
+    assert_eq!(tiny.map(Region::<4096>::new(GB - 4 * KB, 0, 8 * KB), PageFlags::EMPTY), Err(PagingError::NoFrames));
+    assert_eq!(tiny.translate_addr(GB - 4 * KB), None);
+    assert_eq!(tiny.source().used(), 2);
+}
+
+#[test]
+fn unmap_frees_tables() {
+    let mut mapper = mapper(16);
+    let region = Region::<4096>::new(KERNEL, 0x20_0000, 2 * MB + 8 * KB);
+
+    mapper.map(region, PageFlags::WRITABLE).unwrap();
+    assert_eq!(mapper.translate(KERNEL).unwrap().size, PageSize::Size2M);
+
+    //  half of the huge page
+    assert_eq!(mapper.unmap(Region::<4096>::new(KERNEL, 0, MB)), Err(PagingError::HugePage));
+    assert_eq!(mapper.unmap(Region::<4096>::new(KERNEL, 0, 4 * MB)), Err(PagingError::NotMapped));
+    assert!(mapper.translate(KERNEL).is_some());
+
+    mapper.unmap(Region::<4096>::new(KERNEL + 2 * MB + 4 * KB, 0, 4 * KB)).unwrap();
+    assert_eq!(mapper.translate_addr(KERNEL + 2 * MB + 4 * KB), None);
+    assert_eq!(mapper.translate_addr(KERNEL + 2 * MB), Some(0x40_0000));
+
+    assert_eq!(mapper.unmap(region), Err(PagingError::NotMapped));
+    mapper.unmap(Region::<4096>::new(KERNEL, 0, 2 * MB + 4 * KB)).unwrap();
+    assert_eq!(mapper.translate_addr(KERNEL + 2 * MB), None);
+    assert_eq!(mapper.source().used(), 0);
+}
+
+#[test]
+fn protect_changes_flags() {
+    let mut mapper = mapper(8);
+    let region = Region::<4096>::new(0x40_0000, 0x40_0000, 16 * KB);
+
+    mapper.map(region, PageFlags::WRITABLE).unwrap();
+    mapper.protect(region, PageFlags::USER | PageFlags::NO_EXECUTE.cache(CacheMode::WriteThrough)).unwrap();
+
+    let t = mapper.translate(0x40_1000).unwrap();
+    assert_eq!(t.phys, 0x40_1000);
+    assert!(!t.flags.contains(PageFlags::WRITABLE));
+    assert!(t.flags.contains(PageFlags::USER | PageFlags::NO_EXECUTE | PageFlags::PRESENT));
+    assert_eq!(t.flags.cache_mode(), CacheMode::WriteThrough);
+
+    assert_eq!(mapper.protect(Region::<4096>::new(0x40_0000, 0, 32 * KB), PageFlags::EMPTY), Err(PagingError::NotMapped));
+}
+
+#[test]
+fn shootdown_handler_runs() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    fn handler(_virt: usize, size: usize) {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(size, Ordering::Relaxed);
+    }
+
+    let mut mapper = mapper(8);
+    //  addresses used by no other test, the handler is global
+    let region = Region::<4096>::new(0x7000_0000_0000, 0, 8 * KB);
+
+    set_shootdown_handler(Some(handler));
+    mapper.map(region, PageFlags::EMPTY).unwrap();
+    mapper.protect(region, PageFlags::WRITABLE).unwrap();
+    mapper.unmap(region).unwrap();
+    set_shootdown_handler(None);
+
+    assert!(CALLS.load(Ordering::Relaxed) >= 2);
+    assert!(BYTES.load(Ordering::Relaxed) >= 16 * KB);
+}
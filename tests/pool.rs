@@ -0,0 +1,118 @@
+//	tests/pool.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::mem::{Pool, PoolBox, PAGE_SIZE};
+
+
+/// Counts its drops
+struct Tracked<'c>(&'c Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+
+#[test]
+fn empty_pool_does_not_allocate() {
+    let pool: Pool<u64> = Pool::new();
+    let stats = pool.stats();
+
+    assert_eq!(stats.capacity, 0);
+    assert_eq!(stats.chunks, 0);
+    assert_eq!(stats.object_size, 8);
+    assert_eq!(stats.total_bytes(), 0);
+}
+
+#[test]
+fn pool_grows_by_chunks() {
+    let pool: Pool<[u64; 8]> = Pool::new();
+
+    let first = pool.alloc([1; 8]);
+    let per_chunk = pool.capacity();
+    assert!(per_chunk > 1);
+    assert_eq!(pool.stats().chunks, 1);
+    assert!(pool.stats().chunk_bytes <= PAGE_SIZE);
+
+    let mut boxes: std::vec::Vec<PoolBox<'_, [u64; 8]>> = (1..per_chunk).map(|_| pool.alloc([2; 8])).collect();
+    assert_eq!(pool.available(), 0);
+    assert_eq!(pool.stats().chunks, 1);
+
+    boxes.push(pool.alloc([3; 8]));
+    assert_eq!(pool.stats().chunks, 2);
+    assert_eq!(pool.capacity(), 2 * per_chunk);
+
+    assert_eq!(*first, [1; 8]);
+    assert_eq!(*boxes[per_chunk - 1], [3; 8]);
+}
+
+#[test]
+fn freed_slot_is_reused() {
+    let pool: Pool<u32> = Pool::new();
+
+    let a = pool.alloc(1);
+    let b = pool.alloc(2);
+    let slot = &*b as *const u32;
+
+    drop(b);
+    let c = pool.alloc(3);
+
+    assert_eq!(&*c as *const u32, slot);
+    assert_eq!((*a, *c), (1, 3));
+    assert_eq!(pool.stats().chunks, 1);
+}
+
+#[test]
+fn dropped_box_gives_slot_back() {
+    let drops = Cell::new(0);
+    let pool: Pool<Tracked<'_>> = Pool::new();
+
+    let boxed = pool.alloc(Tracked(&drops));
+    assert_eq!(pool.used(), 1);
+
+    drop(boxed);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), pool.capacity());
+
+    let boxed = pool.alloc(Tracked(&drops));
+    let value = PoolBox::into_inner(boxed);
+    //  the slot is free, the value is not dropped yet
+    assert_eq!(pool.used(), 0);
+    assert_eq!(drops.get(), 1);
+    drop(value);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn counters_follow_usage() {
+    let pool: Pool<u64> = Pool::new();
+    pool.reserve(10);
+
+    let capacity = pool.capacity();
+    assert!(capacity >= 10);
+
+    let boxes: std::vec::Vec<_> = (0..5).map(|i| pool.alloc(i)).collect();
+    let stats = pool.stats();
+    assert_eq!(stats.used, 5);
+    assert_eq!(stats.peak, 5);
+    assert_eq!(stats.allocations, 5);
+    assert_eq!(stats.available(), capacity - 5);
+
+    drop(boxes);
+    let _one = pool.alloc(7);
+    let stats = pool.stats();
+    assert_eq!(stats.used, 1);
+    assert_eq!(stats.peak, 5);
+    assert_eq!(stats.allocations, 6);
+    assert_eq!(stats.frees, 5);
+    assert_eq!(stats.failed_allocations, 0);
+    assert_eq!(stats.total_bytes(), stats.chunks * stats.chunk_bytes);
+}
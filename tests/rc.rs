@@ -0,0 +1,59 @@
+//	tests/rc.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::Rc;
+use ministd::sync::Arc;
+
+
+struct Tracked<'c>(&'c Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+
+#[test]
+fn rc_counts_references() {
+    let drops = Cell::new(0);
+
+    let a = Rc::new(Tracked(&drops));
+    let b = a.clone();
+    assert_eq!(a.strong_count(), 2);
+    assert!(a.ptr_eq(&b));
+
+    drop(a);
+    assert_eq!(b.strong_count(), 1);
+    assert_eq!(drops.get(), 0);
+
+    drop(b);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn arc_is_shared_between_threads() {
+    let counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let handles: std::vec::Vec<_> = (0..4).map(|_| {
+        let counter = counter.clone();
+        std::thread::spawn(move || {
+            for _ in 0..1000 {
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        })
+    }).collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(counter.load(std::sync::atomic::Ordering::Relaxed), 4000);
+    assert_eq!(counter.strong_count(), 1);
+}
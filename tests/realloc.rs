@@ -0,0 +1,117 @@
+//	tests/realloc.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+//  the debug heap moves every reallocated block
+#![cfg(not(feature = "debug-heap"))]
+
+
+use std::sync::{Mutex, MutexGuard};
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::ALLOCATOR;
+use ministd::mem::PAGE_SIZE;
+
+
+/// Serializes the tests, they depend on which blocks are free
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the number of reallocations and the number of those that did not move the block
+fn counters() -> (usize, usize) {
+    let stats = ALLOCATOR.stats();
+    (stats.reallocations, stats.in_place_reallocations)
+}
+
+fn layout(size: usize) -> Layout {
+    Layout::from_size_align(size, 8).unwrap()
+}
+
+
+#[test]
+fn shrink_splits_off_the_tail() {
+    let _serial = serial();
+    let (reallocs, in_place) = counters();
+
+    let ptr = unsafe { ALLOCATOR.alloc(layout(2 * PAGE_SIZE)) };
+    let allocated = ALLOCATOR.allocated_bytes();
+
+    let shrunk = unsafe { ALLOCATOR.realloc(ptr, layout(2 * PAGE_SIZE), PAGE_SIZE) };
+    assert_eq!(shrunk, ptr);
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated - PAGE_SIZE);
+    assert_eq!(counters(), (reallocs + 1, in_place + 1));
+
+    //  the upper half is free again, it is the next block of its size
+    let tail = unsafe { ALLOCATOR.alloc(layout(PAGE_SIZE)) };
+    assert_eq!(tail as usize, ptr as usize + PAGE_SIZE);
+
+    unsafe {
+        ALLOCATOR.dealloc(tail, layout(PAGE_SIZE));
+        ALLOCATOR.dealloc(shrunk, layout(PAGE_SIZE));
+    }
+}
+
+#[test]
+fn grow_merges_with_free_buddy() {
+    let _serial = serial();
+
+    //  free upper half right behind the block
+    let ptr = unsafe { ALLOCATOR.alloc(layout(2 * PAGE_SIZE)) };
+    let ptr = unsafe { ALLOCATOR.realloc(ptr, layout(2 * PAGE_SIZE), PAGE_SIZE) };
+    unsafe { ptr.write_bytes(0xab, PAGE_SIZE) };
+
+    let (reallocs, in_place) = counters();
+    let grown = unsafe { ALLOCATOR.realloc(ptr, layout(PAGE_SIZE), 2 * PAGE_SIZE) };
+    assert_eq!(grown, ptr);
+    assert_eq!(counters(), (reallocs + 1, in_place + 1));
+    assert_eq!(unsafe { *grown.add(PAGE_SIZE - 1) }, 0xab);
+
+    unsafe { ALLOCATOR.dealloc(grown, layout(2 * PAGE_SIZE)) };
+}
+
+#[test]
+fn grow_copies_when_buddy_is_used() {
+    let _serial = serial();
+
+    let ptr = unsafe { ALLOCATOR.alloc(layout(2 * PAGE_SIZE)) };
+    let ptr = unsafe { ALLOCATOR.realloc(ptr, layout(2 * PAGE_SIZE), PAGE_SIZE) };
+    let tail = unsafe { ALLOCATOR.alloc(layout(PAGE_SIZE)) };
+    assert_eq!(tail as usize, ptr as usize + PAGE_SIZE);
+    unsafe { ptr.write_bytes(0xcd, PAGE_SIZE) };
+
+    //  the buddy is in use, the block has to move
+    let (reallocs, in_place) = counters();
+    let moved = unsafe { ALLOCATOR.realloc(ptr, layout(PAGE_SIZE), 2 * PAGE_SIZE) };
+    assert!(!moved.is_null());
+    assert_ne!(moved, ptr);
+    assert_eq!(counters(), (reallocs + 1, in_place));
+    assert!((0..PAGE_SIZE).all(|i| unsafe { *moved.add(i) } == 0xcd));
+
+    unsafe {
+        ALLOCATOR.dealloc(tail, layout(PAGE_SIZE));
+        ALLOCATOR.dealloc(moved, layout(2 * PAGE_SIZE));
+    }
+}
+
+#[test]
+fn same_block_size_does_not_touch_the_heap() {
+    let _serial = serial();
+
+    //  both sizes use the same block
+    let ptr = unsafe { ALLOCATOR.alloc(layout(PAGE_SIZE + 1)) };
+    let allocated = ALLOCATOR.allocated_bytes();
+
+    let (reallocs, in_place) = counters();
+    let resized = unsafe { ALLOCATOR.realloc(ptr, layout(PAGE_SIZE + 1), 2 * PAGE_SIZE) };
+    assert_eq!(resized, ptr);
+    assert_eq!(counters(), (reallocs + 1, in_place + 1));
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated);
+
+    unsafe { ALLOCATOR.dealloc(resized, layout(2 * PAGE_SIZE)) };
+}
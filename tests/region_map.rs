@@ -0,0 +1,129 @@
+//	tests/region_map.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::mem::{Region, RegionMap, RegionTag, KB, MB};
+use ministd::mem::region_map::RegionMapError;
+
+
+fn region(start: usize, size: usize) -> Region {
+    Region::new(start, 0, size)
+}
+
+fn layout(map: &RegionMap<8>) -> std::vec::Vec<(usize, usize, RegionTag)> {
+    map.iter().map(|e| (e.start(), e.end(), e.tag)).collect()
+}
+
+
+#[test]
+fn insert_merges_neighbours() {
+    let mut map: RegionMap<8> = RegionMap::new();
+
+    map.insert(region(0x1000, 4 * KB), RegionTag::Heap).unwrap();
+    map.insert(region(0x3000, 4 * KB), RegionTag::Heap).unwrap();
+    map.insert(region(0x2000, 4 * KB), RegionTag::Heap).unwrap();
+    map.insert(region(0x4000, 4 * KB), RegionTag::Stack).unwrap();
+
+    assert_eq!(layout(&map), [(0x1000, 0x4000, RegionTag::Heap), (0x4000, 0x5000, RegionTag::Stack)]);
+
+    //  physical addresses must be contiguous as well
+    map.insert(Region::new(0x10_0000, 0x20_0000, 4 * KB), RegionTag::Mmio).unwrap();
+    map.insert(Region::new(0x10_1000, 0x30_0000, 4 * KB), RegionTag::Mmio).unwrap();
+    map.insert(Region::new(0x10_2000, 0x30_1000, 4 * KB), RegionTag::Mmio).unwrap();
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.find(0x10_2000).unwrap().region.phys(), 0x30_0000);
+}
+
+#[test]
+fn insert_refuses_overlap() {
+    let mut map: RegionMap<8> = RegionMap::new();
+    map.insert(region(MB, MB), RegionTag::KernelText).unwrap();
+    map.insert(region(16 * MB, 4 * MB), RegionTag::Framebuffer).unwrap();
+
+    assert_eq!(map.insert(region(MB + MB / 2, 4 * KB), RegionTag::Heap), Err(RegionMapError::Overlap(RegionTag::KernelText)));
+    assert_eq!(map.insert(region(8 * MB, 9 * MB), RegionTag::Heap), Err(RegionMapError::Overlap(RegionTag::Framebuffer)));
+    assert_eq!(map.insert(region(0, 0), RegionTag::Heap), Err(RegionMapError::Invalid));
+    assert_eq!(map.overlaps(region(19 * MB, 2 * MB)).unwrap().tag, RegionTag::Framebuffer);
+    assert!(map.overlaps(region(2 * MB, 14 * MB)).is_none());
+
+    map.insert(region(2 * MB, 14 * MB), RegionTag::Heap).unwrap();
+    assert_eq!(map.len(), 3);
+}
+
+#[test]
+fn subtract_splits() {
+    let mut map: RegionMap<8> = RegionMap::new();
+    map.insert(Region::new(0, 0x10_0000, 64 * KB), RegionTag::Free).unwrap();
+    map.insert(region(64 * KB, 64 * KB), RegionTag::Heap).unwrap();
+
+    map.subtract(region(16 * KB, 4 * KB)).unwrap();
+    assert_eq!(layout(&map), [
+        (0, 16 * KB, RegionTag::Free),
+        (20 * KB, 64 * KB, RegionTag::Free),
+        (64 * KB, 128 * KB, RegionTag::Heap),
+    ]);
+    assert_eq!(map.as_slice()[1].region.phys(), 0x10_0000 + 20 * KB);
+
+    //  over the boundary of two regions
+    map.subtract(region(60 * KB, 8 * KB)).unwrap();
+    assert_eq!(layout(&map)[1..], [(20 * KB, 60 * KB, RegionTag::Free), (68 * KB, 128 * KB, RegionTag::Heap)]);
+
+    map.subtract(region(0, MB)).unwrap();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn full_map_is_left_unchanged() {
+    let mut map: RegionMap<2> = RegionMap::new();
+    map.insert(region(0, MB), RegionTag::Free).unwrap();
+    map.insert(region(2 * MB, MB), RegionTag::Free).unwrap();
+
+    assert_eq!(map.insert(region(4 * MB, MB), RegionTag::Heap), Err(RegionMapError::Full));
+    assert_eq!(map.subtract(region(4 * KB, 4 * KB)), Err(RegionMapError::Full));
+    assert_eq!(map.set(region(4 * KB, 4 * KB), RegionTag::Heap), Err(RegionMapError::Full));
+    assert_eq!(map.as_slice()[0].region.size(), MB);
+}
+
+#[test]
+fn intersect_clips() {
+    let mut map: RegionMap<8> = RegionMap::new();
+    map.insert(region(0, 8 * KB), RegionTag::KernelText).unwrap();
+    map.insert(region(12 * KB, 8 * KB), RegionTag::KernelData).unwrap();
+    map.insert(region(32 * KB, 4 * KB), RegionTag::Stack).unwrap();
+
+    let parts: std::vec::Vec<_> = map.intersect(region(4 * KB, 12 * KB)).map(|e| (e.start(), e.end(), e.tag)).collect();
+    assert_eq!(parts, [(4 * KB, 8 * KB, RegionTag::KernelText), (12 * KB, 16 * KB, RegionTag::KernelData)]);
+}
+
+#[test]
+fn carve_from_free() {
+    let mut map: RegionMap<8> = RegionMap::new();
+    map.insert(Region::new(4 * KB, 0x40_1000, 2 * MB), RegionTag::Free).unwrap();
+
+    let taken = map.carve(8 * KB, 64 * KB, RegionTag::Dma).unwrap();
+    assert_eq!(taken.virt() as usize, 64 * KB);
+    assert_eq!(taken.phys(), 0x41_0000);
+    assert_eq!(map.len(), 3);
+
+    assert_eq!(map.carve(4 * MB, 4 * KB, RegionTag::Heap), Err(RegionMapError::NoSpace));
+    assert_eq!(map.iter_tag(RegionTag::Free).map(|e| e.region.size()).sum::<usize>(), 2 * MB - 8 * KB);
+}
+
+#[test]
+fn find_gap_skips_regions() {
+    let mut map: RegionMap<8> = RegionMap::new();
+    map.insert(region(0x1000, 0x1000), RegionTag::Stack).unwrap();
+    map.insert(region(0x3000, 0xd000), RegionTag::Heap).unwrap();
+
+    assert_eq!(map.find_gap(0x1000, 0x1000, 0, usize::MAX), Some(0));
+    assert_eq!(map.find_gap(0x1000, 0x1000, 0x1000, usize::MAX), Some(0x2000));
+    assert_eq!(map.find_gap(0x2000, 0x1000, 0x1000, usize::MAX), Some(0x10000));
+    assert_eq!(map.find_gap(0x1000, 0x8000, 0x1000, usize::MAX), Some(0x10000));
+    assert_eq!(map.find_gap(0x2000, 0x1000, 0x1000, 0x11000), None);
+
+    let text = std::format!("{}", map);
+    assert!(text.contains("heap") && text.contains("stack"));
+}
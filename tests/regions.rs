@@ -0,0 +1,173 @@
+//	tests/regions.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::sync::{Mutex, MutexGuard};
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::ALLOCATOR;
+use ministd::alloc::{regions, RegionError};
+use ministd::mem::{Region, KB};
+
+
+/// Serializes the tests, all of them change the same heap
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+const SIZE: usize = 64 * KB;
+
+/// Host buffer that can be given to the heap
+/// - aligned to its size, so the buddy allocator keeps it as one block
+struct HostBuffer(*mut u8);
+
+impl HostBuffer {
+    fn new() -> Self {
+        let ptr = unsafe { std::alloc::alloc(Self::layout()) };
+        assert!(!ptr.is_null());
+        Self(ptr)
+    }
+
+    fn layout() -> Layout {
+        Layout::from_size_align(SIZE, SIZE).unwrap()
+    }
+
+    fn region(&self) -> Region {
+        Region::new(self.0 as usize, self.0 as usize, SIZE)
+    }
+
+    fn contains(&self, ptr: *mut u8) -> bool {
+        (self.0 as usize..self.0 as usize + SIZE).contains(&(ptr as usize))
+    }
+}
+
+impl Drop for HostBuffer {
+    fn drop(&mut self) {
+        unsafe { std::alloc::dealloc(self.0, Self::layout()) };
+    }
+}
+
+/// Allocates blocks of `layout` until one lands in `buffer`, the others are freed
+fn alloc_in(buffer: &HostBuffer, layout: Layout) -> *mut u8 {
+    let mut others = std::vec::Vec::new();
+
+    let found = loop {
+        let ptr = unsafe { ALLOCATOR.alloc(layout) };
+        assert!(!ptr.is_null(), "no block was allocated from the region");
+        if buffer.contains(ptr) {
+            break ptr;
+        }
+        others.push(ptr);
+    };
+
+    for ptr in others {
+        unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    }
+    found
+}
+
+
+#[test]
+fn duplicate_region_is_rejected() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+    assert_eq!(unsafe { ALLOCATOR.add_to_heap(buffer.region()) }, Err(RegionError::Duplicate));
+
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+}
+
+#[test]
+fn overlapping_region_is_rejected() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+
+    let addr = buffer.0 as usize + SIZE / 2;
+    let overlapping = Region::new(addr, addr, SIZE);
+    assert_eq!(unsafe { ALLOCATOR.add_to_heap(overlapping) }, Err(RegionError::Overlap(buffer.region())));
+
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+}
+
+#[test]
+fn free_region_is_removed() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    let total = ALLOCATOR.total_bytes();
+    let count = regions::count();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total + SIZE);
+    assert_eq!(regions::count(), count + 1);
+
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total);
+    assert_eq!(regions::count(), count);
+    assert!(regions::regions().all(|r| r != buffer.region()));
+}
+
+#[test]
+fn region_with_live_allocation_is_kept() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+
+    let layout = Layout::from_size_align(SIZE / 4, 8).unwrap();
+    let ptr = alloc_in(&buffer, layout);
+    let total = ALLOCATOR.total_bytes();
+
+    assert_eq!(unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }, Err(RegionError::InUse));
+    assert_eq!(ALLOCATOR.total_bytes(), total);
+    assert!(regions::regions().any(|r| r == buffer.region()));
+
+    unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+}
+
+#[test]
+fn unknown_region_is_not_removed() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    assert_eq!(unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }, Err(RegionError::NotFound));
+}
+
+#[test]
+fn region_is_shrunk() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+    let total = ALLOCATOR.total_bytes();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+    unsafe { ALLOCATOR.shrink_region(buffer.region(), SIZE / 2) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total + SIZE / 2);
+
+    let shrunk = Region::new(buffer.0 as usize, buffer.0 as usize, SIZE / 2);
+    assert!(regions::regions().any(|r| r == shrunk));
+
+    unsafe { ALLOCATOR.remove_from_heap(shrunk) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total);
+}
+
+#[test]
+fn region_is_not_grown_by_shrink() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+    assert_eq!(unsafe { ALLOCATOR.shrink_region(buffer.region(), SIZE) }, Err(RegionError::Invalid));
+    assert_eq!(unsafe { ALLOCATOR.shrink_region(buffer.region(), 2 * SIZE) }, Err(RegionError::Invalid));
+
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+}
@@ -0,0 +1,180 @@
+//	tests/slab.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing,slab`
+
+//  the debug heap adds red zones to every block, so the slabs see other sizes
+#![cfg(not(feature = "debug-heap"))]
+
+
+use std::sync::{Mutex, MutexGuard};
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::ALLOCATOR;
+use ministd::alloc::{regions, RegionError};
+use ministd::mem::{Region, KB, PAGE_SIZE};
+
+
+/// Serializes the tests, the counts of allocated bytes are global
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Object sizes of the slab size classes
+const CLASSES: [usize; 7] = [16, 32, 64, 128, 256, 512, 1024];
+
+const SIZE: usize = 64 * KB;
+
+/// Host buffer that can be given to the heap
+/// - aligned to its size, so the page allocator keeps it as one block
+struct HostBuffer(*mut u8);
+
+impl HostBuffer {
+    fn new() -> Self {
+        let ptr = unsafe { std::alloc::alloc(Self::layout()) };
+        assert!(!ptr.is_null());
+        Self(ptr)
+    }
+
+    fn layout() -> Layout {
+        Layout::from_size_align(SIZE, SIZE).unwrap()
+    }
+
+    fn region(&self) -> Region {
+        Region::new(self.0 as usize, self.0 as usize, SIZE)
+    }
+
+    fn contains(&self, ptr: *mut u8) -> bool {
+        (self.0 as usize..self.0 as usize + SIZE).contains(&(ptr as usize))
+    }
+}
+
+impl Drop for HostBuffer {
+    fn drop(&mut self) {
+        unsafe { std::alloc::dealloc(self.0, Self::layout()) };
+    }
+}
+
+
+#[test]
+fn size_classes_reuse_objects() {
+    let _serial = serial();
+
+    for class in CLASSES {
+        //  the smallest size that still falls into this class
+        let layout = Layout::from_size_align(class / 2 + 1, 8).unwrap();
+        let allocated = ALLOCATOR.allocated_bytes();
+
+        let first = unsafe { ALLOCATOR.alloc(layout) };
+        let second = unsafe { ALLOCATOR.alloc(layout) };
+        assert_eq!(ALLOCATOR.allocated_bytes(), allocated + 2 * class);
+        assert_eq!(first as usize % class, 0);
+        assert_eq!(second as usize % class, 0);
+
+        //  the freed object is the next one given out
+        unsafe { ALLOCATOR.dealloc(first, layout) };
+        let third = unsafe { ALLOCATOR.alloc(layout) };
+        assert_eq!(third, first);
+
+        unsafe {
+            ALLOCATOR.dealloc(second, layout);
+            ALLOCATOR.dealloc(third, layout);
+        }
+        assert_eq!(ALLOCATOR.allocated_bytes(), allocated);
+    }
+}
+
+#[test]
+fn large_layouts_use_page_blocks() {
+    let _serial = serial();
+    let allocated = ALLOCATOR.allocated_bytes();
+
+    //  just above the largest size class
+    let small = Layout::from_size_align(1024 + 1, 8).unwrap();
+    let ptr = unsafe { ALLOCATOR.alloc(small) };
+    assert_eq!(ptr as usize % PAGE_SIZE, 0);
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated + PAGE_SIZE);
+
+    //  rounded up to a power of two pages, the block is aligned to its size
+    let large = Layout::from_size_align(3 * PAGE_SIZE, 8).unwrap();
+    let block = unsafe { ALLOCATOR.alloc(large) };
+    assert_eq!(block as usize % (4 * PAGE_SIZE), 0);
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated + 5 * PAGE_SIZE);
+
+    unsafe {
+        ALLOCATOR.dealloc(ptr, small);
+        ALLOCATOR.dealloc(block, large);
+    }
+    assert_eq!(ALLOCATOR.allocated_bytes(), allocated);
+}
+
+#[test]
+fn stats_count_free_objects_by_size_class() {
+    let _serial = serial();
+
+    let layout = Layout::from_size_align(200, 8).unwrap();
+    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+
+    let stats = ALLOCATOR.stats();
+    let histogram = stats.free_blocks.unwrap();
+    //  the rest of the slab of the 256 B class
+    assert!(histogram[256usize.trailing_zeros() as usize] > 0);
+    assert!(stats.largest_free_block.unwrap() >= PAGE_SIZE);
+
+    let printed = std::format!("{}", stats);
+    assert!(printed.contains("  free blocks:"));
+    assert!(printed.contains("256 B: "));
+
+    unsafe { ALLOCATOR.dealloc(ptr, layout) };
+}
+
+#[test]
+fn region_with_live_slab_is_kept() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+    let total = ALLOCATOR.total_bytes();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+
+    //  fill the slabs of the class until it takes a new slab from the region
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let mut others = std::vec::Vec::new();
+    let ptr = loop {
+        let ptr = unsafe { ALLOCATOR.alloc(layout) };
+        assert!(!ptr.is_null(), "no slab was taken from the region");
+        if buffer.contains(ptr) {
+            break ptr;
+        }
+        others.push(ptr);
+    };
+
+    assert_eq!(unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }, Err(RegionError::InUse));
+    assert!(regions::regions().any(|r| r == buffer.region()));
+
+    //  the empty slab stays cached by the class, it is released by the removal
+    unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total);
+    assert!(regions::regions().all(|r| r != buffer.region()));
+
+    for ptr in others {
+        unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    }
+}
+
+#[test]
+fn free_region_is_removed() {
+    let _serial = serial();
+    let buffer = HostBuffer::new();
+    let total = ALLOCATOR.total_bytes();
+
+    unsafe { ALLOCATOR.add_to_heap(buffer.region()) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total + SIZE);
+
+    unsafe { ALLOCATOR.remove_from_heap(buffer.region()) }.unwrap();
+    assert_eq!(ALLOCATOR.total_bytes(), total);
+}
@@ -0,0 +1,159 @@
+//	tests/stack.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use ministd::mem::{Region, KB};
+use ministd::mem::paging::{FrameSource, Mapper, PageFlags, PageTable};
+use ministd::mem::stack::{self, Stacks, StackError, GUARD_SIZE, STACK_PATTERN};
+
+
+/// Fake physical address of the first table
+const PHYS: usize = 0x100_0000;
+
+/// Frames in normal memory, handed out as fake physical frames (for tables and stacks)
+struct Frames {
+    memory: std::boxed::Box<[PageTable]>,
+    free: std::vec::Vec<usize>,
+}
+
+impl FrameSource for Frames {
+    fn alloc_table(&mut self) -> Option<usize> {
+        self.free.pop()
+    }
+
+    fn free_table(&mut self, phys: usize) {
+        assert!(!self.free.contains(&phys), "frame freed twice");
+        self.free.push(phys);
+    }
+}
+
+fn mapper(count: usize) -> Mapper<Frames> {
+    let frames = Frames {
+        memory: (0..count).map(|_| PageTable::new()).collect(),
+        free: (1..count).rev().map(|i| PHYS + i * 4 * KB).collect(),
+    };
+    let offset = (frames.memory.as_ptr() as usize).wrapping_sub(PHYS);
+    unsafe { Mapper::new(PHYS, offset, frames) }
+}
+
+/// Virtual area of `size` bytes backed by memory of the test process
+/// - the `Mapper` above is not active, the stacks are filled through this memory
+fn area(size: usize) -> Region {
+    let memory = std::vec![0u64; (size + 4 * KB) / 8].leak();
+    Region::new(memory.as_ptr() as usize, 0, size)
+}
+
+
+#[test]
+fn alloc_maps_below_guard() {
+    let mut mapper = mapper(16);
+    let mut stacks: Stacks<4> = Stacks::new(area(64 * KB));
+
+    let stack = stacks.alloc(&mut mapper, 10 * KB).unwrap();
+    assert_eq!(stack.size(), 12 * KB);
+    assert_eq!(stack.limit(), stacks.area().virt() as usize + GUARD_SIZE);
+
+    assert_eq!(mapper.translate(stack.guard().virt() as usize), None);
+    for page in (stack.limit()..stack.top()).step_by(4 * KB) {
+        let flags = mapper.translate(page).unwrap().flags;
+        assert!(flags.contains(PageFlags::WRITABLE | PageFlags::NO_EXECUTE));
+    }
+
+    assert!(stack.canary_ok());
+    assert_eq!(unsafe { *((stack.top() - 8) as *const u64) }, STACK_PATTERN);
+    assert_eq!(stacks.find(stack.top() - 1), Some(stack));
+    assert_eq!(stacks.alloc(&mut mapper, 0), Err(StackError::Invalid));
+}
+
+#[test]
+fn measures_high_water_mark() {
+    let mut mapper = mapper(16);
+    let mut stacks: Stacks<4> = Stacks::new(area(64 * KB));
+    let stack = stacks.alloc(&mut mapper, 16 * KB).unwrap();
+
+    assert_eq!(stack.high_water_mark(), 0);
+
+    //  a call chain that went 100 bytes deep
+    unsafe { *((stack.top() - 100) as *mut u8) = 1 };
+    assert_eq!(stack.high_water_mark(), 104);
+
+    let table = std::format!("{}", stacks);
+    assert!(table.contains("16 KiB") && table.contains("ok"));
+}
+
+#[test]
+fn detects_overflow() {
+    let mut mapper = mapper(16);
+    let mut stacks: Stacks<4> = Stacks::new(area(64 * KB));
+    let first = stacks.alloc(&mut mapper, 8 * KB).unwrap();
+    let second = stacks.alloc(&mut mapper, 8 * KB).unwrap();
+
+    assert_eq!(stacks.check_all(), Ok(()));
+
+    unsafe { *(second.limit() as *mut u64) = 0 };
+    assert_eq!(second.check(), Err(StackError::Overflow));
+    assert_eq!(first.check(), Ok(()));
+    assert_eq!(stacks.check_all(), Err(second));
+    assert_eq!(second.high_water_mark(), second.size());
+    assert!(std::format!("{}", stacks).contains("OVERFLOW"));
+
+    //  the page fault handler finds the stack by the faulting address
+    assert_eq!(stacks.guard_of(second.limit() - 8), Some(second));
+    assert_eq!(stacks.guard_of(second.limit()), None);
+}
+
+#[test]
+fn free_reuses_space_and_frames() {
+    let mut mapper = mapper(24);
+    let mut stacks: Stacks<4> = Stacks::new(area(32 * KB));
+
+    let first = stacks.alloc(&mut mapper, 8 * KB).unwrap();
+    let second = stacks.alloc(&mut mapper, 8 * KB).unwrap();
+    assert_eq!(second.guard().virt() as usize, first.top());
+    assert_eq!(stacks.alloc(&mut mapper, 8 * KB), Err(StackError::NoSpace));
+
+    let free = mapper.source().free.len();
+    unsafe { stacks.free(&mut mapper, first) }.unwrap();
+    assert_eq!(mapper.source().free.len(), free + 2);
+    assert_eq!(mapper.translate(first.limit()), None);
+    assert_eq!(unsafe { stacks.free(&mut mapper, first) }, Err(StackError::NotFound));
+
+    let third = stacks.alloc(&mut mapper, 4 * KB).unwrap();
+    assert_eq!(third.limit(), first.limit());
+    assert_eq!(stacks.iter().collect::<std::vec::Vec<_>>(), [third, second]);
+}
+
+#[test]
+fn failed_alloc_maps_nothing() {
+    //  root + 3 tables + 2 frames, the stack needs 4
+    let mut mapper = mapper(6);
+    let mut stacks: Stacks<4> = Stacks::new(area(64 * KB));
+
+    assert_eq!(stacks.alloc(&mut mapper, 16 * KB), Err(StackError::NoFrames));
+    assert!(stacks.is_empty());
+    assert_eq!(mapper.source().free.len(), 5);
+
+    let mut full: Stacks<1> = Stacks::new(area(64 * KB));
+    full.alloc(&mut mapper, 4 * KB).unwrap();
+    assert_eq!(full.alloc(&mut mapper, 4 * KB), Err(StackError::Full));
+}
+
+#[test]
+fn global_stacks() {
+    let mut mapper = mapper(16);
+
+    assert_eq!(stack::check_all(), Ok(()));
+    stack::init(area(64 * KB)).unwrap();
+    assert_eq!(stack::init(area(64 * KB)), Err(StackError::AlreadyInitialized));
+
+    let stack = stack::alloc(&mut mapper, 4 * KB).unwrap();
+    assert_eq!(stack::guard_of(stack.limit() - 1), Some(stack));
+
+    unsafe { *(stack.limit() as *mut u64) = 0 };
+    assert_eq!(stack::check_all(), Err(stack));
+    unsafe { stack::free(&mut mapper, stack) }.unwrap();
+    assert_eq!(stack::check_all(), Ok(()));
+}
@@ -0,0 +1,59 @@
+//	tests/string.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use core::fmt::Write;
+
+use ministd::String;
+
+
+#[test]
+fn push_and_compare() {
+    let mut s: String = String::new();
+    s.push_str("hello");
+    s.push(b' ');
+    s.push_str("world");
+
+    assert_eq!(s, "hello world");
+    assert_eq!(s.len(), 11);
+    assert_eq!(s.find("world"), Some(6));
+}
+
+#[test]
+fn insert_remove_and_split() {
+    let mut s: String = String::from("kernel");
+
+    s.insert_str(0, "base ");
+    assert_eq!(s, "base kernel");
+
+    let tail = s.split_off(4);
+    assert_eq!(s, "base");
+    assert_eq!(tail, " kernel");
+
+    s.truncate(2);
+    assert_eq!(s, "ba");
+}
+
+#[test]
+fn formatting() {
+    let mut s: String = String::new();
+    write!(s, "{} + {} = {}", 2, 3, 2 + 3).unwrap();
+
+    assert_eq!(s, "2 + 3 = 5");
+    assert_eq!(std::format!("{}", s), "2 + 3 = 5");
+}
+
+#[test]
+fn grows_past_many_steps() {
+    let mut s: String = String::new();
+    for _ in 0..1000 {
+        s.push_str("0123456789");
+    }
+
+    assert_eq!(s.len(), 10_000);
+    assert!(s.capacity() >= 10_000);
+    assert!(s.ends_with("0123456789"));
+}
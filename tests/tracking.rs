@@ -0,0 +1,65 @@
+//	tests/tracking.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing,alloc-tracking`
+
+
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "debug-heap"))]
+use core::alloc::{GlobalAlloc, Layout};
+
+use ministd::Box;
+use ministd::alloc::tracking;
+#[cfg(not(feature = "debug-heap"))]
+use ministd::ALLOCATOR;
+
+
+/// Serializes the tests, the snapshots see allocations of every thread
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+
+#[test]
+fn leaks_are_reported_at_their_call_site() {
+    let _serial = serial();
+    let snapshot = tracking::snapshot();
+    assert!(snapshot.leaks().is_empty());
+
+    let leaked = Box::new([0u64; 8]);
+
+    let report = snapshot.leaks();
+    assert_eq!(report.count(), 1);
+    assert!(report.bytes() >= 64);
+    assert!(report.sites()[0].location.file().ends_with("tracking.rs"));
+    assert!(tracking::live() >= 1);
+
+    drop(leaked);
+    assert!(snapshot.leaks().is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "debug-heap"))]
+fn in_place_realloc_keeps_the_record() {
+    let _serial = serial();
+    let layout = Layout::from_size_align(20, 8).unwrap();
+    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+    let snapshot = tracking::snapshot();
+
+    //  both sizes fall into the same size class, so the block is not moved
+    //  - the debug heap moves every block, hence the test is skipped with it
+    let new = unsafe { ALLOCATOR.realloc(ptr, layout, 24) };
+    assert_eq!(new, ptr);
+    assert!(snapshot.leaks().is_empty());
+
+    let mut buf = [tracking::Allocation::EMPTY; 64];
+    let len = tracking::allocations(&mut buf);
+    let record = buf[..len].iter().find(|a| a.ptr == ptr as usize).unwrap();
+    assert_eq!(record.layout.size(), 24);
+
+    unsafe { ALLOCATOR.dealloc(new, Layout::from_size_align(24, 8).unwrap()) };
+}
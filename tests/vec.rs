@@ -0,0 +1,387 @@
+//	tests/vec.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::{AllocError, Vec};
+use ministd::alloc::AllocErrorKind;
+
+
+/// Counts its drops
+struct Tracked<'c>(&'c Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+
+#[test]
+fn push_pop_and_index() {
+    let mut v: Vec<u32> = Vec::new();
+    assert!(v.is_empty());
+
+    for i in 0..1000 {
+        v.push(i);
+    }
+
+    assert_eq!(v.len(), 1000);
+    assert!(v.capacity() >= 1000);
+    assert_eq!(v[0], 0);
+    assert_eq!(v[999], 999);
+    assert_eq!(v.iter().sum::<u32>(), 999 * 1000 / 2);
+
+    assert_eq!(v.pop(), Some(999));
+    assert_eq!(v.len(), 999);
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut v: Vec<i32> = Vec::from([1, 2, 4, 5]);
+
+    v.insert(2, 3);
+    assert_eq!(v, [1, 2, 3, 4, 5]);
+
+    assert_eq!(v.remove(0), 1);
+    assert_eq!(v.remove(3), 5);
+    assert_eq!(v, [2, 3, 4]);
+}
+
+#[test]
+fn extend_and_truncate() {
+    let mut v: Vec<u8> = Vec::new();
+    v.extend_from_slice(b"hello");
+    v.extend_from_within(1..3);
+    assert_eq!(v, *b"helloel");
+
+    v.truncate(2);
+    assert_eq!(v, *b"he");
+
+    v.clear();
+    assert!(v.is_empty());
+}
+
+#[test]
+fn drops_every_element_once() {
+    let drops = Cell::new(0);
+
+    {
+        let mut v: Vec<Tracked> = Vec::new();
+        for _ in 0..10 {
+            v.push(Tracked(&drops));
+        }
+        drop(v.pop());
+        assert_eq!(drops.get(), 1);
+    }
+
+    assert_eq!(drops.get(), 10);
+}
+
+#[test]
+fn reserve_reports_overflow() {
+    let mut v: Vec<u64> = Vec::from([1, 2, 3]);
+
+    let err: AllocError = v.try_reserve(usize::MAX).unwrap_err();
+    assert_eq!(err.kind(), AllocErrorKind::CapacityOverflow);
+
+    //  the vector is untouched
+    assert_eq!(v, [1, 2, 3]);
+
+    v.try_reserve(100).unwrap();
+    assert!(v.capacity() >= 103);
+}
+
+#[test]
+fn shrink_keeps_contents() {
+    let mut v: Vec<u16> = Vec::with_capacity(256);
+    v.extend_from_slice(&[7; 10]);
+
+    v.shrink_to_fit();
+    assert!(v.capacity() >= 10 && v.capacity() < 256);
+    assert_eq!(v, [7; 10]);
+}
+
+#[test]
+fn into_iter_moves_elements() {
+    let drops = Cell::new(0);
+
+    let mut v: Vec<Tracked, 3, 64> = Vec::new();
+    for _ in 0..7 {
+        v.push(Tracked(&drops));
+    }
+
+    let mut iter = v.into_iter();
+    assert_eq!(iter.len(), 7);
+    assert_eq!(iter.as_slice().as_ptr() as usize % 64, 0);
+
+    let first = iter.next().unwrap();
+    let last = iter.next_back().unwrap();
+    assert_eq!(iter.len(), 5);
+    drop(iter);
+    assert_eq!(drops.get(), 5);
+
+    drop((first, last));
+    assert_eq!(drops.get(), 7);
+
+    let words: Vec<u64> = Vec::from([1, 2, 3]);
+    assert_eq!(words.into_iter().rev().collect::<std::vec::Vec<_>>(), [3, 2, 1]);
+
+    //  an empty vector has no buffer at all
+    let empty: Vec<u64> = Vec::new();
+    assert_eq!(empty.into_iter().next(), None);
+    let mut empty: Vec<u64> = Vec::new();
+    assert_eq!((&empty).into_iter().count() + (&mut empty).into_iter().count(), 0);
+}
+
+#[test]
+fn drain_removes_range() {
+    let mut v: Vec<u32, 4> = (0..10).collect();
+
+    let drained: std::vec::Vec<u32> = v.drain(2..5).collect();
+    assert_eq!(drained, [2, 3, 4]);
+    assert_eq!(v, [0, 1, 5, 6, 7, 8, 9]);
+
+    //  the range is removed even if it is not iterated whole
+    let mut drain = v.drain(1..=3);
+    assert_eq!(drain.next_back(), Some(6));
+    drop(drain);
+    assert_eq!(v, [0, 7, 8, 9]);
+
+    let mut drain = v.drain(1..);
+    assert_eq!(drain.next(), Some(7));
+    drain.keep_rest();
+    assert_eq!(v, [0, 8, 9]);
+
+    v.drain(..).for_each(drop);
+    assert!(v.is_empty());
+
+    //  a leaked iterator leaves the vector truncated
+    let mut v: Vec<u32> = (0..6).collect();
+    core::mem::forget(v.drain(2..4));
+    assert_eq!(v, [0, 1]);
+}
+
+#[test]
+fn drain_drops_rest_once() {
+    let drops = Cell::new(0);
+
+    let mut v: Vec<Tracked, 0, 32> = Vec::new();
+    for _ in 0..6 {
+        v.push(Tracked(&drops));
+    }
+
+    let mut drain = v.drain(1..5);
+    drop(drain.next());
+    drop(drain);
+    assert_eq!(drops.get(), 4);
+    assert_eq!(v.len(), 2);
+
+    drop(v);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn drain_moves_tail_back_on_panic() {
+    struct Bomb(u32);
+
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            if self.0 == 2 && !std::thread::panicking() {
+                panic!("bomb");
+            }
+        }
+    }
+
+    let mut v: Vec<Bomb> = (0..6).map(Bomb).collect();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        v.drain(1..4);
+    }));
+
+    assert!(result.is_err());
+    let left: std::vec::Vec<u32> = v.iter().map(|b| b.0).collect();
+    assert_eq!(left, [0, 4, 5]);
+}
+
+#[test]
+fn splice_replaces_range() {
+    let mut v: Vec<u32> = (0..6).collect();
+
+    //  shorter replacement
+    let removed: std::vec::Vec<u32> = v.splice(1..4, [10]).collect();
+    assert_eq!(removed, [1, 2, 3]);
+    assert_eq!(v, [0, 10, 4, 5]);
+
+    //  longer replacement, the size is known
+    v.splice(1..2, [11, 12, 13, 14]);
+    assert_eq!(v, [0, 11, 12, 13, 14, 4, 5]);
+
+    //  longer replacement, the size is not known
+    v.splice(..1, (20..30).filter(|i| i % 2 == 0));
+    assert_eq!(v, [20, 22, 24, 26, 28, 11, 12, 13, 14, 4, 5]);
+
+    //  insertion at the end
+    let len = v.len();
+    v.splice(len.., [99]);
+    assert_eq!(v.last(), Some(&99));
+
+    let mut v: Vec<u64, 2, 128> = Vec::new();
+    v.splice(.., 0..100);
+    assert_eq!(v.len(), 100);
+    assert_eq!(v.as_ptr() as usize % 128, 0);
+    assert!(v.iter().copied().eq(0..100));
+}
+
+#[test]
+fn extract_if_removes_matching() {
+    let mut v: Vec<u32> = (0..10).collect();
+
+    let even: std::vec::Vec<u32> = v.extract_if(.., |x| *x % 2 == 0).collect();
+    assert_eq!(even, [0, 2, 4, 6, 8]);
+    assert_eq!(v, [1, 3, 5, 7, 9]);
+
+    //  only the range is visited
+    let big: std::vec::Vec<u32> = v.extract_if(..3, |x| *x > 2).collect();
+    assert_eq!(big, [3, 5]);
+    assert_eq!(v, [1, 7, 9]);
+
+    //  the elements that were not visited stay
+    let mut v: Vec<u32> = (0..10).collect();
+    let mut iter = v.extract_if(.., |x| *x % 3 == 0);
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next(), Some(3));
+    drop(iter);
+    assert_eq!(v, [1, 2, 4, 5, 6, 7, 8, 9]);
+
+    //  the element the predicate panics on is kept
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        v.extract_if(.., |x| if *x == 6 { panic!("predicate") } else { *x < 5 }).for_each(drop);
+    }));
+    assert!(result.is_err());
+    assert_eq!(v, [5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn split_off_moves_tail() {
+    let mut v: Vec<u32, 8> = (0..10).collect();
+
+    let tail = v.split_off(6);
+    assert_eq!(v, [0, 1, 2, 3, 4, 5]);
+    assert_eq!(tail, [6, 7, 8, 9]);
+    assert_eq!(tail.capacity() % 8, 0);
+
+    let empty = v.split_off(6);
+    assert!(empty.is_empty());
+
+    let all = v.try_split_off(0).unwrap();
+    assert!(v.is_empty());
+    assert_eq!(all.len(), 6);
+}
+
+#[test]
+fn sort_is_stable() {
+    //  long enough for the merge sort to allocate scratch space
+    let mut v: Vec<(u32, u32)> = (0..500).map(|i| ((i * 7919) % 13, i)).collect();
+    v.sort_by_key(|e| e.0);
+
+    assert!(v.iter().zip(v.iter().skip(1)).all(|(a, b)| a.0 < b.0 || (a.0 == b.0 && a.1 < b.1)));
+
+    let mut small: Vec<u32> = Vec::from([5, 3, 9, 1, 3]);
+    small.try_sort().unwrap();
+    assert_eq!(small, [1, 3, 3, 5, 9]);
+
+    let mut empty: Vec<u32> = Vec::new();
+    empty.sort();
+    empty.sort_unstable();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn sort_survives_panicking_comparator() {
+    let mut v: Vec<std::string::String> = (0..100).rev().map(|i| std::format!("{i:03}")).collect();
+    let mut calls = 0;
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        v.sort_by(|a, b| {
+            calls += 1;
+            assert!(calls < 300, "comparator gave up");
+            a.cmp(b)
+        });
+    }));
+    assert!(result.is_err());
+
+    //  every element is still there exactly once
+    let mut left: std::vec::Vec<_> = v.iter().cloned().collect();
+    left.sort();
+    assert_eq!(left, (0..100).map(|i| std::format!("{i:03}")).collect::<std::vec::Vec<_>>());
+
+    v.sort_unstable();
+    assert_eq!(v[0], "000");
+    assert_eq!(v[99], "099");
+}
+
+#[test]
+fn dedup_and_retain() {
+    let drops = Cell::new(0);
+
+    let mut v: Vec<u32> = Vec::from([1, 1, 2, 3, 3, 3, 1, 4, 4]);
+    v.dedup();
+    assert_eq!(v, [1, 2, 3, 1, 4]);
+
+    v.dedup_by_key(|e| *e / 2);
+    assert_eq!(v, [1, 2, 1, 4]);
+
+    //  removing neighbours and the last element used to skip elements
+    let mut v: Vec<u32> = (0..10).collect();
+    v.retain(|e| e % 3 == 1);
+    assert_eq!(v, [1, 4, 7]);
+
+    let mut v: Vec<u32> = Vec::from([2, 4, 6]);
+    v.retain_mut(|e| { *e += 1; *e > 5 });
+    assert_eq!(v, [7]);
+
+    let mut t: Vec<Tracked> = (0..6).map(|_| Tracked(&drops)).collect();
+    let mut i = 0;
+    t.retain(|_| { i += 1; i % 2 == 0 });
+    assert_eq!(t.len(), 3);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn retain_keeps_order_when_predicate_panics() {
+    let mut v: Vec<u32> = (0..10).collect();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        v.retain(|e| {
+            assert!(*e != 6, "predicate gave up");
+            e % 2 == 0
+        });
+    }));
+    assert!(result.is_err());
+
+    //  the odd elements before the panic are removed, the rest is kept in order
+    assert_eq!(v, [0, 2, 4, 6, 7, 8, 9]);
+}
+
+#[test]
+fn binary_search_and_insert_sorted() {
+    let mut v: Vec<u32> = Vec::new();
+    assert_eq!(v.binary_search(&5), Err(0));
+
+    for x in [5, 1, 4, 1, 3] {
+        v.insert_sorted(x);
+    }
+    assert_eq!(v, [1, 1, 3, 4, 5]);
+    assert_eq!(v.binary_search(&4), Ok(3));
+    assert_eq!(v.binary_search(&2), Err(2));
+
+    //  equal elements go last
+    let mut pairs: Vec<(u32, char)> = Vec::from([(1, 'a'), (2, 'a')]);
+    assert_eq!(pairs.try_insert_sorted_by((1, 'b'), |a, b| a.0.cmp(&b.0)), Ok(1));
+    assert_eq!(pairs, [(1, 'a'), (1, 'b'), (2, 'a')]);
+    assert_eq!(pairs.binary_search_by_key(&2, |e| e.0), Ok(2));
+}
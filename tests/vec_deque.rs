@@ -0,0 +1,163 @@
+//	tests/vec_deque.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  run with `cargo test --features testing`
+
+
+use std::cell::Cell;
+
+use ministd::{Vec, VecDeque};
+
+
+/// Counts its drops
+struct Tracked<'c>(&'c Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// Queue of `0..len` whose elements wrap around the end of the buffer
+fn wrapped(len: u32) -> VecDeque<u32> {
+    let mut d = VecDeque::with_capacity(len as usize);
+    for i in (0..len / 2).rev() {
+        d.push_front(i);
+    }
+    for i in len / 2..len {
+        d.push_back(i);
+    }
+    d
+}
+
+
+#[test]
+fn push_and_pop_both_ends() {
+    let mut d: VecDeque<u32> = VecDeque::new();
+    assert_eq!(d.pop_front(), None);
+    assert_eq!(d.back(), None);
+
+    for i in 0..100 {
+        d.push_back(i);
+        d.push_front(1000 + i);
+    }
+    assert_eq!(d.len(), 200);
+    assert_eq!(d.front(), Some(&1099));
+    assert_eq!(d.back(), Some(&99));
+    assert_eq!(d[100], 0);
+
+    for i in (0..100).rev() {
+        assert_eq!(d.pop_back(), Some(i));
+    }
+    for i in (0..100).rev() {
+        assert_eq!(d.pop_front(), Some(1000 + i));
+    }
+    assert!(d.is_empty());
+}
+
+#[test]
+fn grows_while_wrapped() {
+    //  both ways of moving the wrapped part
+    for (front, back) in [(6, 2), (2, 6)] {
+        let mut d: VecDeque<u32> = VecDeque::with_capacity(8);
+        for i in (0..front).rev() {
+            d.push_front(i);
+        }
+        for i in front..front + back {
+            d.push_back(i);
+        }
+        assert_eq!(d.capacity(), 8);
+        assert!(!d.as_slices().1.is_empty());
+
+        d.push_back(8);
+        d.push_front(100);
+        assert!(d.capacity() > 8);
+        assert!(d.iter().skip(1).copied().eq(0..9));
+    }
+}
+
+#[test]
+fn slices_and_make_contiguous() {
+    let mut d = wrapped(8);
+    let (a, b) = d.as_slices();
+    assert_eq!((a, b), (&[0, 1, 2, 3][..], &[4, 5, 6, 7][..]));
+
+    assert_eq!(d.make_contiguous(), [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert!(d.as_slices().1.is_empty());
+    assert_eq!(d, [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn rotates() {
+    let mut d = wrapped(8);
+    d.rotate_left(3);
+    assert_eq!(d, [3, 4, 5, 6, 7, 0, 1, 2]);
+    d.rotate_right(5);
+    assert_eq!(d, [6, 7, 0, 1, 2, 3, 4, 5]);
+
+    //  room left in the buffer, the elements are moved one by one
+    let mut d: VecDeque<u32> = (0..5).collect();
+    d.rotate_left(4);
+    assert_eq!(d, [4, 0, 1, 2, 3]);
+    d.rotate_right(1);
+    assert_eq!(d, [3, 4, 0, 1, 2]);
+    d.rotate_left(5);
+    assert_eq!(d, [3, 4, 0, 1, 2]);
+}
+
+#[test]
+fn iterates_ranges() {
+    let mut d = wrapped(8);
+
+    assert!(d.iter().copied().eq(0..8));
+    assert!(d.iter().rev().copied().eq((0..8).rev()));
+    assert!(d.range(2..6).copied().eq(2..6));
+    assert_eq!(d.range(3..=4).len(), 2);
+
+    for e in d.range_mut(4..) {
+        *e *= 10;
+    }
+    assert_eq!(d, [0, 1, 2, 3, 40, 50, 60, 70]);
+    assert_eq!((&d).into_iter().count(), 8);
+    assert!(d.into_iter().rev().take(2).eq([70, 60]));
+}
+
+#[test]
+fn drains_ranges() {
+    //  the front part is shorter
+    let mut d = wrapped(8);
+    assert!(d.drain(1..3).eq([1, 2]));
+    assert_eq!(d, [0, 3, 4, 5, 6, 7]);
+
+    //  the back part is shorter
+    let mut d = wrapped(8);
+    assert!(d.drain(4..7).rev().eq([6, 5, 4]));
+    assert_eq!(d, [0, 1, 2, 3, 7]);
+
+    //  the elements that were not yielded are dropped
+    let drops = Cell::new(0);
+    let mut t: VecDeque<Tracked> = (0..6).map(|_| Tracked(&drops)).collect();
+    let mut drain = t.drain(1..5);
+    drop(drain.next());
+    drop(drain);
+    assert_eq!(drops.get(), 4);
+    assert_eq!(t.len(), 2);
+    drop(t);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn converts_to_and_from_vec() {
+    let v: Vec<u32> = (0..5).collect();
+    let mut d: VecDeque<u32> = VecDeque::from(v);
+    d.push_front(100);
+    d.pop_back();
+
+    let v: Vec<u32> = Vec::from(d);
+    assert_eq!(v, [100, 0, 1, 2, 3]);
+
+    let v: Vec<u32> = Vec::from(wrapped(8));
+    assert_eq!(v, [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(VecDeque::<u32>::from([1, 2]).clone(), [1, 2]);
+}